@@ -5,16 +5,25 @@
 //!
 //! This module provides async actors for each RINA component,
 //! allowing them to run concurrently and communicate via channels.
+//! Fallible requests are answered with `Result<_, AriError>`.
 
-use crate::efcp::{Efcp, FlowConfig};
-use crate::inter_ipcp_fal::InterIpcpFlowAllocator;
+use crate::crypto::FlowKey;
+use crate::efcp::{Efcp, FlowConfig, FlowStats};
+use crate::enrollment::{EnrollmentManager, SyncStatus};
+use crate::error::{ActorError, AriError, EfcpError, RmtError, ShimError};
+use crate::inter_ipcp_fal::{InterIpcpFlowAllocator, InterIpcpFlowState};
 use crate::pdu::Pdu;
 use crate::rib::{Rib, RibValue};
-use crate::rmt::{ForwardingEntry, Rmt};
+use crate::rmt::{DropReason, ForwardingEntry, Rmt, RmtStats};
 use crate::routing::RouteResolver;
-use crate::shim::UdpShim;
+use crate::shim::{ShimStats, UdpShim};
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
+use std::time::Duration;
+use tokio::sync::{RwLock, mpsc, oneshot};
+
+/// How long `ActorHandle::send` waits for mailbox space before giving up
+pub const DEFAULT_SEND_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Messages for RIB actor
 #[derive(Debug)]
@@ -23,27 +32,31 @@ pub enum RibMessage {
         name: String,
         class: String,
         value: RibValue,
-        response: mpsc::Sender<Result<(), String>>,
+        response: oneshot::Sender<Result<(), AriError>>,
     },
     Read {
         name: String,
-        response: mpsc::Sender<Option<RibValue>>,
+        response: oneshot::Sender<Option<RibValue>>,
     },
     Update {
         name: String,
         value: RibValue,
-        response: mpsc::Sender<Result<(), String>>,
+        response: oneshot::Sender<Result<(), AriError>>,
     },
     Delete {
         name: String,
-        response: mpsc::Sender<Result<(), String>>,
+        response: oneshot::Sender<Result<(), AriError>>,
     },
     ListByClass {
         class: String,
-        response: mpsc::Sender<Vec<String>>,
+        response: oneshot::Sender<Vec<String>>,
     },
     Count {
-        response: mpsc::Sender<usize>,
+        response: oneshot::Sender<usize>,
+    },
+    /// Reports how far the RIB is in step with the bootstrap
+    SyncStatus {
+        response: oneshot::Sender<SyncStatus>,
     },
 }
 
@@ -51,16 +64,30 @@ pub enum RibMessage {
 pub struct RibActor {
     rib: Arc<RwLock<Rib>>,
     receiver: mpsc::Receiver<RibMessage>,
+    sync_source: Option<Arc<EnrollmentManager>>,
 }
 
 impl RibActor {
     pub fn new(receiver: mpsc::Receiver<RibMessage>) -> Self {
+        Self::with_rib(Rib::new(), receiver)
+    }
+
+    /// Creates an actor serving an existing RIB
+    pub fn with_rib(rib: Rib, receiver: mpsc::Receiver<RibMessage>) -> Self {
         Self {
-            rib: Arc::new(RwLock::new(Rib::new())),
+            rib: Arc::new(RwLock::new(rib)),
             receiver,
+            sync_source: None,
         }
     }
 
+    /// Sets the enrollment manager whose sync loop `SyncStatus` reports on
+    ///
+    /// Without one only the local version is reported.
+    pub fn set_sync_source(&mut self, enrollment: Arc<EnrollmentManager>) {
+        self.sync_source = Some(enrollment);
+    }
+
     pub async fn run(mut self) {
         while let Some(msg) = self.receiver.recv().await {
             match msg {
@@ -72,12 +99,12 @@ impl RibActor {
                 } => {
                     let rib = self.rib.read().await;
                     let result = rib.create(name, class, value).await;
-                    let _ = response.send(result).await;
+                    let _ = response.send(result.map_err(AriError::from));
                 }
                 RibMessage::Read { name, response } => {
                     let rib = self.rib.read().await;
                     let obj = rib.read(&name).await;
-                    let _ = response.send(obj.map(|o| o.value)).await;
+                    let _ = response.send(obj.ok().map(|o| o.value));
                 }
                 RibMessage::Update {
                     name,
@@ -86,22 +113,32 @@ impl RibActor {
                 } => {
                     let rib = self.rib.read().await;
                     let result = rib.update(&name, value).await;
-                    let _ = response.send(result).await;
+                    let _ = response.send(result.map_err(AriError::from));
                 }
                 RibMessage::Delete { name, response } => {
                     let rib = self.rib.read().await;
                     let result = rib.delete(&name).await;
-                    let _ = response.send(result).await;
+                    let _ = response.send(result.map_err(AriError::from));
                 }
                 RibMessage::ListByClass { class, response } => {
                     let rib = self.rib.read().await;
                     let list = rib.list_by_class(&class).await;
-                    let _ = response.send(list).await;
+                    let _ = response.send(list);
                 }
                 RibMessage::Count { response } => {
                     let rib = self.rib.read().await;
                     let count = rib.count().await;
-                    let _ = response.send(count).await;
+                    let _ = response.send(count);
+                }
+                RibMessage::SyncStatus { response } => {
+                    let status = match &self.sync_source {
+                        Some(enrollment) => enrollment.sync_status().await,
+                        None => SyncStatus {
+                            local_version: self.rib.read().await.current_version().await,
+                            ..SyncStatus::default()
+                        },
+                    };
+                    let _ = response.send(status);
                 }
             }
         }
@@ -115,23 +152,39 @@ pub enum EfcpMessage {
         local_addr: u64,
         remote_addr: u64,
         config: FlowConfig,
-        response: mpsc::Sender<u32>,
+        /// Key negotiated for an encrypted flow
+        key: Option<FlowKey>,
+        /// CEP-ID of the peer's end, if the peer allocated its end first
+        ///
+        /// Otherwise the peer's CEP-ID has to be bound with `BindRemoteCep`
+        /// once it answers; until then no PDU is accepted on the flow.
+        remote_cep_id: Option<u32>,
+        response: oneshot::Sender<u32>,
     },
     SendData {
         flow_id: u32,
         data: Vec<u8>,
-        response: mpsc::Sender<Result<Pdu, String>>,
+        response: oneshot::Sender<Result<Vec<Pdu>, AriError>>,
     },
     ReceivePdu {
         pdu: Pdu,
-        response: mpsc::Sender<Result<Option<Vec<u8>>, String>>,
+        response: oneshot::Sender<Result<Option<Vec<u8>>, AriError>>,
+    },
+    BindRemoteCep {
+        flow_id: u32,
+        remote_cep_id: u32,
+        response: oneshot::Sender<Result<(), AriError>>,
     },
     DeallocateFlow {
         flow_id: u32,
-        response: mpsc::Sender<Result<(), String>>,
+        response: oneshot::Sender<Result<(), AriError>>,
     },
     GetFlowCount {
-        response: mpsc::Sender<usize>,
+        response: oneshot::Sender<usize>,
+    },
+    GetFlowStats {
+        flow_id: u32,
+        response: oneshot::Sender<Option<FlowStats>>,
     },
 }
 
@@ -144,8 +197,13 @@ pub struct EfcpActor {
 
 impl EfcpActor {
     pub fn new(receiver: mpsc::Receiver<EfcpMessage>) -> Self {
+        Self::with_efcp(Efcp::new(), receiver)
+    }
+
+    /// Creates an actor around a pre-configured EFCP instance
+    pub fn with_efcp(efcp: Efcp, receiver: mpsc::Receiver<EfcpMessage>) -> Self {
         Self {
-            efcp: Arc::new(RwLock::new(Efcp::new())),
+            efcp: Arc::new(RwLock::new(efcp)),
             receiver,
             rmt_handle: None,
         }
@@ -162,11 +220,16 @@ impl EfcpActor {
                     local_addr,
                     remote_addr,
                     config,
+                    key,
+                    remote_cep_id,
                     response,
                 } => {
                     let mut efcp = self.efcp.write().await;
-                    let flow_id = efcp.allocate_flow(local_addr, remote_addr, config);
-                    let _ = response.send(flow_id).await;
+                    let flow_id = efcp.allocate_keyed_flow(local_addr, remote_addr, config, key);
+                    if let Some(remote_cep_id) = remote_cep_id {
+                        let _ = efcp.bind_remote_cep(flow_id, remote_cep_id);
+                    }
+                    let _ = response.send(flow_id);
                 }
                 EfcpMessage::SendData {
                     flow_id,
@@ -174,51 +237,85 @@ impl EfcpActor {
                     response,
                 } => {
                     let mut efcp = self.efcp.write().await;
-                    let result = efcp
-                        .get_flow_mut(flow_id)
-                        .ok_or_else(|| format!("Flow {} not found", flow_id))
-                        .and_then(|flow| flow.send_data(data));
-
-                    // Forward PDU to RMT if successful
-                    if let (Ok(pdu), Some(rmt_handle)) = (&result, &self.rmt_handle) {
-                        let (tx, mut rx) = mpsc::channel(1);
-                        if (rmt_handle
-                            .sender
-                            .send(RmtMessage::ProcessOutgoing {
-                                pdu: pdu.clone(),
-                                response: tx,
-                            })
-                            .await)
-                            .is_ok()
-                        {
-                            let _ = rx.recv().await;
+                    let mut result = match efcp.get_flow_mut(flow_id) {
+                        Some(flow) => flow
+                            .send_fragmented(data)
+                            .map_err(|e| AriError::from(EfcpError::SendFailed(e))),
+                        None => Err(EfcpError::FlowNotFound(flow_id as u64).into()),
+                    };
+
+                    drop(efcp);
+
+                    // Forward the fragments to RMT if successful; a PDU the
+                    // RMT could not send fails the call
+                    if let Ok(pdus) = &result {
+                        for pdu in pdus {
+                            if let Err(e) = self.forward_to_rmt(pdu.clone()).await {
+                                result = Err(e);
+                                break;
+                            }
                         }
                     }
 
-                    let _ = response.send(result).await;
+                    let _ = response.send(result);
                 }
                 EfcpMessage::ReceivePdu { pdu, response } => {
                     let mut efcp = self.efcp.write().await;
-                    let flow_id = pdu.dst_cep_id;
                     let result = efcp
-                        .get_flow_mut(flow_id)
-                        .ok_or_else(|| format!("Flow {} not found", flow_id))
-                        .and_then(|flow| flow.receive_pdu(pdu));
-                    let _ = response.send(result).await;
+                        .demux_pdu(pdu)
+                        .map_err(|e| EfcpError::ReceiveFailed(e).into());
+                    let acks = efcp.take_acks();
+                    drop(efcp);
+
+                    // Acknowledge the data so the sender's window keeps opening
+                    for ack in acks {
+                        if let Err(e) = self.forward_to_rmt(ack).await {
+                            eprintln!("  ⚠ Failed to send ACK: {}", e);
+                        }
+                    }
+                    let _ = response.send(result);
+                }
+                EfcpMessage::BindRemoteCep {
+                    flow_id,
+                    remote_cep_id,
+                    response,
+                } => {
+                    let mut efcp = self.efcp.write().await;
+                    let result = efcp
+                        .bind_remote_cep(flow_id, remote_cep_id)
+                        .map_err(|_| EfcpError::FlowNotFound(flow_id as u64).into());
+                    let _ = response.send(result);
                 }
                 EfcpMessage::DeallocateFlow { flow_id, response } => {
                     let mut efcp = self.efcp.write().await;
-                    let result = efcp.deallocate_flow(flow_id);
-                    let _ = response.send(result).await;
+                    let result = efcp
+                        .deallocate_flow(flow_id)
+                        .map_err(|_| EfcpError::FlowNotFound(flow_id as u64).into());
+                    let _ = response.send(result);
                 }
                 EfcpMessage::GetFlowCount { response } => {
                     let efcp = self.efcp.read().await;
                     let count = efcp.flow_count();
-                    let _ = response.send(count).await;
+                    let _ = response.send(count);
+                }
+                EfcpMessage::GetFlowStats { flow_id, response } => {
+                    let efcp = self.efcp.read().await;
+                    let _ = response.send(efcp.flow_stats(flow_id));
                 }
             }
         }
     }
+
+    /// Hands an outgoing PDU to the RMT, if one is connected
+    async fn forward_to_rmt(&self, pdu: Pdu) -> Result<(), AriError> {
+        let Some(rmt_handle) = &self.rmt_handle else {
+            return Ok(());
+        };
+        rmt_handle
+            .request(|response| RmtMessage::ProcessOutgoing { pdu, response })
+            .await??;
+        Ok(())
+    }
 }
 
 /// Messages for RMT actor
@@ -226,45 +323,84 @@ impl EfcpActor {
 pub enum RmtMessage {
     AddForwardingEntry {
         entry: ForwardingEntry,
-        response: mpsc::Sender<()>,
+        response: oneshot::Sender<Result<(), AriError>>,
     },
     ProcessOutgoing {
         pdu: Pdu,
-        response: mpsc::Sender<Result<u64, String>>,
+        response: oneshot::Sender<Result<u64, AriError>>,
     },
     ProcessIncoming {
         pdu: Pdu,
-        response: mpsc::Sender<Result<Option<u64>, String>>,
+        response: oneshot::Sender<Result<Option<u64>, AriError>>,
     },
     DequeueForNextHop {
         next_hop: u64,
-        response: mpsc::Sender<Option<Pdu>>,
+        response: oneshot::Sender<Option<Pdu>>,
     },
     GetForwardingTableSize {
-        response: mpsc::Sender<usize>,
+        response: oneshot::Sender<usize>,
+    },
+    GetForwardingTable {
+        response: oneshot::Sender<Vec<ForwardingEntry>>,
+    },
+    GetStats {
+        response: oneshot::Sender<RmtStats>,
     },
 }
 
 /// RMT Actor - handles relaying and multiplexing
 pub struct RmtActor {
+    local_addr: u64,
     rmt: Arc<RwLock<Rmt>>,
     receiver: mpsc::Receiver<RmtMessage>,
-    flow_allocator: Option<Arc<InterIpcpFlowAllocator>>,
+    fal_handle: Option<InterIpcpFalHandle>,
+    shim_handle: Option<ShimHandle>,
     route_resolver: Option<Arc<RouteResolver>>,
+    management_tx: Option<mpsc::Sender<Pdu>>,
 }
 
 impl RmtActor {
     pub fn new(local_addr: u64, receiver: mpsc::Receiver<RmtMessage>) -> Self {
+        Self::with_rmt(Rmt::new(local_addr), receiver)
+    }
+
+    /// Creates an actor around a pre-configured RMT
+    pub fn with_rmt(rmt: Rmt, receiver: mpsc::Receiver<RmtMessage>) -> Self {
         Self {
-            rmt: Arc::new(RwLock::new(Rmt::new(local_addr))),
+            local_addr: rmt.local_addr(),
+            rmt: Arc::new(RwLock::new(rmt)),
             receiver,
-            flow_allocator: None,
+            fal_handle: None,
+            shim_handle: None,
             route_resolver: None,
+            management_tx: None,
         }
     }
 
-    pub fn set_flow_allocator(&mut self, allocator: Arc<InterIpcpFlowAllocator>) {
-        self.flow_allocator = Some(allocator);
+    /// Delivers incoming management PDUs to `sender` instead of EFCP
+    pub fn set_management_sender(&mut self, sender: mpsc::Sender<Pdu>) {
+        self.management_tx = Some(sender);
+    }
+
+    /// Hands a management PDU to the local management handler
+    async fn deliver_management(&self, pdu: Pdu) -> Result<Option<u64>, AriError> {
+        let Some(tx) = &self.management_tx else {
+            return Err(RmtError::InvalidPdu(
+                "No management handler for management PDU".to_string(),
+            )
+            .into());
+        };
+        tx.send(pdu).await.map_err(|_| AriError::ChannelClosed)?;
+        Ok(None)
+    }
+
+    /// Sends queued PDUs straight through the shim actor instead of the flow allocator
+    pub fn set_shim_handle(&mut self, handle: ShimHandle) {
+        self.shim_handle = Some(handle);
+    }
+
+    pub fn set_fal_handle(&mut self, handle: InterIpcpFalHandle) {
+        self.fal_handle = Some(handle);
     }
 
     pub fn set_route_resolver(&mut self, resolver: Arc<RouteResolver>) {
@@ -273,12 +409,155 @@ impl RmtActor {
 
     /// Populate forwarding table from RIB routes
     ///
-    /// DEPRECATED: With RouteResolver, forwarding is done via next-hop resolution
-    /// rather than pre-populating a forwarding table. This method is kept for
-    /// backward compatibility but may be removed in future versions.
-    pub async fn populate_forwarding_table(&self) {
-        // No-op: RouteResolver handles route lookups dynamically
-        println!("⚠️  populate_forwarding_table() is deprecated - using RouteResolver instead");
+    /// Forwarding normally resolves next hops through the RouteResolver on
+    /// demand; this copies the routes it knows into the RMT forwarding table
+    /// instead, e.g. for diagnostics or a resolver-less fallback. Returns the
+    /// number of entries installed (0 without a resolver).
+    pub async fn populate_forwarding_table(&self) -> usize {
+        let Some(resolver) = &self.route_resolver else {
+            return 0;
+        };
+
+        let mut rmt = self.rmt.write().await;
+        let mut installed = 0;
+        for entry in resolver.forwarding_entries().await {
+            if let Err(e) = rmt.add_forwarding_entry(entry) {
+                eprintln!("  ⚠ Stopped populating forwarding table: {}", e);
+                break;
+            }
+            installed += 1;
+        }
+        installed
+    }
+
+    /// Resolves the next hop for a PDU leaving this IPCP through the RouteResolver
+    ///
+    /// Static and dynamic routes (including TTL expiry) are resolved there and
+    /// the flow allocator actor is told to (re)bind the flow to the resulting
+    /// underlay address.
+    async fn resolve_next_hop(&self, pdu: &Pdu) -> Result<u64, AriError> {
+        let Some(resolver) = &self.route_resolver else {
+            return Err(RmtError::ForwardingFailed("No route resolver".to_string()).into());
+        };
+
+        if pdu.dst_addr == self.local_addr {
+            return Err(
+                RmtError::InvalidPdu("PDU destination is local address".to_string()).into(),
+            );
+        }
+
+        let socket_addr = match resolver.resolve_next_hop(pdu.dst_addr).await {
+            Ok(addr) => addr,
+            Err(e) => {
+                self.rmt.write().await.record_drop(DropReason::NoRoute);
+                return Err(e);
+            }
+        };
+
+        if let Some(fal) = &self.fal_handle {
+            fal.request(|response| InterIpcpFalMessage::GetOrCreateFlow {
+                remote_addr: pdu.dst_addr,
+                socket_addr: Some(socket_addr),
+                response,
+            })
+            .await??;
+        }
+
+        Ok(pdu.dst_addr)
+    }
+
+    /// Hands a single PDU to the shim actor, or to the flow allocator without one
+    async fn transmit(&self, next_hop: u64, pdu: Pdu) -> Result<(), AriError> {
+        if let Some(shim) = &self.shim_handle {
+            shim.request(|response| ShimMessage::SendPdu {
+                pdu,
+                next_hop,
+                response,
+            })
+            .await??;
+            return Ok(());
+        }
+
+        let Some(fal) = &self.fal_handle else {
+            return Err(RmtError::ForwardingFailed("No transport configured".to_string()).into());
+        };
+        fal.request(|response| InterIpcpFalMessage::SendPdu {
+            next_hop,
+            pdu,
+            response,
+        })
+        .await?
+    }
+
+    /// Sends everything queued for `next_hop` in the order its scheduling policy yields
+    ///
+    /// Stops at the first transmission failure, which drops that PDU and is
+    /// counted in the RMT stats; PDUs still queued go out on the next drain.
+    async fn drain_queue(&self, next_hop: u64) -> Result<(), AriError> {
+        loop {
+            let pdu = self.rmt.write().await.dequeue_for_next_hop(next_hop);
+            let Some(pdu) = pdu else {
+                return Ok(());
+            };
+
+            let dst_addr = pdu.dst_addr;
+            if let Err(e) = self.transmit(next_hop, pdu).await {
+                self.rmt.write().await.record_drop(DropReason::SendFailed);
+                eprintln!(
+                    "❌ Failed to send PDU for {} via {}: {}",
+                    dst_addr, next_hop, e
+                );
+                return Err(e);
+            }
+            progress!("📤 Sent PDU to {} via next hop {}", dst_addr, next_hop);
+        }
+    }
+
+    /// Resolves the next hop for a PDU and sends it via the flow allocator
+    ///
+    /// Without a RouteResolver the PDU has been queued by the forwarding table
+    /// lookup, so the queues of its next hops are drained instead.
+    async fn forward(&self, pdu: &Pdu) -> Result<u64, AriError> {
+        if self.route_resolver.is_none() {
+            let next_hops = self
+                .rmt
+                .write()
+                .await
+                .process_outgoing_all(pdu.clone())
+                .map_err(RmtError::ForwardingFailed)?;
+            for &next_hop in &next_hops {
+                self.drain_queue(next_hop).await?;
+            }
+            return Ok(next_hops[0]);
+        }
+
+        let next_hop = self.resolve_next_hop(pdu).await?;
+
+        let Some(fal) = &self.fal_handle else {
+            eprintln!("❌ InterIpcpFlowAllocator not initialized for RMT");
+            return Err(
+                RmtError::ForwardingFailed("Flow allocator not initialized".to_string()).into(),
+            );
+        };
+
+        let result = fal
+            .request(|response| InterIpcpFalMessage::SendPdu {
+                next_hop: pdu.dst_addr,
+                pdu: pdu.clone(),
+                response,
+            })
+            .await?;
+
+        match result {
+            Ok(_) => {
+                progress!("📤 Sent PDU to {} via InterIpcpFlowAllocator", pdu.dst_addr);
+                Ok(next_hop)
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to send PDU via flow allocator: {}", e);
+                Err(e)
+            }
+        }
     }
 
     pub async fn run(mut self) {
@@ -286,56 +565,149 @@ impl RmtActor {
             match msg {
                 RmtMessage::AddForwardingEntry { entry, response } => {
                     let mut rmt = self.rmt.write().await;
-                    rmt.add_forwarding_entry(entry);
-                    let _ = response.send(()).await;
+                    let result = rmt.add_forwarding_entry(entry).map_err(AriError::from);
+                    let _ = response.send(result);
                 }
                 RmtMessage::ProcessOutgoing { pdu, response } => {
-                    let mut rmt = self.rmt.write().await;
-                    let result = rmt.process_outgoing(pdu.clone());
-
-                    if result.is_ok() {
-                        // Use flow allocator to send PDU
-                        if let Some(flow_allocator) = &self.flow_allocator {
-                            match flow_allocator.send_pdu(pdu.dst_addr, &pdu) {
-                                Ok(_) => {
-                                    println!(
-                                        "📤 Sent PDU to {} via InterIpcpFlowAllocator",
-                                        pdu.dst_addr
-                                    );
-                                }
-                                Err(e) => {
-                                    eprintln!("❌ Failed to send PDU via flow allocator: {}", e);
-                                    let _ = response
-                                        .send(Err(format!("Flow allocator error: {}", e)))
-                                        .await;
-                                    continue;
-                                }
+                    let result = self.forward(&pdu).await;
+                    let _ = response.send(result);
+                }
+                RmtMessage::ProcessIncoming { mut pdu, response } => {
+                    let result = if pdu.is_management() {
+                        self.deliver_management(pdu).await
+                    } else if self.route_resolver.is_some() && pdu.dst_addr != self.local_addr {
+                        // Transit PDU - relay it towards its destination
+                        let hop = self.rmt.write().await.consume_hop(&mut pdu);
+                        match hop {
+                            Ok(()) => self.forward(&pdu).await.map(Some),
+                            Err(e) => Err(RmtError::ForwardingFailed(e).into()),
+                        }
+                    } else {
+                        let queued = self.rmt.write().await.process_incoming(pdu);
+                        match queued {
+                            Ok(Some(next_hop)) => {
+                                self.drain_queue(next_hop).await.map(|_| Some(next_hop))
                             }
-                        } else {
-                            eprintln!("❌ InterIpcpFlowAllocator not initialized for RMT");
-                            let _ = response
-                                .send(Err("Flow allocator not initialized".to_string()))
-                                .await;
-                            continue;
+                            Ok(None) => Ok(None),
+                            Err(e) => Err(RmtError::ForwardingFailed(e).into()),
                         }
-                    }
-
-                    let _ = response.send(result).await;
-                }
-                RmtMessage::ProcessIncoming { pdu, response } => {
-                    let mut rmt = self.rmt.write().await;
-                    let result = rmt.process_incoming(pdu);
-                    let _ = response.send(result).await;
+                    };
+                    let _ = response.send(result);
                 }
                 RmtMessage::DequeueForNextHop { next_hop, response } => {
                     let mut rmt = self.rmt.write().await;
                     let pdu = rmt.dequeue_for_next_hop(next_hop);
-                    let _ = response.send(pdu).await;
+                    let _ = response.send(pdu);
                 }
                 RmtMessage::GetForwardingTableSize { response } => {
                     let rmt = self.rmt.read().await;
                     let size = rmt.forwarding_table_size();
-                    let _ = response.send(size).await;
+                    let _ = response.send(size);
+                }
+                RmtMessage::GetForwardingTable { response } => {
+                    let rmt = self.rmt.read().await;
+                    let _ = response.send(rmt.export_table());
+                }
+                RmtMessage::GetStats { response } => {
+                    let rmt = self.rmt.read().await;
+                    let _ = response.send(rmt.stats());
+                }
+            }
+        }
+    }
+}
+
+/// Messages for Inter-IPCP flow allocator actor
+#[derive(Debug)]
+pub enum InterIpcpFalMessage {
+    /// Ensure a flow exists; binds it to `socket_addr` if given, otherwise
+    /// looks the route up in the RIB
+    GetOrCreateFlow {
+        remote_addr: u64,
+        socket_addr: Option<SocketAddr>,
+        response: oneshot::Sender<Result<(), AriError>>,
+    },
+    /// Send a PDU to a next hop, lazily creating the flow if needed
+    SendPdu {
+        next_hop: u64,
+        pdu: Pdu,
+        response: oneshot::Sender<Result<(), AriError>>,
+    },
+    RecordReceived {
+        remote_addr: u64,
+        socket_addr: SocketAddr,
+    },
+    CleanupStale {
+        response: oneshot::Sender<usize>,
+    },
+    GetStats {
+        response: oneshot::Sender<Vec<(u64, InterIpcpFlowState, u64, u64)>>,
+    },
+}
+
+/// Inter-IPCP Flow Allocator Actor - owns N-1 flow lifecycle
+pub struct InterIpcpFalActor {
+    allocator: Arc<InterIpcpFlowAllocator>,
+    receiver: mpsc::Receiver<InterIpcpFalMessage>,
+}
+
+impl InterIpcpFalActor {
+    pub fn new(
+        allocator: Arc<InterIpcpFlowAllocator>,
+        receiver: mpsc::Receiver<InterIpcpFalMessage>,
+    ) -> Self {
+        Self {
+            allocator,
+            receiver,
+        }
+    }
+
+    pub async fn run(mut self) {
+        while let Some(msg) = self.receiver.recv().await {
+            match msg {
+                InterIpcpFalMessage::GetOrCreateFlow {
+                    remote_addr,
+                    socket_addr,
+                    response,
+                } => {
+                    let result = match socket_addr {
+                        Some(addr) => {
+                            self.allocator.update_peer_address(remote_addr, addr);
+                            Ok(())
+                        }
+                        None => self
+                            .allocator
+                            .get_or_create_flow(remote_addr)
+                            .await
+                            .map_err(|e| RmtError::ForwardingFailed(e).into()),
+                    };
+                    let _ = response.send(result);
+                }
+                InterIpcpFalMessage::SendPdu {
+                    next_hop,
+                    pdu,
+                    response,
+                } => {
+                    let result = self
+                        .allocator
+                        .send_pdu_with_recovery(next_hop, &pdu)
+                        .await
+                        .map_err(|e| RmtError::ForwardingFailed(e).into());
+                    let _ = response.send(result);
+                }
+                InterIpcpFalMessage::RecordReceived {
+                    remote_addr,
+                    socket_addr,
+                } => {
+                    self.allocator
+                        .record_received_from(remote_addr, socket_addr);
+                }
+                InterIpcpFalMessage::CleanupStale { response } => {
+                    let removed = self.allocator.cleanup_stale_flows();
+                    let _ = response.send(removed);
+                }
+                InterIpcpFalMessage::GetStats { response } => {
+                    let _ = response.send(self.allocator.get_flow_stats());
                 }
             }
         }
@@ -347,15 +719,28 @@ impl RmtActor {
 pub enum ShimMessage {
     Bind {
         addr: String,
-        response: mpsc::Sender<Result<(), String>>,
+        response: oneshot::Sender<Result<(), AriError>>,
     },
     Send {
         data: Vec<u8>,
         dest: String,
-        response: mpsc::Sender<Result<usize, String>>,
+        response: oneshot::Sender<Result<usize, AriError>>,
     },
     GetLocalAddr {
-        response: mpsc::Sender<Result<String, String>>,
+        response: oneshot::Sender<Result<String, AriError>>,
+    },
+    RegisterPeer {
+        rina_addr: u64,
+        socket_addr: SocketAddr,
+        response: oneshot::Sender<()>,
+    },
+    SendPdu {
+        pdu: Pdu,
+        next_hop: u64,
+        response: oneshot::Sender<Result<usize, AriError>>,
+    },
+    GetStats {
+        response: oneshot::Sender<ShimStats>,
     },
 }
 
@@ -378,8 +763,10 @@ impl ShimActor {
             match msg {
                 ShimMessage::Bind { addr, response } => {
                     let shim = self.shim.read().await;
-                    let result = shim.bind(&addr).map_err(|e| e.to_string());
-                    let _ = response.send(result).await;
+                    let result = shim
+                        .bind(&addr)
+                        .map_err(|e| ShimError::BindFailed(e.to_string()).into());
+                    let _ = response.send(result);
                 }
                 ShimMessage::Send {
                     data,
@@ -387,16 +774,42 @@ impl ShimActor {
                     response,
                 } => {
                     let shim = self.shim.read().await;
-                    let result = shim.send_to(&data, &dest).map_err(|e| e.to_string());
-                    let _ = response.send(result).await;
+                    let result = shim
+                        .send_to(&data, &dest)
+                        .map_err(|e| ShimError::SendFailed(e.to_string()).into());
+                    let _ = response.send(result);
                 }
                 ShimMessage::GetLocalAddr { response } => {
                     let shim = self.shim.read().await;
                     let result = shim
                         .local_addr()
                         .map(|a| a.to_string())
-                        .map_err(|e| e.to_string());
-                    let _ = response.send(result).await;
+                        .map_err(|e| ShimError::IoError(e.to_string()).into());
+                    let _ = response.send(result);
+                }
+                ShimMessage::RegisterPeer {
+                    rina_addr,
+                    socket_addr,
+                    response,
+                } => {
+                    let shim = self.shim.read().await;
+                    shim.register_peer(rina_addr, socket_addr);
+                    let _ = response.send(());
+                }
+                ShimMessage::SendPdu {
+                    pdu,
+                    next_hop,
+                    response,
+                } => {
+                    let shim = self.shim.read().await;
+                    let result = shim
+                        .send_pdu_via(&pdu, next_hop)
+                        .map_err(|e| ShimError::SendFailed(e.to_string()).into());
+                    let _ = response.send(result);
+                }
+                ShimMessage::GetStats { response } => {
+                    let shim = self.shim.read().await;
+                    let _ = response.send(shim.stats());
                 }
             }
         }
@@ -417,38 +830,48 @@ impl ShimActor {
                         break;
                     }
                     _ = tokio::time::sleep(tokio::time::Duration::from_millis(10)) => {
-                        let shim = shim.read().await;
-                        if let Ok(Some((pdu_bytes, src))) = shim.recv_from() {
+                        // The guard is released before the PDU is handed on
+                        let received = shim.read().await.recv_from_async().await;
+                        if let Err(e @ crate::shim::ShimError::Truncated { .. }) = &received {
+                            eprintln!("  ⚠ Dropped datagram: {}", e);
+                        }
+                        if let Ok(Some((pdu_bytes, src))) = received {
                             // Deserialize PDU
-                            match postcard::from_bytes::<Pdu>(&pdu_bytes) {
+                            match Pdu::deserialize(&pdu_bytes) {
                                 Ok(pdu) => {
-                                    println!("📥 Received PDU from {} → dst:{} ({}bytes)",
+                                    progress!("📥 Received PDU from {} → dst:{} ({}bytes)",
                                         src, pdu.dst_addr, pdu_bytes.len());
 
                                     // Send to RMT for processing
-                                    let (resp_tx, mut resp_rx) = mpsc::channel(1);
+                                    let (resp_tx, resp_rx) = oneshot::channel();
                                     let _ = rmt_handle.send(RmtMessage::ProcessIncoming {
                                         pdu: pdu.clone(),
                                         response: resp_tx,
                                     }).await;
 
                                     // Check if PDU is for local delivery
-                                    if let Some(Ok(Some(local_addr))) = resp_rx.recv().await {
+                                    if let Ok(Ok(Some(local_addr))) = resp_rx.await {
                                         if local_addr == local_rina_addr {
-                                            println!("  ✓ PDU is for local delivery, passing to EFCP");
+                                            progress!("  ✓ PDU is for local delivery, passing to EFCP");
 
                                             // Deliver to EFCP
-                                            let (efcp_tx, mut efcp_rx) = mpsc::channel(1);
+                                            let (efcp_tx, efcp_rx) = oneshot::channel();
                                             let _ = efcp_handle.send(EfcpMessage::ReceivePdu {
                                                 pdu,
                                                 response: efcp_tx,
                                             }).await;
 
-                                            if let Some(Ok(Some(data))) = efcp_rx.recv().await {
-                                                println!("  ✓ EFCP delivered {} bytes of data", data.len());
+                                            match efcp_rx.await {
+                                                Ok(Ok(Some(data))) => {
+                                                    progress!("  ✓ EFCP delivered {} bytes of data", data.len());
+                                                }
+                                                Ok(Err(e)) => {
+                                                    eprintln!("  ⚠ Dropped PDU: {}", e);
+                                                }
+                                                _ => {}
                                             }
                                         } else {
-                                            println!("  → PDU queued for forwarding to {}", local_addr);
+                                            progress!("  → PDU queued for forwarding to {}", local_addr);
                                         }
                                     }
                                 }
@@ -467,26 +890,68 @@ impl ShimActor {
 /// Actor handle for sending messages to an actor
 pub struct ActorHandle<T> {
     sender: mpsc::Sender<T>,
+    send_timeout: Duration,
 }
 
 impl<T> Clone for ActorHandle<T> {
     fn clone(&self) -> Self {
         Self {
             sender: self.sender.clone(),
+            send_timeout: self.send_timeout,
         }
     }
 }
 
+impl<T> std::fmt::Debug for ActorHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorHandle")
+            .field("closed", &self.sender.is_closed())
+            .field("send_timeout", &self.send_timeout)
+            .finish()
+    }
+}
+
 impl<T> ActorHandle<T> {
     pub fn new(sender: mpsc::Sender<T>) -> Self {
-        Self { sender }
+        Self {
+            sender,
+            send_timeout: DEFAULT_SEND_TIMEOUT,
+        }
     }
 
-    pub async fn send(&self, msg: T) -> Result<(), String> {
-        self.sender
-            .send(msg)
-            .await
-            .map_err(|_| "Failed to send message".to_string())
+    /// Sets how long `send` waits for a full mailbox to drain
+    pub fn with_send_timeout(mut self, timeout: Duration) -> Self {
+        self.send_timeout = timeout;
+        self
+    }
+
+    /// Sends a message, waiting up to the send timeout for mailbox space
+    pub async fn send(&self, msg: T) -> Result<(), ActorError> {
+        match tokio::time::timeout(self.send_timeout, self.sender.send(msg)).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(_)) => Err(ActorError::Closed),
+            Err(_) => Err(ActorError::Timeout),
+        }
+    }
+
+    /// Sends a request and waits for the actor's reply
+    ///
+    /// `make_msg` receives the response channel to embed in the message.
+    pub async fn request<R>(
+        &self,
+        make_msg: impl FnOnce(oneshot::Sender<R>) -> T,
+    ) -> Result<R, AriError> {
+        let (resp_tx, resp_rx) = oneshot::channel();
+        self.send(make_msg(resp_tx)).await?;
+        resp_rx.await.map_err(|_| AriError::ChannelClosed)
+    }
+
+    /// Sends a message without waiting, reporting whether the actor is busy or gone
+    pub fn try_send(&self, msg: T) -> Result<(), ActorError> {
+        self.sender.try_send(msg).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => ActorError::Busy,
+            mpsc::error::TrySendError::Closed(_) => ActorError::Closed,
+        })
     }
 }
 
@@ -494,10 +959,12 @@ pub type RibHandle = ActorHandle<RibMessage>;
 pub type EfcpHandle = ActorHandle<EfcpMessage>;
 pub type RmtHandle = ActorHandle<RmtMessage>;
 pub type ShimHandle = ActorHandle<ShimMessage>;
+pub type InterIpcpFalHandle = ActorHandle<InterIpcpFalMessage>;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::RibError;
 
     #[tokio::test]
     async fn test_rib_actor_create_and_read() {
@@ -511,7 +978,7 @@ mod tests {
         let handle = RibHandle::new(tx);
 
         // Create
-        let (resp_tx, mut resp_rx) = mpsc::channel(1);
+        let (resp_tx, resp_rx) = oneshot::channel();
         handle
             .send(RibMessage::Create {
                 name: "test".to_string(),
@@ -522,11 +989,11 @@ mod tests {
             .await
             .unwrap();
 
-        let result = resp_rx.recv().await.unwrap();
+        let result = resp_rx.await.unwrap();
         assert!(result.is_ok());
 
         // Read
-        let (resp_tx, mut resp_rx) = mpsc::channel(1);
+        let (resp_tx, resp_rx) = oneshot::channel();
         handle
             .send(RibMessage::Read {
                 name: "test".to_string(),
@@ -535,11 +1002,103 @@ mod tests {
             .await
             .unwrap();
 
-        let value = resp_rx.recv().await.unwrap();
+        let value = resp_rx.await.unwrap();
         assert!(value.is_some());
         assert_eq!(value.unwrap().as_integer(), Some(42));
     }
 
+    #[tokio::test]
+    async fn test_request_round_trips_through_rib_actor() {
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(async move {
+            RibActor::new(rx).run().await;
+        });
+        let rib = RibHandle::new(tx);
+
+        rib.request(|response| RibMessage::Create {
+            name: "req".to_string(),
+            class: "test".to_string(),
+            value: RibValue::Integer(7),
+            response,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        let value = rib
+            .request(|response| RibMessage::Read {
+                name: "req".to_string(),
+                response,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(value.and_then(|v| v.as_integer()), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_rib_actor_reports_sync_status_of_its_enrollment_manager() {
+        let rib = Rib::new();
+        let shim = Arc::new(crate::shim::UdpShim::new(1002));
+        let enrollment = EnrollmentManager::new(rib.clone(), shim, 1002);
+        let (tx, rx) = mpsc::channel(32);
+        let mut actor = RibActor::with_rib(rib.clone(), rx);
+        actor.set_sync_source(enrollment.shared());
+        tokio::spawn(actor.run());
+        let handle = RibHandle::new(tx);
+
+        // Objects created through the actor land in the node's RIB ...
+        handle
+            .request(|response| RibMessage::Create {
+                name: "/dif/app".to_string(),
+                class: "app".to_string(),
+                value: RibValue::Integer(1),
+                response,
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(rib.read("/dif/app").await.is_ok());
+
+        // ... and are reported as waiting to be pushed to the bootstrap
+        let status = handle
+            .request(|response| RibMessage::SyncStatus { response })
+            .await
+            .unwrap();
+        assert_eq!(status, enrollment.sync_status().await);
+        assert_eq!(status.local_version, rib.current_version().await);
+        assert_eq!(status.pending, 1);
+    }
+
+    #[tokio::test]
+    async fn test_rib_actor_duplicate_create_returns_typed_error() {
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(async move {
+            RibActor::new(rx).run().await;
+        });
+        let handle = RibHandle::new(tx);
+
+        let mut results = Vec::new();
+        for _ in 0..2 {
+            let (resp_tx, resp_rx) = oneshot::channel();
+            handle
+                .send(RibMessage::Create {
+                    name: "dup".to_string(),
+                    class: "test".to_string(),
+                    value: RibValue::Integer(1),
+                    response: resp_tx,
+                })
+                .await
+                .unwrap();
+            results.push(resp_rx.await.unwrap());
+        }
+
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            &results[1],
+            Err(AriError::Rib(RibError::AlreadyExists(name))) if name == "dup"
+        ));
+    }
+
     #[tokio::test]
     async fn test_efcp_actor_allocate_flow() {
         let (tx, rx) = mpsc::channel(32);
@@ -551,18 +1110,430 @@ mod tests {
 
         let handle = EfcpHandle::new(tx);
 
-        let (resp_tx, mut resp_rx) = mpsc::channel(1);
+        let (resp_tx, resp_rx) = oneshot::channel();
         handle
             .send(EfcpMessage::AllocateFlow {
                 local_addr: 1000,
                 remote_addr: 2000,
                 config: FlowConfig::default(),
+                key: None,
+                remote_cep_id: None,
                 response: resp_tx,
             })
             .await
             .unwrap();
 
-        let flow_id = resp_rx.recv().await.unwrap();
+        let flow_id = resp_rx.await.unwrap();
         assert_eq!(flow_id, 1);
     }
+
+    /// Spawns an RMT actor at address 1001 wired to a RouteResolver
+    fn spawn_rmt_with_resolver() -> (RmtHandle, Arc<RouteResolver>) {
+        let rib = Rib::new();
+        let resolver = Arc::new(RouteResolver::new(
+            Arc::new(RwLock::new(rib.clone())),
+            crate::routing::RouteResolverConfig::default(),
+        ));
+
+        let shim = Arc::new(UdpShim::new(1001));
+        shim.bind("127.0.0.1:0").unwrap();
+        let flow_allocator = Arc::new(InterIpcpFlowAllocator::new(rib, shim));
+
+        let (fal_tx, fal_rx) = mpsc::channel(32);
+        tokio::spawn(async move {
+            InterIpcpFalActor::new(flow_allocator, fal_rx).run().await;
+        });
+
+        let (tx, rx) = mpsc::channel(32);
+        let mut actor = RmtActor::new(1001, rx);
+        actor.set_fal_handle(InterIpcpFalHandle::new(fal_tx));
+        actor.set_route_resolver(resolver.clone());
+        tokio::spawn(async move {
+            actor.run().await;
+        });
+
+        (RmtHandle::new(tx), resolver)
+    }
+
+    async fn send_outgoing(handle: &RmtHandle, dst_addr: u64) -> Result<u64, AriError> {
+        let (resp_tx, resp_rx) = oneshot::channel();
+        handle
+            .send(RmtMessage::ProcessOutgoing {
+                pdu: Pdu::new_data(1001, dst_addr, 0, 0, 0, b"payload".to_vec()),
+                response: resp_tx,
+            })
+            .await
+            .unwrap();
+        resp_rx.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rmt_actor_forwards_via_dynamic_route() {
+        let (handle, resolver) = spawn_rmt_with_resolver();
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+        resolver
+            .add_dynamic_route(2000, peer.local_addr().unwrap(), Some(3600))
+            .await
+            .unwrap();
+
+        // No forwarding entry exists; only the dynamic route can resolve this
+        assert_eq!(send_outgoing(&handle, 2000).await.unwrap(), 2000);
+
+        let mut buf = [0u8; 1024];
+        let (len, _) = peer.recv_from(&mut buf).unwrap();
+        let pdu = Pdu::deserialize(&buf[..len]).unwrap();
+        assert_eq!(pdu.dst_addr, 2000);
+        assert_eq!(pdu.payload, b"payload");
+    }
+
+    #[tokio::test]
+    async fn test_rmt_actor_drops_pdu_on_expired_route() {
+        let (handle, resolver) = spawn_rmt_with_resolver();
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        resolver
+            .add_dynamic_route(3000, peer.local_addr().unwrap(), Some(1))
+            .await
+            .unwrap();
+
+        // TTL is checked with one-second granularity
+        tokio::time::sleep(std::time::Duration::from_millis(2100)).await;
+
+        let err = send_outgoing(&handle, 3000).await.unwrap_err();
+        assert!(
+            matches!(err, AriError::Rmt(RmtError::RouteNotFound(3000))),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rmt_actor_drains_queue_to_shim_in_scheduled_order() {
+        use crate::pdu::QoSParameters;
+        use crate::policies::PriorityScheduling;
+
+        let with_priority = |seq, priority| {
+            Pdu::new_data_with_qos(
+                1001,
+                3000,
+                0,
+                0,
+                seq,
+                vec![],
+                QoSParameters {
+                    priority,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let mut rmt = Rmt::new(1001);
+        rmt.set_scheduler_factory(|size| Box::new(PriorityScheduling::new(4, size)));
+        rmt.add_forwarding_entry(ForwardingEntry {
+            dst_addr: 3000,
+            next_hop: 2000,
+            cost: 1,
+        })
+        .unwrap();
+        // Two bulk PDUs are already waiting when the urgent one arrives
+        rmt.process_outgoing(with_priority(0, 10)).unwrap();
+        rmt.process_outgoing(with_priority(1, 10)).unwrap();
+
+        let (shim_tx, mut shim_rx) = mpsc::channel(8);
+        let (tx, rx) = mpsc::channel(8);
+        let mut actor = RmtActor::with_rmt(rmt, rx);
+        actor.set_shim_handle(ShimHandle::new(shim_tx));
+        tokio::spawn(actor.run());
+
+        let handle = RmtHandle::new(tx);
+        let outgoing = tokio::spawn(async move {
+            handle
+                .request(|response| RmtMessage::ProcessOutgoing {
+                    pdu: with_priority(2, 250),
+                    response,
+                })
+                .await
+        });
+
+        // Stub shim: record what reaches it and acknowledge each send
+        let mut sent = Vec::new();
+        while sent.len() < 3 {
+            match shim_rx.recv().await.unwrap() {
+                ShimMessage::SendPdu {
+                    pdu,
+                    next_hop,
+                    response,
+                } => {
+                    assert_eq!(next_hop, 2000);
+                    sent.push(pdu.sequence_num);
+                    let _ = response.send(Ok(pdu.size()));
+                }
+                _ => panic!("unexpected shim message"),
+            }
+        }
+
+        assert_eq!(sent, vec![2, 0, 1]);
+        assert_eq!(outgoing.await.unwrap().unwrap().unwrap(), 2000);
+    }
+
+    #[tokio::test]
+    async fn test_rmt_actor_counts_pdus_it_fails_to_send() {
+        let mut rmt = Rmt::new(1001);
+        rmt.add_forwarding_entry(ForwardingEntry {
+            dst_addr: 3000,
+            next_hop: 2000,
+            cost: 1,
+        })
+        .unwrap();
+
+        let (shim_tx, mut shim_rx) = mpsc::channel(8);
+        let (tx, rx) = mpsc::channel(8);
+        let mut actor = RmtActor::with_rmt(rmt, rx);
+        actor.set_shim_handle(ShimHandle::new(shim_tx));
+        tokio::spawn(actor.run());
+
+        // Stub shim that cannot reach the next hop
+        tokio::spawn(async move {
+            while let Some(msg) = shim_rx.recv().await {
+                if let ShimMessage::SendPdu { response, .. } = msg {
+                    let _ = response.send(Err(ShimError::PeerNotRegistered(2000).into()));
+                }
+            }
+        });
+
+        let handle = RmtHandle::new(tx);
+        let result = handle
+            .request(|response| RmtMessage::ProcessOutgoing {
+                pdu: Pdu::new_data(1001, 3000, 0, 0, 0, vec![]),
+                response,
+            })
+            .await
+            .unwrap();
+        assert!(result.is_err());
+
+        let stats = handle
+            .request(|response| RmtMessage::GetStats { response })
+            .await
+            .unwrap();
+        assert_eq!(stats.dropped_send_failed, 1);
+        assert_eq!(stats.total_dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fal_actor_lazily_creates_flow_from_rib_route() {
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let rib = Rib::new();
+        let route = RibValue::from(crate::routing::Route::new(peer.local_addr().unwrap()));
+        rib.create(
+            "/routing/static/2000".to_string(),
+            "static_route".to_string(),
+            route,
+        )
+        .await
+        .unwrap();
+
+        let shim = Arc::new(UdpShim::new(1001));
+        shim.bind("127.0.0.1:0").unwrap();
+        let (tx, rx) = mpsc::channel(32);
+        let actor = InterIpcpFalActor::new(Arc::new(InterIpcpFlowAllocator::new(rib, shim)), rx);
+        tokio::spawn(async move {
+            actor.run().await;
+        });
+        let handle = InterIpcpFalHandle::new(tx);
+
+        // No flow to 2000 exists yet
+        let (resp_tx, resp_rx) = oneshot::channel();
+        handle
+            .send(InterIpcpFalMessage::GetStats { response: resp_tx })
+            .await
+            .unwrap();
+        assert!(resp_rx.await.unwrap().is_empty());
+
+        let (resp_tx, resp_rx) = oneshot::channel();
+        handle
+            .send(InterIpcpFalMessage::SendPdu {
+                next_hop: 2000,
+                pdu: Pdu::new_data(1001, 2000, 0, 0, 0, b"hello".to_vec()),
+                response: resp_tx,
+            })
+            .await
+            .unwrap();
+        resp_rx.await.unwrap().unwrap();
+
+        let mut buf = [0u8; 1024];
+        let (len, _) = peer.recv_from(&mut buf).unwrap();
+        assert_eq!(Pdu::deserialize(&buf[..len]).unwrap().payload, b"hello");
+
+        let (resp_tx, resp_rx) = oneshot::channel();
+        handle
+            .send(InterIpcpFalMessage::GetStats { response: resp_tx })
+            .await
+            .unwrap();
+        let stats = resp_rx.await.unwrap();
+        assert_eq!(stats, vec![(2000, InterIpcpFlowState::Active, 1, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_try_send_reports_busy_before_closed() {
+        // Nobody drains this mailbox, as if the actor had stalled
+        let (tx, rx) = mpsc::channel(1);
+        let handle = RibHandle::new(tx).with_send_timeout(std::time::Duration::from_millis(50));
+
+        let count = || RibMessage::Count {
+            response: oneshot::channel().0,
+        };
+
+        handle.try_send(count()).unwrap();
+        assert_eq!(handle.try_send(count()), Err(ActorError::Busy));
+        assert_eq!(handle.send(count()).await, Err(ActorError::Timeout));
+
+        drop(rx);
+        assert_eq!(handle.try_send(count()), Err(ActorError::Closed));
+        assert_eq!(handle.send(count()).await, Err(ActorError::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_static_and_dynamic_routes_populate_identical_entries() {
+        let hop: SocketAddr = "127.0.0.1:9100".parse().unwrap();
+        let actor_over = |rib: Rib| async move {
+            let resolver = Arc::new(RouteResolver::new(
+                Arc::new(RwLock::new(rib)),
+                crate::routing::RouteResolverConfig::default(),
+            ));
+            let (_tx, rx) = mpsc::channel(1);
+            let mut actor = RmtActor::new(1001, rx);
+            actor.set_route_resolver(resolver.clone());
+            (actor, resolver)
+        };
+
+        // A static route as loaded from configuration
+        let static_rib = Rib::new();
+        static_rib
+            .create(
+                "/routing/static/2000".to_string(),
+                "static_route".to_string(),
+                crate::routing::Route::new(hop)
+                    .with_next_hop_rina_addr(2000)
+                    .with_cost(3)
+                    .into(),
+            )
+            .await
+            .unwrap();
+        let (static_actor, _) = actor_over(static_rib).await;
+        assert_eq!(static_actor.populate_forwarding_table().await, 1);
+
+        // The same route learned during enrollment
+        let (dynamic_actor, resolver) = actor_over(Rib::new()).await;
+        resolver
+            .add_dynamic_route_with_cost(2000, hop, None, 3)
+            .await
+            .unwrap();
+        assert_eq!(dynamic_actor.populate_forwarding_table().await, 1);
+
+        let expected = vec![ForwardingEntry {
+            dst_addr: 2000,
+            next_hop: 2000,
+            cost: 3,
+        }];
+        assert_eq!(static_actor.rmt.read().await.export_table(), expected);
+        assert_eq!(dynamic_actor.rmt.read().await.export_table(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_rmt_hands_management_pdus_to_enrollment() {
+        use crate::cdap::{CdapMessage, CdapOpCode, CdapResult};
+        use crate::enrollment::EnrollmentManager;
+        use crate::shim::UdpShim;
+        use std::time::Duration;
+
+        let bootstrap_shim = Arc::new(UdpShim::new(1000));
+        bootstrap_shim.bind("127.0.0.1:0").unwrap();
+        let member_shim = UdpShim::new(2000);
+        member_shim.bind("127.0.0.1:0").unwrap();
+        bootstrap_shim.register_peer(2000, member_shim.local_addr().unwrap());
+        let enrollment = EnrollmentManager::new(Rib::new(), bootstrap_shim, 1000);
+
+        let (management_tx, management_rx) = mpsc::channel(8);
+        enrollment.start_management_handler(management_rx);
+        let (tx, rx) = mpsc::channel(8);
+        let mut actor = RmtActor::new(1000, rx);
+        actor.set_management_sender(management_tx);
+        tokio::spawn(actor.run());
+        let rmt = RmtHandle::new(tx);
+
+        let request = CdapMessage {
+            op_code: CdapOpCode::Read,
+            obj_name: "/routing/table".to_string(),
+            obj_class: Some("routing".to_string()),
+            obj_value: None,
+            invoke_id: 7,
+            result: CdapResult::Success,
+            result_reason: None,
+            sync_request: None,
+            sync_response: None,
+        };
+        let payload = request.to_payloads(1024).unwrap().remove(0);
+        let delivered = rmt
+            .request(|response| RmtMessage::ProcessIncoming {
+                pdu: Pdu::new_management(2000, 1000, payload),
+                response,
+            })
+            .await
+            .unwrap();
+        assert_eq!(delivered.unwrap(), None);
+
+        // The enrollment manager answered the member over the shim
+        let (reply, _) = member_shim
+            .receive_pdu_within(Duration::from_secs(2))
+            .await
+            .unwrap()
+            .expect("management reply");
+        assert!(reply.is_management());
+        assert_eq!(reply.src_addr, 1000);
+    }
+
+    #[tokio::test]
+    async fn test_receiver_hands_efcp_decompressed_payloads() {
+        let receiver = UdpShim::new(1001);
+        receiver.bind("127.0.0.1:0").unwrap();
+        let receiver_socket = receiver.local_addr().unwrap();
+        let sender = UdpShim::new(2000);
+        sender.bind("127.0.0.1:0").unwrap();
+        sender.register_peer(1001, receiver_socket);
+
+        let (rmt_tx, mut rmt_rx) = mpsc::channel(4);
+        let (efcp_tx, mut efcp_rx) = mpsc::channel(4);
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        ShimActor::spawn_receiver(
+            Arc::new(RwLock::new(receiver)),
+            RmtHandle::new(rmt_tx),
+            EfcpHandle::new(efcp_tx),
+            1001,
+            shutdown_rx,
+        )
+        .await;
+
+        let payload = vec![b'a'; crate::pdu::COMPRESSION_THRESHOLD * 2];
+        sender
+            .send_pdu(&Pdu::new_data(2000, 1001, 0, 0, 0, payload.clone()))
+            .unwrap();
+
+        let Some(RmtMessage::ProcessIncoming { response, .. }) = rmt_rx.recv().await else {
+            panic!("expected the PDU to reach the RMT");
+        };
+        response.send(Ok(Some(1001))).unwrap();
+
+        let Some(EfcpMessage::ReceivePdu { pdu, response }) = efcp_rx.recv().await else {
+            panic!("expected the PDU to reach EFCP");
+        };
+        let _ = response.send(Ok(None));
+        assert!(!pdu.compressed);
+        assert_eq!(pdu.payload, payload);
+    }
 }
@@ -6,10 +6,135 @@
 //! CDAP is used for distributed object management across IPCPs in a DIF.
 //! It enables RIB synchronization and provides operations for managing
 //! distributed state: CREATE, DELETE, READ, WRITE, START, STOP.
+//!
+//! Peers exchange [`CdapCapabilities`] when a member enrolls, so that a
+//! bootstrap and member running different builds only use the features
+//! both of them understand.
+//!
+//! Every [`CdapSession`] draws a fresh epoch that forms the upper half of
+//! its invoke_ids, so responses meant for a session torn down by a
+//! reconnect cannot be mistaken for answers to the new session's requests.
 
+use crate::error::{CdapError, RibError};
 use crate::rib::{Rib, RibChange, RibValue};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+/// Object class of a CDAP message carrying one fragment of a larger message
+pub const FRAGMENT_CLASS: &str = "cdap_fragment";
+
+/// Default maximum number of message bytes carried by a single fragment
+pub const DEFAULT_MAX_FRAGMENT_SIZE: usize = 8 * 1024;
+
+/// Epoch handed to the next session, relative to the process' random base
+static NEXT_SESSION_EPOCH: AtomicU32 = AtomicU32::new(0);
+
+/// Returns a session epoch no other session in this process has used
+///
+/// Epochs count up from a random base, so sessions of a restarted process
+/// are unlikely to reuse the epochs of its previous run either. Zero is
+/// skipped, keeping every invoke_id non-zero.
+fn next_session_epoch() -> u32 {
+    static BASE: OnceLock<u32> = OnceLock::new();
+    let base = *BASE.get_or_init(|| fastrand::u32(..));
+    loop {
+        let epoch = base.wrapping_add(NEXT_SESSION_EPOCH.fetch_add(1, Ordering::Relaxed));
+        if epoch != 0 {
+            return epoch;
+        }
+    }
+}
+
+/// Incomplete messages older than this are discarded by the reassembler
+const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest message the reassembler will put back together (bytes)
+pub const MAX_REASSEMBLED_SIZE: usize = 16 * 1024 * 1024;
+
+/// Most fragments a single message may announce
+///
+/// Fragments may be smaller than the default, so this allows eight times
+/// as many as a maximum-size message cut at `DEFAULT_MAX_FRAGMENT_SIZE`.
+pub const MAX_FRAGMENTS: usize = 8 * MAX_REASSEMBLED_SIZE.div_ceil(DEFAULT_MAX_FRAGMENT_SIZE);
+
+/// Object name of a READ that lists every object of its `obj_class`
+///
+/// The response value is a struct keyed by object name. Each entry holds the
+/// object's value if the request's value was `Boolean(true)`, otherwise its
+/// version.
+pub const CLASS_SCOPE_NAME: &str = "*";
+
+/// CDAP protocol version spoken by this build
+pub const CDAP_VERSION: u16 = 1;
+
+/// Version assumed for peers that predate capability negotiation
+pub const LEGACY_CDAP_VERSION: u16 = 0;
+
+/// Protocol version and optional features a CDAP peer supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CdapCapabilities {
+    /// Highest CDAP protocol version understood
+    pub version: u16,
+    /// Can apply incremental `SyncResponse::changes` instead of full snapshots
+    pub incremental_sync: bool,
+    /// Can decode PDUs with compressed payloads
+    pub compression: bool,
+}
+
+impl CdapCapabilities {
+    /// Returns everything this build supports
+    pub fn local() -> Self {
+        Self {
+            version: CDAP_VERSION,
+            incremental_sync: true,
+            compression: cfg!(feature = "compression"),
+        }
+    }
+
+    /// Returns the features every peer supports, assumed for a peer that
+    /// never advertised its capabilities
+    pub fn minimal() -> Self {
+        Self {
+            version: LEGACY_CDAP_VERSION,
+            incremental_sync: false,
+            compression: false,
+        }
+    }
+
+    /// Disables incremental sync, so RIB syncs always carry a full snapshot
+    pub fn without_incremental_sync(mut self) -> Self {
+        self.incremental_sync = false;
+        self
+    }
+
+    /// Returns the capabilities both sides share
+    ///
+    /// The lower of the two versions is used and a feature is only enabled
+    /// when both peers support it and the version knows about it; a legacy
+    /// version gets the minimal set.
+    pub fn negotiate(&self, peer: &CdapCapabilities) -> Self {
+        let version = self.version.min(peer.version);
+        if version == LEGACY_CDAP_VERSION {
+            return Self::minimal();
+        }
+        Self {
+            version,
+            incremental_sync: self.incremental_sync && peer.incremental_sync,
+            compression: self.compression && peer.compression,
+        }
+    }
+}
+
+impl Default for CdapCapabilities {
+    fn default() -> Self {
+        Self::minimal()
+    }
+}
 
 /// CDAP operation types
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,6 +151,8 @@ pub enum CdapOpCode {
     Start,
     /// Stop an operation
     Stop,
+    /// Apply several operations carried in the value, in order
+    Batch,
 }
 
 impl fmt::Display for CdapOpCode {
@@ -37,6 +164,105 @@ impl fmt::Display for CdapOpCode {
             CdapOpCode::Write => write!(f, "WRITE"),
             CdapOpCode::Start => write!(f, "START"),
             CdapOpCode::Stop => write!(f, "STOP"),
+            CdapOpCode::Batch => write!(f, "BATCH"),
+        }
+    }
+}
+
+/// Outcome of a CDAP operation, carried in a response's result field
+///
+/// On the wire this is the plain integer code, so peers that only know the
+/// "0 = success" convention keep working. Codes without a variant here are
+/// kept as `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum CdapResult {
+    /// The operation succeeded
+    Success,
+    /// The peer refused the request (e.g. an enrollment or sync rejection)
+    Rejected,
+    /// The operation failed for a reason not covered by another code
+    InternalError,
+    /// The named object does not exist
+    ObjectNotFound,
+    /// The request was malformed or inconsistent with the object
+    InvalidRequest,
+    /// The requester may not perform the operation
+    Unauthorized,
+    /// The object to create already exists
+    AlreadyExists,
+    /// The object changed since the version the request was based on
+    Conflict,
+    /// The operation is not supported
+    NotImplemented,
+    /// A code this implementation does not know
+    Other(i32),
+}
+
+impl CdapResult {
+    /// Returns the integer code sent on the wire
+    pub fn code(self) -> i32 {
+        match self {
+            CdapResult::Success => 0,
+            CdapResult::Rejected => 1,
+            CdapResult::InternalError => -1,
+            CdapResult::ObjectNotFound => -2,
+            CdapResult::InvalidRequest => -3,
+            CdapResult::Unauthorized => -4,
+            CdapResult::AlreadyExists => -5,
+            CdapResult::Conflict => -6,
+            CdapResult::NotImplemented => -7,
+            CdapResult::Other(code) => code,
+        }
+    }
+
+    /// Returns whether this is `Success`
+    pub fn is_success(self) -> bool {
+        self == CdapResult::Success
+    }
+}
+
+impl From<i32> for CdapResult {
+    fn from(code: i32) -> Self {
+        match code {
+            0 => CdapResult::Success,
+            1 => CdapResult::Rejected,
+            -1 => CdapResult::InternalError,
+            -2 => CdapResult::ObjectNotFound,
+            -3 => CdapResult::InvalidRequest,
+            -4 => CdapResult::Unauthorized,
+            -5 => CdapResult::AlreadyExists,
+            -6 => CdapResult::Conflict,
+            -7 => CdapResult::NotImplemented,
+            code => CdapResult::Other(code),
+        }
+    }
+}
+
+impl From<CdapResult> for i32 {
+    fn from(result: CdapResult) -> Self {
+        result.code()
+    }
+}
+
+impl From<&RibError> for CdapResult {
+    fn from(error: &RibError) -> Self {
+        match error {
+            RibError::NotFound(_) => CdapResult::ObjectNotFound,
+            RibError::AlreadyExists(_) => CdapResult::AlreadyExists,
+            RibError::VersionConflict { .. } => CdapResult::Conflict,
+            RibError::InvalidName(_) | RibError::InvalidClass(_) => CdapResult::InvalidRequest,
+            RibError::AccessDenied(_) => CdapResult::Unauthorized,
+            _ => CdapResult::InternalError,
+        }
+    }
+}
+
+impl fmt::Display for CdapResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CdapResult::Other(code) => write!(f, "code {}", code),
+            known => write!(f, "{:?} ({})", known, known.code()),
         }
     }
 }
@@ -48,14 +274,14 @@ pub struct CdapMessage {
     pub op_code: CdapOpCode,
     /// Object name (path in RIB)
     pub obj_name: String,
-    /// Object class
+    /// Object class (on a WRITE, creates the object if it does not exist yet)
     pub obj_class: Option<String>,
     /// Object value (for CREATE/WRITE operations)
     pub obj_value: Option<RibValue>,
     /// Unique invoke ID for request/response matching
     pub invoke_id: u64,
-    /// Result code (0 = success, non-zero = error)
-    pub result: i32,
+    /// Outcome of the operation (responses only)
+    pub result: CdapResult,
     /// Result reason (error message if result != 0)
     pub result_reason: Option<String>,
     /// Sync request (for incremental RIB synchronization)
@@ -66,6 +292,62 @@ pub struct CdapMessage {
     pub sync_response: Option<SyncResponse>,
 }
 
+/// One operation inside a BATCH message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CdapOp {
+    /// Operation code (anything but BATCH)
+    pub op_code: CdapOpCode,
+    /// Object name (path in RIB)
+    pub obj_name: String,
+    /// Object class
+    pub obj_class: Option<String>,
+    /// Object value
+    pub obj_value: Option<RibValue>,
+}
+
+impl CdapOp {
+    /// Creates a CREATE operation
+    pub fn create(obj_name: String, obj_class: String, obj_value: RibValue) -> Self {
+        Self {
+            op_code: CdapOpCode::Create,
+            obj_name,
+            obj_class: Some(obj_class),
+            obj_value: Some(obj_value),
+        }
+    }
+
+    /// Creates a WRITE operation
+    pub fn write(obj_name: String, obj_value: RibValue) -> Self {
+        Self {
+            op_code: CdapOpCode::Write,
+            obj_name,
+            obj_class: None,
+            obj_value: Some(obj_value),
+        }
+    }
+
+    /// Creates a DELETE operation
+    pub fn delete(obj_name: String) -> Self {
+        Self {
+            op_code: CdapOpCode::Delete,
+            obj_name,
+            obj_class: None,
+            obj_value: None,
+        }
+    }
+}
+
+/// Outcome of one operation of a BATCH, in the same slot as the operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CdapOpResult {
+    /// Outcome of the operation
+    pub result: CdapResult,
+    /// Error message if the operation failed
+    pub result_reason: Option<String>,
+    /// Value returned by the operation (READ)
+    pub obj_value: Option<RibValue>,
+}
+
 /// Sync request message (sent by member to bootstrap)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncRequest {
@@ -73,25 +355,84 @@ pub struct SyncRequest {
     pub last_known_version: u64,
     /// Requesting IPCP name
     pub requester: String,
+    /// Requester's local changes the bootstrap has not seen yet
+    #[serde(default)]
+    pub changes: Vec<RibChange>,
 }
 
 /// Sync response message (sent by bootstrap to member)
+///
+/// All fields are always serialized: postcard is not self-describing, so
+/// skipping `None` fields would make the response undecodable.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncResponse {
     /// Current RIB version on bootstrap
     pub current_version: u64,
     /// Changes since requested version (None = full sync required)
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub changes: Option<Vec<RibChange>>,
     /// Full snapshot (if changes is None)
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub full_snapshot: Option<Vec<u8>>,
     /// Error message if sync failed
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
+/// One fragment of an oversized, serialized CDAP message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdapFragment {
+    /// Position of this fragment (0-based)
+    pub index: u32,
+    /// Total number of fragments in the message
+    pub total: u32,
+    /// Slice of the serialized original message
+    pub data: Vec<u8>,
+}
+
 impl CdapMessage {
+    /// Serializes this message into one or more PDU payloads
+    ///
+    /// Messages whose serialized form exceeds `max_fragment_size` bytes are
+    /// split into `FRAGMENT_CLASS` messages sharing this message's invoke_id,
+    /// to be put back together by a `CdapReassembler`.
+    pub fn to_payloads(&self, max_fragment_size: usize) -> Result<Vec<Vec<u8>>, CdapError> {
+        let bytes =
+            postcard::to_allocvec(self).map_err(|e| CdapError::InvalidFormat(e.to_string()))?;
+
+        if bytes.len() <= max_fragment_size {
+            return Ok(vec![bytes]);
+        }
+
+        let chunks: Vec<&[u8]> = bytes.chunks(max_fragment_size.max(1)).collect();
+        let total = chunks.len() as u32;
+
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let fragment = CdapFragment {
+                    index: index as u32,
+                    total,
+                    data: chunk.to_vec(),
+                };
+                let fragment_bytes = postcard::to_allocvec(&fragment)
+                    .map_err(|e| CdapError::InvalidFormat(e.to_string()))?;
+                let mut msg = CdapMessage::new_request(
+                    self.op_code.clone(),
+                    self.obj_name.clone(),
+                    Some(FRAGMENT_CLASS.to_string()),
+                    Some(RibValue::Bytes(fragment_bytes)),
+                    self.invoke_id,
+                );
+                msg.result = self.result;
+                postcard::to_allocvec(&msg).map_err(|e| CdapError::InvalidFormat(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Checks if this message is a fragment of a larger message
+    pub fn is_fragment(&self) -> bool {
+        self.obj_class.as_deref() == Some(FRAGMENT_CLASS)
+    }
+
     /// Creates a new CDAP request message
     pub fn new_request(
         op_code: CdapOpCode,
@@ -106,7 +447,7 @@ impl CdapMessage {
             obj_class,
             obj_value,
             invoke_id,
-            result: 0,
+            result: CdapResult::Success,
             result_reason: None,
             sync_request: None,
             sync_response: None,
@@ -114,7 +455,7 @@ impl CdapMessage {
     }
 
     /// Creates a new CDAP response message
-    pub fn new_response(invoke_id: u64, result: i32, result_reason: Option<String>) -> Self {
+    pub fn new_response(invoke_id: u64, result: CdapResult, result_reason: Option<String>) -> Self {
         Self {
             op_code: CdapOpCode::Read, // Placeholder
             obj_name: String::new(),
@@ -130,17 +471,28 @@ impl CdapMessage {
 
     /// Creates a new sync request message
     pub fn new_sync_request(invoke_id: u64, last_known_version: u64, requester: String) -> Self {
+        Self::new_sync_request_with_changes(invoke_id, last_known_version, requester, Vec::new())
+    }
+
+    /// Creates a sync request that also pushes the requester's local changes
+    pub fn new_sync_request_with_changes(
+        invoke_id: u64,
+        last_known_version: u64,
+        requester: String,
+        changes: Vec<RibChange>,
+    ) -> Self {
         Self {
             op_code: CdapOpCode::Read,
             obj_name: "rib_sync".to_string(),
             obj_class: Some("sync".to_string()),
             obj_value: None,
             invoke_id,
-            result: 0,
+            result: CdapResult::Success,
             result_reason: None,
             sync_request: Some(SyncRequest {
                 last_known_version,
                 requester,
+                changes,
             }),
             sync_response: None,
         }
@@ -160,7 +512,11 @@ impl CdapMessage {
             obj_class: Some("sync".to_string()),
             obj_value: None,
             invoke_id,
-            result: if error.is_some() { 1 } else { 0 },
+            result: if error.is_some() {
+                CdapResult::Rejected
+            } else {
+                CdapResult::Success
+            },
             result_reason: error.clone(),
             sync_request: None,
             sync_response: Some(SyncResponse {
@@ -174,7 +530,179 @@ impl CdapMessage {
 
     /// Checks if this is a successful response
     pub fn is_success(&self) -> bool {
-        self.result == 0
+        self.result.is_success()
+    }
+
+    /// Decodes the per-operation results of a BATCH response
+    pub fn batch_results(&self) -> Result<Vec<CdapOpResult>, CdapError> {
+        match &self.obj_value {
+            Some(RibValue::Bytes(bytes)) => {
+                postcard::from_bytes(bytes).map_err(|e| CdapError::InvalidFormat(e.to_string()))
+            }
+            _ => Err(CdapError::InvalidFormat(
+                "BATCH response carries no results".to_string(),
+            )),
+        }
+    }
+}
+
+/// Partially received fragmented message
+#[derive(Debug)]
+struct PartialMessage {
+    /// Received fragments, indexed by position
+    fragments: Vec<Option<Vec<u8>>>,
+    /// Number of distinct fragments received so far
+    received: usize,
+    /// Total size of the fragments received so far
+    bytes: usize,
+    /// When the first fragment arrived
+    started: Instant,
+}
+
+/// Reassembles fragmented CDAP messages
+///
+/// Fragments are keyed by (sender socket, invoke_id), so interleaved
+/// messages from different peers do not mix, even before enrollment when
+/// every sender still uses RINA address 0.
+#[derive(Debug, Default)]
+pub struct CdapReassembler {
+    /// Messages still waiting for fragments
+    partial: HashMap<(SocketAddr, u64), PartialMessage>,
+}
+
+impl CdapReassembler {
+    /// Creates an empty reassembler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds one PDU payload received from `sender`
+    ///
+    /// # Returns
+    /// * `Ok(Some(msg))` - A complete message (unfragmented or fully reassembled)
+    /// * `Ok(None)` - A fragment was buffered; more are needed
+    /// * `Err(CdapError)` - The payload or reassembled message is malformed,
+    ///   or the message would exceed `MAX_FRAGMENTS` / `MAX_REASSEMBLED_SIZE`
+    pub fn accept(
+        &mut self,
+        sender: SocketAddr,
+        payload: &[u8],
+    ) -> Result<Option<CdapMessage>, CdapError> {
+        let msg: CdapMessage =
+            postcard::from_bytes(payload).map_err(|e| CdapError::InvalidFormat(e.to_string()))?;
+
+        if !msg.is_fragment() {
+            return Ok(Some(msg));
+        }
+
+        let fragment: CdapFragment = match &msg.obj_value {
+            Some(RibValue::Bytes(bytes)) => {
+                postcard::from_bytes(bytes).map_err(|e| CdapError::InvalidFormat(e.to_string()))?
+            }
+            _ => {
+                return Err(CdapError::InvalidFormat(
+                    "Fragment without payload".to_string(),
+                ));
+            }
+        };
+
+        if fragment.total == 0 || fragment.index >= fragment.total {
+            return Err(CdapError::InvalidFormat(format!(
+                "Fragment index {} out of range (total {})",
+                fragment.index, fragment.total
+            )));
+        }
+
+        if fragment.total as usize > MAX_FRAGMENTS {
+            return Err(CdapError::InvalidFormat(format!(
+                "Fragment count {} exceeds the limit of {}",
+                fragment.total, MAX_FRAGMENTS
+            )));
+        }
+
+        self.partial
+            .retain(|_, partial| partial.started.elapsed() < REASSEMBLY_TIMEOUT);
+
+        let key = (sender, msg.invoke_id);
+        let partial = self.partial.entry(key).or_insert_with(|| PartialMessage {
+            fragments: vec![None; fragment.total as usize],
+            received: 0,
+            bytes: 0,
+            started: Instant::now(),
+        });
+
+        if partial.fragments.len() != fragment.total as usize {
+            self.partial.remove(&key);
+            return Err(CdapError::InvalidFormat(
+                "Fragment count changed mid-message".to_string(),
+            ));
+        }
+
+        let slot = &mut partial.fragments[fragment.index as usize];
+        if slot.is_none() {
+            partial.bytes += fragment.data.len();
+            *slot = Some(fragment.data);
+            partial.received += 1;
+        }
+        if partial.bytes > MAX_REASSEMBLED_SIZE {
+            self.partial.remove(&key);
+            return Err(CdapError::InvalidFormat(format!(
+                "Reassembled message exceeds {} bytes",
+                MAX_REASSEMBLED_SIZE
+            )));
+        }
+
+        if partial.received < partial.fragments.len() {
+            return Ok(None);
+        }
+
+        let partial = self.partial.remove(&key).expect("entry exists");
+        let bytes: Vec<u8> = partial.fragments.into_iter().flatten().flatten().collect();
+        postcard::from_bytes(&bytes)
+            .map(Some)
+            .map_err(|e| CdapError::InvalidFormat(e.to_string()))
+    }
+
+    /// Returns the number of messages still being reassembled
+    pub fn pending(&self) -> usize {
+        self.partial.len()
+    }
+}
+
+/// Issues invoke IDs from an epoch no other generator in this process uses
+#[derive(Debug)]
+pub struct InvokeIds {
+    /// Epoch of this generator, the upper 32 bits of its invoke IDs
+    epoch: u32,
+    /// Sequence number of the next ID, the lower 32 bits
+    next_sequence: u32,
+}
+
+impl InvokeIds {
+    /// Creates a generator with a fresh epoch
+    pub fn new() -> Self {
+        Self {
+            epoch: next_session_epoch(),
+            next_sequence: 1,
+        }
+    }
+
+    /// Returns the epoch shared by every ID this generator issues
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// Returns the next invoke ID
+    pub fn next_id(&mut self) -> u64 {
+        let id = (u64::from(self.epoch) << 32) | u64::from(self.next_sequence);
+        self.next_sequence = self.next_sequence.checked_add(1).unwrap_or(1);
+        id
+    }
+}
+
+impl Default for InvokeIds {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -183,24 +711,32 @@ impl CdapMessage {
 pub struct CdapSession {
     /// Local RIB
     rib: Rib,
-    /// Next invoke ID for outgoing requests
-    next_invoke_id: u64,
+    /// Source of this session's invoke IDs
+    invoke_ids: InvokeIds,
 }
 
 impl CdapSession {
-    /// Creates a new CDAP session with the given RIB
+    /// Creates a new CDAP session with the given RIB and a fresh epoch
     pub fn new(rib: Rib) -> Self {
         Self {
             rib,
-            next_invoke_id: 1,
+            invoke_ids: InvokeIds::new(),
         }
     }
 
+    /// Returns the epoch shared by every invoke ID this session issues
+    pub fn epoch(&self) -> u32 {
+        self.invoke_ids.epoch()
+    }
+
+    /// Returns the epoch of the session that issued `invoke_id`
+    pub fn epoch_of(invoke_id: u64) -> u32 {
+        (invoke_id >> 32) as u32
+    }
+
     /// Generates the next invoke ID
     fn next_invoke_id(&mut self) -> u64 {
-        let id = self.next_invoke_id;
-        self.next_invoke_id += 1;
-        id
+        self.invoke_ids.next_id()
     }
 
     /// Creates a CREATE request message
@@ -230,6 +766,17 @@ impl CdapSession {
         )
     }
 
+    /// Creates a READ request listing all objects of `obj_class`
+    pub fn list_request(&mut self, obj_class: String, with_values: bool) -> CdapMessage {
+        CdapMessage::new_request(
+            CdapOpCode::Read,
+            CLASS_SCOPE_NAME.to_string(),
+            Some(obj_class),
+            Some(RibValue::Boolean(with_values)),
+            self.next_invoke_id(),
+        )
+    }
+
     /// Creates a WRITE request message
     pub fn write_request(&mut self, obj_name: String, obj_value: RibValue) -> CdapMessage {
         CdapMessage::new_request(
@@ -241,6 +788,25 @@ impl CdapSession {
         )
     }
 
+    /// Creates a WRITE request that creates the object as `obj_class` if absent
+    ///
+    /// Saves callers applying remote state from having to know whether the
+    /// object exists locally: one round trip either creates or updates it.
+    pub fn upsert_request(
+        &mut self,
+        obj_name: String,
+        obj_class: String,
+        obj_value: RibValue,
+    ) -> CdapMessage {
+        CdapMessage::new_request(
+            CdapOpCode::Write,
+            obj_name,
+            Some(obj_class),
+            Some(obj_value),
+            self.next_invoke_id(),
+        )
+    }
+
     /// Creates a DELETE request message
     pub fn delete_request(&mut self, obj_name: String) -> CdapMessage {
         CdapMessage::new_request(
@@ -252,6 +818,22 @@ impl CdapSession {
         )
     }
 
+    /// Creates a BATCH request applying `ops` in order in one round trip
+    ///
+    /// Operations are not atomic: one failing does not stop the rest, and
+    /// each one's outcome is reported in its slot of the response.
+    pub fn batch_request(&mut self, ops: &[CdapOp]) -> Result<CdapMessage, CdapError> {
+        let ops =
+            postcard::to_allocvec(ops).map_err(|e| CdapError::InvalidFormat(e.to_string()))?;
+        Ok(CdapMessage::new_request(
+            CdapOpCode::Batch,
+            String::new(),
+            None,
+            Some(RibValue::Bytes(ops)),
+            self.next_invoke_id(),
+        ))
+    }
+
     /// Creates a START request message (for operations like enrollment)
     pub fn start_request(&mut self, obj_name: String, obj_value: Option<RibValue>) -> CdapMessage {
         CdapMessage::new_request(
@@ -265,27 +847,94 @@ impl CdapSession {
 
     /// Processes an incoming CDAP message and returns a response
     pub async fn process_message(&self, msg: &CdapMessage) -> CdapMessage {
+        match msg.op_code {
+            CdapOpCode::Batch => self.handle_batch(msg).await,
+            _ => self.process_op(msg).await,
+        }
+    }
+
+    /// Applies a single, non-BATCH operation
+    async fn process_op(&self, msg: &CdapMessage) -> CdapMessage {
         match msg.op_code {
             CdapOpCode::Create => self.handle_create(msg).await,
             CdapOpCode::Read => self.handle_read(msg).await,
             CdapOpCode::Write => self.handle_write(msg).await,
             CdapOpCode::Delete => self.handle_delete(msg).await,
+            CdapOpCode::Batch => CdapMessage::new_response(
+                msg.invoke_id,
+                CdapResult::InvalidRequest,
+                Some("BATCH operations cannot be nested".to_string()),
+            ),
             CdapOpCode::Start | CdapOpCode::Stop => {
                 // TODO: Implement START/STOP operations
                 CdapMessage::new_response(
                     msg.invoke_id,
-                    -1,
+                    CdapResult::NotImplemented,
                     Some("Operation not yet implemented".to_string()),
                 )
             }
         }
     }
 
+    async fn handle_batch(&self, msg: &CdapMessage) -> CdapMessage {
+        let ops: Vec<CdapOp> = match &msg.obj_value {
+            Some(RibValue::Bytes(bytes)) => match postcard::from_bytes(bytes) {
+                Ok(ops) => ops,
+                Err(e) => {
+                    return CdapMessage::new_response(
+                        msg.invoke_id,
+                        CdapResult::InvalidRequest,
+                        Some(format!("Malformed BATCH: {}", e)),
+                    );
+                }
+            },
+            _ => {
+                return CdapMessage::new_response(
+                    msg.invoke_id,
+                    CdapResult::InvalidRequest,
+                    Some("Missing operations for BATCH".to_string()),
+                );
+            }
+        };
+
+        let mut results = Vec::with_capacity(ops.len());
+        for op in ops {
+            let request = CdapMessage::new_request(
+                op.op_code,
+                op.obj_name,
+                op.obj_class,
+                op.obj_value,
+                msg.invoke_id,
+            );
+            let response = self.process_op(&request).await;
+            results.push(CdapOpResult {
+                result: response.result,
+                result_reason: response.result_reason,
+                obj_value: response.obj_value,
+            });
+        }
+
+        match postcard::to_allocvec(&results) {
+            Ok(bytes) => {
+                let mut response =
+                    CdapMessage::new_response(msg.invoke_id, CdapResult::Success, None);
+                response.op_code = CdapOpCode::Batch;
+                response.obj_value = Some(RibValue::Bytes(bytes));
+                response
+            }
+            Err(e) => CdapMessage::new_response(
+                msg.invoke_id,
+                CdapResult::InternalError,
+                Some(e.to_string()),
+            ),
+        }
+    }
+
     async fn handle_create(&self, msg: &CdapMessage) -> CdapMessage {
         if msg.obj_class.is_none() || msg.obj_value.is_none() {
             return CdapMessage::new_response(
                 msg.invoke_id,
-                -1,
+                CdapResult::InvalidRequest,
                 Some("Missing class or value for CREATE".to_string()),
             );
         }
@@ -299,50 +948,111 @@ impl CdapSession {
             )
             .await
         {
-            Ok(_) => CdapMessage::new_response(msg.invoke_id, 0, None),
-            Err(e) => CdapMessage::new_response(msg.invoke_id, -1, Some(e)),
+            Ok(_) => CdapMessage::new_response(msg.invoke_id, CdapResult::Success, None),
+            Err(e) => {
+                CdapMessage::new_response(msg.invoke_id, CdapResult::from(&e), Some(e.to_string()))
+            }
         }
     }
 
     async fn handle_read(&self, msg: &CdapMessage) -> CdapMessage {
+        if msg.obj_name == CLASS_SCOPE_NAME
+            && let Some(class) = &msg.obj_class
+        {
+            return self.handle_list(msg, class).await;
+        }
+
         match self.rib.read(&msg.obj_name).await {
-            Some(obj) => {
-                let mut response = CdapMessage::new_response(msg.invoke_id, 0, None);
+            Ok(obj) => {
+                let mut response =
+                    CdapMessage::new_response(msg.invoke_id, CdapResult::Success, None);
                 response.obj_value = Some(obj.value);
                 response.obj_class = Some(obj.class);
                 response
             }
-            None => CdapMessage::new_response(
-                msg.invoke_id,
-                -1,
-                Some(format!("Object '{}' not found", msg.obj_name)),
-            ),
+            Err(e) => {
+                CdapMessage::new_response(msg.invoke_id, CdapResult::from(&e), Some(e.to_string()))
+            }
+        }
+    }
+
+    async fn handle_list(&self, msg: &CdapMessage, class: &str) -> CdapMessage {
+        let with_values = msg
+            .obj_value
+            .as_ref()
+            .and_then(RibValue::as_boolean)
+            .unwrap_or(false);
+
+        let mut entries = HashMap::new();
+        for name in self.rib.list_by_class(class).await {
+            // Objects deleted since the listing are simply left out
+            let Ok(obj) = self.rib.read(&name).await else {
+                continue;
+            };
+            let entry = if with_values {
+                obj.value
+            } else {
+                RibValue::Integer(obj.version as i64)
+            };
+            entries.insert(name, Box::new(entry));
         }
+
+        let mut response = CdapMessage::new_response(msg.invoke_id, CdapResult::Success, None);
+        response.obj_class = Some(class.to_string());
+        response.obj_value = Some(RibValue::Struct(entries));
+        response
     }
 
     async fn handle_write(&self, msg: &CdapMessage) -> CdapMessage {
-        if msg.obj_value.is_none() {
+        let Some(value) = msg.obj_value.clone() else {
             return CdapMessage::new_response(
                 msg.invoke_id,
-                -1,
+                CdapResult::InvalidRequest,
                 Some("Missing value for WRITE".to_string()),
             );
+        };
+
+        let result = match &msg.obj_class {
+            Some(class) => self.upsert(&msg.obj_name, class, value).await,
+            None => self.rib.update(&msg.obj_name, value).await,
+        };
+        match result {
+            Ok(_) => CdapMessage::new_response(msg.invoke_id, CdapResult::Success, None),
+            Err(e) => {
+                CdapMessage::new_response(msg.invoke_id, CdapResult::from(&e), Some(e.to_string()))
+            }
         }
+    }
 
-        match self
-            .rib
-            .update(&msg.obj_name, msg.obj_value.clone().unwrap())
-            .await
-        {
-            Ok(_) => CdapMessage::new_response(msg.invoke_id, 0, None),
-            Err(e) => CdapMessage::new_response(msg.invoke_id, -1, Some(e)),
+    /// Updates `name` if it exists as a `class` object, creating it otherwise
+    async fn upsert(&self, name: &str, class: &str, value: RibValue) -> Result<(), RibError> {
+        match self.rib.read(name).await {
+            Ok(existing) if existing.class != class => Err(RibError::InvalidClass(format!(
+                "{} is a {} object, not {}",
+                name, existing.class, class
+            ))),
+            Ok(_) => self.rib.update(name, value).await,
+            Err(RibError::NotFound(_)) => {
+                match self
+                    .rib
+                    .create(name.to_string(), class.to_string(), value.clone())
+                    .await
+                {
+                    // Created by someone else since the read
+                    Err(RibError::AlreadyExists(_)) => self.rib.update(name, value).await,
+                    result => result,
+                }
+            }
+            Err(e) => Err(e),
         }
     }
 
     async fn handle_delete(&self, msg: &CdapMessage) -> CdapMessage {
         match self.rib.delete(&msg.obj_name).await {
-            Ok(_) => CdapMessage::new_response(msg.invoke_id, 0, None),
-            Err(e) => CdapMessage::new_response(msg.invoke_id, -1, Some(e)),
+            Ok(_) => CdapMessage::new_response(msg.invoke_id, CdapResult::Success, None),
+            Err(e) => {
+                CdapMessage::new_response(msg.invoke_id, CdapResult::from(&e), Some(e.to_string()))
+            }
         }
     }
 }
@@ -351,6 +1061,28 @@ impl CdapSession {
 mod tests {
     use super::*;
 
+    fn peer(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_unknown_and_legacy_peers_get_minimal_capabilities() {
+        assert_eq!(CdapCapabilities::default(), CdapCapabilities::minimal());
+
+        let legacy = CdapCapabilities::minimal();
+        assert_eq!(CdapCapabilities::local().negotiate(&legacy), legacy);
+
+        // A newer peer is spoken to at our version
+        let newer = CdapCapabilities {
+            version: CDAP_VERSION + 1,
+            ..CdapCapabilities::local()
+        };
+        assert_eq!(
+            CdapCapabilities::local().negotiate(&newer),
+            CdapCapabilities::local()
+        );
+    }
+
     #[test]
     fn test_cdap_opcode_display() {
         assert_eq!(CdapOpCode::Create.to_string(), "CREATE");
@@ -370,7 +1102,7 @@ mod tests {
 
         assert_eq!(msg.op_code, CdapOpCode::Create);
         assert_eq!(msg.obj_name, "test/obj");
-        assert_eq!(msg.invoke_id, 1);
+        assert_eq!(msg.invoke_id, (u64::from(session.epoch()) << 32) | 1);
     }
 
     #[tokio::test]
@@ -454,7 +1186,316 @@ mod tests {
         let msg1 = session.read_request("obj1".to_string());
         let msg2 = session.read_request("obj2".to_string());
 
-        assert_eq!(msg1.invoke_id, 1);
-        assert_eq!(msg2.invoke_id, 2);
+        assert_eq!(msg2.invoke_id, msg1.invoke_id + 1);
+        assert_eq!(CdapSession::epoch_of(msg1.invoke_id), session.epoch());
+    }
+
+    #[test]
+    fn test_sessions_have_disjoint_invoke_id_spaces() {
+        let rib = Rib::new();
+        let mut old = CdapSession::new(rib.clone());
+        let mut new = CdapSession::new(rib);
+        assert_ne!(old.epoch(), new.epoch());
+
+        let old_ids: Vec<u64> = (0..100)
+            .map(|i| old.read_request(format!("obj{}", i)).invoke_id)
+            .collect();
+        let new_ids: Vec<u64> = (0..100)
+            .map(|i| new.read_request(format!("obj{}", i)).invoke_id)
+            .collect();
+
+        assert!(old_ids.iter().all(|id| !new_ids.contains(id)));
+        assert!(
+            new_ids
+                .iter()
+                .all(|&id| CdapSession::epoch_of(id) == new.epoch())
+        );
+    }
+
+    #[test]
+    fn test_cdap_small_message_not_fragmented() {
+        let msg =
+            CdapMessage::new_request(CdapOpCode::Read, "/dif/name".to_string(), None, None, 9);
+
+        let payloads = msg.to_payloads(DEFAULT_MAX_FRAGMENT_SIZE).unwrap();
+        assert_eq!(payloads.len(), 1);
+
+        let mut reassembler = CdapReassembler::new();
+        let decoded = reassembler.accept(peer(1), &payloads[0]).unwrap().unwrap();
+        assert_eq!(decoded.obj_name, "/dif/name");
+    }
+
+    #[test]
+    fn test_cdap_fragment_reassembly_out_of_order() {
+        let msg = CdapMessage::new_request(
+            CdapOpCode::Write,
+            "/blob".to_string(),
+            Some("data".to_string()),
+            Some(RibValue::Bytes((0..5000u32).map(|i| i as u8).collect())),
+            42,
+        );
+
+        let mut payloads = msg.to_payloads(1024).unwrap();
+        assert!(payloads.len() > 4);
+        payloads.reverse();
+
+        let mut reassembler = CdapReassembler::new();
+        let last = payloads.pop().unwrap();
+        for payload in &payloads {
+            assert!(reassembler.accept(peer(7), payload).unwrap().is_none());
+        }
+        assert_eq!(reassembler.pending(), 1);
+
+        let decoded = reassembler.accept(peer(7), &last).unwrap().unwrap();
+        assert_eq!(decoded.invoke_id, 42);
+        assert_eq!(decoded.obj_value, msg.obj_value);
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn test_reassembler_rejects_oversized_fragment_count() {
+        let fragment = CdapFragment {
+            index: 0,
+            total: u32::MAX,
+            data: vec![0; 16],
+        };
+        let msg = CdapMessage::new_request(
+            CdapOpCode::Write,
+            "/blob".to_string(),
+            Some(FRAGMENT_CLASS.to_string()),
+            Some(RibValue::Bytes(postcard::to_allocvec(&fragment).unwrap())),
+            1,
+        );
+        let payload = postcard::to_allocvec(&msg).unwrap();
+
+        let mut reassembler = CdapReassembler::new();
+        assert!(reassembler.accept(peer(1), &payload).is_err());
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn test_reassembler_keeps_senders_apart() {
+        // Two unenrolled peers both at address 0 reuse the same invoke_id
+        let first = CdapMessage::new_request(
+            CdapOpCode::Write,
+            "/a".to_string(),
+            None,
+            Some(RibValue::Bytes(vec![1; 3000])),
+            1,
+        );
+        let second = CdapMessage::new_request(
+            CdapOpCode::Write,
+            "/b".to_string(),
+            None,
+            Some(RibValue::Bytes(vec![2; 3000])),
+            1,
+        );
+        let first = first.to_payloads(1024).unwrap();
+        let second = second.to_payloads(1024).unwrap();
+
+        let mut reassembler = CdapReassembler::new();
+        let mut decoded = Vec::new();
+        for (a, b) in first.iter().zip(&second) {
+            decoded.extend(reassembler.accept(peer(1), a).unwrap());
+            decoded.extend(reassembler.accept(peer(2), b).unwrap());
+        }
+        let names: Vec<&str> = decoded.iter().map(|m| m.obj_name.as_str()).collect();
+        assert_eq!(names, vec!["/a", "/b"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_request_returns_all_objects_of_class() {
+        let rib = Rib::new();
+        for (name, addr) in [("ipcp-a", 2001), ("ipcp-b", 2002), ("ipcp-c", 2003)] {
+            rib.create(
+                format!("/neighbors/{}", name),
+                "neighbor".to_string(),
+                RibValue::Integer(addr),
+            )
+            .await
+            .unwrap();
+        }
+        rib.create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("test-dif".to_string()),
+        )
+        .await
+        .unwrap();
+        let mut session = CdapSession::new(rib);
+
+        let request = session.list_request("neighbor".to_string(), false);
+        let response = session.process_message(&request).await;
+        assert!(response.is_success());
+        assert_eq!(response.obj_class.as_deref(), Some("neighbor"));
+        let listed = response.obj_value.unwrap();
+        let mut names: Vec<&String> = listed.as_struct().unwrap().keys().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "/neighbors/ipcp-a",
+                "/neighbors/ipcp-b",
+                "/neighbors/ipcp-c"
+            ]
+        );
+
+        let request = session.list_request("neighbor".to_string(), true);
+        let response = session.process_message(&request).await;
+        let listed = response.obj_value.unwrap();
+        assert_eq!(
+            listed.as_struct().unwrap()["/neighbors/ipcp-b"].as_integer(),
+            Some(2002)
+        );
+
+        // An unknown class lists nothing rather than failing
+        let request = session.list_request("flow".to_string(), false);
+        let response = session.process_message(&request).await;
+        assert!(response.is_success());
+        assert!(response.obj_value.unwrap().as_struct().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upsert_creates_then_updates() {
+        let rib = Rib::new();
+        let mut session = CdapSession::new(rib.clone());
+
+        let create = session.upsert_request(
+            "/neighbors/ipcp-c".to_string(),
+            "neighbor".to_string(),
+            RibValue::Integer(1),
+        );
+        assert_eq!(create.op_code, CdapOpCode::Write);
+        let response = session.process_message(&create).await;
+        assert_eq!(
+            response.result,
+            CdapResult::Success,
+            "{:?}",
+            response.result_reason
+        );
+        let obj = rib.read("/neighbors/ipcp-c").await.unwrap();
+        assert_eq!(obj.class, "neighbor");
+        assert_eq!(obj.value, RibValue::Integer(1));
+
+        let update = session.upsert_request(
+            "/neighbors/ipcp-c".to_string(),
+            "neighbor".to_string(),
+            RibValue::Integer(2),
+        );
+        let response = session.process_message(&update).await;
+        assert_eq!(
+            response.result,
+            CdapResult::Success,
+            "{:?}",
+            response.result_reason
+        );
+        let obj = rib.read("/neighbors/ipcp-c").await.unwrap();
+        assert_eq!(obj.value, RibValue::Integer(2));
+        assert_eq!(obj.version, 2);
+
+        // An upsert naming another class does not clobber the object
+        let wrong_class = session.upsert_request(
+            "/neighbors/ipcp-c".to_string(),
+            "route".to_string(),
+            RibValue::Integer(3),
+        );
+        assert_ne!(
+            session.process_message(&wrong_class).await.result,
+            CdapResult::Success
+        );
+
+        // A plain WRITE still requires the object to exist
+        let write = session.write_request("/neighbors/ipcp-d".to_string(), RibValue::Integer(1));
+        assert_ne!(
+            session.process_message(&write).await.result,
+            CdapResult::Success
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_of_missing_object_reports_object_not_found() {
+        let rib = Rib::new();
+        let mut session = CdapSession::new(rib);
+
+        let read = session.read_request("/neighbors/nobody".to_string());
+        let response = session.process_message(&read).await;
+
+        assert_eq!(response.result, CdapResult::ObjectNotFound);
+        assert!(!response.is_success());
+
+        // The result travels as its integer code, so unknown codes survive
+        let bytes = postcard::to_allocvec(&response).unwrap();
+        let decoded: CdapMessage = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.result, CdapResult::ObjectNotFound);
+        assert_eq!(
+            postcard::to_allocvec(&CdapResult::ObjectNotFound).unwrap(),
+            postcard::to_allocvec(&-2i32).unwrap()
+        );
+        assert_eq!(CdapResult::from(42), CdapResult::Other(42));
+        assert_eq!(CdapResult::from(CdapResult::Other(42).code()).code(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_batch_applies_ops_in_order_and_reports_each() {
+        let rib = Rib::new();
+        let mut session = CdapSession::new(rib.clone());
+
+        let creates: Vec<CdapOp> = (1..=3)
+            .map(|i| {
+                CdapOp::create(
+                    format!("/directory/app-{}", i),
+                    "directory_entry".to_string(),
+                    RibValue::Integer(i),
+                )
+            })
+            .collect();
+        let batch = session.batch_request(&creates).unwrap();
+        let response = session.process_message(&batch).await;
+
+        assert!(response.is_success());
+        let results = response.batch_results().unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.result.is_success()));
+        for i in 1..=3 {
+            let obj = rib.read(&format!("/directory/app-{}", i)).await.unwrap();
+            assert_eq!(obj.value, RibValue::Integer(i));
+        }
+
+        // A failing op is reported in its slot; the ones after it still apply
+        let mixed = session
+            .batch_request(&[
+                CdapOp::write("/directory/app-1".to_string(), RibValue::Integer(10)),
+                CdapOp::create(
+                    "/directory/app-2".to_string(),
+                    "directory_entry".to_string(),
+                    RibValue::Integer(20),
+                ),
+                CdapOp::delete("/directory/app-3".to_string()),
+            ])
+            .unwrap();
+        let results = session
+            .process_message(&mixed)
+            .await
+            .batch_results()
+            .unwrap();
+
+        assert_eq!(
+            results.iter().map(|r| r.result).collect::<Vec<_>>(),
+            vec![
+                CdapResult::Success,
+                CdapResult::AlreadyExists,
+                CdapResult::Success
+            ]
+        );
+        assert!(results[1].result_reason.is_some());
+        assert_eq!(
+            rib.read("/directory/app-1").await.unwrap().value,
+            RibValue::Integer(10)
+        );
+        assert_eq!(
+            rib.read("/directory/app-2").await.unwrap().value,
+            RibValue::Integer(2)
+        );
+        assert!(rib.read("/directory/app-3").await.is_err());
     }
 }
@@ -6,9 +6,19 @@
 //! Supports both command-line arguments and TOML configuration files.
 //! Handles bootstrap vs. member IPCP modes with appropriate parameters.
 
+use crate::discovery::{DEFAULT_DISCOVERY_GROUP, DEFAULT_DISCOVERY_PORT, DiscoveryEndpoint};
+use crate::enrollment::{BackoffJitter, DifNameRules};
+use crate::fal::AdmissionLimits;
+use crate::neighbor::{NeighborSeed, validate_seeds};
+use crate::policies::{
+    QoSCube, QoSPolicyKind, QoSProfile, RoutingPolicyKind, SchedulingPolicyKind,
+};
+use crate::rib::{CapacityPolicy, SerializationFormat};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
+use std::net::Ipv4Addr;
 use std::path::PathBuf;
 
 /// IPCP operational mode
@@ -49,6 +59,40 @@ impl std::str::FromStr for IpcpMode {
     }
 }
 
+/// Format of the startup summary and runtime events on stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON object per line, for log aggregation
+    Json,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "Invalid output format: {}. Use 'text' or 'json'",
+                s
+            )),
+        }
+    }
+}
+
 /// Command-line arguments for IPCP
 #[derive(Parser, Debug)]
 #[command(name = "ari-ipcp")]
@@ -57,8 +101,9 @@ impl std::str::FromStr for IpcpMode {
 #[command(about = "RINA IPC Process", long_about = None)]
 pub struct CliArgs {
     /// Path to TOML configuration file (overrides other arguments)
+    /// Repeatable: later files are merged over earlier ones
     #[arg(short, long, value_name = "FILE")]
-    pub config: Option<PathBuf>,
+    pub config: Vec<PathBuf>,
 
     /// IPCP name
     #[arg(long, value_name = "NAME")]
@@ -92,6 +137,31 @@ pub struct CliArgs {
     /// Address pool end (bootstrap mode only)
     #[arg(long, value_name = "ADDRESS", default_value = "1999")]
     pub address_pool_end: u64,
+
+    /// Static route (bootstrap mode only), repeatable
+    /// Format: "dest,host:port,rina_addr" with an optional ",cost"
+    #[arg(long = "static-route", value_name = "ROUTE")]
+    pub static_routes: Vec<StaticRoute>,
+
+    /// Enrollment response timeout in seconds
+    #[arg(long, value_name = "SECS")]
+    pub enrollment_timeout: Option<u64>,
+
+    /// Maximum number of enrollment attempts
+    #[arg(long, value_name = "COUNT")]
+    pub enrollment_max_retries: Option<u32>,
+
+    /// Initial enrollment retry backoff in milliseconds (doubles per attempt)
+    #[arg(long, value_name = "MS")]
+    pub enrollment_backoff_ms: Option<u64>,
+
+    /// Validate the configuration and print its summary without starting
+    #[arg(long, alias = "dry-run")]
+    pub check: bool,
+
+    /// Output format for the startup summary and runtime events: text or json
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    pub output: OutputFormat,
 }
 
 /// Bootstrap peer configuration
@@ -112,6 +182,54 @@ pub struct StaticRoute {
     pub next_hop_address: String,
     /// Next hop RINA address
     pub next_hop_rina_addr: u64,
+    /// Route cost (lower is preferred over competing routes)
+    #[serde(default = "default_route_cost")]
+    pub cost: u32,
+}
+
+fn default_route_cost() -> u32 {
+    crate::routing::DEFAULT_ROUTE_COST
+}
+
+impl std::str::FromStr for StaticRoute {
+    type Err = String;
+
+    /// Parses `dest,host:port,rina_addr` with an optional trailing `,cost`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split(',').map(str::trim).collect();
+        if !(3..=4).contains(&fields.len()) {
+            return Err(format!(
+                "Invalid static route '{}': expected dest,host:port,rina_addr[,cost]",
+                s
+            ));
+        }
+
+        let parse_addr = |field: &str, what: &str| match field.parse::<u64>() {
+            Ok(0) | Err(_) => Err(format!(
+                "Invalid static route '{}': {} must be a non-zero RINA address",
+                s, what
+            )),
+            Ok(addr) => Ok(addr),
+        };
+        let destination = parse_addr(fields[0], "destination")?;
+        fields[1]
+            .parse::<std::net::SocketAddr>()
+            .map_err(|e| format!("Invalid static route '{}': next hop: {}", s, e))?;
+        let next_hop_rina_addr = parse_addr(fields[2], "next hop RINA address")?;
+        let cost = match fields.get(3) {
+            Some(cost) => cost
+                .parse()
+                .map_err(|e| format!("Invalid static route '{}': cost: {}", s, e))?,
+            None => default_route_cost(),
+        };
+
+        Ok(Self {
+            destination,
+            next_hop_address: fields[1].to_string(),
+            next_hop_rina_addr,
+            cost,
+        })
+    }
 }
 
 /// TOML configuration file structure
@@ -126,6 +244,18 @@ pub struct TomlConfig {
     pub routing: RoutingConfig,
     #[serde(default)]
     pub rib: RibConfig,
+    #[serde(default)]
+    pub policies: PoliciesConfig,
+    #[serde(default)]
+    pub flows: AdmissionLimits,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    /// Statically known neighbors (`[[neighbor]]` entries)
+    #[serde(default, rename = "neighbor")]
+    pub neighbors: Vec<NeighborSeed>,
+    /// QoS classes a bootstrap offers its DIF (`[[qos_class]]` entries)
+    #[serde(default, rename = "qos_class")]
+    pub qos_classes: Vec<QoSProfile>,
 }
 
 /// IPCP section of config
@@ -148,6 +278,12 @@ pub struct DifConfig {
     pub address_pool_start: Option<u64>,
     #[serde(default)]
     pub address_pool_end: Option<u64>,
+    /// Free fraction of the pool at which a low-availability warning is raised
+    #[serde(default)]
+    pub address_pool_low_watermark: Option<f64>,
+    /// Rules the DIF name (and names requested by members) must follow
+    #[serde(default)]
+    pub name_rules: DifNameRules,
 }
 
 /// Shim layer section of config
@@ -155,6 +291,37 @@ pub struct DifConfig {
 pub struct ShimConfig {
     pub bind_address: String,
     pub bind_port: u16,
+    /// Largest datagram a receive can hold (bytes)
+    #[serde(default = "default_recv_buffer_bytes")]
+    pub recv_buffer_bytes: usize,
+    /// How long a receive waits for a datagram (milliseconds)
+    #[serde(default = "default_read_timeout_ms")]
+    pub read_timeout_ms: u64,
+    /// Kernel receive buffer requested per socket (bytes, 0 = OS default)
+    #[serde(default = "default_so_rcvbuf")]
+    pub so_rcvbuf: usize,
+    /// Kernel send buffer requested per socket (bytes, 0 = OS default)
+    #[serde(default)]
+    pub so_sndbuf: usize,
+    /// Largest payload sent in one datagram (bytes); lower it to fit the path MTU
+    #[serde(default = "default_max_payload_bytes")]
+    pub max_payload_bytes: usize,
+}
+
+fn default_recv_buffer_bytes() -> usize {
+    crate::shim::DEFAULT_MAX_BUFFER_SIZE
+}
+
+fn default_max_payload_bytes() -> usize {
+    crate::shim::DEFAULT_MAX_PAYLOAD_SIZE
+}
+
+fn default_read_timeout_ms() -> u64 {
+    crate::shim::DEFAULT_READ_TIMEOUT.as_millis() as u64
+}
+
+fn default_so_rcvbuf() -> usize {
+    crate::shim::DEFAULT_SO_RCVBUF
 }
 
 /// Enrollment section of config
@@ -171,6 +338,32 @@ pub struct EnrollmentConfig {
     /// Initial backoff duration in milliseconds (doubles on each retry)
     #[serde(default = "default_initial_backoff_ms")]
     pub initial_backoff_ms: u64,
+    /// Randomisation of retry delays (none, full or decorrelated)
+    #[serde(default)]
+    pub backoff_jitter: BackoffJitter,
+    /// File the dynamically assigned address is kept in across restarts
+    /// (defaults to `<name>-address.toml`)
+    #[serde(default)]
+    pub address_state_path: Option<String>,
+    /// Enrollment requests accepted per second from one source IP (bootstrap only, 0 = unlimited)
+    #[serde(default = "default_request_rate_limit")]
+    pub request_rate_limit: f64,
+    /// Enrollment requests one source may send back to back before being limited
+    #[serde(default = "default_request_burst")]
+    pub request_burst: u32,
+    /// Secret a member must present to move an address already bound to
+    /// another live socket (unset = such requests are always refused).
+    /// Sent in plaintext, so it does not stop an on-path attacker
+    #[serde(default)]
+    pub rebind_secret: Option<String>,
+    /// Longest one receive waits for a response before the enrollment
+    /// deadline is rechecked (milliseconds)
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Silence from the bootstrap after which a member re-enrolls (seconds,
+    /// defaults to three RIB sync intervals; must exceed one)
+    #[serde(default)]
+    pub connection_timeout_secs: Option<u64>,
 }
 
 fn default_enrollment_timeout() -> u64 {
@@ -185,6 +378,18 @@ fn default_initial_backoff_ms() -> u64 {
     1000
 }
 
+fn default_request_rate_limit() -> f64 {
+    2.0
+}
+
+fn default_request_burst() -> u32 {
+    10
+}
+
+fn default_poll_interval_ms() -> u64 {
+    100
+}
+
 impl Default for EnrollmentConfig {
     fn default() -> Self {
         Self {
@@ -192,6 +397,13 @@ impl Default for EnrollmentConfig {
             timeout_secs: default_enrollment_timeout(),
             max_retries: default_max_retries(),
             initial_backoff_ms: default_initial_backoff_ms(),
+            backoff_jitter: BackoffJitter::default(),
+            address_state_path: None,
+            request_rate_limit: default_request_rate_limit(),
+            request_burst: default_request_burst(),
+            rebind_secret: None,
+            poll_interval_ms: default_poll_interval_ms(),
+            connection_timeout_secs: None,
         }
     }
 }
@@ -234,15 +446,27 @@ pub struct RibConfig {
     /// Enable persistence of RIB state (save/load from snapshot file)
     #[serde(default)]
     pub enable_rib_persistence: bool,
-    /// Path to RIB snapshot file (bincode format)
+    /// Path to RIB snapshot file
     #[serde(default = "default_rib_snapshot_path")]
     pub rib_snapshot_path: String,
+    /// Snapshot file format: "postcard" (default), "json", or "bincode"
+    #[serde(default)]
+    pub rib_snapshot_format: SerializationFormat,
     /// Interval between automatic RIB snapshots in seconds (0 = disabled)
     #[serde(default = "default_rib_snapshot_interval_seconds")]
     pub rib_snapshot_interval_seconds: u64,
+    /// Number of previous RIB snapshots kept to recover from a corrupt one
+    #[serde(default = "default_rib_snapshot_backups")]
+    pub rib_snapshot_backups: usize,
     /// Maximum number of changes to keep in change log for incremental sync
     #[serde(default = "default_change_log_size")]
     pub change_log_size: usize,
+    /// Maximum number of objects the RIB admits through local creates (0 = unlimited)
+    #[serde(default)]
+    pub max_objects: usize,
+    /// What happens to creates past `max_objects`: "reject" (default) or "evict_oldest"
+    #[serde(default)]
+    pub capacity_policy: CapacityPolicy,
     /// RIB synchronization interval for members (seconds, 0 = disabled)
     #[serde(default = "default_rib_sync_interval_seconds")]
     pub rib_sync_interval_secs: u64,
@@ -256,6 +480,10 @@ fn default_rib_snapshot_interval_seconds() -> u64 {
     300 // 5 minutes
 }
 
+fn default_rib_snapshot_backups() -> usize {
+    2
+}
+
 fn default_change_log_size() -> usize {
     1000
 }
@@ -269,27 +497,140 @@ impl Default for RibConfig {
         Self {
             enable_rib_persistence: false,
             rib_snapshot_path: default_rib_snapshot_path(),
+            rib_snapshot_format: SerializationFormat::default(),
             rib_snapshot_interval_seconds: default_rib_snapshot_interval_seconds(),
+            rib_snapshot_backups: default_rib_snapshot_backups(),
             change_log_size: default_change_log_size(),
+            max_objects: 0,
+            capacity_policy: CapacityPolicy::default(),
             rib_sync_interval_secs: default_rib_sync_interval_seconds(),
         }
     }
 }
 
+/// Policies section of config
+///
+/// Unknown policy names are rejected when the TOML file is parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoliciesConfig {
+    /// Routing policy: "shortest_path" (default) or "distance_vector"
+    #[serde(default)]
+    pub routing: RoutingPolicyKind,
+    /// Scheduling policy: "fifo", "priority" (default) or "wfq"
+    #[serde(default)]
+    pub scheduling: SchedulingPolicyKind,
+    /// QoS policy: "simple" (default)
+    #[serde(default)]
+    pub qos: QoSPolicyKind,
+}
+
+/// Discovery section of config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryConfig {
+    /// Bootstraps announce themselves and members without bootstrap peers
+    /// listen for announcements
+    #[serde(default)]
+    pub enabled: bool,
+    /// Multicast group beacons are sent to
+    #[serde(default = "default_discovery_group")]
+    pub group: Ipv4Addr,
+    /// UDP port beacons are sent to
+    #[serde(default = "default_discovery_port")]
+    pub port: u16,
+    /// Local interface address to send and receive beacons on
+    #[serde(default = "default_discovery_interface")]
+    pub interface: Ipv4Addr,
+    /// Interval between beacons sent by a bootstrap (seconds)
+    #[serde(default = "default_announce_interval_secs")]
+    pub announce_interval_secs: u64,
+    /// How long a member waits for a beacon (seconds)
+    #[serde(default = "default_discovery_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_discovery_group() -> Ipv4Addr {
+    DEFAULT_DISCOVERY_GROUP
+}
+
+fn default_discovery_port() -> u16 {
+    DEFAULT_DISCOVERY_PORT
+}
+
+fn default_discovery_interface() -> Ipv4Addr {
+    Ipv4Addr::UNSPECIFIED
+}
+
+fn default_announce_interval_secs() -> u64 {
+    5
+}
+
+fn default_discovery_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            group: default_discovery_group(),
+            port: default_discovery_port(),
+            interface: default_discovery_interface(),
+            announce_interval_secs: default_announce_interval_secs(),
+            timeout_secs: default_discovery_timeout_secs(),
+        }
+    }
+}
+
+impl DiscoveryConfig {
+    /// Returns the multicast endpoint described by this section
+    pub fn endpoint(&self) -> DiscoveryEndpoint {
+        DiscoveryEndpoint::new(self.group, self.port).with_interface(self.interface)
+    }
+}
+
+/// Deep-merges `overlay` into `base`; see [`IpcpConfiguration::from_files`]
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay)
+            }
+            (Some(toml::Value::Array(base)), toml::Value::Array(overlay)) => base.extend(overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
 /// Unified configuration after parsing CLI or file
 #[derive(Debug, Clone)]
 pub struct IpcpConfiguration {
     pub name: String,
     pub mode: IpcpMode,
     pub dif_name: String,
+    pub dif_name_rules: DifNameRules,
     pub address: Option<u64>,
     pub bind_address: String,
+    pub shim_recv_buffer_bytes: usize,
+    pub shim_read_timeout_ms: u64,
+    pub shim_so_rcvbuf: usize,
+    pub shim_so_sndbuf: usize,
+    pub shim_max_payload_bytes: usize,
     pub bootstrap_peers: Vec<String>,
     pub address_pool_start: u64,
     pub address_pool_end: u64,
+    pub address_pool_low_watermark: Option<f64>,
     pub enrollment_timeout_secs: u64,
     pub enrollment_max_retries: u32,
     pub enrollment_initial_backoff_ms: u64,
+    pub enrollment_backoff_jitter: BackoffJitter,
+    pub address_state_path: Option<String>,
+    pub enrollment_request_rate_limit: f64,
+    pub enrollment_request_burst: u32,
+    pub enrollment_rebind_secret: Option<String>,
+    pub enrollment_poll_interval_ms: u64,
+    pub enrollment_connection_timeout_secs: Option<u64>,
     pub static_routes: Vec<StaticRoute>,
     pub enable_route_persistence: bool,
     pub route_snapshot_path: String,
@@ -297,17 +638,26 @@ pub struct IpcpConfiguration {
     pub route_snapshot_interval_seconds: u64,
     pub enable_rib_persistence: bool,
     pub rib_snapshot_path: String,
+    pub rib_snapshot_format: SerializationFormat,
     pub rib_snapshot_interval_seconds: u64,
+    pub rib_snapshot_backups: usize,
     pub change_log_size: usize,
+    pub rib_max_objects: usize,
+    pub rib_capacity_policy: CapacityPolicy,
     pub rib_sync_interval_secs: u64,
+    pub policies: PoliciesConfig,
+    pub flow_limits: AdmissionLimits,
+    pub discovery: DiscoveryConfig,
+    pub neighbors: Vec<NeighborSeed>,
+    pub qos_classes: Vec<QoSProfile>,
 }
 
 impl IpcpConfiguration {
     /// Creates configuration from command-line arguments
     pub fn from_cli(args: CliArgs) -> Result<Self, String> {
-        // If config file is specified, load from file
-        if let Some(config_path) = args.config {
-            return Self::from_file(&config_path);
+        // If config files are specified, load from them
+        if !args.config.is_empty() {
+            return Self::from_files(&args.config);
         }
 
         // Otherwise, use CLI arguments
@@ -323,12 +673,32 @@ impl IpcpConfiguration {
                     dif_name: "demo-dif".to_string(),
                     address: None,
                     bind_address: String::new(),
+                    shim_recv_buffer_bytes: default_recv_buffer_bytes(),
+                    shim_read_timeout_ms: default_read_timeout_ms(),
+                    shim_so_rcvbuf: default_so_rcvbuf(),
+                    shim_so_sndbuf: 0,
+                    shim_max_payload_bytes: default_max_payload_bytes(),
+                    dif_name_rules: DifNameRules::default(),
                     bootstrap_peers: vec![],
                     address_pool_start: 1002,
                     address_pool_end: 1999,
-                    enrollment_timeout_secs: default_enrollment_timeout(),
-                    enrollment_max_retries: default_max_retries(),
-                    enrollment_initial_backoff_ms: default_initial_backoff_ms(),
+                    address_pool_low_watermark: None,
+                    enrollment_timeout_secs: args
+                        .enrollment_timeout
+                        .unwrap_or_else(default_enrollment_timeout),
+                    enrollment_max_retries: args
+                        .enrollment_max_retries
+                        .unwrap_or_else(default_max_retries),
+                    enrollment_initial_backoff_ms: args
+                        .enrollment_backoff_ms
+                        .unwrap_or_else(default_initial_backoff_ms),
+                    enrollment_backoff_jitter: BackoffJitter::default(),
+                    address_state_path: None,
+                    enrollment_request_rate_limit: default_request_rate_limit(),
+                    enrollment_request_burst: default_request_burst(),
+                    enrollment_rebind_secret: None,
+                    enrollment_poll_interval_ms: default_poll_interval_ms(),
+                    enrollment_connection_timeout_secs: None,
                     static_routes: vec![],
                     enable_route_persistence: false,
                     route_snapshot_path: default_route_snapshot_path(),
@@ -336,9 +706,18 @@ impl IpcpConfiguration {
                     route_snapshot_interval_seconds: default_snapshot_interval_seconds(),
                     enable_rib_persistence: false,
                     rib_snapshot_path: default_rib_snapshot_path(),
+                    rib_snapshot_format: SerializationFormat::default(),
                     rib_snapshot_interval_seconds: default_rib_snapshot_interval_seconds(),
+                    rib_snapshot_backups: default_rib_snapshot_backups(),
                     change_log_size: default_change_log_size(),
+                    rib_max_objects: 0,
+                    rib_capacity_policy: CapacityPolicy::default(),
                     rib_sync_interval_secs: default_rib_sync_interval_seconds(),
+                    policies: PoliciesConfig::default(),
+                    flow_limits: AdmissionLimits::default(),
+                    discovery: DiscoveryConfig::default(),
+                    neighbors: vec![],
+                    qos_classes: vec![],
                 })
             }
             IpcpMode::Bootstrap => {
@@ -357,22 +736,51 @@ impl IpcpConfiguration {
                     dif_name,
                     address: Some(address),
                     bind_address: bind,
+                    shim_recv_buffer_bytes: default_recv_buffer_bytes(),
+                    shim_read_timeout_ms: default_read_timeout_ms(),
+                    shim_so_rcvbuf: default_so_rcvbuf(),
+                    shim_so_sndbuf: 0,
+                    shim_max_payload_bytes: default_max_payload_bytes(),
+                    dif_name_rules: DifNameRules::default(),
                     bootstrap_peers: vec![],
                     address_pool_start: args.address_pool_start,
                     address_pool_end: args.address_pool_end,
-                    enrollment_timeout_secs: default_enrollment_timeout(),
-                    enrollment_max_retries: default_max_retries(),
-                    enrollment_initial_backoff_ms: default_initial_backoff_ms(),
-                    static_routes: vec![], // No CLI support for routes yet
+                    address_pool_low_watermark: None,
+                    enrollment_timeout_secs: args
+                        .enrollment_timeout
+                        .unwrap_or_else(default_enrollment_timeout),
+                    enrollment_max_retries: args
+                        .enrollment_max_retries
+                        .unwrap_or_else(default_max_retries),
+                    enrollment_initial_backoff_ms: args
+                        .enrollment_backoff_ms
+                        .unwrap_or_else(default_initial_backoff_ms),
+                    enrollment_backoff_jitter: BackoffJitter::default(),
+                    address_state_path: None,
+                    enrollment_request_rate_limit: default_request_rate_limit(),
+                    enrollment_request_burst: default_request_burst(),
+                    enrollment_rebind_secret: None,
+                    enrollment_poll_interval_ms: default_poll_interval_ms(),
+                    enrollment_connection_timeout_secs: None,
+                    static_routes: args.static_routes,
                     enable_route_persistence: false,
                     route_snapshot_path: default_route_snapshot_path(),
                     route_ttl_seconds: default_route_ttl_seconds(),
                     route_snapshot_interval_seconds: default_snapshot_interval_seconds(),
                     enable_rib_persistence: false,
                     rib_snapshot_path: default_rib_snapshot_path(),
+                    rib_snapshot_format: SerializationFormat::default(),
                     rib_snapshot_interval_seconds: default_rib_snapshot_interval_seconds(),
+                    rib_snapshot_backups: default_rib_snapshot_backups(),
                     change_log_size: default_change_log_size(),
+                    rib_max_objects: 0,
+                    rib_capacity_policy: CapacityPolicy::default(),
                     rib_sync_interval_secs: default_rib_sync_interval_seconds(),
+                    policies: PoliciesConfig::default(),
+                    flow_limits: AdmissionLimits::default(),
+                    discovery: DiscoveryConfig::default(),
+                    neighbors: vec![],
+                    qos_classes: vec![],
                 })
             }
             IpcpMode::Member => {
@@ -384,6 +792,9 @@ impl IpcpConfiguration {
                 let peers = args
                     .bootstrap_peers
                     .ok_or("--bootstrap-peers is required for member mode")?;
+                if !args.static_routes.is_empty() {
+                    return Err("--static-route is only supported in bootstrap mode".to_string());
+                }
 
                 Ok(Self {
                     name,
@@ -391,12 +802,32 @@ impl IpcpConfiguration {
                     dif_name,
                     address: None, // Will be assigned during enrollment
                     bind_address: bind,
+                    shim_recv_buffer_bytes: default_recv_buffer_bytes(),
+                    shim_read_timeout_ms: default_read_timeout_ms(),
+                    shim_so_rcvbuf: default_so_rcvbuf(),
+                    shim_so_sndbuf: 0,
+                    shim_max_payload_bytes: default_max_payload_bytes(),
+                    dif_name_rules: DifNameRules::default(),
                     bootstrap_peers: peers,
                     address_pool_start: args.address_pool_start,
                     address_pool_end: args.address_pool_end,
-                    enrollment_timeout_secs: default_enrollment_timeout(),
-                    enrollment_max_retries: default_max_retries(),
-                    enrollment_initial_backoff_ms: default_initial_backoff_ms(),
+                    address_pool_low_watermark: None,
+                    enrollment_timeout_secs: args
+                        .enrollment_timeout
+                        .unwrap_or_else(default_enrollment_timeout),
+                    enrollment_max_retries: args
+                        .enrollment_max_retries
+                        .unwrap_or_else(default_max_retries),
+                    enrollment_initial_backoff_ms: args
+                        .enrollment_backoff_ms
+                        .unwrap_or_else(default_initial_backoff_ms),
+                    enrollment_backoff_jitter: BackoffJitter::default(),
+                    address_state_path: None,
+                    enrollment_request_rate_limit: default_request_rate_limit(),
+                    enrollment_request_burst: default_request_burst(),
+                    enrollment_rebind_secret: None,
+                    enrollment_poll_interval_ms: default_poll_interval_ms(),
+                    enrollment_connection_timeout_secs: None,
                     static_routes: vec![], // Members learn routes from bootstrap
                     enable_route_persistence: false,
                     route_snapshot_path: default_route_snapshot_path(),
@@ -404,9 +835,18 @@ impl IpcpConfiguration {
                     route_snapshot_interval_seconds: default_snapshot_interval_seconds(),
                     enable_rib_persistence: false,
                     rib_snapshot_path: default_rib_snapshot_path(),
+                    rib_snapshot_format: SerializationFormat::default(),
                     rib_snapshot_interval_seconds: default_rib_snapshot_interval_seconds(),
+                    rib_snapshot_backups: default_rib_snapshot_backups(),
                     change_log_size: default_change_log_size(),
+                    rib_max_objects: 0,
+                    rib_capacity_policy: CapacityPolicy::default(),
                     rib_sync_interval_secs: default_rib_sync_interval_seconds(),
+                    policies: PoliciesConfig::default(),
+                    flow_limits: AdmissionLimits::default(),
+                    discovery: DiscoveryConfig::default(),
+                    neighbors: vec![],
+                    qos_classes: vec![],
                 })
             }
         }
@@ -414,11 +854,29 @@ impl IpcpConfiguration {
 
     /// Loads configuration from a TOML file
     pub fn from_file(path: &PathBuf) -> Result<Self, String> {
-        let contents =
-            fs::read_to_string(path).map_err(|e| format!("Failed to read config file: {}", e))?;
+        Self::from_files(std::slice::from_ref(path))
+    }
 
-        let config: TomlConfig =
-            toml::from_str(&contents).map_err(|e| format!("Failed to parse TOML config: {}", e))?;
+    /// Creates configuration from several TOML files merged in order
+    ///
+    /// Later files override earlier ones: tables are merged key by key,
+    /// arrays (such as `static_routes`, `[[neighbor]]` and `bootstrap_peers`)
+    /// are appended, and any other value is replaced.
+    pub fn from_files(paths: &[PathBuf]) -> Result<Self, String> {
+        let mut merged = toml::Table::new();
+        for path in paths {
+            let contents = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+            let table: toml::Table = toml::from_str(&contents)
+                .map_err(|e| format!("Failed to parse TOML config {}: {}", path.display(), e))?;
+            merge_tables(&mut merged, table);
+        }
+
+        let config: TomlConfig = toml::Value::Table(merged)
+            .try_into()
+            .map_err(|e| format!("Failed to parse TOML config: {}", e))?;
+
+        config.dif.name_rules.validate(&config.dif.name)?;
 
         let bind_address = format!("{}:{}", config.shim.bind_address, config.shim.bind_port);
 
@@ -433,14 +891,28 @@ impl IpcpConfiguration {
             name: config.ipcp.name,
             mode: config.ipcp.mode,
             dif_name: config.dif.name,
+            dif_name_rules: config.dif.name_rules,
             address: config.dif.address,
             bind_address,
+            shim_recv_buffer_bytes: config.shim.recv_buffer_bytes,
+            shim_read_timeout_ms: config.shim.read_timeout_ms,
+            shim_so_rcvbuf: config.shim.so_rcvbuf,
+            shim_so_sndbuf: config.shim.so_sndbuf,
+            shim_max_payload_bytes: config.shim.max_payload_bytes,
             bootstrap_peers,
             address_pool_start: config.dif.address_pool_start.unwrap_or(1002),
             address_pool_end: config.dif.address_pool_end.unwrap_or(1999),
+            address_pool_low_watermark: config.dif.address_pool_low_watermark,
             enrollment_timeout_secs: config.enrollment.timeout_secs,
             enrollment_max_retries: config.enrollment.max_retries,
             enrollment_initial_backoff_ms: config.enrollment.initial_backoff_ms,
+            enrollment_backoff_jitter: config.enrollment.backoff_jitter,
+            address_state_path: config.enrollment.address_state_path,
+            enrollment_request_rate_limit: config.enrollment.request_rate_limit,
+            enrollment_request_burst: config.enrollment.request_burst,
+            enrollment_rebind_secret: config.enrollment.rebind_secret,
+            enrollment_poll_interval_ms: config.enrollment.poll_interval_ms,
+            enrollment_connection_timeout_secs: config.enrollment.connection_timeout_secs,
             static_routes: config.routing.static_routes,
             enable_route_persistence: config.routing.enable_route_persistence,
             route_snapshot_path: config.routing.route_snapshot_path,
@@ -448,14 +920,25 @@ impl IpcpConfiguration {
             route_snapshot_interval_seconds: config.routing.route_snapshot_interval_seconds,
             enable_rib_persistence: config.rib.enable_rib_persistence,
             rib_snapshot_path: config.rib.rib_snapshot_path,
+            rib_snapshot_format: config.rib.rib_snapshot_format,
             rib_snapshot_interval_seconds: config.rib.rib_snapshot_interval_seconds,
+            rib_snapshot_backups: config.rib.rib_snapshot_backups,
             change_log_size: config.rib.change_log_size,
+            rib_max_objects: config.rib.max_objects,
+            rib_capacity_policy: config.rib.capacity_policy,
             rib_sync_interval_secs: config.rib.rib_sync_interval_secs,
+            policies: config.policies,
+            flow_limits: config.flows,
+            discovery: config.discovery,
+            neighbors: config.neighbors,
+            qos_classes: config.qos_classes,
         })
     }
 
     /// Validates configuration based on mode
     pub fn validate(&self) -> Result<(), String> {
+        self.dif_name_rules.validate(&self.dif_name)?;
+
         match self.mode {
             IpcpMode::Bootstrap => {
                 if self.address.is_none() {
@@ -464,49 +947,148 @@ impl IpcpConfiguration {
                 if self.bind_address.is_empty() {
                     return Err("Bootstrap mode requires a bind address".to_string());
                 }
+                if let Some(fraction) = self.address_pool_low_watermark
+                    && !(0.0..=1.0).contains(&fraction)
+                {
+                    return Err(format!(
+                        "Address pool low watermark must be between 0 and 1, got {}",
+                        fraction
+                    ));
+                }
+                let mut class_names = HashSet::new();
+                if let Some(profile) = self
+                    .qos_classes
+                    .iter()
+                    .find(|profile| !class_names.insert(profile.name.as_str()))
+                {
+                    return Err(format!("QoS class '{}' is defined twice", profile.name));
+                }
             }
             IpcpMode::Member => {
-                if self.bootstrap_peers.is_empty() {
-                    return Err("Member mode requires at least one bootstrap peer".to_string());
+                if self.bootstrap_peers.is_empty() && !self.discovery.enabled {
+                    return Err(
+                        "Member mode requires at least one bootstrap peer or discovery".to_string(),
+                    );
                 }
                 if self.bind_address.is_empty() {
                     return Err("Member mode requires a bind address".to_string());
                 }
+                // Sync responses are the heartbeat, so a shorter timeout
+                // would re-enroll between two perfectly healthy syncs
+                if self.rib_sync_interval_secs > 0
+                    && self.connection_timeout_secs() <= self.rib_sync_interval_secs
+                {
+                    return Err(format!(
+                        "Connection timeout ({}s) must be longer than the RIB sync interval ({}s)",
+                        self.connection_timeout_secs(),
+                        self.rib_sync_interval_secs
+                    ));
+                }
             }
             IpcpMode::Demo => {
                 // Demo mode has minimal requirements
             }
         }
-        Ok(())
+        validate_seeds(&self.neighbors)
+    }
+
+    /// Returns how long a member waits for its bootstrap before re-enrolling
+    pub fn connection_timeout_secs(&self) -> u64 {
+        self.enrollment_connection_timeout_secs
+            .unwrap_or(self.rib_sync_interval_secs.saturating_mul(3))
+    }
+
+    /// Returns the QoS cube configured for the DIF, if any classes are given
+    pub fn qos_cube(&self) -> Option<QoSCube> {
+        (!self.qos_classes.is_empty()).then(|| QoSCube::from_profiles(self.qos_classes.clone()))
+    }
+
+    /// Lists settings that are accepted but will not behave as written
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.mode == IpcpMode::Member && self.enable_route_persistence {
+            warnings.push(
+                "Route persistence is ignored in member mode; members learn routes from the bootstrap"
+                    .to_string(),
+            );
+        }
+        if self.mode == IpcpMode::Bootstrap && !self.bootstrap_peers.is_empty() {
+            warnings.push("Bootstrap peers are ignored in bootstrap mode".to_string());
+        }
+        if self.mode == IpcpMode::Member && !self.qos_classes.is_empty() {
+            warnings.push(
+                "QoS classes are ignored in member mode; members take the bootstrap's QoS cube"
+                    .to_string(),
+            );
+        }
+        warnings
     }
 
     /// Prints configuration summary
     pub fn print_summary(&self) {
-        println!("=== IPCP Configuration ===");
-        println!("Name: {}", self.name);
-        println!("Mode: {}", self.mode);
-        println!("DIF: {}", self.dif_name);
+        progress!("=== IPCP Configuration ===");
+        progress!("Name: {}", self.name);
+        progress!("Mode: {}", self.mode);
+        progress!("DIF: {}", self.dif_name);
 
         if let Some(addr) = self.address {
-            println!("RINA Address: {}", addr);
+            progress!("RINA Address: {}", addr);
         }
 
         if !self.bind_address.is_empty() {
-            println!("Bind Address: {}", self.bind_address);
+            progress!("Bind Address: {}", self.bind_address);
         }
 
         if !self.bootstrap_peers.is_empty() {
-            println!("Bootstrap Peers: {:?}", self.bootstrap_peers);
+            progress!("Bootstrap Peers: {:?}", self.bootstrap_peers);
         }
 
         if self.mode == IpcpMode::Bootstrap {
-            println!(
+            progress!(
                 "Address Pool: {}-{}",
-                self.address_pool_start, self.address_pool_end
+                self.address_pool_start,
+                self.address_pool_end
             );
         }
 
-        println!();
+        progress!(
+            "Policies: routing={}, scheduling={}, qos={}",
+            self.policies.routing,
+            self.policies.scheduling,
+            self.policies.qos
+        );
+
+        progress!();
+    }
+
+    /// Builds the configuration summary as a single JSON object
+    ///
+    /// The address pool is only reported in bootstrap mode, matching the
+    /// text summary; absent values are `null`.
+    pub fn summary_json(&self) -> serde_json::Value {
+        let pool = (self.mode == IpcpMode::Bootstrap).then(|| {
+            serde_json::json!({
+                "start": self.address_pool_start,
+                "end": self.address_pool_end,
+            })
+        });
+        let bind = (!self.bind_address.is_empty()).then_some(self.bind_address.as_str());
+
+        serde_json::json!({
+            "event": "config",
+            "mode": self.mode.to_string(),
+            "name": self.name,
+            "dif": self.dif_name,
+            "address": self.address,
+            "bind": bind,
+            "bootstrap_peers": self.bootstrap_peers,
+            "address_pool": pool,
+            "policies": {
+                "routing": self.policies.routing.to_string(),
+                "scheduling": self.policies.scheduling.to_string(),
+                "qos": self.policies.qos.to_string(),
+            },
+        })
     }
 }
 
@@ -524,4 +1106,326 @@ mod tests {
         assert_eq!("demo".parse::<IpcpMode>().unwrap(), IpcpMode::Demo);
         assert!("invalid".parse::<IpcpMode>().is_err());
     }
+
+    fn write_config(file_name: &str, policies: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(file_name);
+        let contents = format!(
+            r#"
+[ipcp]
+name = "ipcp-policies"
+type = "normal"
+mode = "bootstrap"
+
+[dif]
+name = "policy-dif"
+address = 1001
+
+[shim]
+bind_address = "127.0.0.1"
+bind_port = 7000
+
+{}
+"#,
+            policies
+        );
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_policies_section_selects_wfq_scheduling() {
+        let path = write_config(
+            "ari-test-policies-wfq.toml",
+            "[policies]\nrouting = \"distance_vector\"\nscheduling = \"wfq\"",
+        );
+        let config = IpcpConfiguration::from_file(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(config.policies.scheduling, SchedulingPolicyKind::Wfq);
+        assert_eq!(config.policies.scheduling.build().name(), "WFQ");
+        assert_eq!(config.policies.routing.build().name(), "DistanceVector");
+        assert_eq!(config.policies.qos, QoSPolicyKind::Simple);
+    }
+
+    #[test]
+    fn test_policies_section_rejects_unknown_name() {
+        let path = write_config(
+            "ari-test-policies-unknown.toml",
+            "[policies]\nscheduling = \"round_robin\"",
+        );
+        let result = IpcpConfiguration::from_file(&path);
+        let _ = fs::remove_file(&path);
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_neighbor_sections_seed_table_and_address_mapper() {
+        let path = write_config(
+            "ari-test-neighbors.toml",
+            r#"
+[[neighbor]]
+name = "ipcp-east"
+address = 2001
+socket = "127.0.0.1:7101"
+
+[[neighbor]]
+name = "ipcp-west"
+address = 2002
+socket = "127.0.0.1:7102"
+"#,
+        );
+        let config = IpcpConfiguration::from_file(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        config.validate().unwrap();
+        assert_eq!(config.neighbors.len(), 2);
+
+        let rib = crate::rib::Rib::new();
+        let table =
+            crate::neighbor::NeighborTable::new(rib.clone(), std::time::Duration::from_secs(60));
+        let shim = crate::shim::UdpShim::new(1001);
+        assert_eq!(table.seed(&config.neighbors, &shim).await.unwrap(), 2);
+
+        assert_eq!(table.reachable_neighbors().await, vec![2001, 2002]);
+        assert!(rib.read("/local/neighbors/ipcp-east").await.is_ok());
+        assert_eq!(
+            shim.lookup_peer(2002),
+            Some("127.0.0.1:7102".parse().unwrap())
+        );
+
+        // Duplicate addresses are rejected before anything is loaded
+        let mut duplicated = config.neighbors.clone();
+        duplicated[1].address = 2001;
+        assert!(validate_seeds(&duplicated).is_err());
+    }
+
+    #[test]
+    fn test_qos_class_sections_build_the_qos_cube() {
+        let path = write_config(
+            "ari-test-qos-classes.toml",
+            r#"
+[[qos_class]]
+name = "gold"
+parameters = { priority = 250, max_delay_ms = 20 }
+
+[[qos_class]]
+name = "bronze"
+parameters = { priority = 50 }
+"#,
+        );
+        let mut config = IpcpConfiguration::from_file(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        config.validate().unwrap();
+
+        let cube = config.qos_cube().unwrap();
+        assert_eq!(cube.get("gold").unwrap().priority, 250);
+        assert_eq!(cube.get("gold").unwrap().max_delay_ms, Some(20));
+        assert_eq!(cube.get("bronze").unwrap().max_delay_ms, None);
+
+        config.qos_classes[1].name = "gold".to_string();
+        assert!(config.validate().unwrap_err().contains("gold"));
+        config.qos_classes.clear();
+        assert!(config.qos_cube().is_none());
+    }
+
+    #[test]
+    fn test_connection_timeout_must_outlast_the_sync_interval() {
+        let path = write_config(
+            "ari-test-connection-timeout.toml",
+            "[rib]\nrib_sync_interval_secs = 120",
+        );
+        let mut config = IpcpConfiguration::from_file(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        config.mode = IpcpMode::Member;
+        config.bootstrap_peers = vec!["127.0.0.1:7000".to_string()];
+
+        // Derived from the sync interval unless set
+        assert_eq!(config.connection_timeout_secs(), 360);
+        config.validate().unwrap();
+
+        config.enrollment_connection_timeout_secs = Some(120);
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("120s"), "{}", error);
+
+        config.enrollment_connection_timeout_secs = Some(121);
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_dif_name_is_rejected_at_load() {
+        let path = write_config(
+            "ari-test-dif-name.toml",
+            "[dif.name_rules]\nallow_namespaces = false",
+        );
+        let contents = fs::read_to_string(&path)
+            .unwrap()
+            .replace("\"policy-dif\"", "\"core.policy-dif\"");
+        fs::write(&path, contents).unwrap();
+        let result = IpcpConfiguration::from_file(&path);
+        let _ = fs::remove_file(&path);
+
+        let error = result.unwrap_err();
+        assert!(error.contains("core.policy-dif"), "{}", error);
+
+        let rules = DifNameRules::default();
+        assert!(rules.validate("core.policy-dif").is_ok());
+        assert!(rules.validate("core..dif").is_err());
+        assert!(rules.validate("my dif").is_err());
+        assert!(rules.validate(&"d".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_enrollment_flags_override_defaults() {
+        let args = CliArgs::parse_from([
+            "ari",
+            "--mode",
+            "member",
+            "--name",
+            "ipcp-b",
+            "--dif-name",
+            "test-dif",
+            "--bind",
+            "127.0.0.1:7001",
+            "--bootstrap-peers",
+            "127.0.0.1:7000",
+            "--enrollment-timeout",
+            "12",
+            "--enrollment-max-retries",
+            "7",
+            "--enrollment-backoff-ms",
+            "250",
+        ]);
+        let config = IpcpConfiguration::from_cli(args).unwrap();
+        assert_eq!(config.enrollment_timeout_secs, 12);
+        assert_eq!(config.enrollment_max_retries, 7);
+        assert_eq!(config.enrollment_initial_backoff_ms, 250);
+
+        // Without the flags the defaults apply
+        let args = CliArgs::parse_from([
+            "ari",
+            "--mode",
+            "member",
+            "--name",
+            "ipcp-b",
+            "--dif-name",
+            "test-dif",
+            "--bind",
+            "127.0.0.1:7001",
+            "--bootstrap-peers",
+            "127.0.0.1:7000",
+        ]);
+        let config = IpcpConfiguration::from_cli(args).unwrap();
+        assert_eq!(config.enrollment_timeout_secs, default_enrollment_timeout());
+        assert_eq!(config.enrollment_max_retries, default_max_retries());
+        assert_eq!(
+            config.enrollment_initial_backoff_ms,
+            default_initial_backoff_ms()
+        );
+    }
+
+    #[test]
+    fn test_static_route_flags_populate_configuration() {
+        let bootstrap_args = |routes: &[&str]| {
+            let mut argv = vec![
+                "ari",
+                "--mode",
+                "bootstrap",
+                "--name",
+                "ipcp-a",
+                "--dif-name",
+                "test-dif",
+                "--address",
+                "1001",
+                "--bind",
+                "127.0.0.1:7000",
+            ];
+            for route in routes {
+                argv.extend(["--static-route", route]);
+            }
+            CliArgs::try_parse_from(argv)
+        };
+
+        let args =
+            bootstrap_args(&["1002,127.0.0.1:7001,1002", "1003,127.0.0.1:7001,1002,5"]).unwrap();
+        let config = IpcpConfiguration::from_cli(args).unwrap();
+        assert_eq!(config.static_routes.len(), 2);
+
+        let first = &config.static_routes[0];
+        assert_eq!(first.destination, 1002);
+        assert_eq!(first.next_hop_address, "127.0.0.1:7001");
+        assert_eq!(first.next_hop_rina_addr, 1002);
+        assert_eq!(first.cost, default_route_cost());
+
+        let second = &config.static_routes[1];
+        assert_eq!(second.destination, 1003);
+        assert_eq!(second.next_hop_rina_addr, 1002);
+        assert_eq!(second.cost, 5);
+
+        // Missing field, unparseable socket and zero address are all rejected
+        assert!(bootstrap_args(&["1002,127.0.0.1:7001"]).is_err());
+        assert!(bootstrap_args(&["1002,not-a-socket,1002"]).is_err());
+        assert!(bootstrap_args(&["0,127.0.0.1:7001,1002"]).is_err());
+    }
+
+    #[test]
+    fn test_later_config_files_merge_over_earlier_ones() {
+        let base = std::env::temp_dir().join("ari-test-merge-base.toml");
+        fs::write(
+            &base,
+            r#"
+[ipcp]
+name = "ipcp-merge"
+type = "normal"
+mode = "bootstrap"
+
+[dif]
+name = "merge-dif"
+address = 1001
+
+[shim]
+bind_address = "127.0.0.1"
+bind_port = 7000
+
+[[routing.static_routes]]
+destination = 1002
+next_hop_address = "127.0.0.1:7001"
+next_hop_rina_addr = 1002
+
+[[routing.static_routes]]
+destination = 1003
+next_hop_address = "127.0.0.1:7002"
+next_hop_rina_addr = 1003
+"#,
+        )
+        .unwrap();
+        let overlay = std::env::temp_dir().join("ari-test-merge-override.toml");
+        fs::write(
+            &overlay,
+            r#"
+[shim]
+bind_port = 7100
+
+[[routing.static_routes]]
+destination = 1004
+next_hop_address = "127.0.0.1:7003"
+next_hop_rina_addr = 1004
+"#,
+        )
+        .unwrap();
+
+        let args = CliArgs::parse_from([
+            "ari",
+            "--config",
+            base.to_str().unwrap(),
+            "--config",
+            overlay.to_str().unwrap(),
+        ]);
+        let config = IpcpConfiguration::from_cli(args).unwrap();
+
+        // Untouched keys survive, scalars are replaced, arrays are appended
+        assert_eq!(config.name, "ipcp-merge");
+        assert_eq!(config.bind_address, "127.0.0.1:7100");
+        let destinations: Vec<u64> = config.static_routes.iter().map(|r| r.destination).collect();
+        assert_eq!(destinations, vec![1002, 1003, 1004]);
+    }
 }
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: EUPL-1.2-or-later
+// Copyright © 2026-present ARI Contributors
+
+//! Flow Encryption
+//!
+//! Optional per-flow confidentiality. When a flow's configuration asks for
+//! encryption, the two ends exchange X25519 public keys in the flow
+//! allocation request and response and derive a shared ChaCha20-Poly1305
+//! key. Each data PDU's payload is then sealed with a nonce built from the
+//! sender's role and the PDU sequence number, with the PDU header fields
+//! authenticated alongside it.
+//!
+//! The exchange itself is unauthenticated: nothing binds the public keys
+//! to the IPCPs that sent them, so an attacker able to rewrite the flow
+//! allocation messages can sit in the middle of both key exchanges. This
+//! keeps payloads from passive observers only.
+
+use crate::error::EfcpError;
+use crate::pdu::Pdu;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use serde::{Deserialize, Serialize};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Domain separation input for deriving flow keys from the X25519 secret
+const KEY_DERIVATION_CONTEXT: &[u8; 16] = b"ari-flow-key-v1\0";
+
+/// Length of the authentication tag appended to every sealed payload
+pub const TAG_SIZE: usize = 16;
+
+/// How a flow protects its payloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptionMode {
+    /// Payloads are sent in the clear
+    #[default]
+    None,
+    /// Payloads are sealed with ChaCha20-Poly1305 under a negotiated key
+    ChaCha20Poly1305,
+}
+
+impl EncryptionMode {
+    /// Returns whether a key has to be negotiated for this mode
+    pub fn is_enabled(self) -> bool {
+        self != EncryptionMode::None
+    }
+}
+
+/// Which end of the flow allocation a key holder was
+///
+/// The two ends share one key, so the role keeps their nonces apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyRole {
+    /// Sent the flow allocation request
+    Initiator,
+    /// Answered the flow allocation request
+    Responder,
+}
+
+impl KeyRole {
+    fn nonce_prefix(self) -> [u8; 4] {
+        match self {
+            KeyRole::Initiator => [0, 0, 0, 0],
+            KeyRole::Responder => [0, 0, 0, 1],
+        }
+    }
+
+    fn peer(self) -> Self {
+        match self {
+            KeyRole::Initiator => KeyRole::Responder,
+            KeyRole::Responder => KeyRole::Initiator,
+        }
+    }
+}
+
+/// One side of an X25519 key exchange, kept until the peer's share arrives
+pub struct KeyExchange {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+impl KeyExchange {
+    /// Generates a fresh key pair from the operating system's RNG
+    pub fn new() -> Result<Self, EfcpError> {
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes)
+            .map_err(|e| EfcpError::KeyNegotiationFailed(e.to_string()))?;
+        let secret = StaticSecret::from(bytes);
+        let public = PublicKey::from(&secret);
+        Ok(Self { secret, public })
+    }
+
+    /// Returns the public share to send to the peer
+    pub fn public_share(&self) -> [u8; 32] {
+        self.public.to_bytes()
+    }
+
+    /// Combines our secret with the peer's share into the flow key
+    ///
+    /// Fails if the peer's share is a low-order point, which would give a
+    /// key an attacker can predict.
+    pub fn derive(self, peer_share: [u8; 32], role: KeyRole) -> Result<FlowKey, EfcpError> {
+        let shared = self.secret.diffie_hellman(&PublicKey::from(peer_share));
+        if !shared.was_contributory() {
+            return Err(EfcpError::KeyNegotiationFailed(
+                "peer sent a low-order public key".to_string(),
+            ));
+        }
+
+        let key = chacha20::hchacha::<chacha20::cipher::consts::U10>(
+            shared.as_bytes().into(),
+            KEY_DERIVATION_CONTEXT.into(),
+        );
+        Ok(FlowKey {
+            cipher: ChaCha20Poly1305::new(&key),
+            role,
+        })
+    }
+}
+
+impl std::fmt::Debug for KeyExchange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyExchange")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Symmetric key protecting one flow's payloads
+#[derive(Clone)]
+pub struct FlowKey {
+    cipher: ChaCha20Poly1305,
+    role: KeyRole,
+}
+
+impl FlowKey {
+    /// Returns which end of the allocation this key belongs to
+    pub fn role(&self) -> KeyRole {
+        self.role
+    }
+
+    /// Encrypts the payload of an outgoing PDU
+    pub fn seal(&self, pdu: &Pdu, plaintext: &[u8]) -> Result<Vec<u8>, EfcpError> {
+        let nonce = Self::nonce(self.role, pdu.sequence_num);
+        self.cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: &Self::associated_data(pdu),
+                },
+            )
+            .map_err(|_| EfcpError::EncryptionFailed(pdu.sequence_num))
+    }
+
+    /// Decrypts the payload of a PDU received from the peer
+    ///
+    /// Fails if the payload was sealed under another key or if it or the
+    /// header was modified in transit.
+    pub fn open(&self, pdu: &Pdu) -> Result<Vec<u8>, EfcpError> {
+        let nonce = Self::nonce(self.role.peer(), pdu.sequence_num);
+        self.cipher
+            .decrypt(
+                &nonce,
+                Payload {
+                    msg: &pdu.payload,
+                    aad: &Self::associated_data(pdu),
+                },
+            )
+            .map_err(|_| EfcpError::DecryptionFailed(pdu.sequence_num))
+    }
+
+    fn nonce(sender: KeyRole, sequence_num: u64) -> Nonce {
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(&sender.nonce_prefix());
+        nonce[4..].copy_from_slice(&sequence_num.to_be_bytes());
+        nonce.into()
+    }
+
+    /// Header fields the sender fixes and relays do not change
+    fn associated_data(pdu: &Pdu) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(32);
+        aad.extend_from_slice(&pdu.src_addr.to_be_bytes());
+        aad.extend_from_slice(&pdu.dst_addr.to_be_bytes());
+        aad.extend_from_slice(&pdu.src_cep_id.to_be_bytes());
+        aad.extend_from_slice(&pdu.dst_cep_id.to_be_bytes());
+        aad.extend_from_slice(&pdu.sequence_num.to_be_bytes());
+        aad.push(u8::from(pdu.more_fragments));
+        aad
+    }
+}
+
+impl std::fmt::Debug for FlowKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlowKey")
+            .field("role", &self.role)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn negotiated_pair() -> (FlowKey, FlowKey) {
+        let initiator = KeyExchange::new().unwrap();
+        let responder = KeyExchange::new().unwrap();
+        let (initiator_share, responder_share) =
+            (initiator.public_share(), responder.public_share());
+        (
+            initiator
+                .derive(responder_share, KeyRole::Initiator)
+                .unwrap(),
+            responder
+                .derive(initiator_share, KeyRole::Responder)
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_sealed_payload_opens_only_with_negotiated_key() {
+        let (initiator, responder) = negotiated_pair();
+        let (_, stranger) = negotiated_pair();
+
+        let mut pdu = Pdu::new_data(100, 200, 10, 20, 7, Vec::new());
+        pdu.payload = initiator.seal(&pdu, b"secret payload").unwrap();
+        assert_eq!(pdu.payload.len(), b"secret payload".len() + TAG_SIZE);
+
+        assert_eq!(responder.open(&pdu).unwrap(), b"secret payload");
+        assert_eq!(stranger.open(&pdu), Err(EfcpError::DecryptionFailed(7)));
+
+        // The header is authenticated too
+        let mut moved = pdu.clone();
+        moved.sequence_num = 8;
+        assert!(responder.open(&moved).is_err());
+    }
+
+    #[test]
+    fn test_low_order_peer_share_is_refused() {
+        let exchange = KeyExchange::new().unwrap();
+        assert!(matches!(
+            exchange.derive([0u8; 32], KeyRole::Initiator),
+            Err(EfcpError::KeyNegotiationFailed(_))
+        ));
+    }
+}
@@ -5,9 +5,34 @@
 //!
 //! Provides name resolution and registration for RINA.
 //! Maps application names to IPCP addresses.
+//!
+//! A directory created with [`Directory::with_rib`] stores every registration
+//! as a RIB object under `/directory/<name>`. RIB synchronization carries
+//! those objects across the DIF over CDAP, so names registered on other
+//! IPCPs become resolvable locally; the in-memory map then only acts as a
+//! cache in front of the RIB, reloaded whenever the RIB version moves on.
+//!
+//! [`Directory::watch`] follows one name through RIB change notifications
+//! and reports each change to its address set, wherever it was registered.
 
+use crate::error::RibError;
+use crate::rib::{CapacityPolicy, Rib, RibChange, RibValue};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
+use tokio::sync::mpsc;
+
+/// RIB name prefix for directory registrations
+pub const DIRECTORY_RIB_PREFIX: &str = "/directory/";
+
+/// RIB object class for directory registrations
+const DIRECTORY_CLASS: &str = "directory_entry";
+
+/// RIB name of the address pool summary written by `AddressPool::mirror_to_rib`
+pub const ADDRESS_POOL_RIB_NAME: &str = "/dif/address-pool";
+
+/// RIB object class of the address pool summary
+const ADDRESS_POOL_CLASS: &str = "address-pool";
 
 /// A naming entry in the directory
 #[derive(Debug, Clone)]
@@ -20,11 +45,88 @@ pub struct DirectoryEntry {
     pub timestamp: u64,
 }
 
+impl DirectoryEntry {
+    fn to_rib_value(&self) -> RibValue {
+        let addresses = self
+            .addresses
+            .iter()
+            .map(|addr| (addr.to_string(), Box::new(RibValue::Integer(*addr as i64))))
+            .collect();
+
+        let mut fields = HashMap::new();
+        fields.insert(
+            "name".to_string(),
+            Box::new(RibValue::String(self.name.clone())),
+        );
+        fields.insert(
+            "addresses".to_string(),
+            Box::new(RibValue::Struct(addresses)),
+        );
+        fields.insert(
+            "timestamp".to_string(),
+            Box::new(RibValue::Integer(self.timestamp as i64)),
+        );
+        RibValue::Struct(fields)
+    }
+
+    fn from_rib_value(value: &RibValue) -> Option<Self> {
+        let fields = value.as_struct()?;
+        let mut addresses: Vec<u64> = fields
+            .get("addresses")?
+            .as_struct()?
+            .values()
+            .filter_map(|addr| addr.as_integer())
+            .map(|addr| addr as u64)
+            .collect();
+        addresses.sort_unstable();
+
+        Some(Self {
+            name: fields.get("name")?.as_string()?.to_string(),
+            addresses,
+            timestamp: fields
+                .get("timestamp")
+                .and_then(|t| t.as_integer())
+                .unwrap_or(0) as u64,
+        })
+    }
+}
+
+/// How the directory compares application names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameMatching {
+    /// Names are trimmed and lowercased, so `Service.Example` and
+    /// ` service.example` are the same name
+    #[default]
+    Normalized,
+    /// Names must match exactly
+    Strict,
+}
+
+impl NameMatching {
+    /// Returns the form of `name` used as the directory key
+    pub fn normalize(self, name: &str) -> String {
+        match self {
+            NameMatching::Normalized => name.trim().to_lowercase(),
+            NameMatching::Strict => name.to_string(),
+        }
+    }
+}
+
 /// Directory Service for name resolution
 #[derive(Debug, Clone)]
 pub struct Directory {
     /// Map of names to directory entries
     entries: Arc<RwLock<HashMap<String, DirectoryEntry>>>,
+    /// RIB that registrations are stored in, if the directory is distributed
+    rib: Option<Rib>,
+    /// How names are compared on register and resolve
+    matching: NameMatching,
+    /// Maximum number of registered names (None = unbounded)
+    max_names: Option<usize>,
+    /// What `register` does with a new name once `max_names` is reached
+    capacity_policy: CapacityPolicy,
+    /// RIB version the cache was last reloaded at
+    cached_version: Arc<AtomicU64>,
 }
 
 impl Directory {
@@ -32,53 +134,196 @@ impl Directory {
     pub fn new() -> Self {
         Self {
             entries: Arc::new(RwLock::new(HashMap::new())),
+            rib: None,
+            matching: NameMatching::default(),
+            max_names: None,
+            capacity_policy: CapacityPolicy::default(),
+            cached_version: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Creates a directory whose registrations are shared through `rib`
+    pub fn with_rib(rib: Rib) -> Self {
+        Self {
+            rib: Some(rib),
+            ..Self::new()
         }
     }
 
+    /// Sets how names are compared
+    pub fn with_name_matching(mut self, matching: NameMatching) -> Self {
+        self.matching = matching;
+        self
+    }
+
+    /// Returns how names are compared
+    pub fn name_matching(&self) -> NameMatching {
+        self.matching
+    }
+
+    /// Caps the number of registered names, applying `policy` to new names once full
+    ///
+    /// With `CapacityPolicy::EvictOldest` the name registered or refreshed
+    /// longest ago is unregistered from every address to make room.
+    pub fn with_max_names(mut self, max: usize, policy: CapacityPolicy) -> Self {
+        self.max_names = Some(max);
+        self.capacity_policy = policy;
+        self
+    }
+
     /// Registers a name at a specific address
-    pub fn register(&self, name: String, address: u64) -> Result<(), String> {
-        let mut entries = self.entries.write().unwrap();
+    pub async fn register(&self, name: String, address: u64) -> Result<(), RibError> {
+        let name = self.matching.normalize(&name);
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
 
-        entries
-            .entry(name.clone())
-            .and_modify(|e| {
-                if !e.addresses.contains(&address) {
-                    e.addresses.push(address);
-                }
-                e.timestamp = timestamp;
-            })
-            .or_insert(DirectoryEntry {
+        // Start from the shared entry so addresses registered elsewhere survive
+        let shared = self.read_from_rib(&name).await;
+        let (entry, evicted) = {
+            let mut entries = self.entries.write().unwrap();
+            let evicted = self.make_room(&mut entries, &name)?;
+            let entry = entries.entry(name.clone()).or_insert(DirectoryEntry {
                 name,
-                addresses: vec![address],
+                addresses: Vec::new(),
                 timestamp,
             });
+            if let Some(shared) = shared {
+                for addr in shared.addresses {
+                    if !entry.addresses.contains(&addr) {
+                        entry.addresses.push(addr);
+                    }
+                }
+            }
+            if !entry.addresses.contains(&address) {
+                entry.addresses.push(address);
+            }
+            entry.timestamp = timestamp;
+            (entry.clone(), evicted)
+        };
 
-        Ok(())
+        if let Some(evicted) = evicted
+            && let Some(rib) = &self.rib
+        {
+            let _ = rib.delete(&rib_name(&evicted.name)).await;
+        }
+        self.write_to_rib(&entry).await
+    }
+
+    /// Applies the capacity policy before `name` is added
+    ///
+    /// Returns the evicted entry, if one had to go.
+    fn make_room(
+        &self,
+        entries: &mut HashMap<String, DirectoryEntry>,
+        name: &str,
+    ) -> Result<Option<DirectoryEntry>, RibError> {
+        let Some(max) = self.max_names else {
+            return Ok(None);
+        };
+        if entries.contains_key(name) || entries.len() < max {
+            return Ok(None);
+        }
+
+        match self.capacity_policy {
+            CapacityPolicy::Reject => Err(RibError::CapacityExceeded {
+                name: name.to_string(),
+                max,
+            }),
+            CapacityPolicy::EvictOldest => {
+                let stalest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.timestamp)
+                    .map(|(key, _)| key.clone());
+                Ok(stalest.and_then(|key| entries.remove(&key)))
+            }
+        }
     }
 
     /// Unregisters a name from a specific address
-    pub fn unregister(&self, name: &str, address: u64) -> Result<(), String> {
-        let mut entries = self.entries.write().unwrap();
+    pub async fn unregister(&self, name: &str, address: u64) -> Result<(), RibError> {
+        let name = &self.matching.normalize(name);
+        let shared = self.read_from_rib(name).await;
+        let remaining = {
+            let mut entries = self.entries.write().unwrap();
+            if let Some(shared) = shared {
+                entries.insert(name.to_string(), shared);
+            }
 
-        if let Some(entry) = entries.get_mut(name) {
+            let Some(entry) = entries.get_mut(name) else {
+                return Err(RibError::NotFound(name.to_string()));
+            };
             entry.addresses.retain(|&addr| addr != address);
             if entry.addresses.is_empty() {
                 entries.remove(name);
+                None
+            } else {
+                Some(entry.clone())
+            }
+        };
+
+        match remaining {
+            Some(entry) => self.write_to_rib(&entry).await,
+            None => {
+                if let Some(rib) = &self.rib {
+                    let _ = rib.delete(&rib_name(name)).await;
+                }
+                Ok(())
             }
-            Ok(())
-        } else {
-            Err(format!("Name '{}' not found", name))
         }
     }
 
     /// Resolves a name to a list of addresses
-    pub fn resolve(&self, name: &str) -> Option<Vec<u64>> {
-        let entries = self.entries.read().unwrap();
-        entries.get(name).map(|e| e.addresses.clone())
+    ///
+    /// Answers from the local cache when possible and falls back to the RIB,
+    /// caching whatever it finds there. The cache is reloaded first if the
+    /// RIB changed since it was last loaded, so updates synced in from other
+    /// IPCPs are not masked by a stale entry.
+    pub async fn resolve(&self, name: &str) -> Option<Vec<u64>> {
+        let name = &self.matching.normalize(name);
+        if let Some(rib) = &self.rib
+            && rib.current_version().await != self.cached_version.load(Ordering::Acquire)
+        {
+            self.refresh().await;
+        }
+        if let Some(entry) = self.entries.read().unwrap().get(name) {
+            return Some(entry.addresses.clone());
+        }
+
+        let entry = self.read_from_rib(name).await?;
+        let addresses = entry.addresses.clone();
+        self.entries
+            .write()
+            .unwrap()
+            .insert(name.to_string(), entry);
+        Some(addresses)
+    }
+
+    /// Reloads the cache from the RIB, picking up registrations synced in
+    /// from other IPCPs and dropping ones removed elsewhere
+    ///
+    /// Returns the number of cached names.
+    pub async fn refresh(&self) -> usize {
+        let Some(rib) = &self.rib else {
+            return self.count();
+        };
+
+        // Taken first: a change made while loading triggers another reload
+        let version = rib.current_version().await;
+        let mut refreshed = HashMap::new();
+        for object_name in rib.list_by_class(DIRECTORY_CLASS).await {
+            if let Ok(obj) = rib.read(&object_name).await
+                && let Some(entry) = DirectoryEntry::from_rib_value(&obj.value)
+            {
+                refreshed.insert(self.matching.normalize(&entry.name), entry);
+            }
+        }
+
+        let mut entries = self.entries.write().unwrap();
+        *entries = refreshed;
+        self.cached_version.store(version, Ordering::Release);
+        entries.len()
     }
 
     /// Lists all registered names
@@ -93,11 +338,61 @@ impl Directory {
         entries.len()
     }
 
-    /// Clears all entries
+    /// Watches a name for changes to its address set
+    ///
+    /// Returns `None` for a directory without a RIB, as there is nothing to
+    /// be notified by. Registrations synced in from other IPCPs are reported
+    /// like local ones.
+    pub async fn watch(&self, name: &str) -> Option<DirectoryWatch> {
+        let rib = self.rib.as_ref()?;
+        let name = self.matching.normalize(name);
+        let object_name = rib_name(&name);
+
+        // Subscribe first so no change slips in between reading and watching
+        let changes = rib.subscribe(&object_name);
+        let addresses = self
+            .read_from_rib(&name)
+            .await
+            .map(|entry| entry.addresses)
+            .unwrap_or_default();
+
+        Some(DirectoryWatch {
+            name,
+            object_name,
+            addresses,
+            changes,
+        })
+    }
+
+    /// Clears all cached entries
     pub fn clear(&self) {
         let mut entries = self.entries.write().unwrap();
         entries.clear();
     }
+
+    async fn read_from_rib(&self, name: &str) -> Option<DirectoryEntry> {
+        let obj = self.rib.as_ref()?.read(&rib_name(name)).await.ok()?;
+        DirectoryEntry::from_rib_value(&obj.value)
+    }
+
+    /// Writes the entry to its `/directory/<name>` RIB object
+    async fn write_to_rib(&self, entry: &DirectoryEntry) -> Result<(), RibError> {
+        let Some(rib) = &self.rib else {
+            return Ok(());
+        };
+
+        let name = rib_name(&entry.name);
+        let value = entry.to_rib_value();
+        if rib.read(&name).await.is_ok() {
+            rib.update(&name, value).await
+        } else {
+            rib.create(name, DIRECTORY_CLASS.to_string(), value).await
+        }
+    }
+}
+
+fn rib_name(name: &str) -> String {
+    format!("{}{}", DIRECTORY_RIB_PREFIX, name)
 }
 
 impl Default for Directory {
@@ -106,71 +401,268 @@ impl Default for Directory {
     }
 }
 
+/// A change to the address set of a watched name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEvent {
+    /// The watched name
+    pub name: String,
+    /// Addresses the name became reachable at
+    pub added: Vec<u64>,
+    /// Addresses the name is no longer reachable at
+    pub removed: Vec<u64>,
+    /// Every address after the change; empty once the name is gone
+    pub addresses: Vec<u64>,
+}
+
+/// Address changes of one name, created by [`Directory::watch`]
+#[derive(Debug)]
+pub struct DirectoryWatch {
+    /// The watched name, normalized
+    name: String,
+    /// RIB object holding the name's registrations
+    object_name: String,
+    /// Addresses as of the last reported event
+    addresses: Vec<u64>,
+    /// Changes under the object's name
+    changes: mpsc::Receiver<RibChange>,
+}
+
+impl DirectoryWatch {
+    /// Returns the addresses as of the last reported event
+    pub fn addresses(&self) -> &[u64] {
+        &self.addresses
+    }
+
+    /// Waits for the next change to the name's address set
+    ///
+    /// Writes that leave the set as it was, such as a refreshed
+    /// registration, are skipped. Returns `None` once the RIB is dropped.
+    pub async fn next(&mut self) -> Option<DirectoryEvent> {
+        loop {
+            let change = self.changes.recv().await?;
+            if change.object_name() != self.object_name {
+                continue;
+            }
+
+            let addresses = match &change {
+                RibChange::Created(obj) | RibChange::Updated(obj) => {
+                    DirectoryEntry::from_rib_value(&obj.value)
+                        .map(|entry| entry.addresses)
+                        .unwrap_or_default()
+                }
+                RibChange::Deleted { .. } => Vec::new(),
+            };
+            let added: Vec<u64> = addresses
+                .iter()
+                .filter(|addr| !self.addresses.contains(addr))
+                .copied()
+                .collect();
+            let removed: Vec<u64> = self
+                .addresses
+                .iter()
+                .filter(|addr| !addresses.contains(addr))
+                .copied()
+                .collect();
+            if added.is_empty() && removed.is_empty() {
+                continue;
+            }
+
+            self.addresses = addresses.clone();
+            return Some(DirectoryEvent {
+                name: self.name.clone(),
+                added,
+                removed,
+                addresses,
+            });
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_directory_register_and_resolve() {
+    #[tokio::test]
+    async fn test_directory_register_and_resolve() {
         let dir = Directory::new();
 
-        dir.register("app.example".to_string(), 1000).unwrap();
+        dir.register("app.example".to_string(), 1000).await.unwrap();
 
-        let addresses = dir.resolve("app.example");
+        let addresses = dir.resolve("app.example").await;
         assert!(addresses.is_some());
         assert_eq!(addresses.unwrap(), vec![1000]);
     }
 
-    #[test]
-    fn test_directory_multiple_addresses() {
+    #[tokio::test]
+    async fn test_cached_entry_follows_later_rib_updates() {
+        let rib = Rib::new();
+        let local = Directory::with_rib(rib.clone());
+        // Stands in for registrations synced in from another IPCP
+        let remote = Directory::with_rib(rib.clone());
+
+        local.register("svc".to_string(), 1000).await.unwrap();
+        assert_eq!(local.resolve("svc").await, Some(vec![1000]));
+
+        remote.register("svc".to_string(), 2000).await.unwrap();
+        let mut addresses = local.resolve("svc").await.unwrap();
+        addresses.sort_unstable();
+        assert_eq!(addresses, vec![1000, 2000]);
+
+        remote.unregister("svc", 1000).await.unwrap();
+        remote.unregister("svc", 2000).await.unwrap();
+        assert_eq!(local.resolve("svc").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_directory_multiple_addresses() {
         let dir = Directory::new();
 
-        dir.register("service.example".to_string(), 1000).unwrap();
-        dir.register("service.example".to_string(), 2000).unwrap();
+        dir.register("service.example".to_string(), 1000)
+            .await
+            .unwrap();
+        dir.register("service.example".to_string(), 2000)
+            .await
+            .unwrap();
 
-        let addresses = dir.resolve("service.example").unwrap();
+        let addresses = dir.resolve("service.example").await.unwrap();
         assert_eq!(addresses.len(), 2);
         assert!(addresses.contains(&1000));
         assert!(addresses.contains(&2000));
     }
 
-    #[test]
-    fn test_directory_unregister() {
+    #[tokio::test]
+    async fn test_directory_unregister() {
         let dir = Directory::new();
 
-        dir.register("app".to_string(), 1000).unwrap();
-        dir.register("app".to_string(), 2000).unwrap();
+        dir.register("app".to_string(), 1000).await.unwrap();
+        dir.register("app".to_string(), 2000).await.unwrap();
 
-        dir.unregister("app", 1000).unwrap();
+        dir.unregister("app", 1000).await.unwrap();
 
-        let addresses = dir.resolve("app").unwrap();
+        let addresses = dir.resolve("app").await.unwrap();
         assert_eq!(addresses, vec![2000]);
     }
 
-    #[test]
-    fn test_directory_unregister_last_address() {
+    #[tokio::test]
+    async fn test_directory_unregister_last_address() {
         let dir = Directory::new();
 
-        dir.register("app".to_string(), 1000).unwrap();
-        dir.unregister("app", 1000).unwrap();
+        dir.register("app".to_string(), 1000).await.unwrap();
+        dir.unregister("app", 1000).await.unwrap();
 
-        assert!(dir.resolve("app").is_none());
+        assert!(dir.resolve("app").await.is_none());
     }
 
-    #[test]
-    fn test_directory_list_names() {
+    #[tokio::test]
+    async fn test_name_matching_normalized_and_strict() {
+        let dir = Directory::new();
+        dir.register("Service.Example".to_string(), 1000)
+            .await
+            .unwrap();
+        assert_eq!(dir.resolve("service.example").await, Some(vec![1000]));
+        assert_eq!(dir.resolve("  SERVICE.example ").await, Some(vec![1000]));
+
+        let strict = Directory::new().with_name_matching(NameMatching::Strict);
+        strict
+            .register("Service.Example".to_string(), 1000)
+            .await
+            .unwrap();
+        assert!(strict.resolve("service.example").await.is_none());
+        assert_eq!(strict.resolve("Service.Example").await, Some(vec![1000]));
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_second_address_and_removal() {
+        let dir = Directory::with_rib(Rib::new());
+        dir.register("backend".to_string(), 1000).await.unwrap();
+
+        let mut watch = dir.watch("Backend").await.unwrap();
+        assert_eq!(watch.addresses(), &[1000]);
+
+        // Refreshing an existing registration changes nothing
+        dir.register("backend".to_string(), 1000).await.unwrap();
+        dir.register("other".to_string(), 3000).await.unwrap();
+        dir.register("backend".to_string(), 2000).await.unwrap();
+
+        let event = watch.next().await.unwrap();
+        assert_eq!(
+            event,
+            DirectoryEvent {
+                name: "backend".to_string(),
+                added: vec![2000],
+                removed: vec![],
+                addresses: vec![1000, 2000],
+            }
+        );
+
+        dir.unregister("backend", 1000).await.unwrap();
+        dir.unregister("backend", 2000).await.unwrap();
+        assert_eq!(watch.next().await.unwrap().removed, vec![1000]);
+        let gone = watch.next().await.unwrap();
+        assert_eq!(gone.removed, vec![2000]);
+        assert!(gone.addresses.is_empty());
+
+        assert!(Directory::new().watch("backend").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_directory_list_names() {
         let dir = Directory::new();
 
-        dir.register("app1".to_string(), 1000).unwrap();
-        dir.register("app2".to_string(), 2000).unwrap();
+        dir.register("app1".to_string(), 1000).await.unwrap();
+        dir.register("app2".to_string(), 2000).await.unwrap();
 
         let names = dir.list_names();
         assert_eq!(names.len(), 2);
         assert!(names.contains(&"app1".to_string()));
         assert!(names.contains(&"app2".to_string()));
     }
+    #[tokio::test]
+    async fn test_max_names_rejects_or_evicts() {
+        let dir = Directory::new().with_max_names(2, CapacityPolicy::Reject);
+        dir.register("app1".to_string(), 1000).await.unwrap();
+        dir.register("app2".to_string(), 2000).await.unwrap();
+        let err = dir.register("app3".to_string(), 3000).await.unwrap_err();
+        assert!(matches!(err, RibError::CapacityExceeded { max: 2, .. }));
+        // Existing names can still gain addresses
+        dir.register("app1".to_string(), 1001).await.unwrap();
+        assert_eq!(dir.count(), 2);
+
+        let rib = Rib::new();
+        let dir = Directory::with_rib(rib.clone()).with_max_names(2, CapacityPolicy::EvictOldest);
+        dir.register("app1".to_string(), 1000).await.unwrap();
+        dir.register("app2".to_string(), 2000).await.unwrap();
+        dir.entries
+            .write()
+            .unwrap()
+            .get_mut("app1")
+            .unwrap()
+            .timestamp = 0;
+
+        dir.register("app3".to_string(), 3000).await.unwrap();
+        assert_eq!(dir.count(), 2);
+        assert!(dir.resolve("app1").await.is_none());
+        assert!(rib.read("/directory/app1").await.is_err());
+        assert_eq!(dir.resolve("app3").await, Some(vec![3000]));
+    }
+}
+
+/// Order in which an `AddressPool` hands out addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AllocationStrategy {
+    /// Always hand out the lowest free address, reusing released ones first
+    #[default]
+    LowestAvailable,
+    /// Hand out increasing addresses, only wrapping around to released ones
+    /// once the end of the range is reached
+    Sequential,
+    /// Hand out free addresses in unpredictable order
+    Random,
 }
 
+/// Random probes made before `AllocationStrategy::Random` falls back to a scan
+const RANDOM_PROBES: usize = 16;
+
 /// Address pool for dynamic address assignment (used by bootstrap IPCP)
 #[derive(Debug, Clone)]
 pub struct AddressPool {
@@ -180,18 +672,65 @@ pub struct AddressPool {
     end: u64,
     /// Currently assigned addresses
     assigned: Arc<RwLock<std::collections::HashSet<u64>>>,
+    /// Addresses set aside for specific members, never allocated dynamically
+    reserved: Arc<RwLock<std::collections::HashSet<u64>>>,
+    /// Who claimed each reserved address, until it is released
+    reserved_holders: Arc<RwLock<HashMap<u64, String>>>,
+    /// How the next address is picked
+    strategy: AllocationStrategy,
+    /// Where the next sequential scan starts
+    cursor: Arc<RwLock<u64>>,
+    /// Available count at or below which the pool is low (None = never)
+    low_watermark: Option<u64>,
+    /// Whether availability is currently at or below the watermark
+    low_availability: Arc<AtomicBool>,
+    /// Times availability has dropped to the watermark
+    low_availability_alerts: Arc<AtomicU64>,
 }
 
 impl AddressPool {
     /// Creates a new address pool with the given range
     pub fn new(start: u64, end: u64) -> Self {
+        Self::with_strategy(start, end, AllocationStrategy::default())
+    }
+
+    /// Creates a new address pool that allocates according to `strategy`
+    pub fn with_strategy(start: u64, end: u64, strategy: AllocationStrategy) -> Self {
         Self {
             start,
             end,
             assigned: Arc::new(RwLock::new(std::collections::HashSet::new())),
+            reserved: Arc::new(RwLock::new(std::collections::HashSet::new())),
+            reserved_holders: Arc::new(RwLock::new(HashMap::new())),
+            strategy,
+            cursor: Arc::new(RwLock::new(start)),
+            low_watermark: None,
+            low_availability: Arc::new(AtomicBool::new(false)),
+            low_availability_alerts: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Warns once the available fraction of the pool drops to `fraction`
+    ///
+    /// A fraction of 0.1 flags a pool of 10 as low after its 9th allocation.
+    /// The watermark is rounded up, so any non-zero fraction of a small pool
+    /// still warns before its last address is handed out. The alert fires
+    /// again only after availability has recovered.
+    pub fn with_low_watermark(mut self, fraction: f64) -> Self {
+        let fraction = fraction.clamp(0.0, 1.0);
+        // Shave off float noise so that 0.3 of 10 stays 3 instead of 4
+        let exact = self.capacity() as f64 * fraction - 1e-9;
+        let watermark = exact.ceil().max(0.0) as u64;
+        self.low_watermark = Some(if fraction > 0.0 { watermark.max(1) } else { 0 });
+        self.check_availability();
+        self
+    }
+
+    /// Returns the allocation strategy of this pool
+    pub fn strategy(&self) -> AllocationStrategy {
+        self.strategy
+    }
+
     /// Allocates the next available address
     ///
     /// # Returns
@@ -199,16 +738,101 @@ impl AddressPool {
     /// * `Err(String)` if no addresses are available
     pub fn allocate(&self) -> Result<u64, String> {
         let mut assigned = self.assigned.write().unwrap();
+        let reserved = self.reserved.read().unwrap();
+        let is_free = |addr: &u64| !assigned.contains(addr) && !reserved.contains(addr);
 
-        // Find first available address
-        for addr in self.start..=self.end {
-            if !assigned.contains(&addr) {
-                assigned.insert(addr);
-                return Ok(addr);
+        let addr = match self.strategy {
+            AllocationStrategy::LowestAvailable => self.scan_from(self.start, is_free),
+            AllocationStrategy::Sequential => {
+                let mut cursor = self.cursor.write().unwrap();
+                let addr = self.scan_from(*cursor, is_free);
+                if let Some(addr) = addr {
+                    *cursor = if addr == self.end {
+                        self.start
+                    } else {
+                        addr + 1
+                    };
+                }
+                addr
             }
+            AllocationStrategy::Random => (0..RANDOM_PROBES)
+                .map(|_| fastrand::u64(self.start..=self.end))
+                .find(is_free)
+                .or_else(|| self.scan_from(fastrand::u64(self.start..=self.end), is_free)),
+        };
+
+        let addr = addr.ok_or_else(|| "Address pool exhausted".to_string())?;
+        assigned.insert(addr);
+        drop((assigned, reserved));
+        self.check_availability();
+        Ok(addr)
+    }
+
+    /// Finds the first free address at or after `from`, wrapping around once
+    fn scan_from(&self, from: u64, is_free: impl Fn(&u64) -> bool) -> Option<u64> {
+        (from..=self.end).chain(self.start..from).find(is_free)
+    }
+
+    /// Allocates a specific address if it is free and not reserved
+    pub fn claim(&self, address: u64) -> Result<u64, String> {
+        if address < self.start || address > self.end {
+            return Err("Address out of pool range".to_string());
+        }
+
+        let mut assigned = self.assigned.write().unwrap();
+        if self.reserved.read().unwrap().contains(&address) {
+            return Err(format!("Address {} is reserved", address));
+        }
+        if !assigned.insert(address) {
+            return Err(format!("Address {} is already allocated", address));
+        }
+        drop(assigned);
+        self.check_availability();
+        Ok(address)
+    }
+
+    /// Sets an address aside so that `allocate` never hands it out
+    ///
+    /// The address can only be obtained through `allocate_reserved`.
+    pub fn reserve(&self, address: u64) -> Result<(), String> {
+        if address < self.start || address > self.end {
+            return Err("Address out of pool range".to_string());
         }
 
-        Err("Address pool exhausted".to_string())
+        let assigned = self.assigned.read().unwrap();
+        if assigned.contains(&address) {
+            return Err(format!("Address {} is already allocated", address));
+        }
+        self.reserved.write().unwrap().insert(address);
+        drop(assigned);
+        self.check_availability();
+        Ok(())
+    }
+
+    /// Claims a previously reserved address for `holder`
+    ///
+    /// Claiming an address `holder` already claimed succeeds, so a member
+    /// that re-enrolls keeps its address; anyone else is refused until it is
+    /// released. The reservation outlives `release`.
+    pub fn allocate_reserved(&self, address: u64, holder: &str) -> Result<u64, String> {
+        let mut assigned = self.assigned.write().unwrap();
+        if !self.reserved.read().unwrap().contains(&address) {
+            return Err(format!("Address {} is not reserved", address));
+        }
+        let mut holders = self.reserved_holders.write().unwrap();
+        if let Some(current) = holders.get(&address)
+            && current != holder
+        {
+            return Err(format!("Address {} is already allocated", address));
+        }
+        holders.insert(address, holder.to_string());
+        assigned.insert(address);
+        Ok(address)
+    }
+
+    /// Checks if an address is reserved
+    pub fn is_reserved(&self, address: u64) -> bool {
+        self.reserved.read().unwrap().contains(&address)
     }
 
     /// Releases an address back to the pool
@@ -222,6 +846,9 @@ impl AddressPool {
         if !assigned.remove(&address) {
             return Err("Address was not allocated".to_string());
         }
+        self.reserved_holders.write().unwrap().remove(&address);
+        drop(assigned);
+        self.check_availability();
 
         Ok(())
     }
@@ -243,9 +870,105 @@ impl AddressPool {
         self.end - self.start + 1
     }
 
-    /// Returns available addresses count
+    /// Returns how many addresses are in use (allocated or reserved) out of the total
+    pub fn utilization(&self) -> (u64, u64) {
+        (self.capacity() - self.available_count(), self.capacity())
+    }
+
+    /// Whether availability is at or below the low watermark
+    pub fn is_low(&self) -> bool {
+        self.low_availability.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many times availability has dropped to the low watermark
+    pub fn low_availability_alerts(&self) -> u64 {
+        self.low_availability_alerts.load(Ordering::Relaxed)
+    }
+
+    /// Re-evaluates the low watermark, warning when availability drops to it
+    fn check_availability(&self) {
+        let Some(watermark) = self.low_watermark else {
+            return;
+        };
+        let available = self.available_count();
+        let low = available <= watermark;
+        if low && !self.low_availability.swap(true, Ordering::Relaxed) {
+            self.low_availability_alerts.fetch_add(1, Ordering::Relaxed);
+            let (used, total) = self.utilization();
+            eprintln!(
+                "  ⚠ Address pool {}-{} is running low: {} of {} in use, {} available",
+                self.start, self.end, used, total, available
+            );
+        } else if !low {
+            self.low_availability.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the number of addresses `allocate` can still hand out
     pub fn available_count(&self) -> u64 {
-        self.capacity() - self.allocated_count() as u64
+        let assigned = self.assigned.read().unwrap();
+        let reserved = self.reserved.read().unwrap();
+        let unclaimed_reservations = reserved.difference(&assigned).count();
+        self.capacity() - (assigned.len() + unclaimed_reservations) as u64
+    }
+
+    /// Returns every address in the range with whether `allocate` could hand it out
+    pub fn snapshot(&self) -> Vec<(u64, bool)> {
+        let assigned = self.assigned.read().unwrap();
+        let reserved = self.reserved.read().unwrap();
+        (self.start..=self.end)
+            .map(|addr| (addr, !assigned.contains(&addr) && !reserved.contains(&addr)))
+            .collect()
+    }
+
+    /// Writes a summary of the pool to a single `/dif/address-pool` RIB object
+    ///
+    /// The summary holds the range, the allocated addresses and the number
+    /// still available; it is a point-in-time copy, so call this again after
+    /// allocations that should be visible through the RIB.
+    pub async fn mirror_to_rib(&self, rib: &Rib) -> Result<(), String> {
+        let allocated: HashMap<String, Box<RibValue>> = {
+            let assigned = self.assigned.read().unwrap();
+            assigned
+                .iter()
+                .map(|addr| (addr.to_string(), Box::new(RibValue::Integer(*addr as i64))))
+                .collect()
+        };
+
+        let mut fields = HashMap::new();
+        fields.insert(
+            "start".to_string(),
+            Box::new(RibValue::Integer(self.start as i64)),
+        );
+        fields.insert(
+            "end".to_string(),
+            Box::new(RibValue::Integer(self.end as i64)),
+        );
+        fields.insert(
+            "available".to_string(),
+            Box::new(RibValue::Integer(self.available_count() as i64)),
+        );
+        fields.insert(
+            "allocated".to_string(),
+            Box::new(RibValue::Struct(allocated)),
+        );
+        fields.insert(
+            "low_availability".to_string(),
+            Box::new(RibValue::Boolean(self.is_low())),
+        );
+        let value = RibValue::Struct(fields);
+
+        let result = if rib.read(ADDRESS_POOL_RIB_NAME).await.is_ok() {
+            rib.update(ADDRESS_POOL_RIB_NAME, value).await
+        } else {
+            rib.create(
+                ADDRESS_POOL_RIB_NAME.to_string(),
+                ADDRESS_POOL_CLASS.to_string(),
+                value,
+            )
+            .await
+        };
+        result.map_err(|e| e.to_string())
     }
 }
 
@@ -261,8 +984,8 @@ mod address_pool_tests {
         let addr2 = pool.allocate().unwrap();
 
         assert_ne!(addr1, addr2);
-        assert!(addr1 >= 1000 && addr1 <= 1005);
-        assert!(addr2 >= 1000 && addr2 <= 1005);
+        assert!((1000..=1005).contains(&addr1));
+        assert!((1000..=1005).contains(&addr2));
     }
 
     #[test]
@@ -292,6 +1015,71 @@ mod address_pool_tests {
         assert_eq!(addr, addr2);
     }
 
+    #[test]
+    fn test_sequential_strategy_yields_increasing_addresses() {
+        let pool = AddressPool::with_strategy(1000, 1005, AllocationStrategy::Sequential);
+
+        let first = pool.allocate().unwrap();
+        let second = pool.allocate().unwrap();
+        pool.release(first).unwrap();
+        let third = pool.allocate().unwrap();
+
+        assert_eq!((first, second, third), (1000, 1001, 1002));
+    }
+
+    #[test]
+    fn test_lowest_available_strategy_reuses_freed_address() {
+        let pool = AddressPool::with_strategy(1000, 1005, AllocationStrategy::LowestAvailable);
+
+        let _a = pool.allocate().unwrap();
+        let b = pool.allocate().unwrap();
+        let _c = pool.allocate().unwrap();
+        pool.release(b).unwrap();
+
+        assert_eq!(pool.allocate().unwrap(), b);
+    }
+
+    #[test]
+    fn test_random_strategy_stays_in_bounds_without_duplicates() {
+        let pool = AddressPool::with_strategy(1000, 1019, AllocationStrategy::Random);
+
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..20 {
+            let addr = pool.allocate().unwrap();
+            assert!((1000..=1019).contains(&addr));
+            assert!(seen.insert(addr), "address {} handed out twice", addr);
+        }
+        assert!(pool.allocate().is_err());
+    }
+
+    #[test]
+    fn test_reserved_address_is_not_allocated_dynamically() {
+        let pool = AddressPool::new(1000, 1002);
+        pool.reserve(1000).unwrap();
+
+        assert_eq!(pool.allocate().unwrap(), 1001);
+        assert_eq!(pool.allocate().unwrap(), 1002);
+        assert!(pool.allocate().is_err());
+
+        assert_eq!(pool.allocate_reserved(1000, "member-a").unwrap(), 1000);
+        assert!(pool.allocate_reserved(1001, "member-a").is_err());
+    }
+
+    #[test]
+    fn test_reserved_address_is_only_regranted_to_its_holder() {
+        let pool = AddressPool::new(1000, 1002);
+        pool.reserve(1000).unwrap();
+
+        assert_eq!(pool.allocate_reserved(1000, "member-a").unwrap(), 1000);
+        assert_eq!(pool.allocate_reserved(1000, "member-a").unwrap(), 1000);
+        assert!(pool.allocate_reserved(1000, "member-b").is_err());
+
+        // Once released the reservation can be claimed afresh
+        pool.release(1000).unwrap();
+        assert_eq!(pool.allocate_reserved(1000, "member-b").unwrap(), 1000);
+        assert!(pool.allocate_reserved(1000, "member-a").is_err());
+    }
+
     #[test]
     fn test_address_pool_capacity() {
         let pool = AddressPool::new(1000, 1010);
@@ -302,4 +1090,90 @@ mod address_pool_tests {
         pool.allocate().unwrap();
         assert_eq!(pool.available_count(), 10);
     }
+
+    #[tokio::test]
+    async fn test_snapshot_tracks_allocation_without_rib_objects() {
+        let pool = AddressPool::new(1000, 3999);
+        let addr = pool.allocate().unwrap();
+
+        let snapshot = pool.snapshot();
+        assert_eq!(snapshot.len(), 3000);
+        assert_eq!(snapshot[0], (1000, false));
+        assert_eq!(addr, 1000);
+        assert!(snapshot[1..].iter().all(|&(_, available)| available));
+
+        pool.release(addr).unwrap();
+        assert_eq!(pool.snapshot()[0], (1000, true));
+
+        // Mirroring writes one summary object, not one per address
+        let rib = Rib::new();
+        pool.allocate().unwrap();
+        pool.mirror_to_rib(&rib).await.unwrap();
+        pool.mirror_to_rib(&rib).await.unwrap();
+        assert_eq!(rib.count().await, 1);
+        let summary = rib.read(ADDRESS_POOL_RIB_NAME).await.unwrap();
+        assert_eq!(
+            summary.value.as_struct().unwrap()["available"].as_integer(),
+            Some(2999)
+        );
+    }
+
+    #[test]
+    fn test_low_watermark_signals_before_exhaustion() {
+        let pool = AddressPool::new(1000, 1009).with_low_watermark(0.1);
+
+        for _ in 0..8 {
+            pool.allocate().unwrap();
+        }
+        assert_eq!(pool.utilization(), (8, 10));
+        assert!(!pool.is_low());
+        assert_eq!(pool.low_availability_alerts(), 0);
+
+        // The 9th allocation leaves 10% free
+        let addr = pool.allocate().unwrap();
+        assert_eq!(pool.utilization(), (9, 10));
+        assert!(pool.is_low());
+        assert_eq!(pool.low_availability_alerts(), 1);
+
+        // Staying low does not alert again; recovering and dropping back does
+        pool.allocate().unwrap();
+        assert_eq!(pool.low_availability_alerts(), 1);
+        pool.release(addr).unwrap();
+        pool.release(1000).unwrap();
+        assert!(!pool.is_low());
+        pool.allocate().unwrap();
+        assert_eq!(pool.low_availability_alerts(), 2);
+    }
+
+    #[test]
+    fn test_low_watermark_rounds_up() {
+        // 10% of 5 addresses still leaves one to warn about
+        let pool = AddressPool::new(1000, 1004).with_low_watermark(0.1);
+        for _ in 0..3 {
+            pool.allocate().unwrap();
+        }
+        assert!(!pool.is_low());
+        pool.allocate().unwrap();
+        assert!(pool.is_low());
+
+        // 25% of 10 rounds up to 3, while 30% stays exactly 3
+        let quarter = AddressPool::new(1000, 1009).with_low_watermark(0.25);
+        let thirty = AddressPool::new(1000, 1009).with_low_watermark(0.3);
+        for _ in 0..7 {
+            quarter.allocate().unwrap();
+            thirty.allocate().unwrap();
+        }
+        assert!(quarter.is_low());
+        assert!(thirty.is_low());
+        quarter.release(1000).unwrap();
+        thirty.release(1000).unwrap();
+        assert!(!quarter.is_low());
+        assert!(!thirty.is_low());
+
+        // A zero fraction only flags an exhausted pool
+        let pool = AddressPool::new(1000, 1000).with_low_watermark(0.0);
+        assert!(!pool.is_low());
+        pool.allocate().unwrap();
+        assert!(pool.is_low());
+    }
 }
@@ -0,0 +1,252 @@
+// SPDX-License-Identifier: EUPL-1.2-or-later
+// Copyright © 2026-present ARI Contributors
+
+//! Bootstrap Discovery
+//!
+//! Lets members find a bootstrap IPCP without a configured peer address.
+//! A bootstrap periodically multicasts a small beacon naming its DIF, its
+//! RINA address and the UDP port its shim listens on; a member joins the
+//! multicast group, waits for a beacon for the DIF it wants to join, and
+//! registers the announcing IPCP as a peer before enrolling.
+
+use crate::error::{AriError, ShimError};
+use crate::shim::UdpShim;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// Default multicast group beacons are sent to (administratively scoped)
+pub const DEFAULT_DISCOVERY_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 70, 77);
+
+/// Default UDP port beacons are sent to
+pub const DEFAULT_DISCOVERY_PORT: u16 = 7077;
+
+/// Announcement multicast by a bootstrap IPCP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Beacon {
+    /// DIF the announcing IPCP enrolls members into
+    pub dif_name: String,
+    /// RINA address of the announcing IPCP
+    pub rina_addr: u64,
+    /// UDP port of the announcing IPCP's shim
+    pub shim_port: u16,
+}
+
+/// A bootstrap IPCP learned from a beacon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredPeer {
+    /// RINA address of the bootstrap
+    pub rina_addr: u64,
+    /// Shim socket address of the bootstrap
+    ///
+    /// The IP is the beacon's source address, as the bootstrap's shim is
+    /// usually bound to the wildcard address.
+    pub socket_addr: SocketAddr,
+}
+
+/// Multicast group, port and interface used for discovery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscoveryEndpoint {
+    /// Multicast group beacons are sent to
+    pub group: Ipv4Addr,
+    /// UDP port beacons are sent to
+    pub port: u16,
+    /// Local interface to send and receive beacons on
+    pub interface: Ipv4Addr,
+}
+
+impl DiscoveryEndpoint {
+    /// Creates an endpoint using the default interface
+    pub fn new(group: Ipv4Addr, port: u16) -> Self {
+        Self {
+            group,
+            port,
+            interface: Ipv4Addr::UNSPECIFIED,
+        }
+    }
+
+    /// Sends and receives beacons on the interface with address `interface`
+    pub fn with_interface(mut self, interface: Ipv4Addr) -> Self {
+        self.interface = interface;
+        self
+    }
+
+    /// Starts multicasting `beacon` every `interval`
+    ///
+    /// The task runs until aborted; send failures are logged and retried
+    /// on the next tick.
+    pub fn spawn_announcer(
+        self,
+        beacon: Beacon,
+        interval: Duration,
+    ) -> Result<JoinHandle<()>, ShimError> {
+        let payload =
+            postcard::to_allocvec(&beacon).map_err(|e| ShimError::SendFailed(e.to_string()))?;
+
+        let socket = std::net::UdpSocket::bind(SocketAddrV4::new(self.interface, 0))
+            .map_err(|e| ShimError::BindFailed(e.to_string()))?;
+        socket
+            .set_multicast_loop_v4(true)
+            .and_then(|_| socket.set_multicast_ttl_v4(1))
+            .and_then(|_| socket.set_nonblocking(true))
+            .map_err(|e| ShimError::BindFailed(e.to_string()))?;
+        let socket =
+            UdpSocket::from_std(socket).map_err(|e| ShimError::BindFailed(e.to_string()))?;
+
+        let target = SocketAddrV4::new(self.group, self.port);
+        Ok(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = socket.send_to(&payload, target).await {
+                    eprintln!("  ⚠ Failed to send discovery beacon: {}", e);
+                }
+            }
+        }))
+    }
+
+    /// Joins the multicast group, ready to receive beacons
+    pub fn listen(&self) -> Result<BeaconListener, ShimError> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))
+            .map_err(|e| ShimError::BindFailed(e.to_string()))?;
+        // Several IPCPs on one host may listen for beacons at the same time
+        socket
+            .set_reuse_address(true)
+            .map_err(|e| ShimError::BindFailed(e.to_string()))?;
+        socket
+            .bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, self.port).into())
+            .map_err(|e| {
+                ShimError::BindFailed(format!("Failed to bind port {}: {}", self.port, e))
+            })?;
+        socket
+            .join_multicast_v4(&self.group, &self.interface)
+            .map_err(|e| {
+                ShimError::BindFailed(format!("Failed to join group {}: {}", self.group, e))
+            })?;
+        socket
+            .set_nonblocking(true)
+            .map_err(|e| ShimError::BindFailed(e.to_string()))?;
+
+        let socket =
+            UdpSocket::from_std(socket.into()).map_err(|e| ShimError::BindFailed(e.to_string()))?;
+        Ok(BeaconListener { socket })
+    }
+}
+
+impl Default for DiscoveryEndpoint {
+    fn default() -> Self {
+        Self::new(DEFAULT_DISCOVERY_GROUP, DEFAULT_DISCOVERY_PORT)
+    }
+}
+
+/// Socket joined to the discovery group
+#[derive(Debug)]
+pub struct BeaconListener {
+    socket: UdpSocket,
+}
+
+impl BeaconListener {
+    /// Returns the port the listener is bound to
+    pub fn local_port(&self) -> Result<u16, ShimError> {
+        self.socket
+            .local_addr()
+            .map(|addr| addr.port())
+            .map_err(|e| ShimError::IoError(e.to_string()))
+    }
+
+    /// Waits for a beacon announcing `dif_name`
+    ///
+    /// Beacons for other DIFs and malformed datagrams are skipped. Fails
+    /// with `AriError::Timeout` if nothing matching arrives within `timeout`.
+    pub async fn next_peer(
+        &self,
+        dif_name: &str,
+        timeout: Duration,
+    ) -> Result<DiscoveredPeer, AriError> {
+        let wait = async {
+            let mut buf = [0u8; 1024];
+            loop {
+                let (len, src) = self
+                    .socket
+                    .recv_from(&mut buf)
+                    .await
+                    .map_err(|e| AriError::from(ShimError::ReceiveFailed(e.to_string())))?;
+
+                let Ok(beacon) = postcard::from_bytes::<Beacon>(&buf[..len]) else {
+                    continue;
+                };
+                if beacon.dif_name == dif_name {
+                    return Ok(DiscoveredPeer {
+                        rina_addr: beacon.rina_addr,
+                        socket_addr: SocketAddr::new(src.ip(), beacon.shim_port),
+                    });
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| AriError::Timeout)?
+    }
+
+    /// Waits for a beacon for `dif_name` and registers its sender with `shim`
+    pub async fn discover_into(
+        &self,
+        shim: &UdpShim,
+        dif_name: &str,
+        timeout: Duration,
+    ) -> Result<DiscoveredPeer, AriError> {
+        let peer = self.next_peer(dif_name, timeout).await?;
+        shim.register_peer(peer.rina_addr, peer.socket_addr);
+        Ok(peer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_member_discovers_bootstrap_over_loopback_multicast() {
+        let loopback = Ipv4Addr::LOCALHOST;
+        let listener = DiscoveryEndpoint::new(DEFAULT_DISCOVERY_GROUP, 0)
+            .with_interface(loopback)
+            .listen()
+            .unwrap();
+        let port = listener.local_port().unwrap();
+
+        let announcer = DiscoveryEndpoint::new(DEFAULT_DISCOVERY_GROUP, port)
+            .with_interface(loopback)
+            .spawn_announcer(
+                Beacon {
+                    dif_name: "discovery-dif".to_string(),
+                    rina_addr: 1001,
+                    shim_port: 7000,
+                },
+                Duration::from_millis(50),
+            )
+            .unwrap();
+
+        let shim = UdpShim::new(0);
+        let peer = listener
+            .discover_into(&shim, "discovery-dif", Duration::from_secs(2))
+            .await
+            .unwrap();
+        announcer.abort();
+
+        assert_eq!(peer.rina_addr, 1001);
+        assert_eq!(peer.socket_addr, "127.0.0.1:7000".parse().unwrap());
+        assert_eq!(shim.lookup_peer(1001), Some(peer.socket_addr));
+
+        // Beacons for other DIFs are ignored
+        assert!(
+            listener
+                .next_peer("other-dif", Duration::from_millis(200))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -7,14 +7,105 @@
 //! error detection, and retransmission capabilities. It's the core data
 //! transfer protocol in RINA.
 
-use crate::pdu::{Pdu, PduType};
+use crate::crypto::{EncryptionMode, FlowKey, TAG_SIZE};
+use crate::error::EfcpError;
+use crate::pdu::{PDU_HEADER_OVERHEAD, Pdu, PduType};
 use std::collections::{HashMap, VecDeque};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Returns true if sequence number `a` comes after `b`
+///
+/// Uses serial number arithmetic (RFC 1982) so that ordering survives
+/// wraparound: 0 follows `u64::MAX`. Numbers exactly half the space apart
+/// are considered unordered.
+fn seq_after(a: u64, b: u64) -> bool {
+    let diff = a.wrapping_sub(b);
+    diff != 0 && diff < 1 << 63
+}
+
+/// Number of sequence numbers below the highest received that are tracked for replays
+pub const REPLAY_WINDOW_SIZE: u64 = 64;
+
+/// Sliding window of recently received sequence numbers
+///
+/// Kept for encrypted flows only, where the sequence number is
+/// authenticated: PDUs whose sequence number was already seen, or that fall
+/// below the window, are dropped so a captured PDU cannot be injected again.
+#[derive(Debug, Clone, Default)]
+struct ReplayWindow {
+    /// Highest sequence number received so far
+    highest: Option<u64>,
+    /// Bit `i` is set when `highest - i` has been received
+    seen: u64,
+}
+
+impl ReplayWindow {
+    /// Checks that `seq` is neither a replay nor below the window
+    fn check(&self, seq: u64) -> Result<(), EfcpError> {
+        let Some(highest) = self.highest else {
+            return Ok(());
+        };
+        if seq_after(seq, highest) {
+            return Ok(());
+        }
+
+        let offset = highest.wrapping_sub(seq);
+        if offset >= REPLAY_WINDOW_SIZE {
+            return Err(EfcpError::PduTooOld {
+                seq,
+                low_edge: highest.wrapping_sub(REPLAY_WINDOW_SIZE - 1),
+            });
+        }
+        if self.seen & (1 << offset) != 0 {
+            return Err(EfcpError::ReplayedPdu(seq));
+        }
+        Ok(())
+    }
+
+    /// Marks `seq` as received, sliding the window forward if it is the newest
+    fn record(&mut self, seq: u64) {
+        match self.highest {
+            Some(highest) if !seq_after(seq, highest) => {
+                self.seen |= 1 << highest.wrapping_sub(seq);
+            }
+            Some(highest) => {
+                let shift = seq.wrapping_sub(highest);
+                self.seen = if shift >= REPLAY_WINDOW_SIZE {
+                    1
+                } else {
+                    (self.seen << shift) | 1
+                };
+                self.highest = Some(seq);
+            }
+            None => {
+                self.seen = 1;
+                self.highest = Some(seq);
+            }
+        }
+    }
+}
+
+/// Path MTU assumed for peers without a configured one (Ethernet)
+pub const DEFAULT_PATH_MTU: usize = 1500;
+
+/// Bytes of the path MTU taken by the IP and UDP headers (IPv6 worst case)
+pub const UDP_IP_OVERHEAD: usize = 48;
+
+/// Smallest path MTU that leaves room for a payload behind every header and
+/// the AEAD tag
+pub const MIN_PATH_MTU: usize = UDP_IP_OVERHEAD + PDU_HEADER_OVERHEAD + TAG_SIZE + 1;
+
+/// Largest SDU a flow reassembles from fragments
+pub const MAX_REASSEMBLED_SDU: usize = 16 * 1024 * 1024;
+
+/// Congestion window a new flow starts slow start from, unless configured
+pub const DEFAULT_INITIAL_CONGESTION_WINDOW: u64 = 4;
 
 /// Flow state and configuration
 #[derive(Debug, Clone)]
 pub struct FlowConfig {
-    /// Maximum PDU size
+    /// Largest datagram a PDU may take on the path (the path MTU); payloads
+    /// are fragmented to leave room for the headers and the AEAD tag
     pub max_pdu_size: usize,
     /// Window size for flow control
     pub window_size: u64,
@@ -22,19 +113,55 @@ pub struct FlowConfig {
     pub reliable: bool,
     /// Timeout for retransmission (milliseconds)
     pub retransmit_timeout_ms: u64,
+    /// Congestion window at flow start; slow start grows it towards `window_size`
+    pub initial_congestion_window: u64,
+    /// Payload encryption; a key is negotiated during flow allocation
+    pub encryption: EncryptionMode,
+    /// Bandwidth reserved for the flow (bytes/sec), charged to the admission budget
+    pub bandwidth_bps: Option<u64>,
 }
 
 impl Default for FlowConfig {
     fn default() -> Self {
         Self {
-            max_pdu_size: 1500,
+            max_pdu_size: DEFAULT_PATH_MTU,
             window_size: 64,
             reliable: true,
             retransmit_timeout_ms: 1000,
+            initial_congestion_window: DEFAULT_INITIAL_CONGESTION_WINDOW,
+            encryption: EncryptionMode::None,
+            bandwidth_bps: None,
         }
     }
 }
 
+/// Traffic counters and round-trip estimate of a single flow
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlowStats {
+    /// Data PDUs sent, not counting retransmissions
+    pub pdus_sent: u64,
+    /// Data PDUs received, including duplicates
+    pub pdus_received: u64,
+    /// Payload bytes sent, not counting retransmissions
+    pub bytes_sent: u64,
+    /// Payload bytes received, including duplicates
+    pub bytes_received: u64,
+    /// PDUs handed back for retransmission
+    pub retransmits: u64,
+    /// Smoothed round-trip time, once an ACK has been timed
+    pub smoothed_rtt: Option<Duration>,
+}
+
+impl FlowStats {
+    /// Folds an RTT sample into the smoothed estimate (RFC 6298, alpha = 1/8)
+    fn record_rtt(&mut self, sample: Duration) {
+        self.smoothed_rtt = Some(match self.smoothed_rtt {
+            Some(srtt) => (srtt * 7 + sample) / 8,
+            None => sample,
+        });
+    }
+}
+
 /// Represents a flow connection
 #[derive(Debug)]
 pub struct Flow {
@@ -58,6 +185,27 @@ pub struct Flow {
     send_window: HashMap<u64, (Pdu, u64)>, // (PDU, timestamp)
     /// Receive buffer for out-of-order PDUs
     receive_buffer: VecDeque<Pdu>,
+    /// Fragments of the SDU being reassembled, in order
+    reassembly: Vec<u8>,
+    /// Congestion window: how many PDUs may be in flight, at most `window_size`
+    congestion_window: u64,
+    /// Below this the window grows per ACK (slow start), above it per window
+    slow_start_threshold: u64,
+    /// Clean ACKs received since the congestion window last grew
+    acks_since_increase: u64,
+    /// Whether a received data PDU was marked and the mark is not yet echoed
+    congestion_echo_pending: bool,
+    /// Whether received data is waiting to be acknowledged
+    ack_due: bool,
+    /// When each unacknowledged PDU was first sent; retransmitted PDUs are
+    /// removed so they never yield ambiguous RTT samples
+    send_times: HashMap<u64, Instant>,
+    /// Traffic counters
+    stats: FlowStats,
+    /// Key sealing payloads when the flow is encrypted
+    key: Option<FlowKey>,
+    /// Sequence numbers recently received, for replay detection
+    replay_window: ReplayWindow,
 }
 
 impl Flow {
@@ -76,36 +224,101 @@ impl Flow {
             remote_cep_id,
             local_addr,
             remote_addr,
-            config,
             next_seq_num: 0,
             expected_seq_num: 0,
             send_window: HashMap::new(),
             receive_buffer: VecDeque::new(),
+            reassembly: Vec::new(),
+            congestion_window: config
+                .initial_congestion_window
+                .clamp(1, config.window_size.max(1)),
+            slow_start_threshold: config.window_size,
+            acks_since_increase: 0,
+            congestion_echo_pending: false,
+            ack_due: false,
+            send_times: HashMap::new(),
+            stats: FlowStats::default(),
+            key: None,
+            replay_window: ReplayWindow::default(),
+            config,
+        }
+    }
+
+    /// Installs the key negotiated for this flow during allocation
+    pub fn set_key(&mut self, key: FlowKey) {
+        self.key = Some(key);
+    }
+
+    /// Returns whether payloads are sealed with a negotiated key
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Returns the flow key, or an error if encryption is on but no key is set
+    fn required_key(&self) -> Result<Option<&FlowKey>, EfcpError> {
+        match (&self.key, self.config.encryption.is_enabled()) {
+            (Some(key), _) => Ok(Some(key)),
+            (None, true) => Err(EfcpError::KeyNotNegotiated(self.flow_id)),
+            (None, false) => Ok(None),
+        }
+    }
+
+    /// Decrypts a received data PDU's payload, or returns it as is on a clear flow
+    pub fn open_payload(&self, pdu: &Pdu) -> Result<Vec<u8>, EfcpError> {
+        match self.required_key()? {
+            Some(key) => key.open(pdu),
+            None => Ok(pdu.payload.clone()),
         }
     }
 
+    /// Returns the largest payload a single PDU of this flow carries
+    ///
+    /// The path MTU less the IP, UDP and PDU headers and, on encrypted
+    /// flows, the AEAD tag.
+    pub fn max_fragment_size(&self) -> usize {
+        let tag = if self.key.is_some() || self.config.encryption.is_enabled() {
+            TAG_SIZE
+        } else {
+            0
+        };
+        self.config
+            .max_pdu_size
+            .saturating_sub(UDP_IP_OVERHEAD + PDU_HEADER_OVERHEAD + tag)
+            .max(1)
+    }
+
     /// Prepares a PDU for sending data
     pub fn send_data(&mut self, payload: Vec<u8>) -> Result<Pdu, String> {
-        if payload.len() > self.config.max_pdu_size {
+        self.send_fragment(payload, false)
+    }
+
+    /// Prepares one PDU, marked if more fragments of its SDU follow
+    fn send_fragment(&mut self, payload: Vec<u8>, more_fragments: bool) -> Result<Pdu, String> {
+        if payload.len() > self.max_fragment_size() {
             return Err(format!(
-                "Payload size {} exceeds max PDU size {}",
+                "Payload size {} exceeds max fragment size {}",
                 payload.len(),
-                self.config.max_pdu_size
+                self.max_fragment_size()
             ));
         }
 
-        if self.send_window.len() >= self.config.window_size as usize {
+        if self.send_window.len() as u64 >= self.effective_window() {
             return Err("Send window is full".to_string());
         }
 
-        let pdu = Pdu::new_data(
+        let mut pdu = Pdu::new_data(
             self.local_addr,
             self.remote_addr,
             self.local_cep_id,
             self.remote_cep_id,
             self.next_seq_num,
-            payload,
+            Vec::new(),
         );
+        pdu.more_fragments = more_fragments;
+        pdu.payload = match self.required_key().map_err(|e| e.to_string())? {
+            Some(key) => key.seal(&pdu, &payload).map_err(|e| e.to_string())?,
+            None => payload,
+        };
 
         if self.config.reliable {
             let timestamp = SystemTime::now()
@@ -114,24 +327,73 @@ impl Flow {
                 .as_millis() as u64;
             self.send_window
                 .insert(self.next_seq_num, (pdu.clone(), timestamp));
+            self.send_times.insert(self.next_seq_num, Instant::now());
         }
 
-        self.next_seq_num += 1;
+        self.stats.pdus_sent += 1;
+        self.stats.bytes_sent += pdu.payload.len() as u64;
+
+        self.next_seq_num = self.next_seq_num.wrapping_add(1);
         Ok(pdu)
     }
 
-    fn handle_data_pdu(&mut self, pdu: Pdu) -> Result<Option<Vec<u8>>, String> {
-        if pdu.sequence_num == self.expected_seq_num {
-            // In-order PDU
-            self.expected_seq_num += 1;
+    /// Splits `payload` into PDUs that fit the path MTU
+    ///
+    /// Either every fragment fits the send window and is prepared, or none
+    /// is. Fragments carry consecutive sequence numbers and all but the last
+    /// are marked, so the receiver reassembles the SDU.
+    pub fn send_fragmented(&mut self, payload: Vec<u8>) -> Result<Vec<Pdu>, String> {
+        let mut chunks: Vec<Vec<u8>> = payload
+            .chunks(self.max_fragment_size())
+            .map(<[u8]>::to_vec)
+            .collect();
+        if chunks.is_empty() {
+            chunks.push(Vec::new());
+        }
+        let room = self
+            .effective_window()
+            .saturating_sub(self.send_window.len() as u64);
+        if self.config.reliable && chunks.len() as u64 > room {
+            return Err(format!(
+                "Send window has room for {} of {} fragments",
+                room,
+                chunks.len()
+            ));
+        }
 
-            if self.config.reliable {
-                // Generate ACK (caller should send this)
-                // In a real implementation, we'd queue this for sending
-            }
+        let last = chunks.len() - 1;
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| self.send_fragment(chunk, index < last))
+            .collect()
+    }
+
+    fn handle_data_pdu(&mut self, mut pdu: Pdu) -> Result<Option<Vec<u8>>, String> {
+        // Tampered or foreign PDUs are rejected before they touch any state
+        if self.key.is_some() || self.config.encryption.is_enabled() {
+            pdu.payload = self.open_payload(&pdu).map_err(|e| e.to_string())?;
+        }
+        // Only a sequence number the key vouches for may move the replay window
+        if self.key.is_some() && !self.admit_authenticated(pdu.sequence_num)? {
+            return Ok(None);
+        }
+
+        self.stats.pdus_received += 1;
+        self.stats.bytes_received += pdu.payload.len() as u64;
+
+        if pdu.congestion_experienced {
+            self.congestion_echo_pending = true;
+        }
+        // Duplicates are acknowledged too, in case the earlier ACK was lost;
+        // unreliable flows still send one to echo a congestion mark
+        self.ack_due |= self.config.reliable || self.congestion_echo_pending;
 
-            Ok(Some(pdu.payload))
-        } else if pdu.sequence_num > self.expected_seq_num {
+        if pdu.sequence_num == self.expected_seq_num {
+            // In-order PDU
+            self.expected_seq_num = self.expected_seq_num.wrapping_add(1);
+            self.reassemble(pdu)
+        } else if seq_after(pdu.sequence_num, self.expected_seq_num) {
             // Out-of-order PDU - buffer it
             self.receive_buffer.push_back(pdu);
             Ok(None)
@@ -141,15 +403,132 @@ impl Flow {
         }
     }
 
+    /// Adds an in-order PDU to the SDU being reassembled
+    ///
+    /// Returns the SDU once its last fragment arrives.
+    fn reassemble(&mut self, pdu: Pdu) -> Result<Option<Vec<u8>>, String> {
+        if !pdu.more_fragments && self.reassembly.is_empty() {
+            return Ok(Some(pdu.payload));
+        }
+        if self.reassembly.len() + pdu.payload.len() > MAX_REASSEMBLED_SDU {
+            self.reassembly.clear();
+            return Err(format!(
+                "Reassembled SDU exceeds {} bytes",
+                MAX_REASSEMBLED_SDU
+            ));
+        }
+        self.reassembly.extend_from_slice(&pdu.payload);
+        if pdu.more_fragments {
+            return Ok(None);
+        }
+        Ok(Some(std::mem::take(&mut self.reassembly)))
+    }
+
+    /// Runs an authenticated sequence number through the replay window
+    ///
+    /// Returns false for a replay or a PDU below the window, which is then
+    /// dropped like any other duplicate. A PDU a full receive window or
+    /// more ahead of the next expected one cannot legitimately be in flight
+    /// and is refused, so it cannot drag the window past PDUs still to come.
+    fn admit_authenticated(&mut self, seq: u64) -> Result<bool, String> {
+        let high_edge = self.expected_seq_num.wrapping_add(self.config.window_size);
+        if !seq_after(high_edge, seq) {
+            return Err(EfcpError::PduBeyondWindow { seq, high_edge }.to_string());
+        }
+        if self.replay_window.check(seq).is_err() {
+            return Ok(false);
+        }
+        self.replay_window.record(seq);
+        Ok(true)
+    }
+
     fn handle_ack_pdu(&mut self, pdu: Pdu) -> Result<Option<Vec<u8>>, String> {
         let ack_num = pdu.sequence_num;
 
         // Remove ACKed PDUs from send window
-        self.send_window.retain(|seq_num, _| *seq_num > ack_num);
+        self.send_window
+            .retain(|seq_num, _| seq_after(*seq_num, ack_num));
+
+        if let Some(sent_at) = self.send_times.get(&ack_num) {
+            self.stats.record_rtt(sent_at.elapsed());
+        }
+        self.send_times
+            .retain(|seq_num, _| seq_after(*seq_num, ack_num));
+
+        if pdu.congestion_experienced {
+            self.on_congestion();
+        } else {
+            self.on_clean_ack();
+        }
 
         Ok(None)
     }
 
+    /// Grows the congestion window after an unmarked ACK
+    ///
+    /// Slow start adds one PDU per ACK, roughly doubling the window each
+    /// round trip; past the threshold congestion avoidance adds one PDU per
+    /// window's worth of ACKs. The window never exceeds `window_size`.
+    fn on_clean_ack(&mut self) {
+        if self.congestion_window < self.slow_start_threshold {
+            self.congestion_window += 1;
+        } else {
+            self.acks_since_increase += 1;
+            if self.acks_since_increase < self.congestion_window {
+                return;
+            }
+            self.congestion_window += 1;
+            self.acks_since_increase = 0;
+        }
+        self.congestion_window = self.congestion_window.min(self.config.window_size);
+    }
+
+    /// Halves the congestion window on a congestion mark or a loss
+    ///
+    /// Recovery continues in congestion avoidance from the halved window.
+    fn on_congestion(&mut self) {
+        self.congestion_window = (self.congestion_window / 2).max(1);
+        self.slow_start_threshold = self.congestion_window;
+        self.acks_since_increase = 0;
+    }
+
+    /// Builds a cumulative ACK for everything received in order so far
+    ///
+    /// Echoes a congestion mark seen on received data back to the sender.
+    pub fn build_ack(&mut self) -> Pdu {
+        let mut ack = Pdu::new_ack(
+            self.local_addr,
+            self.remote_addr,
+            self.local_cep_id,
+            self.remote_cep_id,
+            self.expected_seq_num.wrapping_sub(1),
+        );
+        ack.congestion_experienced = std::mem::take(&mut self.congestion_echo_pending);
+        ack
+    }
+
+    /// Returns the ACK owed for data received since the last one, if any
+    pub fn take_ack(&mut self) -> Option<Pdu> {
+        std::mem::take(&mut self.ack_due).then(|| self.build_ack())
+    }
+
+    /// Returns how many PDUs may currently be in flight
+    ///
+    /// The configured window, reduced while the path signals congestion.
+    pub fn effective_window(&self) -> u64 {
+        self.congestion_window.min(self.config.window_size)
+    }
+
+    /// Returns the current congestion window
+    pub fn congestion_window(&self) -> u64 {
+        self.congestion_window
+    }
+
+    /// Returns the slow start threshold
+    pub fn slow_start_threshold(&self) -> u64 {
+        self.slow_start_threshold
+    }
+
     fn handle_control_pdu(&mut self, _pdu: Pdu) -> Result<Option<Vec<u8>>, String> {
         // TODO: Handle control PDUs (e.g., flow control updates)
         Ok(None)
@@ -171,7 +550,10 @@ impl Flow {
     }
 
     /// Checks for PDUs that need retransmission
-    pub fn check_retransmits(&self) -> Vec<Pdu> {
+    ///
+    /// A timeout is taken as a loss: the congestion window is halved once
+    /// per check and the returned PDUs' timers are restarted.
+    pub fn check_retransmits(&mut self) -> Vec<Pdu> {
         if !self.config.reliable {
             return Vec::new();
         }
@@ -181,17 +563,49 @@ impl Flow {
             .unwrap()
             .as_millis() as u64;
 
-        self.send_window
-            .values()
-            .filter(|(_, timestamp)| now - timestamp > self.config.retransmit_timeout_ms)
-            .map(|(pdu, _)| pdu.clone())
-            .collect()
+        let timeout = self.config.retransmit_timeout_ms;
+        let mut due: Vec<(u64, Pdu)> = self
+            .send_window
+            .iter_mut()
+            .filter(|(_, (_, timestamp))| now.saturating_sub(*timestamp) > timeout)
+            .map(|(seq, (pdu, timestamp))| {
+                *timestamp = now;
+                (*seq, pdu.clone())
+            })
+            .collect();
+
+        if !due.is_empty() {
+            self.on_congestion();
+            self.stats.retransmits += due.len() as u64;
+            for (seq, _) in &due {
+                self.send_times.remove(seq);
+            }
+        }
+
+        due.sort_by_key(|(seq, _)| seq.wrapping_sub(self.next_seq_num));
+        due.into_iter().map(|(_, pdu)| pdu).collect()
     }
 
     /// Returns the current send window size
     pub fn send_window_size(&self) -> usize {
         self.send_window.len()
     }
+
+    /// Returns a snapshot of the flow's traffic counters
+    pub fn stats(&self) -> FlowStats {
+        self.stats.clone()
+    }
+}
+
+/// Expected peer of a local connection endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionEndpoint {
+    /// Flow the endpoint belongs to
+    pub flow_id: u32,
+    /// Address incoming PDUs must come from
+    pub remote_addr: u64,
+    /// CEP-ID incoming PDUs must come from
+    pub remote_cep_id: u32,
 }
 
 /// EFCP instance managing multiple flows
@@ -199,8 +613,14 @@ impl Flow {
 pub struct Efcp {
     /// Active flows, keyed by flow ID
     flows: HashMap<u32, Flow>,
+    /// Connection endpoints, keyed by local CEP-ID
+    connections: HashMap<u32, ConnectionEndpoint>,
     /// Next available flow ID
     next_flow_id: u32,
+    /// Known path MTU per peer address
+    peer_mtus: HashMap<u64, usize>,
+    /// ACKs produced by received data, waiting to be sent
+    pending_acks: Vec<Pdu>,
 }
 
 impl Efcp {
@@ -208,16 +628,47 @@ impl Efcp {
     pub fn new() -> Self {
         Self {
             flows: HashMap::new(),
+            connections: HashMap::new(),
             next_flow_id: 1,
+            peer_mtus: HashMap::new(),
+            pending_acks: Vec::new(),
         }
     }
 
+    /// Records the path MTU towards `remote_addr`, e.g. 9000 for a jumbo-frame link
+    pub fn set_peer_mtu(&mut self, remote_addr: u64, mtu: usize) {
+        self.peer_mtus.insert(remote_addr, mtu);
+    }
+
+    /// Returns the path MTU towards `remote_addr`, if one is known
+    pub fn peer_mtu(&self, remote_addr: u64) -> Option<usize> {
+        self.peer_mtus.get(&remote_addr).copied()
+    }
+
     /// Allocates a new flow
+    ///
+    /// A known path MTU for `remote_addr` replaces the config's
+    /// `max_pdu_size`, so the flow fragments to fit that peer's link.
     pub fn allocate_flow(&mut self, local_addr: u64, remote_addr: u64, config: FlowConfig) -> u32 {
+        self.allocate_keyed_flow(local_addr, remote_addr, config, None)
+    }
+
+    /// Allocates a new flow sealed with the key negotiated by the flow allocator
+    pub fn allocate_keyed_flow(
+        &mut self,
+        local_addr: u64,
+        remote_addr: u64,
+        mut config: FlowConfig,
+        key: Option<FlowKey>,
+    ) -> u32 {
+        if let Some(mtu) = self.peer_mtu(remote_addr) {
+            config.max_pdu_size = mtu;
+        }
+
         let flow_id = self.next_flow_id;
         self.next_flow_id += 1;
 
-        let flow = Flow::new(
+        let mut flow = Flow::new(
             flow_id,
             flow_id, // Using flow_id as CEP-ID for simplicity
             0,       // Remote CEP-ID will be set during connection
@@ -225,11 +676,81 @@ impl Efcp {
             remote_addr,
             config,
         );
+        if let Some(key) = key {
+            flow.set_key(key);
+        }
 
+        self.connections.insert(
+            flow.local_cep_id,
+            ConnectionEndpoint {
+                flow_id,
+                remote_addr: flow.remote_addr,
+                remote_cep_id: flow.remote_cep_id,
+            },
+        );
         self.flows.insert(flow_id, flow);
         flow_id
     }
 
+    /// Records the remote CEP-ID once the peer has been connected
+    pub fn bind_remote_cep(&mut self, flow_id: u32, remote_cep_id: u32) -> Result<(), String> {
+        let flow = self
+            .flows
+            .get_mut(&flow_id)
+            .ok_or_else(|| format!("Flow {} not found", flow_id))?;
+        flow.remote_cep_id = remote_cep_id;
+
+        if let Some(endpoint) = self.connections.get_mut(&flow.local_cep_id) {
+            endpoint.remote_cep_id = remote_cep_id;
+        }
+        Ok(())
+    }
+
+    /// Looks up the connection endpoint for a local CEP-ID
+    pub fn connection(&self, local_cep_id: u32) -> Option<&ConnectionEndpoint> {
+        self.connections.get(&local_cep_id)
+    }
+
+    /// Delivers an incoming PDU to the flow owning its destination CEP-ID
+    ///
+    /// The PDU is rejected unless its source address and CEP-ID match the
+    /// peer the endpoint was connected to, so stale or spoofed PDUs never
+    /// reach a flow. Any ACK the PDU calls for is queued for
+    /// [`Efcp::take_acks`].
+    pub fn demux_pdu(&mut self, pdu: Pdu) -> Result<Option<Vec<u8>>, String> {
+        let endpoint = self
+            .connections
+            .get(&pdu.dst_cep_id)
+            .ok_or_else(|| format!("No connection endpoint for CEP-ID {}", pdu.dst_cep_id))?;
+
+        if pdu.src_addr != endpoint.remote_addr || pdu.src_cep_id != endpoint.remote_cep_id {
+            return Err(format!(
+                "PDU for CEP-ID {} rejected: expected source {}:{}, got {}:{}",
+                pdu.dst_cep_id,
+                endpoint.remote_addr,
+                endpoint.remote_cep_id,
+                pdu.src_addr,
+                pdu.src_cep_id
+            ));
+        }
+
+        let flow_id = endpoint.flow_id;
+        let flow = self
+            .flows
+            .get_mut(&flow_id)
+            .ok_or_else(|| format!("Flow {} not found", flow_id))?;
+        let delivered = flow.receive_pdu(pdu);
+        if let Some(ack) = flow.take_ack() {
+            self.pending_acks.push(ack);
+        }
+        delivered
+    }
+
+    /// Returns the ACKs queued by received data, for sending to the peers
+    pub fn take_acks(&mut self) -> Vec<Pdu> {
+        std::mem::take(&mut self.pending_acks)
+    }
+
     /// Gets a mutable reference to a flow
     pub fn get_flow_mut(&mut self, flow_id: u32) -> Option<&mut Flow> {
         self.flows.get_mut(&flow_id)
@@ -240,12 +761,19 @@ impl Efcp {
         self.flows.get(&flow_id)
     }
 
+    /// Returns the traffic counters of a flow
+    pub fn flow_stats(&self, flow_id: u32) -> Option<FlowStats> {
+        self.flows.get(&flow_id).map(|flow| flow.stats())
+    }
+
     /// Deallocates a flow
     pub fn deallocate_flow(&mut self, flow_id: u32) -> Result<(), String> {
-        self.flows
+        let flow = self
+            .flows
             .remove(&flow_id)
-            .map(|_| ())
-            .ok_or_else(|| format!("Flow {} not found", flow_id))
+            .ok_or_else(|| format!("Flow {} not found", flow_id))?;
+        self.connections.remove(&flow.local_cep_id);
+        Ok(())
     }
 
     /// Returns the number of active flows
@@ -342,6 +870,187 @@ mod tests {
         assert_eq!(flow.send_window_size(), 1);
     }
 
+    #[test]
+    fn test_congestion_echo_halves_then_regrows_window() {
+        let config = FlowConfig {
+            window_size: 8,
+            initial_congestion_window: 8,
+            ..Default::default()
+        };
+        let mut flow = Flow::new(1, 10, 20, 100, 200, config);
+        assert_eq!(flow.effective_window(), 8);
+
+        let mut echo = Pdu::new_ack(200, 100, 20, 10, 0);
+        echo.congestion_experienced = true;
+        flow.receive_pdu(echo.clone()).unwrap();
+        assert_eq!(flow.effective_window(), 4);
+        flow.receive_pdu(echo).unwrap();
+        assert_eq!(flow.effective_window(), 2);
+
+        // Two clean ACKs make up one window's worth, growing it by one
+        flow.receive_pdu(Pdu::new_ack(200, 100, 20, 10, 1)).unwrap();
+        flow.receive_pdu(Pdu::new_ack(200, 100, 20, 10, 2)).unwrap();
+        assert_eq!(flow.effective_window(), 3);
+    }
+
+    #[test]
+    fn test_congestion_window_halves_on_loss_and_recovers() {
+        let config = FlowConfig {
+            window_size: 8,
+            initial_congestion_window: 2,
+            retransmit_timeout_ms: 1,
+            ..Default::default()
+        };
+        let mut flow = Flow::new(1, 10, 20, 100, 200, config);
+
+        // Sends a full window and acknowledges each PDU separately
+        let round = |flow: &mut Flow| {
+            let mut seqs = Vec::new();
+            while let Ok(pdu) = flow.send_data(vec![0]) {
+                seqs.push(pdu.sequence_num);
+            }
+            for seq in seqs {
+                flow.receive_pdu(Pdu::new_ack(200, 100, 20, 10, seq))
+                    .unwrap();
+                assert!(flow.congestion_window() <= 8);
+            }
+        };
+
+        // Slow start doubles the window each round up to the ceiling
+        round(&mut flow);
+        assert_eq!(flow.congestion_window(), 4);
+        round(&mut flow);
+        assert_eq!(flow.congestion_window(), 8);
+        round(&mut flow);
+        assert_eq!(flow.congestion_window(), 8);
+
+        // Lose a full window mid-transfer
+        while flow.send_data(vec![0]).is_ok() {}
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let lost = flow.check_retransmits();
+        assert_eq!(lost.len(), 8);
+        assert_eq!(flow.congestion_window(), 4);
+        assert_eq!(flow.slow_start_threshold(), 4);
+
+        // The retransmissions are acknowledged, then congestion avoidance
+        // grows the window by one per round
+        let last = lost.last().unwrap().sequence_num;
+        flow.receive_pdu(Pdu::new_ack(200, 100, 20, 10, last))
+            .unwrap();
+        assert_eq!(flow.send_window_size(), 0);
+        let mut windows = Vec::new();
+        for _ in 0..5 {
+            round(&mut flow);
+            windows.push(flow.congestion_window());
+        }
+        assert_eq!(windows, [5, 6, 7, 8, 8]);
+    }
+
+    #[test]
+    fn test_receiver_acks_open_the_congestion_window() {
+        let mut sender = Efcp::new();
+        let mut receiver = Efcp::new();
+        let tx = sender.allocate_flow(100, 200, FlowConfig::default());
+        let rx = receiver.allocate_flow(200, 100, FlowConfig::default());
+        sender.bind_remote_cep(tx, rx).unwrap();
+        receiver.bind_remote_cep(rx, tx).unwrap();
+
+        // Far more than the initial window, moved one window at a time
+        let mut delivered = 0;
+        while delivered < 40 {
+            let mut burst = Vec::new();
+            while let Ok(pdu) = sender.get_flow_mut(tx).unwrap().send_data(vec![0]) {
+                burst.push(pdu);
+            }
+            assert!(!burst.is_empty(), "flow stalled after {delivered} PDUs");
+            for pdu in burst {
+                assert!(receiver.demux_pdu(pdu).unwrap().is_some());
+                delivered += 1;
+            }
+            for ack in receiver.take_acks() {
+                sender.demux_pdu(ack).unwrap();
+            }
+        }
+
+        let flow = sender.get_flow(tx).unwrap();
+        assert_eq!(flow.send_window_size(), 0);
+        assert!(flow.congestion_window() > DEFAULT_INITIAL_CONGESTION_WINDOW);
+    }
+
+    #[test]
+    fn test_receive_path_echoes_congestion_marks() {
+        for reliable in [true, false] {
+            let config = FlowConfig {
+                reliable,
+                initial_congestion_window: 8,
+                ..Default::default()
+            };
+            let mut sender = Efcp::new();
+            let mut receiver = Efcp::new();
+            let tx = sender.allocate_flow(100, 200, config.clone());
+            let rx = receiver.allocate_flow(200, 100, config);
+            sender.bind_remote_cep(tx, rx).unwrap();
+            receiver.bind_remote_cep(rx, tx).unwrap();
+
+            let mut pdu = sender.get_flow_mut(tx).unwrap().send_data(vec![1]).unwrap();
+            pdu.congestion_experienced = true;
+            receiver.demux_pdu(pdu).unwrap();
+
+            let acks = receiver.take_acks();
+            assert_eq!(acks.len(), 1);
+            assert!(acks[0].congestion_experienced);
+            sender.demux_pdu(acks[0].clone()).unwrap();
+            assert_eq!(sender.get_flow(tx).unwrap().congestion_window(), 4);
+
+            // The mark is echoed once; unmarked data on an unreliable flow
+            // needs no ACK
+            let pdu = sender.get_flow_mut(tx).unwrap().send_data(vec![2]).unwrap();
+            receiver.demux_pdu(pdu).unwrap();
+            let acks = receiver.take_acks();
+            assert_eq!(acks.len(), usize::from(reliable));
+            assert!(acks.iter().all(|ack| !ack.congestion_experienced));
+        }
+    }
+
+    #[test]
+    fn test_flow_stats_count_traffic_and_estimate_rtt() {
+        let mut sender = Efcp::new();
+        let mut receiver = Efcp::new();
+        let tx = sender.allocate_flow(100, 200, FlowConfig::default());
+        let rx = receiver.allocate_flow(200, 100, FlowConfig::default());
+        sender.bind_remote_cep(tx, rx).unwrap();
+        receiver.bind_remote_cep(rx, tx).unwrap();
+
+        for payload in [vec![1; 10], vec![2; 20], vec![3; 30]] {
+            let pdu = sender.get_flow_mut(tx).unwrap().send_data(payload).unwrap();
+            receiver.demux_pdu(pdu).unwrap();
+        }
+
+        std::thread::sleep(Duration::from_millis(20));
+        for seq in 0..3 {
+            let ack = Pdu::new_ack(200, 100, rx, tx, seq);
+            sender.demux_pdu(ack).unwrap();
+        }
+
+        let sent = sender.flow_stats(tx).unwrap();
+        assert_eq!(sent.pdus_sent, 3);
+        assert_eq!(sent.bytes_sent, 60);
+        assert_eq!(sent.retransmits, 0);
+        let rtt = sent.smoothed_rtt.unwrap();
+        assert!(
+            rtt >= Duration::from_millis(20) && rtt < Duration::from_secs(1),
+            "implausible RTT {:?}",
+            rtt
+        );
+
+        let received = receiver.flow_stats(rx).unwrap();
+        assert_eq!(received.pdus_received, 3);
+        assert_eq!(received.bytes_received, 60);
+        assert_eq!(received.smoothed_rtt, None);
+
+        assert!(sender.flow_stats(999).is_none());
+    }
+
     #[test]
     fn test_window_full() {
         let config = FlowConfig {
@@ -358,4 +1067,267 @@ mod tests {
         let result = flow.send_data(vec![3]);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_sequence_numbers_wrap_around() {
+        let mut flow = Flow::new(1, 10, 20, 100, 200, FlowConfig::default());
+        flow.expected_seq_num = u64::MAX - 1;
+
+        let data = |seq| Pdu::new_data(200, 100, 20, 10, seq, vec![seq as u8]);
+
+        assert!(flow.receive_pdu(data(u64::MAX - 1)).unwrap().is_some());
+
+        // 1 lies beyond the wrap and is buffered, not dropped as old
+        assert!(flow.receive_pdu(data(1)).unwrap().is_none());
+        assert_eq!(flow.receive_buffer.len(), 1);
+
+        // A genuinely old PDU is still discarded
+        assert!(flow.receive_pdu(data(u64::MAX - 2)).unwrap().is_none());
+        assert_eq!(flow.receive_buffer.len(), 1);
+
+        assert!(flow.receive_pdu(data(u64::MAX)).unwrap().is_some());
+        assert!(flow.receive_pdu(data(0)).unwrap().is_some());
+        assert_eq!(flow.expected_seq_num, 1);
+
+        // Sender side: ACKing u64::MAX must not release seq 0
+        flow.next_seq_num = u64::MAX;
+        assert_eq!(flow.send_data(vec![1]).unwrap().sequence_num, u64::MAX);
+        assert_eq!(flow.send_data(vec![2]).unwrap().sequence_num, 0);
+        flow.receive_pdu(Pdu::new_ack(200, 100, 20, 10, u64::MAX))
+            .unwrap();
+        assert_eq!(flow.send_window_size(), 1);
+        assert!(flow.send_window.contains_key(&0));
+    }
+
+    #[test]
+    fn test_demux_rejects_mismatched_source() {
+        let mut efcp = Efcp::new();
+        let flow_id = efcp.allocate_flow(100, 200, FlowConfig::default());
+        efcp.bind_remote_cep(flow_id, 7).unwrap();
+        let local_cep = efcp.get_flow(flow_id).unwrap().local_cep_id;
+
+        // Wrong source address
+        let spoofed = Pdu::new_data(999, 100, 7, local_cep, 0, vec![0xEE]);
+        assert!(efcp.demux_pdu(spoofed).is_err());
+
+        // Right address, stale source CEP-ID
+        let stale = Pdu::new_data(200, 100, 6, local_cep, 0, vec![0xEE]);
+        assert!(efcp.demux_pdu(stale).is_err());
+
+        // Unknown destination CEP-ID
+        let unknown = Pdu::new_data(200, 100, 7, local_cep + 1, 0, vec![0xEE]);
+        assert!(efcp.demux_pdu(unknown).is_err());
+
+        // Nothing above reached the flow
+        assert_eq!(efcp.get_flow(flow_id).unwrap().expected_seq_num, 0);
+
+        let valid = Pdu::new_data(200, 100, 7, local_cep, 0, vec![1, 2, 3]);
+        assert_eq!(efcp.demux_pdu(valid).unwrap(), Some(vec![1, 2, 3]));
+    }
+
+    /// Returns the sending and receiving ends of an encrypted, unreliable flow
+    fn keyed_flows() -> (Flow, Flow) {
+        use crate::crypto::{KeyExchange, KeyRole};
+
+        let config = FlowConfig {
+            reliable: false,
+            encryption: EncryptionMode::ChaCha20Poly1305,
+            ..Default::default()
+        };
+        let initiator = KeyExchange::new().unwrap();
+        let responder = KeyExchange::new().unwrap();
+        let (initiator_share, responder_share) =
+            (initiator.public_share(), responder.public_share());
+
+        let mut sender = Flow::new(1, 10, 20, 100, 200, config.clone());
+        sender.set_key(
+            initiator
+                .derive(responder_share, KeyRole::Initiator)
+                .unwrap(),
+        );
+        let mut receiver = Flow::new(1, 20, 10, 200, 100, config);
+        receiver.set_key(
+            responder
+                .derive(initiator_share, KeyRole::Responder)
+                .unwrap(),
+        );
+        (sender, receiver)
+    }
+
+    #[test]
+    fn test_replay_window_drops_replayed_and_stale_pdus() {
+        let (mut sender, mut receiver) = keyed_flows();
+        let sent: Vec<Pdu> = (0..=100u8)
+            .map(|i| sender.send_data(vec![i]).unwrap())
+            .collect();
+
+        for pdu in &sent[..100] {
+            assert!(receiver.receive_pdu(pdu.clone()).unwrap().is_some());
+        }
+
+        // Re-injecting a delivered PDU is dropped
+        assert_eq!(receiver.receive_pdu(sent[99].clone()), Ok(None));
+        assert_eq!(receiver.receive_pdu(sent[50].clone()), Ok(None));
+        assert_eq!(receiver.stats().pdus_received, 100);
+
+        // A fresh PDU is accepted, sliding the window
+        assert_eq!(
+            receiver.receive_pdu(sent[100].clone()).unwrap(),
+            Some(vec![100])
+        );
+
+        // Anything below the window is dropped as well
+        assert_eq!(receiver.receive_pdu(sent[3].clone()), Ok(None));
+        assert_eq!(receiver.stats().pdus_received, 101);
+    }
+
+    #[test]
+    fn test_replay_window_only_advances_within_receive_window() {
+        let (mut sender, mut receiver) = keyed_flows();
+        let sent: Vec<Pdu> = (0..=64u8)
+            .map(|i| sender.send_data(vec![i]).unwrap())
+            .collect();
+
+        // Sequence number 64 is a whole receive window ahead of the expected 0
+        let err = receiver.receive_pdu(sent[64].clone()).unwrap_err();
+        assert_eq!(
+            err,
+            EfcpError::PduBeyondWindow {
+                seq: 64,
+                high_edge: 64
+            }
+            .to_string()
+        );
+
+        // The refused PDU did not move the window past the early ones
+        assert_eq!(
+            receiver.receive_pdu(sent[0].clone()).unwrap(),
+            Some(vec![0])
+        );
+        assert_eq!(receiver.receive_pdu(sent[64].clone()), Ok(None));
+
+        // On a clear flow nothing vouches for the sequence number, so a
+        // forged one far ahead cannot lock out the real PDUs
+        let mut clear = Flow::new(1, 10, 20, 100, 200, FlowConfig::default());
+        let data = |seq| Pdu::new_data(200, 100, 20, 10, seq, vec![seq as u8]);
+        assert_eq!(clear.receive_pdu(data(u64::MAX / 4)), Ok(None));
+        assert_eq!(clear.receive_pdu(data(0)).unwrap(), Some(vec![0]));
+        assert_eq!(clear.receive_pdu(data(0)), Ok(None));
+    }
+
+    #[test]
+    fn test_replay_window_accepts_unseen_pdus_inside_window() {
+        let mut window = ReplayWindow::default();
+        window.record(10);
+        window.record(12);
+
+        assert!(window.check(11).is_ok());
+        assert_eq!(window.check(12), Err(EfcpError::ReplayedPdu(12)));
+        window.record(11);
+        assert_eq!(window.check(11), Err(EfcpError::ReplayedPdu(11)));
+
+        // A jump past the whole window forgets everything before it
+        window.record(12 + REPLAY_WINDOW_SIZE);
+        assert!(window.check(13).is_ok());
+        assert!(matches!(
+            window.check(12),
+            Err(EfcpError::PduTooOld { seq: 12, .. })
+        ));
+    }
+
+    #[test]
+    fn test_flows_fragment_at_their_peer_path_mtu() {
+        let mut efcp = Efcp::new();
+        efcp.set_peer_mtu(2000, 9000);
+        // Open the congestion window wide enough for every fragment
+        let config = FlowConfig {
+            initial_congestion_window: 16,
+            ..FlowConfig::default()
+        };
+
+        let jumbo = efcp.allocate_flow(1000, 2000, config.clone());
+        let plain = efcp.allocate_flow(1000, 3000, config);
+        let payload = vec![7u8; 12_000];
+
+        let overhead = UDP_IP_OVERHEAD + PDU_HEADER_OVERHEAD;
+        let pdus = efcp
+            .get_flow_mut(jumbo)
+            .unwrap()
+            .send_fragmented(payload.clone())
+            .unwrap();
+        let sizes: Vec<usize> = pdus.iter().map(|p| p.payload.len()).collect();
+        assert_eq!(sizes, vec![9000 - overhead, 12_000 - (9000 - overhead)]);
+
+        let pdus = efcp
+            .get_flow_mut(plain)
+            .unwrap()
+            .send_fragmented(payload)
+            .unwrap();
+        assert_eq!(pdus.len(), 9);
+        assert!(
+            pdus[..8]
+                .iter()
+                .all(|p| p.payload.len() == DEFAULT_PATH_MTU - overhead)
+        );
+        let seqs: Vec<u64> = pdus.iter().map(|p| p.sequence_num).collect();
+        assert_eq!(seqs, (0..9).collect::<Vec<u64>>());
+        for pdu in &pdus {
+            let wire = pdu.serialize_uncompressed().unwrap();
+            assert!(wire.len() + UDP_IP_OVERHEAD <= DEFAULT_PATH_MTU);
+        }
+    }
+
+    #[test]
+    fn test_fragments_are_reassembled_in_order() {
+        let config = FlowConfig {
+            initial_congestion_window: 16,
+            ..FlowConfig::default()
+        };
+        let mut sender = Flow::new(1, 10, 20, 100, 200, config.clone());
+        let mut receiver = Flow::new(1, 20, 10, 200, 100, config);
+        let payload: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+
+        let pdus = sender.send_fragmented(payload.clone()).unwrap();
+        assert_eq!(pdus.len(), 4);
+        assert!(pdus[..3].iter().all(|p| p.more_fragments));
+        assert!(!pdus[3].more_fragments);
+
+        let mut delivered = Vec::new();
+        for pdu in pdus {
+            delivered.push(receiver.receive_pdu(pdu).unwrap());
+        }
+        assert_eq!(delivered, vec![None, None, None, Some(payload)]);
+
+        // The next SDU starts afresh
+        let pdu = sender.send_data(b"next".to_vec()).unwrap();
+        assert_eq!(receiver.receive_pdu(pdu).unwrap(), Some(b"next".to_vec()));
+    }
+
+    #[test]
+    fn test_encrypted_fragments_leave_room_for_the_tag() {
+        let (mut sender, mut receiver) = keyed_flows();
+        let plain = Flow::new(2, 11, 21, 100, 200, FlowConfig::default());
+        assert_eq!(
+            sender.max_fragment_size(),
+            plain.max_fragment_size() - TAG_SIZE
+        );
+
+        let payload = vec![9u8; 3000];
+        let pdus = sender.send_fragmented(payload.clone()).unwrap();
+        for pdu in &pdus {
+            let wire = pdu.serialize_uncompressed().unwrap();
+            assert!(wire.len() + UDP_IP_OVERHEAD <= DEFAULT_PATH_MTU);
+        }
+
+        // The fragment mark is authenticated along with the header
+        let mut tampered = pdus[0].clone();
+        tampered.more_fragments = false;
+        assert!(receiver.receive_pdu(tampered).is_err());
+
+        let delivered: Vec<_> = pdus
+            .into_iter()
+            .map(|pdu| receiver.receive_pdu(pdu).unwrap())
+            .collect();
+        assert_eq!(delivered.last().unwrap().as_deref(), Some(&payload[..]));
+    }
 }
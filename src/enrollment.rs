@@ -6,20 +6,37 @@
 //! Handles the enrollment process where a new IPCP joins a DIF.
 //! Fully async implementation with timeout and retry logic.
 
-use crate::cdap::{CdapMessage, CdapOpCode};
+use crate::cdap::{
+    CdapCapabilities, CdapMessage, CdapOpCode, CdapReassembler, CdapResult, CdapSession,
+    DEFAULT_MAX_FRAGMENT_SIZE, InvokeIds,
+};
 use crate::directory::AddressPool;
 use crate::error::EnrollmentError;
-use crate::pdu::Pdu;
-use crate::rib::{Rib, RibValue};
-use crate::routing::RouteResolver;
+use crate::neighbor::NeighborTable;
+use crate::pdu::{Pdu, PduBuilder, PduType};
+use crate::policies::QoSCube;
+use crate::rib::{Rib, RibChange, RibValue, is_local_name};
+use crate::routing::{Route, RouteResolver};
 use crate::shim::UdpShim;
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{Mutex, RwLock, mpsc};
 use tokio::time::{sleep, timeout};
 
+/// CDAP object class of the bootstrap hello exchange
+const BOOTSTRAP_HELLO_CLASS: &str = "bootstrap_hello";
+
+/// How long the bootstrap answers a retried enrollment request from cache
+const ENROLLMENT_RESPONSE_TTL: Duration = Duration::from_secs(120);
+
+/// Accepted enrollment responses keyed by member name and request id
+type ResponseCache = HashMap<(String, u64), (Instant, EnrollmentResponse)>;
+
 /// Configuration for enrollment behavior
 #[derive(Debug, Clone)]
 pub struct EnrollmentConfig {
@@ -29,10 +46,15 @@ pub struct EnrollmentConfig {
     pub max_retries: u32,
     /// Initial backoff duration in milliseconds (doubles on each retry)
     pub initial_backoff_ms: u64,
+    /// Randomisation applied to the retry backoff
+    pub backoff_jitter: BackoffJitter,
     /// Heartbeat interval for connection monitoring (0 = disabled)
     pub heartbeat_interval_secs: u64,
     /// Connection timeout before triggering re-enrollment
     pub connection_timeout_secs: u64,
+    /// Longest a single receive waits before the response deadline is
+    /// rechecked; a response is returned as soon as it arrives regardless
+    pub poll_interval: Duration,
 }
 
 impl Default for EnrollmentConfig {
@@ -41,10 +63,181 @@ impl Default for EnrollmentConfig {
             timeout: Duration::from_secs(5),
             max_retries: 3,
             initial_backoff_ms: 1000,
+            backoff_jitter: BackoffJitter::default(),
             heartbeat_interval_secs: 30, // Heartbeat every 30 seconds
             connection_timeout_secs: 90, // Re-enroll if no heartbeat for 90 seconds
+            poll_interval: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Token bucket refilled at a fixed rate up to a burst size
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(burst: u32) -> Self {
+        Self {
+            tokens: burst as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes one token if available, refilling for the time elapsed first
+    fn try_take(&mut self, per_second: f64, burst: u32) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(burst as f64);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Number of tracked sources above which idle buckets are pruned
+const RATE_LIMITER_PRUNE_THRESHOLD: usize = 1024;
+
+/// Per-source token buckets for incoming enrollment requests (bootstrap side)
+///
+/// Each source IP address gets its own bucket, so a peer flooding the
+/// bootstrap only exhausts its own allowance, however many ports it uses.
+#[derive(Debug)]
+struct RequestRateLimiter {
+    per_second: f64,
+    burst: u32,
+    buckets: HashMap<IpAddr, TokenBucket>,
+}
+
+impl RequestRateLimiter {
+    fn new(per_second: f64, burst: u32) -> Self {
+        Self {
+            per_second,
+            burst: burst.max(1),
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Returns whether a request from `source` may be processed now
+    fn allow(&mut self, source: SocketAddr) -> bool {
+        if self.buckets.len() >= RATE_LIMITER_PRUNE_THRESHOLD {
+            // A bucket that would be full again carries no state worth keeping
+            let refill_time = self.burst as f64 / self.per_second;
+            self.buckets
+                .retain(|_, b| b.last_refill.elapsed().as_secs_f64() < refill_time);
+        }
+
+        let (per_second, burst) = (self.per_second, self.burst);
+        self.buckets
+            .entry(source.ip())
+            .or_insert_with(|| TokenBucket::full(burst))
+            .try_take(per_second, burst)
+    }
+}
+
+/// Upper bound on a decorrelated-jitter retry delay
+const MAX_DECORRELATED_BACKOFF_MS: u64 = 60_000;
+
+/// How enrollment retry delays are randomised
+///
+/// Members that restart together would otherwise retry in lockstep and hit
+/// the bootstrap at the same instants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackoffJitter {
+    /// Plain doubling: `initial * 2^(attempt-1)`
+    None,
+    /// Uniform between zero and the doubled delay
+    #[default]
+    Full,
+    /// Uniform between the initial delay and three times the previous one
+    Decorrelated,
+}
+
+impl std::fmt::Display for BackoffJitter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackoffJitter::None => write!(f, "none"),
+            BackoffJitter::Full => write!(f, "full"),
+            BackoffJitter::Decorrelated => write!(f, "decorrelated"),
+        }
+    }
+}
+
+/// Computes successive retry delays for one enrollment run
+#[derive(Debug)]
+pub struct Backoff {
+    jitter: BackoffJitter,
+    initial_ms: u64,
+    previous_ms: u64,
+    rng: fastrand::Rng,
+}
+
+impl Backoff {
+    /// Creates a backoff starting at `initial_ms`
+    pub fn new(jitter: BackoffJitter, initial_ms: u64) -> Self {
+        Self::with_rng(jitter, initial_ms, fastrand::Rng::new())
+    }
+
+    /// Creates a backoff with a deterministic random sequence
+    pub fn with_seed(jitter: BackoffJitter, initial_ms: u64, seed: u64) -> Self {
+        Self::with_rng(jitter, initial_ms, fastrand::Rng::with_seed(seed))
+    }
+
+    fn with_rng(jitter: BackoffJitter, initial_ms: u64, rng: fastrand::Rng) -> Self {
+        Self {
+            jitter,
+            initial_ms,
+            previous_ms: initial_ms,
+            rng,
         }
     }
+
+    /// Returns the delay to wait after failed attempt number `attempt` (1-based)
+    pub fn delay(&mut self, attempt: u32) -> Duration {
+        let ceiling_ms = self
+            .initial_ms
+            .saturating_mul(1 << attempt.saturating_sub(1).min(63));
+
+        let delay_ms = match self.jitter {
+            BackoffJitter::None => ceiling_ms,
+            BackoffJitter::Full => self.rng.u64(0..=ceiling_ms),
+            BackoffJitter::Decorrelated => {
+                let upper = self.previous_ms.saturating_mul(3).clamp(
+                    self.initial_ms,
+                    MAX_DECORRELATED_BACKOFF_MS.max(self.initial_ms),
+                );
+                self.rng.u64(self.initial_ms..=upper)
+            }
+        };
+
+        self.previous_ms = delay_ms;
+        Duration::from_millis(delay_ms)
+    }
+}
+
+/// Progress notification pushed to an enrollment observer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnrollmentEvent {
+    /// An enrollment attempt towards `bootstrap_addr` began
+    AttemptStarted { bootstrap_addr: u64, attempt: u32 },
+    /// The bootstrap assigned us a RINA address
+    AddressAssigned(u64),
+    /// The bootstrap's RIB snapshot was applied locally
+    RibSynced { objects: usize },
+    /// Static routes were copied from the bootstrap
+    RoutesSynced { routes: usize },
+    /// Enrollment completed
+    Enrolled { dif_name: String },
+    /// Every attempt failed
+    Failed { reason: String },
 }
 
 /// Enrollment state
@@ -65,6 +258,11 @@ pub enum EnrollmentState {
 }
 
 /// Enrollment request
+///
+/// postcard is not self-describing, so fields added after
+/// `request_address` are only ever appended: older builds ignore the
+/// trailing bytes, and [`EnrollmentRequest::decode`] reads a request from an
+/// older build with the added fields defaulted.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnrollmentRequest {
     /// IPCP name requesting enrollment
@@ -77,9 +275,53 @@ pub struct EnrollmentRequest {
     pub timestamp: u64,
     /// Whether requesting dynamic address assignment
     pub request_address: bool,
+    /// Address held before a restart, granted again if still available
+    pub preferred_address: Option<u64>,
+    /// CDAP version and features the requester supports
+    pub capabilities: CdapCapabilities,
+    /// Identifies the enrollment across retries, so a retried request is
+    /// answered from cache instead of allocating again (0 = not set)
+    pub request_id: u64,
+    /// Shared secret allowing the requester to take over an address that is
+    /// already mapped to another socket (sent unencrypted)
+    pub rebind_secret: Option<String>,
+}
+
+/// Enrollment request as sent by builds without capability negotiation
+#[derive(Deserialize)]
+struct LegacyEnrollmentRequest {
+    ipcp_name: String,
+    ipcp_address: u64,
+    dif_name: String,
+    timestamp: u64,
+    request_address: bool,
+}
+
+impl EnrollmentRequest {
+    /// Decodes a request, accepting the layout of builds that predate
+    /// capability negotiation; such requesters get the minimal capabilities
+    pub fn decode(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes).or_else(|e| {
+            let legacy: LegacyEnrollmentRequest = postcard::from_bytes(bytes).map_err(|_| e)?;
+            Ok(Self {
+                ipcp_name: legacy.ipcp_name,
+                ipcp_address: legacy.ipcp_address,
+                dif_name: legacy.dif_name,
+                timestamp: legacy.timestamp,
+                request_address: legacy.request_address,
+                preferred_address: None,
+                capabilities: CdapCapabilities::minimal(),
+                request_id: 0,
+                rebind_secret: None,
+            })
+        })
+    }
 }
 
 /// Enrollment response
+///
+/// Like [`EnrollmentRequest`], fields added after `rib_snapshot` are only
+/// appended; [`EnrollmentResponse::decode`] reads older responses.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnrollmentResponse {
     /// Whether enrollment was accepted
@@ -92,6 +334,42 @@ pub struct EnrollmentResponse {
     pub dif_name: String,
     /// RIB snapshot for synchronization
     pub rib_snapshot: Option<Vec<u8>>,
+    /// Name of the responding bootstrap IPCP (recorded as a neighbor)
+    pub bootstrap_name: Option<String>,
+    /// Capabilities both sides support, used for the rest of the session
+    pub capabilities: CdapCapabilities,
+    /// QoS classes the DIF offers, as published in the bootstrap's RIB
+    pub qos_cube: Option<QoSCube>,
+}
+
+/// Enrollment response as sent by builds without capability negotiation
+#[derive(Deserialize)]
+struct LegacyEnrollmentResponse {
+    accepted: bool,
+    error: Option<String>,
+    assigned_address: Option<u64>,
+    dif_name: String,
+    rib_snapshot: Option<Vec<u8>>,
+}
+
+impl EnrollmentResponse {
+    /// Decodes a response, accepting the layout of builds that predate
+    /// capability negotiation; such bootstraps get the minimal capabilities
+    pub fn decode(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes).or_else(|e| {
+            let legacy: LegacyEnrollmentResponse = postcard::from_bytes(bytes).map_err(|_| e)?;
+            Ok(Self {
+                accepted: legacy.accepted,
+                error: legacy.error,
+                assigned_address: legacy.assigned_address,
+                dif_name: legacy.dif_name,
+                rib_snapshot: legacy.rib_snapshot,
+                bootstrap_name: None,
+                capabilities: CdapCapabilities::minimal(),
+                qos_cube: None,
+            })
+        })
+    }
 }
 
 /// DIF configuration provided during enrollment
@@ -107,6 +385,110 @@ pub struct DifConfiguration {
     pub rib_snapshot: Vec<u8>, // Serialized RIB data
 }
 
+/// Identity a bootstrap IPCP reports in reply to a hello
+///
+/// Lets a member that only knows the bootstrap's socket address learn the
+/// RINA address and DIF it should enroll with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootstrapHello {
+    /// RINA address of the bootstrap IPCP
+    pub rina_addr: u64,
+    /// DIF the bootstrap enrolls members into
+    pub dif_name: String,
+}
+
+/// Rules a DIF name must follow
+///
+/// Names are made of ASCII letters, digits, `-` and `_`. With namespaces
+/// allowed, `.` separates hierarchical segments such as `parent.child`;
+/// every segment must be non-empty and start with a letter or digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifNameRules {
+    /// Longest name accepted, in bytes
+    pub max_length: usize,
+    /// Whether `.`-separated namespaces are accepted
+    pub allow_namespaces: bool,
+}
+
+impl Default for DifNameRules {
+    fn default() -> Self {
+        Self {
+            max_length: 64,
+            allow_namespaces: true,
+        }
+    }
+}
+
+impl DifNameRules {
+    /// Checks `name` against the rules, describing the first violation
+    pub fn validate(&self, name: &str) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("DIF name is empty".to_string());
+        }
+        if name.len() > self.max_length {
+            return Err(format!(
+                "DIF name '{}' is longer than {} characters",
+                name, self.max_length
+            ));
+        }
+        if !self.allow_namespaces && name.contains('.') {
+            return Err(format!(
+                "DIF name '{}' uses namespaces, which are disabled",
+                name
+            ));
+        }
+
+        for segment in name.split('.') {
+            let Some(first) = segment.chars().next() else {
+                return Err(format!("DIF name '{}' has an empty segment", name));
+            };
+            if !first.is_ascii_alphanumeric() {
+                return Err(format!(
+                    "DIF name '{}' has a segment starting with '{}'",
+                    name, first
+                ));
+            }
+            if let Some(bad) = segment
+                .chars()
+                .find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_')
+            {
+                return Err(format!(
+                    "DIF name '{}' contains invalid character '{}'",
+                    name, bad
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Dynamically assigned address remembered across member restarts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressState {
+    /// Name of the IPCP the address was assigned to
+    pub ipcp_name: String,
+    /// Assigned RINA address
+    pub address: u64,
+}
+
+impl AddressState {
+    /// Loads the state from a TOML file
+    pub fn load(path: &Path) -> Result<Self, EnrollmentError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))?;
+        toml::from_str(&content).map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))
+    }
+
+    /// Writes the state to a TOML file
+    pub fn save(&self, path: &Path) -> Result<(), EnrollmentError> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
+        std::fs::write(path, content)
+            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))
+    }
+}
+
 /// Information about a neighbor IPCP
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NeighborInfo {
@@ -118,15 +500,31 @@ pub struct NeighborInfo {
     pub reachable: bool,
 }
 
+/// How far a member's RIB is in step with its bootstrap
+///
+/// A member whose `last_sync_time` stops advancing, or whose `pending`
+/// count keeps growing, is no longer synchronizing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncStatus {
+    /// Current version of the local RIB
+    pub local_version: u64,
+    /// Bootstrap RIB version the last successful sync brought us to
+    pub last_synced_version: u64,
+    /// When the last sync cycle succeeded, if one has
+    pub last_sync_time: Option<SystemTime>,
+    /// Local changes not yet pushed to the bootstrap
+    pub pending: usize,
+}
+
 /// Enrollment manager - fully async implementation
 #[derive(Debug)]
 pub struct EnrollmentManager {
-    /// Current enrollment state
-    state: EnrollmentState,
+    /// Current enrollment state, shared with the connection watchdog
+    state: Arc<std::sync::RwLock<EnrollmentState>>,
     /// Local IPCP name
     ipcp_name: Option<String>,
-    /// Local RINA address
-    local_addr: u64,
+    /// Local RINA address, shared with the connection watchdog
+    local_addr: Arc<AtomicU64>,
     /// Local RIB
     rib: Rib,
     /// UDP shim for network communication
@@ -137,14 +535,60 @@ pub struct EnrollmentManager {
     address_pool: Option<Arc<AddressPool>>,
     /// Bootstrap address for re-enrollment (None for bootstrap IPCP)
     bootstrap_addr: Option<u64>,
+    /// Socket address of the bootstrap peer enrollment succeeded through
+    bootstrap_peer: Option<SocketAddr>,
     /// Last successful heartbeat time
     last_heartbeat: Arc<RwLock<Option<Instant>>>,
     /// Whether re-enrollment is in progress
     re_enrollment_in_progress: Arc<RwLock<bool>>,
+    /// Held by whichever exchange with the bootstrap is reading the shim,
+    /// so enrollment and sync never consume each other's responses
+    exchange: Arc<Mutex<()>>,
     /// Route resolver for managing dynamic routes
     route_resolver: Option<Arc<RouteResolver>>,
     /// Last synced RIB version (for incremental sync)
     last_synced_version: Arc<RwLock<u64>>,
+    /// When the last sync cycle succeeded
+    last_sync_time: Arc<RwLock<Option<SystemTime>>>,
+    /// Highest local RIB version already pushed to the bootstrap (member side)
+    last_pushed_version: Arc<RwLock<u64>>,
+    /// Highest version seen from each peer's pushed changes (bootstrap side)
+    peer_versions: Arc<RwLock<HashMap<String, u64>>>,
+    /// Reassembles fragmented CDAP messages received from peers
+    reassembler: Arc<std::sync::Mutex<CdapReassembler>>,
+    /// Invoke IDs for the requests this manager sends
+    invoke_ids: Arc<std::sync::Mutex<InvokeIds>>,
+    /// Live table of neighbor IPCPs, mirrored into the RIB
+    neighbors: NeighborTable,
+    /// Name of the bootstrap IPCP we enrolled with (member side)
+    bootstrap_name: Option<String>,
+    /// Reserved pool addresses keyed by the IPCP name they are kept for (bootstrap side)
+    address_reservations: Arc<RwLock<HashMap<String, u64>>>,
+    /// Observer notified of enrollment progress
+    events: Option<mpsc::Sender<EnrollmentEvent>>,
+    /// Events dropped because the observer's channel was full
+    dropped_events: Arc<AtomicU64>,
+    /// File the assigned address is persisted to (member side)
+    address_state_path: Option<PathBuf>,
+    /// Dynamically allocated addresses keyed by member name (bootstrap side)
+    address_holders: Arc<RwLock<HashMap<String, u64>>>,
+    /// CDAP version and features advertised to peers
+    capabilities: CdapCapabilities,
+    /// Capabilities agreed with the bootstrap at enrollment (member side)
+    negotiated_capabilities: Arc<RwLock<Option<CdapCapabilities>>>,
+    /// Capabilities agreed with each enrolled member, keyed by name (bootstrap side)
+    peer_capabilities: Arc<RwLock<HashMap<String, CdapCapabilities>>>,
+    /// Limits enrollment requests per source address (bootstrap side, None = unlimited)
+    rate_limiter: Option<Arc<std::sync::Mutex<RequestRateLimiter>>>,
+    /// Recent accepted responses keyed by member name and request id (bootstrap side)
+    recent_responses: Arc<RwLock<ResponseCache>>,
+    /// Secret required to rebind a claimed address (bootstrap side) or sent
+    /// with enrollment requests (member side)
+    rebind_secret: Option<String>,
+    /// DIF this member expects to join (member side, None = accept any)
+    dif_name: Option<String>,
+    /// Rules requested DIF names must follow (bootstrap side)
+    dif_name_rules: DifNameRules,
 }
 
 impl EnrollmentManager {
@@ -160,19 +604,46 @@ impl EnrollmentManager {
         local_addr: u64,
         config: EnrollmentConfig,
     ) -> Self {
+        let neighbors = NeighborTable::new(
+            rib.clone(),
+            Duration::from_secs(config.connection_timeout_secs),
+        );
+
         Self {
-            state: EnrollmentState::NotEnrolled,
+            state: Arc::new(std::sync::RwLock::new(EnrollmentState::NotEnrolled)),
             ipcp_name: None,
-            local_addr,
+            local_addr: Arc::new(AtomicU64::new(local_addr)),
             rib,
             shim,
             config,
             address_pool: None,
             bootstrap_addr: None,
+            bootstrap_peer: None,
             last_heartbeat: Arc::new(RwLock::new(None)),
             re_enrollment_in_progress: Arc::new(RwLock::new(false)),
+            exchange: Arc::new(Mutex::new(())),
             route_resolver: None,
             last_synced_version: Arc::new(RwLock::new(0)),
+            last_sync_time: Arc::new(RwLock::new(None)),
+            last_pushed_version: Arc::new(RwLock::new(0)),
+            peer_versions: Arc::new(RwLock::new(HashMap::new())),
+            reassembler: Arc::new(std::sync::Mutex::new(CdapReassembler::new())),
+            invoke_ids: Arc::new(std::sync::Mutex::new(InvokeIds::new())),
+            neighbors,
+            bootstrap_name: None,
+            address_reservations: Arc::new(RwLock::new(HashMap::new())),
+            events: None,
+            dropped_events: Arc::new(AtomicU64::new(0)),
+            address_state_path: None,
+            address_holders: Arc::new(RwLock::new(HashMap::new())),
+            capabilities: CdapCapabilities::local(),
+            negotiated_capabilities: Arc::new(RwLock::new(None)),
+            peer_capabilities: Arc::new(RwLock::new(HashMap::new())),
+            rate_limiter: None,
+            recent_responses: Arc::new(RwLock::new(HashMap::new())),
+            rebind_secret: None,
+            dif_name: None,
+            dif_name_rules: DifNameRules::default(),
         }
     }
 
@@ -184,19 +655,48 @@ impl EnrollmentManager {
         pool_start: u64,
         pool_end: u64,
     ) -> Self {
+        let config = EnrollmentConfig::default();
+        let neighbors = NeighborTable::new(
+            rib.clone(),
+            Duration::from_secs(config.connection_timeout_secs),
+        );
+
         Self {
-            state: EnrollmentState::Enrolled, // Bootstrap is pre-enrolled
+            // Bootstrap is pre-enrolled
+            state: Arc::new(std::sync::RwLock::new(EnrollmentState::Enrolled)),
             ipcp_name: None,
-            local_addr,
+            local_addr: Arc::new(AtomicU64::new(local_addr)),
             rib,
             shim,
-            config: EnrollmentConfig::default(),
+            config,
             address_pool: Some(Arc::new(AddressPool::new(pool_start, pool_end))),
             bootstrap_addr: None, // Bootstrap has no bootstrap
+            bootstrap_peer: None,
             last_heartbeat: Arc::new(RwLock::new(Some(Instant::now()))),
             re_enrollment_in_progress: Arc::new(RwLock::new(false)),
+            exchange: Arc::new(Mutex::new(())),
             route_resolver: None,
             last_synced_version: Arc::new(RwLock::new(0)),
+            last_sync_time: Arc::new(RwLock::new(None)),
+            last_pushed_version: Arc::new(RwLock::new(0)),
+            peer_versions: Arc::new(RwLock::new(HashMap::new())),
+            reassembler: Arc::new(std::sync::Mutex::new(CdapReassembler::new())),
+            invoke_ids: Arc::new(std::sync::Mutex::new(InvokeIds::new())),
+            neighbors,
+            bootstrap_name: None,
+            address_reservations: Arc::new(RwLock::new(HashMap::new())),
+            events: None,
+            dropped_events: Arc::new(AtomicU64::new(0)),
+            address_state_path: None,
+            address_holders: Arc::new(RwLock::new(HashMap::new())),
+            capabilities: CdapCapabilities::local(),
+            negotiated_capabilities: Arc::new(RwLock::new(None)),
+            peer_capabilities: Arc::new(RwLock::new(HashMap::new())),
+            rate_limiter: None,
+            recent_responses: Arc::new(RwLock::new(HashMap::new())),
+            rebind_secret: None,
+            dif_name: None,
+            dif_name_rules: DifNameRules::default(),
         }
     }
 
@@ -205,44 +705,378 @@ impl EnrollmentManager {
         self.route_resolver = Some(resolver);
     }
 
+    /// Limits enrollment requests to `per_second` per source IP, allowing bursts of `burst`
+    ///
+    /// A rate of zero disables the limit. Requests over the limit are dropped
+    /// without a response; well-behaved members retry with backoff.
+    pub fn set_request_rate_limit(&mut self, per_second: f64, burst: u32) {
+        self.rate_limiter = (per_second > 0.0).then(|| {
+            Arc::new(std::sync::Mutex::new(RequestRateLimiter::new(
+                per_second, burst,
+            )))
+        });
+    }
+
+    /// Returns whether a request from `source` fits within the rate limit
+    fn admit_request(&self, source: SocketAddr) -> bool {
+        self.rate_limiter
+            .as_ref()
+            .is_none_or(|limiter| limiter.lock().unwrap().allow(source))
+    }
+
     /// Sets the IPCP name
     pub fn set_ipcp_name(&mut self, name: String) {
         self.ipcp_name = Some(name);
-        self.state = EnrollmentState::Initiated;
+        self.set_state(EnrollmentState::Initiated);
+    }
+
+    /// Reserves a pool address for the member IPCP called `ipcp_name` (bootstrap only)
+    ///
+    /// The address is withheld from dynamic assignment and granted to that
+    /// member when it enrolls and asks for an address.
+    pub async fn reserve_address(
+        &self,
+        ipcp_name: &str,
+        address: u64,
+    ) -> Result<(), EnrollmentError> {
+        let pool = self.address_pool.as_ref().ok_or_else(|| {
+            EnrollmentError::AddressAssignmentFailed("Bootstrap has no address pool".to_string())
+        })?;
+        pool.reserve(address)
+            .map_err(EnrollmentError::AddressAssignmentFailed)?;
+
+        self.address_reservations
+            .write()
+            .await
+            .insert(ipcp_name.to_string(), address);
+        Ok(())
+    }
+
+    /// Warns when the free fraction of the address pool drops to `fraction`
+    ///
+    /// Has no effect on a member, which has no pool.
+    pub fn set_address_pool_low_watermark(&mut self, fraction: f64) {
+        if let Some(pool) = self.address_pool.take() {
+            let pool = Arc::unwrap_or_clone(pool).with_low_watermark(fraction);
+            self.address_pool = Some(Arc::new(pool));
+        }
+    }
+
+    /// Returns the address pool members are assigned from (bootstrap only)
+    pub fn address_pool(&self) -> Option<&AddressPool> {
+        self.address_pool.as_deref()
     }
 
     /// Returns the current enrollment state
-    pub fn state(&self) -> &EnrollmentState {
-        &self.state
+    pub fn state(&self) -> EnrollmentState {
+        self.state.read().unwrap().clone()
     }
 
     /// Checks if enrolled
     pub fn is_enrolled(&self) -> bool {
-        self.state == EnrollmentState::Enrolled
+        *self.state.read().unwrap() == EnrollmentState::Enrolled
+    }
+
+    fn set_state(&self, state: EnrollmentState) {
+        *self.state.write().unwrap() = state;
     }
 
     /// Returns the local address (may be updated after enrollment)
     pub fn local_addr(&self) -> u64 {
-        self.local_addr
+        self.local_addr.load(Ordering::Relaxed)
+    }
+
+    /// Sets the secret guarding address rebinds
+    ///
+    /// A bootstrap refuses enrollment requests claiming an address that is
+    /// already mapped to a different, still live socket, unless they carry
+    /// this secret. A member sends it with every enrollment request.
+    ///
+    /// The secret travels in plaintext, so anyone who can observe enrollment
+    /// traffic can replay it; it only keeps off-path peers from taking over.
+    pub fn set_rebind_secret(&mut self, secret: Option<String>) {
+        self.rebind_secret = secret;
+    }
+
+    /// Sets the DIF this member expects to join
+    ///
+    /// The name is sent with enrollment requests, and a bootstrap serving a
+    /// different DIF fails the enrollment instead of being joined.
+    pub fn set_dif_name(&mut self, dif_name: String) {
+        self.dif_name = Some(dif_name);
+    }
+
+    /// Sets the rules DIF names in enrollment requests must follow
+    pub fn set_dif_name_rules(&mut self, rules: DifNameRules) {
+        self.dif_name_rules = rules;
+    }
+
+    /// Persists the assigned address to `path` and re-claims it on restart
+    pub fn set_address_state_path(&mut self, path: PathBuf) {
+        self.address_state_path = Some(path);
+    }
+
+    /// Returns the address persisted for `ipcp_name`, if any
+    fn persisted_address(&self, ipcp_name: &str) -> Option<u64> {
+        let path = self.address_state_path.as_ref()?;
+        let state = AddressState::load(path).ok()?;
+        if state.ipcp_name != ipcp_name {
+            return None;
+        }
+        progress!("Re-claiming previously assigned address {}", state.address);
+        Some(state.address)
+    }
+
+    /// Saves the assigned address; failures only cost a fresh address later
+    fn persist_address(&self, ipcp_name: &str, address: u64) {
+        let Some(path) = &self.address_state_path else {
+            return;
+        };
+        let state = AddressState {
+            ipcp_name: ipcp_name.to_string(),
+            address,
+        };
+        if let Err(e) = state.save(path) {
+            progress!("Warning: Failed to persist assigned address: {}", e);
+        }
+    }
+
+    /// Pushes enrollment progress events to `sender`
+    ///
+    /// Enrollment never waits for the observer: events that find the channel
+    /// full are dropped and counted in [`dropped_events`](Self::dropped_events).
+    pub fn set_event_sender(&mut self, sender: mpsc::Sender<EnrollmentEvent>) {
+        self.events = Some(sender);
+    }
+
+    /// Returns how many events were dropped because the observer lagged
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// Notifies the observer, if any; a dropped receiver is ignored
+    fn emit(&self, event: EnrollmentEvent) {
+        let Some(tx) = &self.events else {
+            return;
+        };
+        if let Err(mpsc::error::TrySendError::Full(event)) = tx.try_send(event) {
+            self.dropped_events.fetch_add(1, Ordering::Relaxed);
+            eprintln!("  ⚠ Enrollment observer lagging, dropped {:?}", event);
+        }
+    }
+
+    /// Overrides the CDAP capabilities advertised when enrolling or accepting members
+    pub fn set_capabilities(&mut self, capabilities: CdapCapabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Returns the capabilities agreed with the bootstrap (member side)
+    ///
+    /// `None` until enrollment has completed.
+    pub async fn negotiated_capabilities(&self) -> Option<CdapCapabilities> {
+        *self.negotiated_capabilities.read().await
+    }
+
+    /// Returns the capabilities agreed with the member called `ipcp_name` (bootstrap side)
+    pub async fn peer_capabilities(&self, ipcp_name: &str) -> Option<CdapCapabilities> {
+        self.peer_capabilities.read().await.get(ipcp_name).copied()
+    }
+
+    /// Returns the QoS classes the DIF offers, as advertised at enrollment
+    pub async fn qos_cube(&self) -> Option<QoSCube> {
+        QoSCube::from_rib(&self.rib).await
+    }
+
+    /// Returns the RIB this manager enrolls and synchronises
+    pub fn rib(&self) -> &Rib {
+        &self.rib
+    }
+
+    /// Returns a handle sharing this manager's state, for actors that report on it
+    pub fn shared(&self) -> Arc<Self> {
+        Arc::new(self.share())
+    }
+
+    /// Returns the socket address of the bootstrap peer we enrolled through
+    pub fn bootstrap_peer(&self) -> Option<SocketAddr> {
+        self.bootstrap_peer
+    }
+
+    /// Returns the live neighbor table
+    pub fn neighbor_table(&self) -> &NeighborTable {
+        &self.neighbors
+    }
+
+    /// Returns the addresses of neighbors heard from within the connection timeout
+    pub async fn reachable_neighbors(&self) -> Vec<u64> {
+        self.neighbors.reachable_neighbors().await
+    }
+
+    /// Asks the IPCP at `socket_addr` for its RINA address and DIF name
+    ///
+    /// The hello is sent straight to the socket address since no RINA
+    /// mapping exists for the bootstrap yet. On success the bootstrap is
+    /// registered as a peer, ready for `enrol_with_bootstrap`.
+    pub async fn hello_bootstrap(
+        &self,
+        socket_addr: SocketAddr,
+    ) -> Result<BootstrapHello, EnrollmentError> {
+        let request = CdapMessage::new_request(
+            CdapOpCode::Read,
+            "/dif/bootstrap".to_string(),
+            Some(BOOTSTRAP_HELLO_CLASS.to_string()),
+            None,
+            self.next_invoke_id(),
+        );
+        // The bootstrap's RINA address is what we are asking for
+        self.send_cdap_to_socket(0, socket_addr, &request)?;
+
+        let response = self
+            .receive_cdap_response(Some(BOOTSTRAP_HELLO_CLASS), request.invoke_id)
+            .await?;
+        let hello: BootstrapHello = match &response.obj_value {
+            Some(RibValue::Bytes(bytes)) => postcard::from_bytes(bytes)
+                .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))?,
+            _ => {
+                return Err(EnrollmentError::InvalidResponse(
+                    "Missing bootstrap hello".to_string(),
+                ));
+            }
+        };
+
+        self.shim.register_peer(hello.rina_addr, socket_addr);
+        Ok(hello)
+    }
+
+    /// Greets the IPCP at `socket_addr`, retrying with backoff
+    ///
+    /// Uses the same `max_retries` and backoff as `enrol_with_bootstrap`, so
+    /// a bootstrap that starts a little after us is still found.
+    async fn hello_with_retries(
+        &self,
+        socket_addr: SocketAddr,
+    ) -> Result<BootstrapHello, EnrollmentError> {
+        let mut backoff = Backoff::new(self.config.backoff_jitter, self.config.initial_backoff_ms);
+
+        for attempt in 1..=self.config.max_retries {
+            match self.hello_bootstrap(socket_addr).await {
+                Ok(hello) => return Ok(hello),
+                Err(e) => eprintln!(
+                    "Hello to {} failed (attempt {}/{}): {}",
+                    socket_addr, attempt, self.config.max_retries, e
+                ),
+            }
+
+            if attempt < self.config.max_retries {
+                let delay = backoff.delay(attempt);
+                progress!("Retrying in {:?}...", delay);
+                sleep(delay).await;
+            }
+        }
+
+        Err(EnrollmentError::Timeout {
+            attempts: self.config.max_retries,
+        })
+    }
+
+    /// Enrolls through the first of `peers` that answers and accepts us
+    ///
+    /// Each peer is greeted to learn its RINA address, then enrolled with;
+    /// both steps get the full retry/backoff budget of the enrollment config.
+    /// Unreachable or rejecting peers are skipped in order.
+    pub async fn enrol_with_peers(
+        &mut self,
+        peers: &[SocketAddr],
+    ) -> Result<String, EnrollmentError> {
+        if peers.is_empty() {
+            return Err(EnrollmentError::NoBootstrapPeers);
+        }
+
+        for (index, &peer) in peers.iter().enumerate() {
+            progress!(
+                "Trying bootstrap peer {} ({}/{})",
+                peer,
+                index + 1,
+                peers.len()
+            );
+
+            let hello = match self.hello_with_retries(peer).await {
+                Ok(hello) => hello,
+                Err(e) => {
+                    eprintln!("Bootstrap peer {} did not answer: {}", peer, e);
+                    continue;
+                }
+            };
+            progress!(
+                "  Bootstrap peer {} is IPCP {} in DIF '{}'",
+                peer,
+                hello.rina_addr,
+                hello.dif_name
+            );
+
+            match self.enrol_with_bootstrap(hello.rina_addr).await {
+                Ok(dif_name) => {
+                    self.bootstrap_peer = Some(peer);
+                    return Ok(dif_name);
+                }
+                Err(e) => {
+                    eprintln!("Enrollment via {} failed: {}", peer, e);
+                }
+            }
+        }
+
+        Err(EnrollmentError::PeerUnreachable(format!(
+            "none of {} bootstrap peers accepted enrollment",
+            peers.len()
+        )))
     }
 
     /// Enrol with bootstrap IPCP with timeout and retry logic
+    ///
+    /// A sync cycle already waiting for its response is let finish first;
+    /// no other exchange reads the shim until enrollment is over.
     pub async fn enrol_with_bootstrap(
         &mut self,
         bootstrap_addr: u64,
     ) -> Result<String, EnrollmentError> {
-        for attempt in 1..=self.config.max_retries {
-            println!("Enrollment attempt {}/{}", attempt, self.config.max_retries);
+        let exchange = self.exchange.clone();
+        let _exchange = exchange.lock().await;
+        let mut backoff = Backoff::new(self.config.backoff_jitter, self.config.initial_backoff_ms);
+        // Retries reuse the id so the bootstrap can recognise them
+        let request_id = fastrand::u64(1..);
 
-            match timeout(self.config.timeout, self.try_enrol(bootstrap_addr)).await {
+        for attempt in 1..=self.config.max_retries {
+            progress!("Enrollment attempt {}/{}", attempt, self.config.max_retries);
+            self.emit(EnrollmentEvent::AttemptStarted {
+                bootstrap_addr,
+                attempt,
+            });
+
+            match timeout(
+                self.config.timeout,
+                self.try_enrol(bootstrap_addr, request_id),
+            )
+            .await
+            {
                 Ok(Ok(dif_name)) => {
-                    println!("Successfully enrolled in DIF: {}", dif_name);
+                    progress!("Successfully enrolled in DIF: {}", dif_name);
                     // Save bootstrap address for re-enrollment
                     self.bootstrap_addr = Some(bootstrap_addr);
                     // Initialize heartbeat
-                    *self.last_heartbeat.write().await = Some(Instant::now());
+                    self.update_heartbeat().await;
+                    self.emit(EnrollmentEvent::Enrolled {
+                        dif_name: dif_name.clone(),
+                    });
                     return Ok(dif_name);
                 }
+                // Retrying cannot fix joining the wrong DIF
+                Ok(Err(e @ EnrollmentError::DifNameMismatch { .. })) => {
+                    eprintln!("Enrollment attempt {} failed: {}", attempt, e);
+                    self.emit(EnrollmentEvent::Failed {
+                        reason: e.to_string(),
+                    });
+                    return Err(e);
+                }
                 Ok(Err(e)) => {
                     eprintln!("Enrollment attempt {} failed: {}", attempt, e);
                 }
@@ -252,36 +1086,55 @@ impl EnrollmentManager {
             }
 
             if attempt < self.config.max_retries {
-                let backoff =
-                    Duration::from_millis(self.config.initial_backoff_ms * (1 << (attempt - 1)));
-                println!("Retrying in {:?}...", backoff);
-                sleep(backoff).await;
+                let delay = backoff.delay(attempt);
+                progress!("Retrying in {:?}...", delay);
+                sleep(delay).await;
             }
         }
 
-        Err(EnrollmentError::Timeout {
+        let error = EnrollmentError::Timeout {
             attempts: self.config.max_retries,
-        })
+        };
+        self.emit(EnrollmentEvent::Failed {
+            reason: error.to_string(),
+        });
+        Err(error)
     }
 
     /// Single enrollment attempt
-    async fn try_enrol(&mut self, bootstrap_addr: u64) -> Result<String, EnrollmentError> {
+    async fn try_enrol(
+        &mut self,
+        bootstrap_addr: u64,
+        request_id: u64,
+    ) -> Result<String, EnrollmentError> {
         let ipcp_name = self
             .ipcp_name
             .as_ref()
             .ok_or(EnrollmentError::IpcpNameNotSet)?
             .clone();
 
+        // A restarted member asks for the address it held before
+        let preferred_address = if self.local_addr() == 0 {
+            self.persisted_address(&ipcp_name)
+        } else {
+            None
+        };
+
         // Create enrollment request
         let request = EnrollmentRequest {
             ipcp_name: ipcp_name.clone(),
-            ipcp_address: self.local_addr,
-            dif_name: String::new(), // Will be provided by bootstrap
+            ipcp_address: self.local_addr(),
+            // Empty lets the bootstrap enroll us into whichever DIF it serves
+            dif_name: self.dif_name.clone().unwrap_or_default(),
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
-            request_address: self.local_addr == 0, // Request address if we don't have one
+            request_address: self.local_addr() == 0, // Request address if we don't have one
+            preferred_address,
+            capabilities: self.capabilities,
+            request_id,
+            rebind_secret: self.rebind_secret.clone(),
         };
 
         // Create CDAP message with enrollment request
@@ -293,36 +1146,20 @@ impl EnrollmentManager {
                 postcard::to_allocvec(&request)
                     .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?,
             )),
-            invoke_id: 1,
-            result: 0,
+            invoke_id: self.next_invoke_id(),
+            result: CdapResult::Success,
             result_reason: None,
             sync_request: None,
             sync_response: None,
         };
 
-        // Serialize CDAP message with postcard
-        let cdap_bytes = postcard::to_allocvec(&cdap_msg)
-            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
-
-        // Create PDU with CDAP payload
-        let pdu = Pdu::new_data(
-            self.local_addr, // src_addr - member's configured address (or 0)
-            bootstrap_addr,  // dst_addr
-            0,               // src_cep_id
-            0,               // dst_cep_id
-            0,               // sequence_num
-            cdap_bytes,      // payload
-        );
-
         // Send enrollment request
-        self.shim
-            .send_pdu(&pdu)
-            .map_err(|e| EnrollmentError::SendFailed(e.to_string()))?;
+        self.send_cdap(bootstrap_addr, &cdap_msg)?;
 
-        println!("Sent enrollment request to bootstrap IPCP");
+        progress!("Sent enrollment request to bootstrap IPCP");
 
         // Wait for response
-        let response = self.receive_response().await?;
+        let response = self.receive_response(cdap_msg.invoke_id).await?;
 
         // Deserialize enrollment response from CDAP message
         let response_bytes =
@@ -334,7 +1171,7 @@ impl EnrollmentManager {
                 ))?;
 
         let enroll_response: EnrollmentResponse = match response_bytes {
-            RibValue::Bytes(bytes) => postcard::from_bytes(bytes)
+            RibValue::Bytes(bytes) => EnrollmentResponse::decode(bytes)
                 .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))?,
             RibValue::String(s) => {
                 // Legacy support for old string-based responses
@@ -344,6 +1181,9 @@ impl EnrollmentManager {
                     assigned_address: None,
                     dif_name: s.clone(),
                     rib_snapshot: None,
+                    bootstrap_name: None,
+                    capabilities: CdapCapabilities::minimal(),
+                    qos_cube: None,
                 }
             }
             _ => {
@@ -353,6 +1193,17 @@ impl EnrollmentManager {
             }
         };
 
+        // A bootstrap of another DIF is never joined, whatever it answered
+        if let Some(expected) = &self.dif_name
+            && !enroll_response.dif_name.is_empty()
+            && enroll_response.dif_name != *expected
+        {
+            return Err(EnrollmentError::DifNameMismatch {
+                expected: expected.clone(),
+                actual: enroll_response.dif_name,
+            });
+        }
+
         if !enroll_response.accepted {
             return Err(EnrollmentError::Rejected(
                 enroll_response
@@ -361,10 +1212,22 @@ impl EnrollmentManager {
             ));
         }
 
+        progress!(
+            "Negotiated CDAP v{} (incremental sync: {}, compression: {})",
+            enroll_response.capabilities.version,
+            enroll_response.capabilities.incremental_sync,
+            enroll_response.capabilities.compression
+        );
+        *self.negotiated_capabilities.write().await = Some(enroll_response.capabilities);
+        self.shim
+            .set_peer_compression(bootstrap_addr, enroll_response.capabilities.compression);
+
         // Update local address if one was assigned
         if let Some(assigned_addr) = enroll_response.assigned_address {
-            println!("Received assigned address: {}", assigned_addr);
-            self.local_addr = assigned_addr;
+            progress!("Received assigned address: {}", assigned_addr);
+            self.local_addr.store(assigned_addr, Ordering::Relaxed);
+            self.emit(EnrollmentEvent::AddressAssigned(assigned_addr));
+            self.persist_address(&ipcp_name, assigned_addr);
 
             // Store assigned address in RIB
             let _ = self
@@ -379,24 +1242,36 @@ impl EnrollmentManager {
 
         // Synchronize RIB if snapshot provided
         if let Some(rib_data) = enroll_response.rib_snapshot {
-            println!("Synchronizing RIB...");
+            progress!("Synchronizing RIB...");
             match self.rib.deserialize(&rib_data).await {
                 Ok(count) => {
-                    println!("Synchronized {} RIB objects", count);
+                    progress!("Synchronized {} RIB objects", count);
                     // Store RIB version for future incremental syncs
                     let rib_version = self.rib.current_version().await;
                     let mut last_version = self.last_synced_version.write().await;
                     *last_version = rib_version;
-                    println!("  RIB version: {}", rib_version);
+                    progress!("  RIB version: {}", rib_version);
+                    drop(last_version);
+                    self.emit(EnrollmentEvent::RibSynced { objects: count });
                 }
-                Err(e) => println!("Warning: Failed to sync RIB: {}", e),
+                Err(e) => progress!("Warning: Failed to sync RIB: {}", e),
             }
         }
 
-        let dif_name = enroll_response.dif_name.clone();
+        if let Some(cube) = &enroll_response.qos_cube {
+            match cube.publish(&self.rib).await {
+                Ok(()) => progress!("DIF offers {} QoS classes", cube.profiles().len()),
+                Err(e) => progress!("Warning: Failed to store QoS cube: {}", e),
+            }
+        }
+
+        let dif_name = enroll_response.dif_name.clone();
+        if enroll_response.bootstrap_name.is_some() {
+            self.bootstrap_name = enroll_response.bootstrap_name.clone();
+        }
 
         // Update state
-        self.state = EnrollmentState::Enrolled;
+        self.set_state(EnrollmentState::Enrolled);
 
         // Store DIF name in RIB
         let _ = self
@@ -409,109 +1284,136 @@ impl EnrollmentManager {
             .await;
 
         // Request routing table from bootstrap
-        println!("Requesting routing table from bootstrap...");
-        let _ = self.sync_routes_from_bootstrap(bootstrap_addr).await;
+        progress!("Requesting routing table from bootstrap...");
+        match self.sync_routes_from_bootstrap(bootstrap_addr).await {
+            Ok(routes) => self.emit(EnrollmentEvent::RoutesSynced { routes }),
+            // Non-fatal - continue enrollment
+            Err(e) => progress!("Warning: Failed to sync routes: {}", e),
+        }
+
+        // Everything up to here is either local-only or came from the
+        // bootstrap, so only later changes are pushed back during sync
+        *self.last_pushed_version.write().await = self.rib.current_version().await;
 
         Ok(dif_name)
     }
 
     /// Synchronize routing table from bootstrap's RIB
-    async fn sync_routes_from_bootstrap(&self, bootstrap_addr: u64) -> Result<(), EnrollmentError> {
+    ///
+    /// Returns the number of routes received.
+    async fn sync_routes_from_bootstrap(
+        &self,
+        bootstrap_addr: u64,
+    ) -> Result<usize, EnrollmentError> {
         // Request all static routes from bootstrap
         let cdap_msg = CdapMessage {
             op_code: CdapOpCode::Read,
             obj_name: "/routing/static/*".to_string(),
             obj_class: Some("static_route".to_string()),
             obj_value: None,
-            invoke_id: 2,
-            result: 0,
+            invoke_id: self.next_invoke_id(),
+            result: CdapResult::Success,
             result_reason: None,
             sync_request: None,
             sync_response: None,
         };
 
-        let cdap_bytes = postcard::to_allocvec(&cdap_msg)
-            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
-
-        let pdu = Pdu::new_data(self.local_addr, bootstrap_addr, 0, 0, 0, cdap_bytes);
-
-        self.shim
-            .send_pdu(&pdu)
-            .map_err(|e| EnrollmentError::SendFailed(e.to_string()))?;
+        self.send_cdap(bootstrap_addr, &cdap_msg)?;
 
         // Wait for routing table response (no filter on obj_class)
-        match self.receive_cdap_response(None).await {
-            Ok(response) => {
-                if let Some(RibValue::Struct(routes)) = response.obj_value {
-                    println!("Received {} routes from bootstrap", routes.len());
-
-                    // Store routes in local RIB
-                    for (dest, route_info) in routes {
-                        let route_name = format!("/routing/static/{}", dest);
-                        let _ = self
-                            .rib
-                            .create(route_name, "static_route".to_string(), *route_info)
-                            .await;
-                    }
+        let response = self.receive_cdap_response(None, cdap_msg.invoke_id).await?;
+        let Some(RibValue::Struct(routes)) = response.obj_value else {
+            return Ok(0);
+        };
+        progress!("Received {} routes from bootstrap", routes.len());
+
+        // Store routes in local RIB, normalised to the current encoding
+        let mut count = 0;
+        for (dest, route_info) in routes {
+            let route = match Route::try_from(route_info.as_ref()) {
+                Ok(route) => route,
+                Err(e) => {
+                    progress!("  ⚠ Ignoring route to {}: {}", dest, e);
+                    continue;
                 }
-                Ok(())
-            }
-            Err(e) => {
-                println!("Warning: Failed to sync routes: {}", e);
-                Ok(()) // Non-fatal - continue enrollment
-            }
+            };
+            let route_name = format!("/routing/static/{}", dest);
+            let _ = self
+                .rib
+                .create(route_name, "static_route".to_string(), route.into())
+                .await;
+            count += 1;
         }
+        Ok(count)
     }
 
     /// Receive enrollment response with polling
-    async fn receive_response(&self) -> Result<CdapMessage, EnrollmentError> {
-        self.receive_cdap_response(Some("enrollment")).await
+    ///
+    /// Rejections are returned as messages too: their body carries the
+    /// bootstrap's reason, which the result code alone does not.
+    async fn receive_response(&self, invoke_id: u64) -> Result<CdapMessage, EnrollmentError> {
+        let cdap_msg = self
+            .receive_cdap_message(Some("enrollment"), invoke_id)
+            .await?;
+        if !cdap_msg.is_success() && !matches!(cdap_msg.obj_value, Some(RibValue::Bytes(_))) {
+            return Err(Self::rejection(&cdap_msg));
+        }
+        Ok(cdap_msg)
     }
 
     /// Receive any CDAP response with polling
     async fn receive_cdap_response(
         &self,
         expected_class: Option<&str>,
+        invoke_id: u64,
     ) -> Result<CdapMessage, EnrollmentError> {
-        let poll_interval = Duration::from_millis(100);
-        let max_polls = (self.config.timeout.as_millis() / poll_interval.as_millis()) as u32;
+        let cdap_msg = self.receive_cdap_message(expected_class, invoke_id).await?;
+        if !cdap_msg.is_success() {
+            return Err(Self::rejection(&cdap_msg));
+        }
+        Ok(cdap_msg)
+    }
+
+    /// Describes a response carrying a non-zero result code
+    fn rejection(cdap_msg: &CdapMessage) -> EnrollmentError {
+        EnrollmentError::Rejected(format!("Request rejected: {}", cdap_msg.result))
+    }
+
+    /// Waits for the response to request `invoke_id`, of `expected_class`
+    /// (any class if None)
+    ///
+    /// Responses to other requests, such as late answers from a session
+    /// torn down by a reconnect, are dropped.
+    async fn receive_cdap_message(
+        &self,
+        expected_class: Option<&str>,
+        invoke_id: u64,
+    ) -> Result<CdapMessage, EnrollmentError> {
+        let deadline = Instant::now() + self.config.timeout;
 
-        for _ in 0..max_polls {
-            if let Some((pdu, _src_addr)) = self
+        while let Some(wait) = self.next_wait(deadline) {
+            if let Some((pdu, src_addr)) = self
                 .shim
-                .receive_pdu()
+                .receive_pdu_within(wait)
+                .await
                 .map_err(|e| EnrollmentError::ReceiveFailed(e.to_string()))?
             {
-                // Deserialize CDAP message from PDU payload
-                let cdap_msg: CdapMessage = postcard::from_bytes(&pdu.payload)
-                    .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))?;
+                // Deserialize CDAP message from PDU payload (reassembling fragments)
+                let Some(cdap_msg) = self.decode_cdap(&pdu, src_addr)? else {
+                    // More fragments pending - read the next one right away
+                    continue;
+                };
+                if !Self::answers(&cdap_msg, invoke_id) {
+                    continue;
+                }
 
                 // If expected_class is specified, filter by it
-                if let Some(expected) = expected_class {
-                    if cdap_msg.obj_class.as_deref() == Some(expected) {
-                        if cdap_msg.result == 0 {
-                            return Ok(cdap_msg);
-                        } else {
-                            return Err(EnrollmentError::Rejected(format!(
-                                "Request rejected with code: {}",
-                                cdap_msg.result
-                            )));
-                        }
-                    }
-                } else {
-                    // Accept any CDAP message if no filter specified
-                    if cdap_msg.result == 0 {
-                        return Ok(cdap_msg);
-                    } else {
-                        return Err(EnrollmentError::Rejected(format!(
-                            "Request rejected with code: {}",
-                            cdap_msg.result
-                        )));
-                    }
+                if expected_class
+                    .is_none_or(|expected| cdap_msg.obj_class.as_deref() == Some(expected))
+                {
+                    return Ok(cdap_msg);
                 }
             }
-
-            sleep(poll_interval).await;
         }
 
         Err(EnrollmentError::ReceiveFailed(
@@ -519,13 +1421,80 @@ impl EnrollmentManager {
         ))
     }
 
+    /// Whether `cdap_msg` answers request `invoke_id`, logging stale responses
+    fn answers(cdap_msg: &CdapMessage, invoke_id: u64) -> bool {
+        if cdap_msg.invoke_id == invoke_id {
+            return true;
+        }
+        if CdapSession::epoch_of(cdap_msg.invoke_id) != CdapSession::epoch_of(invoke_id) {
+            eprintln!(
+                "  ⚠ Ignoring response {} from an earlier session",
+                cdap_msg.invoke_id
+            );
+        } else {
+            eprintln!(
+                "  ⚠ Ignoring response {} while waiting for {}",
+                cdap_msg.invoke_id, invoke_id
+            );
+        }
+        false
+    }
+
+    /// How long the next receive may wait, or None once `deadline` has passed
+    fn next_wait(&self, deadline: Instant) -> Option<Duration> {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then(|| remaining.min(self.config.poll_interval))
+    }
+
+    /// Returns the bootstrap RIB version this member last synchronized to
+    pub async fn last_synced_version(&self) -> u64 {
+        *self.last_synced_version.read().await
+    }
+
+    /// Reports how far the local RIB is in step with the bootstrap
+    pub async fn sync_status(&self) -> SyncStatus {
+        let last_pushed = *self.last_pushed_version.read().await;
+        let pending = match self.rib.get_changes_since(last_pushed).await {
+            Ok(changes) => changes.iter().filter(|c| !c.is_local()).count(),
+            // Change log overflowed: every newer object will be pushed
+            Err(_) => self
+                .rib
+                .get_all_objects()
+                .await
+                .iter()
+                .filter(|obj| obj.version > last_pushed && !is_local_name(&obj.name))
+                .count(),
+        };
+
+        SyncStatus {
+            local_version: self.rib.current_version().await,
+            last_synced_version: *self.last_synced_version.read().await,
+            last_sync_time: *self.last_sync_time.read().await,
+            pending,
+        }
+    }
+
+    /// Returns the highest version of `peer`'s changes merged so far (bootstrap side)
+    pub async fn peer_synced_version(&self, peer: &str) -> Option<u64> {
+        self.peer_versions.read().await.get(peer).copied()
+    }
+
     /// Start periodic RIB synchronization task (for members)
     ///
+    /// Every `sync_interval_secs` the member sends a `SyncRequest` carrying its
+    /// last synced version to the bootstrap and applies the returned changes
+    /// (or full snapshot). An interval of 0 disables synchronization.
+    ///
     /// Returns a join handle for the background task
     pub fn start_sync_task(
         self: Arc<Self>,
         sync_interval_secs: u64,
     ) -> tokio::task::JoinHandle<()> {
+        if sync_interval_secs == 0 {
+            // Synchronization disabled
+            return tokio::spawn(async {});
+        }
+
         tokio::spawn(async move {
             let mut interval =
                 tokio::time::interval(tokio::time::Duration::from_secs(sync_interval_secs));
@@ -536,55 +1505,80 @@ impl EnrollmentManager {
             loop {
                 interval.tick().await;
 
+                if *self.re_enrollment_in_progress.read().await {
+                    progress!("⏸  RIB sync paused while re-enrolling");
+                    continue;
+                }
                 if let Err(e) = self.sync_rib().await {
                     eprintln!("⚠️  RIB sync failed: {}", e);
                 } else {
-                    println!("✓ RIB sync completed");
+                    progress!("✓ RIB sync completed");
                 }
             }
         })
     }
 
     /// Request incremental RIB synchronization from bootstrap
-    async fn sync_rib(&self) -> Result<(), EnrollmentError> {
+    ///
+    /// Performs a single sync cycle; `start_sync_task` calls this periodically.
+    /// The cycle waits for any enrollment exchange in progress to finish.
+    pub async fn sync_rib(&self) -> Result<(), EnrollmentError> {
         let bootstrap_addr = self.bootstrap_addr.ok_or(EnrollmentError::NotEnrolled)?;
+        let _exchange = self.exchange.lock().await;
 
         let last_version = *self.last_synced_version.read().await;
 
+        // Collect local changes the bootstrap has not seen yet
+        let last_pushed = *self.last_pushed_version.read().await;
+        let local_changes = match self.rib.get_changes_since(last_pushed).await {
+            Ok(changes) => changes,
+            // Change log overflowed: push every object instead
+            Err(_) => self
+                .rib
+                .get_all_objects()
+                .await
+                .into_iter()
+                .filter(|obj| obj.version > last_pushed)
+                .map(RibChange::Updated)
+                .collect(),
+        };
+        let pushed_up_to = local_changes
+            .iter()
+            .map(RibChange::version)
+            .max()
+            .unwrap_or(last_pushed);
+        // Local objects are covered by `pushed_up_to` but never leave this IPCP
+        let local_changes: Vec<RibChange> = local_changes
+            .into_iter()
+            .filter(|change| !change.is_local())
+            .collect();
+
         // Create CDAP message with sync request
-        let cdap_msg = CdapMessage::new_sync_request(
-            1, // invoke_id
+        let cdap_msg = CdapMessage::new_sync_request_with_changes(
+            self.next_invoke_id(),
             last_version,
             self.ipcp_name.clone().unwrap_or_default(),
+            local_changes,
         );
 
-        // Serialize and send
-        let cdap_bytes = postcard::to_allocvec(&cdap_msg)
-            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
-
-        let pdu = Pdu::new_data(
-            self.local_addr,
-            bootstrap_addr,
-            0, // flow_id
-            0, // seq_num
-            0, // flags
-            cdap_bytes,
-        );
-
-        self.shim
-            .send_pdu(&pdu)
-            .map_err(|e| EnrollmentError::SendFailed(e.to_string()))?;
+        self.send_cdap(bootstrap_addr, &cdap_msg)?;
 
         // Wait for sync response
-        let response_pdu = self.receive_sync_response().await?;
+        let cdap_response = self.receive_sync_response(cdap_msg.invoke_id).await?;
 
-        // Deserialize CDAP response
-        let cdap_response: CdapMessage = postcard::from_bytes(&response_pdu.payload)
-            .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))?;
+        // Any sync response proves the bootstrap is reachable
+        self.update_heartbeat().await;
+
+        // The bootstrap has merged our pushed changes
+        if cdap_response.is_success() {
+            *self.last_pushed_version.write().await = pushed_up_to;
+        }
 
         // Process sync response
         if let Some(sync_resp) = cdap_response.sync_response {
             if let Some(error) = sync_resp.error {
+                // Start over from scratch on the next cycle
+                *self.last_synced_version.write().await = 0;
                 return Err(EnrollmentError::RibSyncFailed(error));
             }
 
@@ -596,7 +1590,7 @@ impl EnrollmentManager {
                     .await
                     .map_err(EnrollmentError::RibSyncFailed)?;
 
-                println!("  ✓ Applied {} incremental changes", applied);
+                progress!("  ✓ Applied {} incremental changes", applied);
 
                 // Update last synced version
                 let mut last_version = self.last_synced_version.write().await;
@@ -609,16 +1603,17 @@ impl EnrollmentManager {
                     .await
                     .map_err(EnrollmentError::RibSyncFailed)?;
 
-                println!("  ✓ Full sync: {} objects", synced);
+                progress!("  ✓ Full sync: {} objects", synced);
 
                 // Update last synced version
                 let mut last_version = self.last_synced_version.write().await;
                 *last_version = sync_resp.current_version;
             } else {
                 // No changes
-                println!("  ✓ RIB up to date (version {})", last_version);
+                progress!("  ✓ RIB up to date (version {})", last_version);
             }
 
+            *self.last_sync_time.write().await = Some(SystemTime::now());
             Ok(())
         } else {
             Err(EnrollmentError::InvalidResponse(
@@ -627,27 +1622,29 @@ impl EnrollmentManager {
         }
     }
 
-    /// Wait for sync response from bootstrap
-    async fn receive_sync_response(&self) -> Result<Pdu, EnrollmentError> {
-        let poll_interval = Duration::from_millis(50);
-        let max_wait = Duration::from_secs(5);
-        let start = Instant::now();
+    /// Wait for the bootstrap's response to sync request `invoke_id`
+    async fn receive_sync_response(&self, invoke_id: u64) -> Result<CdapMessage, EnrollmentError> {
+        let deadline = Instant::now() + Duration::from_secs(5);
 
         loop {
-            if start.elapsed() > max_wait {
+            let Some(wait) = self.next_wait(deadline) else {
                 return Err(EnrollmentError::Timeout { attempts: 1 });
-            }
-
-            if let Ok(Some((pdu, _src_addr))) = self.shim.receive_pdu() {
-                // Check if it's a sync response (contains sync_response field)
-                if let Ok(cdap_msg) = postcard::from_bytes::<CdapMessage>(&pdu.payload)
-                    && cdap_msg.sync_response.is_some()
-                {
-                    return Ok(pdu);
+            };
+
+            if let Ok(Some((pdu, src_addr))) = self.shim.receive_pdu_within(wait).await {
+                match self.decode_cdap(&pdu, src_addr) {
+                    // Check if it's a sync response (contains sync_response field)
+                    Ok(Some(cdap_msg))
+                        if cdap_msg.sync_response.is_some()
+                            && Self::answers(&cdap_msg, invoke_id) =>
+                    {
+                        return Ok(cdap_msg);
+                    }
+                    // More fragments pending - read the next one right away
+                    Ok(None) => continue,
+                    _ => {}
                 }
             }
-
-            sleep(poll_interval).await;
         }
     }
 
@@ -657,13 +1654,21 @@ impl EnrollmentManager {
         pdu: &Pdu,
         src_socket_addr: SocketAddr,
     ) -> Result<(), EnrollmentError> {
-        // Register the peer mapping so we can send response back
-        self.shim.register_peer(pdu.src_addr, src_socket_addr);
-
         // Deserialize CDAP message from PDU payload
         let cdap_msg: CdapMessage = postcard::from_bytes(&pdu.payload)
             .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))?;
 
+        self.process_enrollment_request(pdu, &cdap_msg, src_socket_addr)
+            .await
+    }
+
+    /// Processes a decoded enrollment request (bootstrap side)
+    async fn process_enrollment_request(
+        &self,
+        pdu: &Pdu,
+        cdap_msg: &CdapMessage,
+        src_socket_addr: SocketAddr,
+    ) -> Result<(), EnrollmentError> {
         // Check if this is an enrollment request
         if cdap_msg.obj_class.as_deref() != Some("enrollment")
             || cdap_msg.op_code != CdapOpCode::Create
@@ -675,7 +1680,7 @@ impl EnrollmentManager {
 
         // Extract enrollment request
         let enroll_request: EnrollmentRequest = match &cdap_msg.obj_value {
-            Some(RibValue::Bytes(bytes)) => postcard::from_bytes(bytes)
+            Some(RibValue::Bytes(bytes)) => EnrollmentRequest::decode(bytes)
                 .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))?,
             Some(RibValue::String(name)) => {
                 // Legacy support for old string-based requests
@@ -688,6 +1693,10 @@ impl EnrollmentManager {
                         .unwrap()
                         .as_secs(),
                     request_address: false,
+                    preferred_address: None,
+                    capabilities: CdapCapabilities::minimal(),
+                    request_id: 0,
+                    rebind_secret: None,
                 }
             }
             _ => {
@@ -697,38 +1706,104 @@ impl EnrollmentManager {
             }
         };
 
-        println!(
+        progress!(
             "Received enrollment request from: {} (requesting address: {})",
-            enroll_request.ipcp_name, enroll_request.request_address
+            enroll_request.ipcp_name,
+            enroll_request.request_address
         );
 
-        // Get DIF name from RIB
-        let dif_name_obj =
-            self.rib
-                .read("/dif/name")
-                .await
-                .ok_or(EnrollmentError::InvalidState {
-                    expected: "DIF name configured".to_string(),
-                    actual: "DIF name not set in RIB".to_string(),
-                })?;
-        let dif_name = dif_name_obj
-            .value
-            .as_string()
-            .ok_or(EnrollmentError::InvalidResponse(
-                "DIF name is not a string".to_string(),
-            ))?
-            .to_string();
+        // Refuse to let a newcomer take over a live member's address mapping
+        if let Some(existing) = self.shim.lookup_peer(pdu.src_addr)
+            && pdu.src_addr != 0
+            && existing != src_socket_addr
+            && self.holder_is_live(pdu.src_addr).await
+            && !self.may_rebind(&enroll_request)
+        {
+            let reason = format!("Address {} is already bound to {}", pdu.src_addr, existing);
+            eprintln!(
+                "  ✗ Rejected {} from {}: {}",
+                enroll_request.ipcp_name, src_socket_addr, reason
+            );
+            let rejection = EnrollmentResponse {
+                accepted: false,
+                error: Some(reason.clone()),
+                assigned_address: None,
+                dif_name: String::new(),
+                rib_snapshot: None,
+                bootstrap_name: self.ipcp_name.clone(),
+                capabilities: self.capabilities,
+                qos_cube: None,
+            };
+            // Answer the requesting socket, not the one the address is mapped to
+            let response = Self::enroll_response_message(&rejection, cdap_msg)?;
+            self.send_cdap_to_socket(pdu.src_addr, src_socket_addr, &response)?;
+            return Err(EnrollmentError::Rejected(reason));
+        }
+
+        // Register the peer mapping so we can send response back
+        self.shim.register_peer(pdu.src_addr, src_socket_addr);
+
+        // A retry of a request we already accepted gets the same answer
+        if let Some(mut cached) = self.cached_response(&enroll_request).await {
+            progress!(
+                "  ✓ Request {} from {} already answered, resending response",
+                enroll_request.request_id,
+                enroll_request.ipcp_name
+            );
+            cached.rib_snapshot = Some(self.rib.serialize_shared().await);
+            return self.send_enroll_response(pdu, &cached, cdap_msg).await;
+        }
+
+        let dif_name = self.local_dif_name().await?;
+        let capabilities = self.capabilities.negotiate(&enroll_request.capabilities);
+        self.shim
+            .set_peer_compression(pdu.src_addr, capabilities.compression);
+
+        // An empty DIF name comes from members that accept whichever DIF we serve
+        if !enroll_request.dif_name.is_empty() {
+            let error = match self.dif_name_rules.validate(&enroll_request.dif_name) {
+                Err(reason) => Some(EnrollmentError::InvalidDifName(reason)),
+                Ok(()) if enroll_request.dif_name != dif_name => {
+                    Some(EnrollmentError::DifNameMismatch {
+                        expected: dif_name.clone(),
+                        actual: enroll_request.dif_name.clone(),
+                    })
+                }
+                Ok(()) => None,
+            };
+            if let Some(error) = error {
+                progress!("  ✗ Rejected {}: {}", enroll_request.ipcp_name, error);
+                let rejection = EnrollmentResponse {
+                    accepted: false,
+                    error: Some(error.to_string()),
+                    assigned_address: None,
+                    dif_name: dif_name.clone(),
+                    rib_snapshot: None,
+                    bootstrap_name: self.ipcp_name.clone(),
+                    capabilities,
+                    qos_cube: None,
+                };
+                self.send_enroll_response(pdu, &rejection, cdap_msg).await?;
+                return Err(error);
+            }
+        }
 
         // Allocate address if requested
         let assigned_address = if enroll_request.request_address {
             match &self.address_pool {
-                Some(pool) => match pool.allocate() {
+                Some(pool) => match self
+                    .allocate_member_address(pool, &enroll_request, src_socket_addr)
+                    .await
+                {
                     Ok(addr) => {
-                        println!("  ✓ Allocated address: {}", addr);
+                        progress!("  ✓ Allocated address: {}", addr);
+                        if let Err(e) = pool.mirror_to_rib(&self.rib).await {
+                            progress!("  ⚠ Failed to mirror address pool: {}", e);
+                        }
                         Some(addr)
                     }
                     Err(e) => {
-                        println!("  ✗ Failed to allocate address: {}", e);
+                        progress!("  ✗ Failed to allocate address: {}", e);
                         // Send rejection response
                         let error_response = EnrollmentResponse {
                             accepted: false,
@@ -736,14 +1811,17 @@ impl EnrollmentManager {
                             assigned_address: None,
                             dif_name: dif_name.clone(),
                             rib_snapshot: None,
+                            bootstrap_name: self.ipcp_name.clone(),
+                            capabilities,
+                            qos_cube: None,
                         };
-                        self.send_enroll_response(pdu, &error_response, &cdap_msg)
+                        self.send_enroll_response(pdu, &error_response, cdap_msg)
                             .await?;
                         return Ok(());
                     }
                 },
                 None => {
-                    println!("  ✗ No address pool configured");
+                    progress!("  ✗ No address pool configured");
                     return Err(EnrollmentError::AddressAssignmentFailed(
                         "Bootstrap has no address pool".to_string(),
                     ));
@@ -754,7 +1832,8 @@ impl EnrollmentManager {
         };
 
         // Get RIB snapshot for synchronization
-        let rib_snapshot = Some(self.rib.serialize().await);
+        let rib_snapshot = Some(self.rib.serialize_shared().await);
+        let qos_cube = QoSCube::from_rib(&self.rib).await;
 
         // Create success response
         let response = EnrollmentResponse {
@@ -763,25 +1842,41 @@ impl EnrollmentManager {
             assigned_address,
             dif_name: dif_name.clone(),
             rib_snapshot,
+            bootstrap_name: self.ipcp_name.clone(),
+            capabilities,
+            qos_cube,
         };
 
+        self.cache_response(&enroll_request, &response).await;
+
         // Send response
-        self.send_enroll_response(pdu, &response, &cdap_msg).await?;
+        self.send_enroll_response(pdu, &response, cdap_msg).await?;
+        self.peer_capabilities
+            .write()
+            .await
+            .insert(enroll_request.ipcp_name.clone(), capabilities);
 
-        println!(
+        progress!(
             "Sent enrollment response to {} with DIF name: {}",
-            enroll_request.ipcp_name, dif_name
+            enroll_request.ipcp_name,
+            dif_name
         );
 
         // Add dynamic route for the enrolled member
         let member_addr = assigned_address.unwrap_or(pdu.src_addr);
+        self.neighbors
+            .record_seen(&enroll_request.ipcp_name, member_addr)
+            .await;
         if member_addr != 0 {
             // If we assigned a new address, update the peer mapping
             if let Some(new_addr) = assigned_address {
                 self.shim.register_peer(new_addr, src_socket_addr);
-                println!(
+                self.shim
+                    .set_peer_compression(new_addr, capabilities.compression);
+                progress!(
                     "  ✓ Updated peer mapping: {} → {}",
-                    new_addr, src_socket_addr
+                    new_addr,
+                    src_socket_addr
                 );
             }
 
@@ -797,15 +1892,17 @@ impl EnrollmentManager {
                         ))
                     })?;
 
-                println!(
+                progress!(
                     "  ✓ Created dynamic route: {} → {} ({})",
-                    member_addr, src_socket_addr, enroll_request.ipcp_name
+                    member_addr,
+                    src_socket_addr,
+                    enroll_request.ipcp_name
                 );
             } else {
                 eprintln!("  ⚠ RouteResolver not set, cannot add dynamic route");
             }
         } else {
-            println!("  ⚠ Member enrolled with address 0, skipping route creation");
+            progress!("  ⚠ Member enrolled with address 0, skipping route creation");
         }
 
         Ok(())
@@ -818,43 +1915,55 @@ impl EnrollmentManager {
         response: &EnrollmentResponse,
         request_cdap: &CdapMessage,
     ) -> Result<(), EnrollmentError> {
+        let cdap_response = Self::enroll_response_message(response, request_cdap)?;
+
+        // Send response back to the requester (fragmented if it carries a large snapshot)
+        self.send_cdap(request_pdu.src_addr, &cdap_response)
+    }
+
+    /// Wraps an enrollment response in the CDAP message answering `request_cdap`
+    fn enroll_response_message(
+        response: &EnrollmentResponse,
+        request_cdap: &CdapMessage,
+    ) -> Result<CdapMessage, EnrollmentError> {
         // Serialize enrollment response
         let response_bytes = postcard::to_allocvec(response)
             .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
 
-        // Create CDAP response message
-        let cdap_response = CdapMessage {
+        Ok(CdapMessage {
             op_code: CdapOpCode::Create,
             obj_name: request_cdap.obj_name.clone(),
             obj_class: Some("enrollment".to_string()),
             obj_value: Some(RibValue::Bytes(response_bytes)),
             invoke_id: request_cdap.invoke_id,
-            result: if response.accepted { 0 } else { 1 },
+            result: if response.accepted {
+                CdapResult::Success
+            } else {
+                CdapResult::Rejected
+            },
             result_reason: response.error.clone(),
             sync_request: None,
             sync_response: None,
-        };
-
-        // Serialize CDAP response
-        let cdap_bytes = postcard::to_allocvec(&cdap_response)
-            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
-
-        // Create response PDU
-        let response_pdu = Pdu::new_data(
-            self.local_addr,      // src_addr - bootstrap's address
-            request_pdu.src_addr, // dst_addr - respond to sender
-            0,                    // src_cep_id
-            0,                    // dst_cep_id
-            0,                    // sequence_num
-            cdap_bytes,           // payload
-        );
+        })
+    }
 
-        // Send response
-        self.shim
-            .send_pdu(&response_pdu)
-            .map_err(|e| EnrollmentError::SendFailed(e.to_string()))?;
+    /// Returns whether the member bound to `address` has been heard from recently
+    ///
+    /// A holder silent for longer than the neighbor reachability timeout no
+    /// longer protects its mapping, so a restarted member can reclaim it.
+    async fn holder_is_live(&self, address: u64) -> bool {
+        self.neighbors
+            .get(address)
+            .await
+            .is_some_and(|entry| entry.last_seen.elapsed() <= self.neighbors.reachability_timeout())
+    }
 
-        Ok(())
+    /// Returns whether a request may move its claimed address to a new socket
+    fn may_rebind(&self, request: &EnrollmentRequest) -> bool {
+        match (&self.rebind_secret, &request.rebind_secret) {
+            (Some(required), Some(offered)) => secrets_match(required, offered),
+            _ => false,
+        }
     }
 
     /// Handle incoming CDAP message (routes to appropriate handler)
@@ -863,15 +1972,33 @@ impl EnrollmentManager {
         pdu: &Pdu,
         src_socket_addr: SocketAddr,
     ) -> Result<(), EnrollmentError> {
-        // Deserialize CDAP message from PDU payload
-        let cdap_msg: CdapMessage = postcard::from_bytes(&pdu.payload)
-            .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))?;
+        // Deserialize CDAP message from PDU payload (reassembling fragments)
+        let Some(cdap_msg) = self.decode_cdap(pdu, src_socket_addr)? else {
+            // Wait for the remaining fragments
+            return Ok(());
+        };
+
+        // Enrollment requests and hellos allocate state, so each source is rate limited
+        let is_enrollment_request = matches!(
+            (&cdap_msg.op_code, cdap_msg.obj_class.as_deref()),
+            (CdapOpCode::Create, Some("enrollment"))
+                | (CdapOpCode::Read, Some(BOOTSTRAP_HELLO_CLASS))
+        );
+        if is_enrollment_request && !self.admit_request(src_socket_addr) {
+            return Err(EnrollmentError::RateLimited(src_socket_addr));
+        }
 
         // Route based on operation type and object class
         match (&cdap_msg.op_code, cdap_msg.obj_class.as_deref()) {
             // Enrollment request
             (CdapOpCode::Create, Some("enrollment")) => {
-                self.handle_enrollment_request(pdu, src_socket_addr).await
+                self.process_enrollment_request(pdu, &cdap_msg, src_socket_addr)
+                    .await
+            }
+            // Bootstrap hello from a member that only knows our socket address
+            (CdapOpCode::Read, Some(BOOTSTRAP_HELLO_CLASS)) => {
+                self.handle_bootstrap_hello(pdu, &cdap_msg, src_socket_addr)
+                    .await
             }
             // Routing table read request
             (CdapOpCode::Read, _) if cdap_msg.obj_name.starts_with("/routing/") => {
@@ -887,6 +2014,164 @@ impl EnrollmentManager {
         }
     }
 
+    /// Picks the address to grant a member requesting one (bootstrap side)
+    ///
+    /// A reservation for the member's name wins. Otherwise an address the
+    /// member held before a restart is granted again if it is still free or
+    /// still recorded as the member's own; failing that, a fresh one is
+    /// allocated and the name's previous address returned to the pool.
+    ///
+    /// A name that already holds an address is only served to the socket
+    /// holding it, once that holder has gone silent, or with the rebind
+    /// secret, so knowing a member's name is not enough to take its address.
+    async fn allocate_member_address(
+        &self,
+        pool: &AddressPool,
+        request: &EnrollmentRequest,
+        src_socket_addr: SocketAddr,
+    ) -> Result<u64, String> {
+        let reserved = self
+            .address_reservations
+            .read()
+            .await
+            .get(&request.ipcp_name)
+            .copied();
+        if let Some(addr) = reserved {
+            return pool.allocate_reserved(addr, &request.ipcp_name);
+        }
+
+        let mut holders = self.address_holders.write().await;
+        let held = holders.get(&request.ipcp_name).copied();
+        if let Some(held) = held
+            && !self.may_regrant(held, src_socket_addr, request).await
+        {
+            return Err(format!(
+                "{} already holds address {} at another socket",
+                request.ipcp_name, held
+            ));
+        }
+
+        let address = match request.preferred_address {
+            Some(preferred) if held == Some(preferred) => preferred,
+            Some(preferred) => match pool.claim(preferred) {
+                Ok(addr) => addr,
+                Err(e) => {
+                    progress!("  ⚠ Cannot re-grant address {}: {}", preferred, e);
+                    pool.allocate()?
+                }
+            },
+            None => pool.allocate()?,
+        };
+        if let Some(old) = holders.insert(request.ipcp_name.clone(), address)
+            && old != address
+            && let Err(e) = pool.release(old)
+        {
+            progress!("  ⚠ Failed to release previous address {}: {}", old, e);
+        }
+        Ok(address)
+    }
+
+    /// Returns whether the holder of `address` may be served by this request
+    async fn may_regrant(
+        &self,
+        address: u64,
+        src_socket_addr: SocketAddr,
+        request: &EnrollmentRequest,
+    ) -> bool {
+        self.shim
+            .lookup_peer(address)
+            .is_none_or(|socket| socket == src_socket_addr)
+            || !self.holder_is_live(address).await
+            || self.may_rebind(request)
+    }
+
+    /// Returns the response already sent for this request, if still cached
+    async fn cached_response(&self, request: &EnrollmentRequest) -> Option<EnrollmentResponse> {
+        if request.request_id == 0 {
+            return None;
+        }
+        let key = (request.ipcp_name.clone(), request.request_id);
+        self.recent_responses
+            .read()
+            .await
+            .get(&key)
+            .filter(|(sent_at, _)| sent_at.elapsed() < ENROLLMENT_RESPONSE_TTL)
+            .map(|(_, response)| response.clone())
+    }
+
+    /// Remembers an accepted response, dropping entries past their expiry
+    async fn cache_response(&self, request: &EnrollmentRequest, response: &EnrollmentResponse) {
+        if request.request_id == 0 {
+            return;
+        }
+        let mut responses = self.recent_responses.write().await;
+        responses.retain(|_, (sent_at, _)| sent_at.elapsed() < ENROLLMENT_RESPONSE_TTL);
+
+        // The snapshot is rebuilt on resend, so there is no point keeping it
+        let mut response = response.clone();
+        response.rib_snapshot = None;
+        responses.insert(
+            (request.ipcp_name.clone(), request.request_id),
+            (Instant::now(), response),
+        );
+    }
+
+    /// Reads the DIF name this IPCP belongs to from the RIB
+    async fn local_dif_name(&self) -> Result<String, EnrollmentError> {
+        let dif_name_obj =
+            self.rib
+                .read("/dif/name")
+                .await
+                .map_err(|_| EnrollmentError::InvalidState {
+                    expected: "DIF name configured".to_string(),
+                    actual: "DIF name not set in RIB".to_string(),
+                })?;
+        dif_name_obj
+            .value
+            .as_string()
+            .map(str::to_string)
+            .ok_or(EnrollmentError::InvalidResponse(
+                "DIF name is not a string".to_string(),
+            ))
+    }
+
+    /// Answers a bootstrap hello with our RINA address and DIF name
+    ///
+    /// The reply goes back to the socket the hello came from, as the sender
+    /// is not registered as a peer (and may not have an address yet).
+    async fn handle_bootstrap_hello(
+        &self,
+        pdu: &Pdu,
+        request: &CdapMessage,
+        src_socket_addr: SocketAddr,
+    ) -> Result<(), EnrollmentError> {
+        let hello = BootstrapHello {
+            rina_addr: self.local_addr(),
+            dif_name: self.local_dif_name().await?,
+        };
+        progress!(
+            "👋 Bootstrap hello from {}, replying with address {}",
+            src_socket_addr,
+            hello.rina_addr
+        );
+
+        let hello_bytes = postcard::to_allocvec(&hello)
+            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
+        let response = CdapMessage {
+            op_code: CdapOpCode::Read,
+            obj_name: request.obj_name.clone(),
+            obj_class: Some(BOOTSTRAP_HELLO_CLASS.to_string()),
+            obj_value: Some(RibValue::Bytes(hello_bytes)),
+            invoke_id: request.invoke_id,
+            result: CdapResult::Success,
+            result_reason: None,
+            sync_request: None,
+            sync_response: None,
+        };
+
+        self.send_cdap_to_socket(pdu.src_addr, src_socket_addr, &response)
+    }
+
     /// Handle routing table read request
     async fn handle_routing_read_request(
         &self,
@@ -901,22 +2186,13 @@ impl EnrollmentManager {
             obj_class: request.obj_class.clone(),
             obj_value: Some(RibValue::Struct(std::collections::HashMap::new())),
             invoke_id: request.invoke_id,
-            result: 0,
+            result: CdapResult::Success,
             result_reason: None,
             sync_request: None,
             sync_response: None,
         };
 
-        let response_bytes = postcard::to_allocvec(&response)
-            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
-
-        let response_pdu = Pdu::new_data(self.local_addr, pdu.src_addr, 0, 0, 0, response_bytes);
-
-        self.shim
-            .send_pdu(&response_pdu)
-            .map_err(|e| EnrollmentError::SendFailed(e.to_string()))?;
-
-        Ok(())
+        self.send_cdap(pdu.src_addr, &response)
     }
 
     /// Handle RIB sync request from member (bootstrap side)
@@ -932,150 +2208,364 @@ impl EnrollmentManager {
                 "Missing sync_request".to_string(),
             ))?;
 
-        println!(
+        progress!(
             "📥 RIB sync request from {} (version {})",
-            sync_req.requester, sync_req.last_known_version
+            sync_req.requester,
+            sync_req.last_known_version
         );
+        self.neighbors
+            .record_seen(&sync_req.requester, pdu.src_addr)
+            .await;
 
-        // Get current RIB version
-        let current_version = self.rib.current_version().await;
+        // Merge the member's own changes before computing our delta
+        if !sync_req.changes.is_empty() {
+            let pushed_version = sync_req
+                .changes
+                .iter()
+                .map(RibChange::version)
+                .max()
+                .unwrap_or(0);
+            let applied = self.rib.apply_peer_changes(sync_req.changes.clone()).await;
+            progress!(
+                "  ✓ Merged {} of {} changes from {}",
+                applied,
+                sync_req.changes.len(),
+                sync_req.requester
+            );
 
-        // Try to get incremental changes
-        let changes = self
-            .rib
-            .get_changes_since(sync_req.last_known_version)
+            let mut peer_versions = self.peer_versions.write().await;
+            let seen = peer_versions.entry(sync_req.requester.clone()).or_insert(0);
+            *seen = (*seen).max(pushed_version);
+        }
+
+        // Members that never negotiated (e.g. before a bootstrap restart)
+        // are only assumed to support the minimal set
+        let incremental = self
+            .peer_capabilities(&sync_req.requester)
+            .await
+            .unwrap_or_default()
+            .incremental_sync;
+        let response = self
+            .build_sync_response(request.invoke_id, sync_req.last_known_version, incremental)
             .await;
 
-        let response = if let Ok(changes_vec) = changes {
-            // Member's version is within change log window - send incremental
-            println!(
-                "  ✓ Sending {} incremental changes (version {} → {})",
-                changes_vec.len(),
-                sync_req.last_known_version,
+        // Send response (fragmented if it carries a full snapshot)
+        self.send_cdap(pdu.src_addr, &response)
+    }
+
+    /// Builds the sync response for a member at `last_known_version`
+    ///
+    /// Replies with incremental changes when the version is still covered by
+    /// the change log, a full snapshot when it is too old or the member
+    /// cannot apply changes (`incremental` is false), and an error when the
+    /// member claims a version newer than ours.
+    async fn build_sync_response(
+        &self,
+        invoke_id: u64,
+        last_known_version: u64,
+        incremental: bool,
+    ) -> CdapMessage {
+        let current_version = self.rib.current_version().await;
+
+        if last_known_version > current_version {
+            // Member is ahead of us (e.g. bootstrap restarted with an older RIB)
+            progress!(
+                "  ✗ Version {} is ahead of local version {}",
+                last_known_version,
                 current_version
             );
 
-            CdapMessage::new_sync_response(
-                request.invoke_id,
+            return CdapMessage::new_sync_response(
+                invoke_id,
                 current_version,
-                Some(changes_vec),
                 None,
                 None,
-            )
-        } else {
-            // Member's version too old - send full snapshot
-            println!(
-                "  ⚠️  Version {} too old, sending full snapshot (current: {})",
-                sync_req.last_known_version, current_version
+                Some(format!(
+                    "Requested version {} is ahead of bootstrap version {}",
+                    last_known_version, current_version
+                )),
             );
+        }
 
-            let snapshot = self.rib.serialize().await;
-
-            CdapMessage::new_sync_response(
-                request.invoke_id,
-                current_version,
-                None,
-                Some(snapshot),
-                None,
-            )
+        let changes = if incremental {
+            self.rib
+                .get_changes_since(last_known_version)
+                .await
+                .ok()
+                .map(|changes| {
+                    changes
+                        .into_iter()
+                        .filter(|c| !c.is_local())
+                        .collect::<Vec<_>>()
+                })
+        } else {
+            None
         };
 
-        // Serialize and send response
-        let response_bytes = postcard::to_allocvec(&response)
-            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
+        match changes {
+            Some(changes) => {
+                // Member's version is within change log window - send incremental
+                progress!(
+                    "  ✓ Sending {} incremental changes (version {} → {})",
+                    changes.len(),
+                    last_known_version,
+                    current_version
+                );
 
-        let response_pdu = Pdu::new_data(
-            self.local_addr,
-            pdu.src_addr,
-            0, // flow_id
-            0, // seq_num
-            0, // flags
-            response_bytes,
-        );
+                CdapMessage::new_sync_response(
+                    invoke_id,
+                    current_version,
+                    Some(changes),
+                    None,
+                    None,
+                )
+            }
+            None => {
+                // Member's version too old or no incremental support - send full snapshot
+                if incremental {
+                    progress!(
+                        "  ⚠️  Version {} too old, sending full snapshot (current: {})",
+                        last_known_version,
+                        current_version
+                    );
+                } else {
+                    progress!(
+                        "  ✓ Sending full snapshot to member without incremental sync (current: {})",
+                        current_version
+                    );
+                }
 
-        self.shim
-            .send_pdu(&response_pdu)
-            .map_err(|e| EnrollmentError::SendFailed(e.to_string()))?;
+                let snapshot = self.rib.serialize_shared().await;
+
+                CdapMessage::new_sync_response(
+                    invoke_id,
+                    current_version,
+                    None,
+                    Some(snapshot),
+                    None,
+                )
+            }
+        }
+    }
+
+    /// Sends a CDAP message to `dst_addr`, fragmenting it across PDUs if needed
+    fn send_cdap(&self, dst_addr: u64, msg: &CdapMessage) -> Result<(), EnrollmentError> {
+        for pdu in self.cdap_pdus(dst_addr, msg)? {
+            self.shim
+                .send_pdu(&pdu)
+                .map_err(|e| EnrollmentError::SendFailed(e.to_string()))?;
+        }
 
         Ok(())
     }
 
+    /// Sends a CDAP message directly to a socket address, bypassing the peer mapping
+    fn send_cdap_to_socket(
+        &self,
+        dst_addr: u64,
+        socket_addr: SocketAddr,
+        msg: &CdapMessage,
+    ) -> Result<(), EnrollmentError> {
+        for pdu in self.cdap_pdus(dst_addr, msg)? {
+            let bytes = self
+                .shim
+                .serialize_for(&pdu, dst_addr)
+                .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
+            self.shim
+                .send_to(&bytes, &socket_addr.to_string())
+                .map_err(|e| EnrollmentError::SendFailed(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Wraps a CDAP message in management PDUs addressed to `dst_addr`
+    fn cdap_pdus(&self, dst_addr: u64, msg: &CdapMessage) -> Result<Vec<Pdu>, EnrollmentError> {
+        let payloads = msg
+            .to_payloads(DEFAULT_MAX_FRAGMENT_SIZE)
+            .map_err(|e| EnrollmentError::SerializationFailed(e.to_string()))?;
+
+        payloads
+            .into_iter()
+            .map(|payload| {
+                PduBuilder::new(PduType::Management)
+                    .src_addr(self.local_addr())
+                    .dst_addr(dst_addr)
+                    .payload(payload)
+                    .build()
+                    .map_err(|e| EnrollmentError::SendFailed(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Decodes the CDAP message in `pdu`, buffering fragments
+    ///
+    /// Returns `Ok(None)` while a fragmented message is still incomplete.
+    fn decode_cdap(
+        &self,
+        pdu: &Pdu,
+        sender: SocketAddr,
+    ) -> Result<Option<CdapMessage>, EnrollmentError> {
+        self.reassembler
+            .lock()
+            .unwrap()
+            .accept(sender, &pdu.payload)
+            .map_err(|e| EnrollmentError::DeserializationFailed(e.to_string()))
+    }
+
+    /// Returns a fresh invoke ID for an outgoing request
+    fn next_invoke_id(&self) -> u64 {
+        self.invoke_ids.lock().unwrap().next_id()
+    }
+
     /// Start connection monitoring task (member IPCP only)
     /// Returns a task handle that can be awaited or aborted
+    ///
+    /// When the bootstrap has been silent for longer than the connection
+    /// timeout, the manager drops back to `NotEnrolled` and re-enrolls with
+    /// the same bootstrap, keeping its current address. Failed attempts are
+    /// retried on the next check.
     pub fn start_connection_monitoring(&mut self) -> tokio::task::JoinHandle<()> {
         if self.config.heartbeat_interval_secs == 0 {
             // Monitoring disabled
             return tokio::spawn(async {});
         }
 
-        let last_heartbeat = self.last_heartbeat.clone();
-        let re_enrollment_in_progress = self.re_enrollment_in_progress.clone();
         let connection_timeout = Duration::from_secs(self.config.connection_timeout_secs);
-        let check_interval = Duration::from_secs(self.config.heartbeat_interval_secs / 2);
-        let bootstrap_addr = self.bootstrap_addr;
-        let shim = self.shim.clone();
-        let rib = self.rib.clone();
-        let ipcp_name = self.ipcp_name.clone();
-        let local_addr = self.local_addr;
-        let config = self.config.clone();
+        let check_interval = Duration::from_millis(self.config.heartbeat_interval_secs * 500);
+        let mut watchdog = self.share();
 
         tokio::spawn(async move {
             loop {
                 sleep(check_interval).await;
 
+                watchdog.neighbors.expire_stale().await;
+
                 // Check if connection is still alive
-                let last_beat = *last_heartbeat.read().await;
-                if let Some(last) = last_beat {
-                    let elapsed = last.elapsed();
-                    if elapsed > connection_timeout {
-                        println!(
-                            "⚠️  Connection timeout detected ({}s since last heartbeat)",
-                            elapsed.as_secs()
-                        );
-
-                        // Check if re-enrollment is already in progress
-                        let mut in_progress = re_enrollment_in_progress.write().await;
-                        if !*in_progress {
-                            *in_progress = true;
-                            drop(in_progress); // Release lock before re-enrollment
-
-                            // Attempt re-enrollment
-                            if let Some(bootstrap) = bootstrap_addr {
-                                println!("🔄 Attempting automatic re-enrollment...");
-
-                                let mut temp_manager = EnrollmentManager::with_config(
-                                    rib.clone(),
-                                    shim.clone(),
-                                    local_addr,
-                                    config.clone(),
-                                );
-
-                                if let Some(name) = &ipcp_name {
-                                    temp_manager.set_ipcp_name(name.clone());
-                                }
-
-                                match temp_manager.enrol_with_bootstrap(bootstrap).await {
-                                    Ok(_dif_name) => {
-                                        println!("✅ Re-enrollment successful!");
-                                        *last_heartbeat.write().await = Some(Instant::now());
-                                    }
-                                    Err(e) => {
-                                        eprintln!("❌ Re-enrollment failed: {}", e);
-                                    }
-                                }
-
-                                *re_enrollment_in_progress.write().await = false;
-                            }
+                let last_beat = *watchdog.last_heartbeat.read().await;
+                let Some(last) = last_beat else {
+                    continue;
+                };
+                let elapsed = last.elapsed();
+                if elapsed <= connection_timeout {
+                    continue;
+                }
+                progress!(
+                    "⚠️  Connection timeout detected ({}s since last heartbeat)",
+                    elapsed.as_secs()
+                );
+
+                // Check if re-enrollment is already in progress
+                let mut in_progress = watchdog.re_enrollment_in_progress.write().await;
+                if *in_progress {
+                    continue;
+                }
+                *in_progress = true;
+                drop(in_progress); // Release lock before re-enrollment
+
+                // Attempt re-enrollment
+                if let Some(bootstrap) = watchdog.bootstrap_addr {
+                    progress!("🔄 Attempting automatic re-enrollment...");
+                    watchdog.set_state(EnrollmentState::NotEnrolled);
+
+                    match watchdog.enrol_with_bootstrap(bootstrap).await {
+                        Ok(_dif_name) => {
+                            progress!(
+                                "✅ Re-enrollment successful (address {})",
+                                watchdog.local_addr()
+                            );
+                        }
+                        Err(e) => {
+                            eprintln!("❌ Re-enrollment failed: {}", e);
                         }
                     }
                 }
+
+                *watchdog.re_enrollment_in_progress.write().await = false;
+            }
+        })
+    }
+
+    /// Handles the management PDUs the RMT delivers until its channel closes
+    ///
+    /// The RMT does not carry the sender's socket, so replies go to the
+    /// socket registered for the PDU's source address; PDUs from unknown
+    /// sources are dropped.
+    pub fn start_management_handler(
+        &self,
+        mut pdus: mpsc::Receiver<Pdu>,
+    ) -> tokio::task::JoinHandle<()> {
+        let handler = self.share();
+
+        tokio::spawn(async move {
+            while let Some(pdu) = pdus.recv().await {
+                let Some(src_socket) = handler.shim.lookup_peer(pdu.src_addr) else {
+                    eprintln!(
+                        "  ⚠ Dropped management PDU from unknown address {}",
+                        pdu.src_addr
+                    );
+                    continue;
+                };
+                if let Err(e) = handler.handle_cdap_message(&pdu, src_socket).await {
+                    eprintln!("  ⚠ Failed to handle management PDU: {}", e);
+                }
             }
         })
     }
 
+    /// Returns a manager sharing this one's state, address and heartbeat
+    ///
+    /// Used by background tasks that must act on behalf of this manager.
+    fn share(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            ipcp_name: self.ipcp_name.clone(),
+            local_addr: self.local_addr.clone(),
+            rib: self.rib.clone(),
+            shim: self.shim.clone(),
+            config: self.config.clone(),
+            address_pool: self.address_pool.clone(),
+            bootstrap_addr: self.bootstrap_addr,
+            bootstrap_peer: self.bootstrap_peer,
+            last_heartbeat: self.last_heartbeat.clone(),
+            re_enrollment_in_progress: self.re_enrollment_in_progress.clone(),
+            exchange: self.exchange.clone(),
+            route_resolver: self.route_resolver.clone(),
+            last_synced_version: self.last_synced_version.clone(),
+            last_sync_time: self.last_sync_time.clone(),
+            last_pushed_version: self.last_pushed_version.clone(),
+            peer_versions: self.peer_versions.clone(),
+            reassembler: self.reassembler.clone(),
+            invoke_ids: self.invoke_ids.clone(),
+            neighbors: self.neighbors.clone(),
+            bootstrap_name: self.bootstrap_name.clone(),
+            address_reservations: self.address_reservations.clone(),
+            events: self.events.clone(),
+            dropped_events: self.dropped_events.clone(),
+            address_state_path: self.address_state_path.clone(),
+            address_holders: self.address_holders.clone(),
+            capabilities: self.capabilities,
+            negotiated_capabilities: self.negotiated_capabilities.clone(),
+            peer_capabilities: self.peer_capabilities.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            recent_responses: self.recent_responses.clone(),
+            rebind_secret: self.rebind_secret.clone(),
+            dif_name: self.dif_name.clone(),
+            dif_name_rules: self.dif_name_rules,
+        }
+    }
+
     /// Update heartbeat timestamp (called when receiving messages from bootstrap)
     pub async fn update_heartbeat(&self) {
         *self.last_heartbeat.write().await = Some(Instant::now());
+
+        if let Some(bootstrap_addr) = self.bootstrap_addr {
+            let name = self
+                .bootstrap_name
+                .clone()
+                .unwrap_or_else(|| format!("ipcp-{}", bootstrap_addr));
+            self.neighbors.record_seen(&name, bootstrap_addr).await;
+        }
     }
 
     /// Check if connection is healthy
@@ -1094,10 +2584,10 @@ impl EnrollmentManager {
             .bootstrap_addr
             .ok_or(EnrollmentError::NoBootstrapPeers)?;
 
-        println!("🔄 Manual re-enrollment initiated");
+        progress!("🔄 Manual re-enrollment initiated");
 
         // Reset state
-        self.state = EnrollmentState::Initiated;
+        self.set_state(EnrollmentState::Initiated);
 
         // Attempt enrollment
         let result = self.enrol_with_bootstrap(bootstrap_addr).await;
@@ -1111,20 +2601,361 @@ impl EnrollmentManager {
     }
 }
 
+/// Compares two secrets in time independent of where they first differ
+///
+/// Only the length of the secret can leak through timing.
+fn secrets_match(required: &str, offered: &str) -> bool {
+    required.len() == offered.len()
+        && required
+            .bytes()
+            .zip(offered.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_enrollment_messages_decode_across_builds() {
+        // Layouts sent by builds without capability negotiation
+        let old_request = postcard::to_allocvec(&("member", 0u64, "dif", 1u64, true)).unwrap();
+        let request = EnrollmentRequest::decode(&old_request).unwrap();
+        assert_eq!(request.ipcp_name, "member");
+        assert!(request.request_address);
+        assert_eq!(request.capabilities, CdapCapabilities::minimal());
+        assert_eq!(request.rebind_secret, None);
+
+        let old_response =
+            postcard::to_allocvec(&(true, None::<String>, Some(5u64), "dif", None::<Vec<u8>>))
+                .unwrap();
+        let response = EnrollmentResponse::decode(&old_response).unwrap();
+        assert_eq!(response.assigned_address, Some(5));
+        assert_eq!(response.capabilities, CdapCapabilities::minimal());
+
+        // Older builds read the fields they know and skip the appended ones
+        let request = EnrollmentRequest {
+            preferred_address: Some(9),
+            capabilities: CdapCapabilities::local(),
+            request_id: 3,
+            rebind_secret: Some("secret".to_string()),
+            ..request
+        };
+        let bytes = postcard::to_allocvec(&request).unwrap();
+        let legacy: LegacyEnrollmentRequest = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(legacy.ipcp_name, "member");
+        assert_eq!(legacy.timestamp, 1);
+        assert_eq!(EnrollmentRequest::decode(&bytes).unwrap().request_id, 3);
+
+        assert!(EnrollmentRequest::decode(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_backoff_jitter_stays_within_bounds() {
+        let initial_ms = 100;
+
+        let mut plain = Backoff::with_seed(BackoffJitter::None, initial_ms, 7);
+        let geometric: Vec<Duration> = (1..=5).map(|attempt| plain.delay(attempt)).collect();
+        assert_eq!(
+            geometric,
+            [100, 200, 400, 800, 1600].map(Duration::from_millis)
+        );
+
+        let mut full = Backoff::with_seed(BackoffJitter::Full, initial_ms, 7);
+        let delays: Vec<Duration> = (1..=5).map(|attempt| full.delay(attempt)).collect();
+        for (delay, ceiling) in delays.iter().zip(&geometric) {
+            assert!(delay <= ceiling, "{:?} exceeds {:?}", delay, ceiling);
+        }
+        assert_ne!(delays, geometric);
+
+        // Same seed, same sequence
+        let mut replay = Backoff::with_seed(BackoffJitter::Full, initial_ms, 7);
+        let replayed: Vec<Duration> = (1..=5).map(|attempt| replay.delay(attempt)).collect();
+        assert_eq!(delays, replayed);
+
+        let mut decorrelated = Backoff::with_seed(BackoffJitter::Decorrelated, initial_ms, 7);
+        let mut previous = Duration::from_millis(initial_ms);
+        let mut delays = Vec::new();
+        for attempt in 1..=5 {
+            let delay = decorrelated.delay(attempt);
+            assert!(delay >= Duration::from_millis(initial_ms));
+            assert!(delay <= previous * 3);
+            previous = delay;
+            delays.push(delay);
+        }
+        assert!(delays.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
     #[tokio::test]
     async fn test_enrollment_state() {
         let rib = Rib::new();
         let shim = Arc::new(UdpShim::new(0));
         let mut em = EnrollmentManager::new(rib, shim, 1000);
 
-        assert_eq!(*em.state(), EnrollmentState::NotEnrolled);
+        assert_eq!(em.state(), EnrollmentState::NotEnrolled);
         assert!(!em.is_enrolled());
 
         em.set_ipcp_name("ipcp-1".to_string());
-        assert_eq!(*em.state(), EnrollmentState::Initiated);
+        assert_eq!(em.state(), EnrollmentState::Initiated);
+    }
+
+    #[tokio::test]
+    async fn test_sync_response_incremental_for_recent_version() {
+        let rib = Rib::new();
+        let shim = Arc::new(UdpShim::new(0));
+        let em = EnrollmentManager::new_bootstrap(rib.clone(), shim, 1001, 2000, 2999);
+
+        rib.create("/a".to_string(), "test".to_string(), RibValue::Integer(1))
+            .await
+            .unwrap();
+        let known = rib.current_version().await;
+        rib.create("/b".to_string(), "test".to_string(), RibValue::Integer(2))
+            .await
+            .unwrap();
+
+        let response = em.build_sync_response(7, known, true).await;
+        assert_eq!(response.invoke_id, 7);
+        assert!(response.is_success());
+
+        let sync = response.sync_response.unwrap();
+        assert_eq!(sync.current_version, rib.current_version().await);
+        assert!(sync.full_snapshot.is_none());
+        let changes = sync.changes.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].object_name(), "/b");
+    }
+
+    #[tokio::test]
+    async fn test_sync_response_full_snapshot_after_overflow() {
+        let rib = Rib::with_change_log_size(5);
+        let shim = Arc::new(UdpShim::new(0));
+        let em = EnrollmentManager::new_bootstrap(rib.clone(), shim, 1001, 2000, 2999);
+
+        for i in 0..10 {
+            rib.create(
+                format!("/obj/{}", i),
+                "test".to_string(),
+                RibValue::Integer(i),
+            )
+            .await
+            .unwrap();
+        }
+
+        let sync = em
+            .build_sync_response(1, 0, true)
+            .await
+            .sync_response
+            .unwrap();
+        assert!(sync.changes.is_none());
+        assert!(sync.error.is_none());
+
+        let member_rib = Rib::new();
+        let synced = member_rib
+            .deserialize(&sync.full_snapshot.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(synced, 10);
+    }
+
+    #[tokio::test]
+    async fn test_sync_response_error_when_member_ahead() {
+        let rib = Rib::new();
+        let shim = Arc::new(UdpShim::new(0));
+        let em = EnrollmentManager::new_bootstrap(rib, shim, 1001, 2000, 2999);
+
+        let response = em.build_sync_response(3, 42, true).await;
+        assert!(!response.is_success());
+        let sync = response.sync_response.unwrap();
+        assert!(sync.error.is_some());
+        assert!(sync.changes.is_none() && sync.full_snapshot.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lagging_observer_loses_events_without_blocking() {
+        let mut em = EnrollmentManager::new(Rib::new(), Arc::new(UdpShim::new(0)), 0);
+        let (tx, mut rx) = mpsc::channel(1);
+        em.set_event_sender(tx);
+
+        em.emit(EnrollmentEvent::AddressAssigned(2001));
+        em.emit(EnrollmentEvent::AddressAssigned(2002));
+        em.emit(EnrollmentEvent::RoutesSynced { routes: 0 });
+
+        assert_eq!(em.dropped_events(), 2);
+        assert_eq!(
+            rx.recv().await,
+            Some(EnrollmentEvent::AddressAssigned(2001))
+        );
+        em.emit(EnrollmentEvent::AddressAssigned(2003));
+        assert_eq!(
+            rx.recv().await,
+            Some(EnrollmentEvent::AddressAssigned(2003))
+        );
+    }
+
+    #[test]
+    fn test_rebind_secrets_must_match_exactly() {
+        assert!(secrets_match("let-me-move", "let-me-move"));
+        assert!(!secrets_match("let-me-move", "let-me-movE"));
+        assert!(!secrets_match("let-me-move", "let-me-mov"));
+        assert!(!secrets_match("let-me-move", ""));
+    }
+
+    #[tokio::test]
+    async fn test_silent_address_holder_no_longer_blocks_a_rebind() {
+        let rib = Rib::new();
+        let mut em = EnrollmentManager::new_bootstrap(
+            rib.clone(),
+            Arc::new(UdpShim::new(1001)),
+            1001,
+            2000,
+            2099,
+        );
+        em.neighbors = NeighborTable::new(rib, Duration::from_millis(50));
+
+        assert!(!em.holder_is_live(5000).await);
+        em.neighbors.record_seen("member-static", 5000).await;
+        assert!(em.holder_is_live(5000).await);
+
+        sleep(Duration::from_millis(80)).await;
+        assert!(!em.holder_is_live(5000).await);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_drops_burst_from_one_source_only() {
+        let rib = Rib::new();
+        let shim = Arc::new(UdpShim::new(0));
+        let mut em = EnrollmentManager::new_bootstrap(rib, shim, 1001, 2000, 2999);
+        let flooder: SocketAddr = "127.0.0.1:40001".parse().unwrap();
+        let member: SocketAddr = "127.0.0.2:40002".parse().unwrap();
+
+        // Unlimited until configured
+        assert!((0..50).all(|_| em.admit_request(flooder)));
+
+        em.set_request_rate_limit(1.0, 5);
+        let admitted = (0..50).filter(|_| em.admit_request(flooder)).count();
+        assert!((5..50).contains(&admitted), "{} of 50 admitted", admitted);
+        assert!(!em.admit_request(flooder));
+
+        // Switching ports does not buy the flooder a fresh allowance
+        assert!(!em.admit_request("127.0.0.1:40003".parse().unwrap()));
+
+        // Another source still has its full allowance
+        assert!((0..5).all(|_| em.admit_request(member)));
+
+        // Shared handles enforce the same limit
+        assert!(!em.share().admit_request(flooder));
+    }
+
+    #[tokio::test]
+    async fn test_responses_to_other_requests_are_ignored() {
+        let member_shim = UdpShim::new(2001);
+        member_shim.bind("127.0.0.1:0").unwrap();
+        let member_socket = member_shim.local_addr().unwrap();
+        let member = EnrollmentManager::new(Rib::new(), Arc::new(member_shim), 2001);
+
+        let peer_shim = Arc::new(UdpShim::new(1001));
+        peer_shim.bind("127.0.0.1:0").unwrap();
+        let peer = EnrollmentManager::new(Rib::new(), peer_shim, 1001);
+
+        // A session torn down by a reconnect used another epoch
+        let stale_id = InvokeIds::new().next_id();
+        let pending_id = member.next_invoke_id();
+        assert_ne!(
+            CdapSession::epoch_of(stale_id),
+            CdapSession::epoch_of(pending_id)
+        );
+
+        for invoke_id in [stale_id, pending_id + 1, pending_id] {
+            let response = CdapMessage::new_response(invoke_id, CdapResult::Success, None);
+            peer.send_cdap_to_socket(2001, member_socket, &response)
+                .unwrap();
+        }
+
+        let response = member.receive_cdap_message(None, pending_id).await.unwrap();
+        assert_eq!(response.invoke_id, pending_id);
+    }
+
+    #[tokio::test]
+    async fn test_response_is_returned_when_it_arrives_not_on_a_poll_tick() {
+        // A 1ms read timeout makes every empty receive return at once, so
+        // any pause between receives would show up as added latency
+        let mut member_shim = UdpShim::new(2001);
+        member_shim.set_read_timeout(Duration::from_millis(1));
+        member_shim.bind("127.0.0.1:0").unwrap();
+        let member_socket = member_shim.local_addr().unwrap();
+        let member = EnrollmentManager::new(Rib::new(), Arc::new(member_shim), 2001);
+
+        let peer_shim = Arc::new(UdpShim::new(1001));
+        peer_shim.bind("127.0.0.1:0").unwrap();
+        let peer = EnrollmentManager::new(Rib::new(), peer_shim, 1001);
+
+        let started = Instant::now();
+        let responder = tokio::spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            let response = CdapMessage::new_response(7, CdapResult::Success, None);
+            peer.send_cdap_to_socket(2001, member_socket, &response)
+                .unwrap();
+        });
+
+        let response = member.receive_cdap_message(None, 7).await.unwrap();
+        let elapsed = started.elapsed();
+        responder.await.unwrap();
+
+        assert_eq!(response.invoke_id, 7);
+        assert!(elapsed >= Duration::from_millis(10));
+        assert!(
+            elapsed < Duration::from_millis(60),
+            "response took {:?}",
+            elapsed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_waits_for_enrollment_to_release_the_shim() {
+        // A silent bootstrap records which requests arrive, and when
+        let bootstrap = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+        let shim = UdpShim::new(2001);
+        shim.bind("127.0.0.1:0").unwrap();
+        shim.register_peer(1001, bootstrap.local_addr().unwrap());
+        let config = EnrollmentConfig {
+            timeout: Duration::from_millis(300),
+            max_retries: 1,
+            ..EnrollmentConfig::default()
+        };
+        let mut em = EnrollmentManager::with_config(Rib::new(), Arc::new(shim), 2001, config);
+        em.set_ipcp_name("member".to_string());
+        em.bootstrap_addr = Some(1001);
+
+        let started = Instant::now();
+        let mut enroller = em.share();
+        let enrollment = tokio::spawn(async move { enroller.enrol_with_bootstrap(1001).await });
+        sleep(Duration::from_millis(50)).await;
+        let sync = tokio::spawn(async move {
+            let _ = timeout(Duration::from_millis(500), em.sync_rib()).await;
+        });
+
+        let mut buf = vec![0u8; 65536];
+        let mut arrivals = Vec::new();
+        for _ in 0..2 {
+            let (len, _) = timeout(Duration::from_secs(2), bootstrap.recv_from(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            let pdu = Pdu::deserialize(&buf[..len]).unwrap();
+            let cdap: CdapMessage = postcard::from_bytes(&pdu.payload).unwrap();
+            arrivals.push((cdap.sync_request.is_some(), started.elapsed()));
+        }
+        assert!(enrollment.await.unwrap().is_err());
+        sync.await.unwrap();
+
+        assert!(!arrivals[0].0, "enrollment request is sent first");
+        assert!(arrivals[1].0, "sync request follows");
+        assert!(
+            arrivals[1].1 >= Duration::from_millis(300),
+            "sync request sent after {:?}, while enrollment was waiting",
+            arrivals[1].1
+        );
     }
 }
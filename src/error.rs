@@ -20,9 +20,15 @@ pub enum AriError {
     #[error("RMT error: {0}")]
     Rmt(#[from] RmtError),
 
+    #[error("PDU error: {0}")]
+    Pdu(#[from] PduError),
+
     #[error("EFCP error: {0}")]
     Efcp(#[from] EfcpError),
 
+    #[error("Flow allocation error: {0}")]
+    Flow(#[from] FlowError),
+
     #[error("Shim error: {0}")]
     Shim(#[from] ShimError),
 
@@ -41,9 +47,15 @@ pub enum AriError {
     #[error("Actor channel closed")]
     ChannelClosed,
 
+    #[error("Actor error: {0}")]
+    Actor(#[from] ActorError),
+
     #[error("Operation timed out")]
     Timeout,
 
+    #[error("Invalid IPCP state: {0}")]
+    InvalidState(String),
+
     #[error("Feature not implemented: {0}")]
     NotImplemented(String),
 }
@@ -101,6 +113,15 @@ pub enum EnrollmentError {
 
     #[error("Re-enrollment required")]
     ReEnrollmentRequired,
+
+    #[error("Request from {0} dropped by rate limiter")]
+    RateLimited(std::net::SocketAddr),
+
+    #[error("Invalid DIF name: {0}")]
+    InvalidDifName(String),
+
+    #[error("DIF name mismatch: expected '{expected}', got '{actual}'")]
+    DifNameMismatch { expected: String, actual: String },
 }
 
 /// RIB-specific errors
@@ -112,6 +133,9 @@ pub enum RibError {
     #[error("Object already exists: {0}")]
     AlreadyExists(String),
 
+    #[error("Version conflict: expected {expected}, found {actual}")]
+    VersionConflict { expected: u64, actual: u64 },
+
     #[error("Invalid object name: {0}")]
     InvalidName(String),
 
@@ -129,6 +153,9 @@ pub enum RibError {
 
     #[error("RIB operation failed: {0}")]
     OperationFailed(String),
+
+    #[error("Capacity of {max} objects reached, rejected: {name}")]
+    CapacityExceeded { name: String, max: usize },
 }
 
 /// RMT-specific errors
@@ -143,6 +170,9 @@ pub enum RmtError {
     #[error("Queue full for next hop: {0}")]
     QueueFull(u64),
 
+    #[error("Forwarding table full ({0} entries)")]
+    TableFull(usize),
+
     #[error("Invalid PDU: {0}")]
     InvalidPdu(String),
 
@@ -154,10 +184,23 @@ pub enum RmtError {
 
     #[error("Network error: {0}")]
     Network(String),
+
+    #[error("Invalid route: {0}")]
+    InvalidRoute(String),
+}
+
+/// PDU construction errors
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PduError {
+    #[error("{0} address must be non-zero for data PDUs")]
+    ZeroAddress(&'static str),
+
+    #[error("Payload of {size} bytes exceeds maximum of {max} bytes")]
+    PayloadTooLarge { size: usize, max: usize },
 }
 
 /// EFCP-specific errors
-#[derive(Error, Debug, Clone)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum EfcpError {
     #[error("Flow not found: {0}")]
     FlowNotFound(u64),
@@ -182,6 +225,67 @@ pub enum EfcpError {
 
     #[error("Sequence number error: expected {expected}, got {actual}")]
     SequenceError { expected: u64, actual: u64 },
+
+    #[error("Flow {0} is encrypted but has no negotiated key")]
+    KeyNotNegotiated(u32),
+
+    #[error("Key negotiation failed: {0}")]
+    KeyNegotiationFailed(String),
+
+    #[error("Failed to encrypt PDU {0}")]
+    EncryptionFailed(u64),
+
+    #[error("Failed to decrypt PDU {0}")]
+    DecryptionFailed(u64),
+
+    #[error("PDU {0} was already received")]
+    ReplayedPdu(u64),
+
+    #[error("PDU {seq} is older than the replay window starting at {low_edge}")]
+    PduTooOld { seq: u64, low_edge: u64 },
+
+    #[error("PDU {seq} is beyond the receive window ending before {high_edge}")]
+    PduBeyondWindow { seq: u64, high_edge: u64 },
+}
+
+/// Flow allocation errors
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FlowError {
+    #[error("Flow allocation request {request_id} timed out after {timeout_ms}ms")]
+    Timeout { request_id: u64, timeout_ms: u64 },
+
+    #[error("Flow allocation request {request_id} rejected: {reason}")]
+    Rejected { request_id: u64, reason: String },
+
+    #[error("Flow allocation request {0} was cancelled")]
+    Cancelled(u64),
+
+    #[error("No transport configured for flow allocation requests")]
+    NoTransport,
+
+    #[error("Application name '{0}' could not be resolved")]
+    UnresolvedName(String),
+
+    #[error("Failed to send flow allocation request: {0}")]
+    SendFailed(String),
+
+    #[error("Key negotiation for flow allocation request {request_id} failed: {reason}")]
+    KeyNegotiationFailed { request_id: u64, reason: String },
+
+    #[error("Flow limit of {0} concurrent flows reached")]
+    FlowLimitReached(usize),
+
+    #[error("Bandwidth budget exhausted: {requested_bps} B/s requested, {available_bps} B/s left")]
+    BandwidthExhausted {
+        requested_bps: u64,
+        available_bps: u64,
+    },
+
+    #[error("QoS class '{0}' is not offered by this DIF")]
+    QoSClassNotOffered(String),
+
+    #[error("This DIF only admits flows that request one of its QoS classes")]
+    QoSClassRequired,
 }
 
 /// Shim layer errors
@@ -231,6 +335,19 @@ pub enum CdapError {
     SessionError(String),
 }
 
+/// Errors delivering a message to an actor's mailbox
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActorError {
+    #[error("Actor mailbox is full")]
+    Busy,
+
+    #[error("Actor has stopped")]
+    Closed,
+
+    #[error("Timed out waiting for space in actor mailbox")]
+    Timeout,
+}
+
 /// Serialization/deserialization errors
 #[derive(Error, Debug)]
 pub enum SerializationError {
@@ -240,8 +357,14 @@ pub enum SerializationError {
     #[error("JSON serialization failed: {0}")]
     JsonSerialization(#[from] serde_json::Error),
 
+    #[error("Bincode serialization failed: {0}")]
+    BincodeSerialization(#[from] bincode::Error),
+
     #[error("Invalid data format: {0}")]
     InvalidFormat(String),
+
+    #[error("Unsupported PDU version {version} (this node speaks version {supported})")]
+    UnsupportedVersion { version: u8, supported: u8 },
 }
 
 // Conversion from String for backwards compatibility during migration
@@ -288,12 +411,24 @@ impl From<EfcpError> for String {
     }
 }
 
+impl From<FlowError> for String {
+    fn from(err: FlowError) -> Self {
+        err.to_string()
+    }
+}
+
 impl From<ShimError> for String {
     fn from(err: ShimError) -> Self {
         err.to_string()
     }
 }
 
+impl From<ActorError> for String {
+    fn from(err: ActorError) -> Self {
+        err.to_string()
+    }
+}
+
 impl From<CdapError> for String {
     fn from(err: CdapError) -> Self {
         err.to_string()
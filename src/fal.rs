@@ -6,9 +6,66 @@
 //! Manages flow allocation and deallocation requests.
 //! Handles the flow allocation protocol between IPCPs.
 
+use crate::crypto::{FlowKey, KeyExchange, KeyRole};
+use crate::directory::Directory;
 use crate::efcp::FlowConfig;
+use crate::error::{EfcpError, FlowError};
+use crate::policies::QoSCube;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// How long `request_flow_by_name` waits for the peer's answer by default
+pub const DEFAULT_ALLOCATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resource limits new flows are admitted against (`[flows]` section)
+///
+/// Unset limits are not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdmissionLimits {
+    /// Maximum number of concurrently allocated flows
+    #[serde(default)]
+    pub max_flows: Option<usize>,
+    /// Sum of the `bandwidth_bps` all allocated flows may reserve (bytes/sec)
+    #[serde(default)]
+    pub bandwidth_budget_bps: Option<u64>,
+}
+
+impl AdmissionLimits {
+    /// Checks whether a flow reserving `requested_bps` fits beside the
+    /// allocated `flows` and the bandwidth held by in-flight `requests`
+    fn admit(
+        &self,
+        flows: &HashMap<u32, AllocatedFlow>,
+        requests: &HashMap<u64, u64>,
+        requested_bps: u64,
+    ) -> Result<(), FlowError> {
+        let count = flows.len() + requests.len();
+        let reserved_bps = flows
+            .values()
+            .map(|flow| flow.config.bandwidth_bps.unwrap_or(0))
+            .chain(requests.values().copied())
+            .fold(0u64, u64::saturating_add);
+
+        if let Some(max) = self.max_flows
+            && count >= max
+        {
+            return Err(FlowError::FlowLimitReached(max));
+        }
+        if let Some(budget) = self.bandwidth_budget_bps {
+            let available_bps = budget.saturating_sub(reserved_bps);
+            if requested_bps > available_bps {
+                return Err(FlowError::BandwidthExhausted {
+                    requested_bps,
+                    available_bps,
+                });
+            }
+        }
+        Ok(())
+    }
+}
 
 /// Flow allocation request
 #[derive(Debug, Clone)]
@@ -23,8 +80,12 @@ pub struct FlowAllocRequest {
     pub dst_addr: u64,
     /// Requested QoS parameters
     pub qos: FlowConfig,
+    /// Name of the QoS class `qos` was taken from, if any
+    pub qos_class: Option<String>,
     /// Request ID
     pub request_id: u64,
+    /// Requester's X25519 public key, present when `qos.encryption` is enabled
+    pub key_share: Option<[u8; 32]>,
 }
 
 /// Flow allocation response
@@ -38,6 +99,8 @@ pub struct FlowAllocResponse {
     pub flow_id: Option<u32>,
     /// Error message (if failed)
     pub error: Option<String>,
+    /// Responder's X25519 public key, answering the request's key share
+    pub key_share: Option<[u8; 32]>,
 }
 
 /// Flow state
@@ -70,6 +133,8 @@ pub struct AllocatedFlow {
     pub config: FlowConfig,
     /// Current flow state
     pub state: FlowState,
+    /// Payload key negotiated during allocation, for encrypted flows
+    pub key: Option<FlowKey>,
 }
 
 /// Flow Allocator
@@ -83,6 +148,26 @@ pub struct FlowAllocator {
     next_flow_id: Arc<RwLock<u32>>,
     /// Next request ID
     next_request_id: Arc<RwLock<u64>>,
+    /// Named QoS classes mapping to preset flow configurations
+    qos_classes: Arc<RwLock<HashMap<String, FlowConfig>>>,
+    /// Outbound channel used to issue requests to the remote peer
+    request_tx: Option<mpsc::Sender<FlowAllocRequest>>,
+    /// Callers awaiting a peer response, keyed by request ID
+    waiters: Arc<Mutex<HashMap<u64, oneshot::Sender<FlowAllocResponse>>>>,
+    /// Directory used to resolve destination application names
+    directory: Option<Directory>,
+    /// Address local applications are reachable at
+    local_addr: u64,
+    /// Time to wait for the peer when allocating by name
+    allocation_timeout: Duration,
+    /// Our half of the key exchange for pending encrypted requests
+    key_exchanges: Arc<Mutex<HashMap<u64, KeyExchange>>>,
+    /// QoS classes the DIF offers; any class is accepted when unset
+    qos_cube: Option<QoSCube>,
+    /// Limits new flows are admitted against
+    admission_limits: AdmissionLimits,
+    /// Bandwidth held by our requests awaiting the peer, keyed by request ID
+    reservations: Arc<Mutex<HashMap<u64, u64>>>,
 }
 
 impl FlowAllocator {
@@ -93,10 +178,124 @@ impl FlowAllocator {
             pending_requests: Arc::new(RwLock::new(HashMap::new())),
             next_flow_id: Arc::new(RwLock::new(1)),
             next_request_id: Arc::new(RwLock::new(1)),
+            qos_classes: Arc::new(RwLock::new(HashMap::new())),
+            request_tx: None,
+            waiters: Arc::new(Mutex::new(HashMap::new())),
+            directory: None,
+            local_addr: 0,
+            allocation_timeout: DEFAULT_ALLOCATION_TIMEOUT,
+            key_exchanges: Arc::new(Mutex::new(HashMap::new())),
+            qos_cube: None,
+            admission_limits: AdmissionLimits::default(),
+            reservations: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Sets the channel that carries outgoing requests towards the peer
+    pub fn set_request_sender(&mut self, tx: mpsc::Sender<FlowAllocRequest>) {
+        self.request_tx = Some(tx);
+    }
+
+    /// Sets the directory used to resolve application names
+    pub fn set_directory(&mut self, directory: Directory) {
+        self.directory = Some(directory);
+    }
+
+    /// Sets the address used as the source of flows requested by name
+    pub fn set_local_addr(&mut self, local_addr: u64) {
+        self.local_addr = local_addr;
+    }
+
+    /// Sets how long `request_flow_by_name` waits for the peer
+    pub fn set_allocation_timeout(&mut self, timeout: Duration) {
+        self.allocation_timeout = timeout;
+    }
+
+    /// Restricts flows to the QoS classes the DIF advertises
+    pub fn set_qos_cube(&mut self, cube: QoSCube) {
+        self.qos_cube = Some(cube);
+    }
+
+    /// Sets the limits flows are admitted against
+    pub fn set_admission_limits(&mut self, limits: AdmissionLimits) {
+        self.admission_limits = limits;
+    }
+
+    /// Checks whether a flow with `config` would currently be admitted
+    ///
+    /// Requests still awaiting the peer's answer count as allocated flows.
+    pub fn check_admission(&self, config: &FlowConfig) -> Result<(), FlowError> {
+        let flows = self.flows.read().unwrap();
+        let reservations = self.reservations.lock().unwrap();
+        self.admission_limits
+            .admit(&flows, &reservations, config.bandwidth_bps.unwrap_or(0))
+    }
+
+    /// Holds a flow slot and the request's bandwidth until the peer answers
+    fn reserve(&self, request: &FlowAllocRequest) -> Result<(), FlowError> {
+        let flows = self.flows.read().unwrap();
+        let mut reservations = self.reservations.lock().unwrap();
+        let requested_bps = request.qos.bandwidth_bps.unwrap_or(0);
+        self.admission_limits
+            .admit(&flows, &reservations, requested_bps)?;
+        reservations.insert(request.request_id, requested_bps);
+        Ok(())
+    }
+
+    /// Fails if `class_name` is not in the DIF's QoS cube
+    ///
+    /// Once a cube is set every flow must name one of its classes.
+    fn check_offered(&self, class_name: Option<&str>) -> Result<(), FlowError> {
+        match (&self.qos_cube, class_name) {
+            (Some(_), None) => Err(FlowError::QoSClassRequired),
+            (Some(cube), Some(name)) if !cube.supports(name) => {
+                Err(FlowError::QoSClassNotOffered(name.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Registers (or replaces) a named QoS class
+    pub fn register_qos_class(&self, class_name: &str, config: FlowConfig) {
+        let mut classes = self.qos_classes.write().unwrap();
+        classes.insert(class_name.to_string(), config);
+    }
+
+    /// Gets the preset flow configuration for a QoS class
+    pub fn qos_class(&self, class_name: &str) -> Option<FlowConfig> {
+        let classes = self.qos_classes.read().unwrap();
+        classes.get(class_name).cloned()
+    }
+
+    /// Creates a flow allocation request using a registered QoS class
+    pub fn create_request_with_class(
+        &self,
+        src_app_name: String,
+        dst_app_name: String,
+        src_addr: u64,
+        dst_addr: u64,
+        class_name: &str,
+    ) -> Result<FlowAllocRequest, String> {
+        self.check_offered(Some(class_name))
+            .map_err(|e| e.to_string())?;
+        let qos = self
+            .qos_class(class_name)
+            .ok_or_else(|| format!("Unknown QoS class: {}", class_name))?;
+
+        self.new_request(
+            src_app_name,
+            dst_app_name,
+            src_addr,
+            dst_addr,
+            qos,
+            Some(class_name.to_string()),
+        )
+        .map_err(|e| e.to_string())
+    }
+
     /// Creates a flow allocation request
+    ///
+    /// Refused once a QoS cube is set; use `create_request_with_class` then.
     pub fn create_request(
         &self,
         src_app_name: String,
@@ -104,55 +303,265 @@ impl FlowAllocator {
         src_addr: u64,
         dst_addr: u64,
         qos: FlowConfig,
-    ) -> FlowAllocRequest {
+    ) -> Result<FlowAllocRequest, FlowError> {
+        self.new_request(src_app_name, dst_app_name, src_addr, dst_addr, qos, None)
+    }
+
+    /// Creates and records a pending request, taken from `qos_class` if given
+    ///
+    /// Fails if an encrypted flow is asked for but no key pair can be generated.
+    fn new_request(
+        &self,
+        src_app_name: String,
+        dst_app_name: String,
+        src_addr: u64,
+        dst_addr: u64,
+        qos: FlowConfig,
+        qos_class: Option<String>,
+    ) -> Result<FlowAllocRequest, FlowError> {
+        self.check_offered(qos_class.as_deref())?;
+
         let mut request_id_lock = self.next_request_id.write().unwrap();
         let request_id = *request_id_lock;
         *request_id_lock += 1;
 
+        let key_share = if qos.encryption.is_enabled() {
+            let exchange = KeyExchange::new().map_err(|e| FlowError::KeyNegotiationFailed {
+                request_id,
+                reason: e.to_string(),
+            })?;
+            let share = exchange.public_share();
+            self.key_exchanges
+                .lock()
+                .unwrap()
+                .insert(request_id, exchange);
+            Some(share)
+        } else {
+            None
+        };
+
         let request = FlowAllocRequest {
             src_app_name,
             dst_app_name,
             src_addr,
             dst_addr,
             qos,
+            qos_class,
             request_id,
+            key_share,
         };
 
         let mut pending = self.pending_requests.write().unwrap();
         pending.insert(request_id, request.clone());
 
-        request
+        Ok(request)
     }
 
     /// Processes a flow allocation request and returns a response
+    ///
+    /// For an encrypted flow our half of the key exchange is returned in the
+    /// response and the derived key is stored with the allocated flow.
     pub fn process_request(&self, request: FlowAllocRequest) -> FlowAllocResponse {
-        let mut flow_id_lock = self.next_flow_id.write().unwrap();
-        let flow_id = *flow_id_lock;
-        *flow_id_lock += 1;
+        if let Err(e) = self.check_offered(request.qos_class.as_deref()) {
+            return FlowAllocResponse {
+                request_id: request.request_id,
+                success: false,
+                flow_id: None,
+                error: Some(e.to_string()),
+                key_share: None,
+            };
+        }
 
-        let allocated_flow = AllocatedFlow {
-            flow_id,
-            src_app_name: request.src_app_name.clone(),
-            dst_app_name: request.dst_app_name.clone(),
-            src_addr: request.src_addr,
-            dst_addr: request.dst_addr,
-            config: request.qos.clone(),
-            state: FlowState::Allocated,
+        let negotiated = if request.qos.encryption.is_enabled() {
+            match Self::answer_key_share(&request) {
+                Ok((key, share)) => Some((key, share)),
+                Err(e) => {
+                    return FlowAllocResponse {
+                        request_id: request.request_id,
+                        success: false,
+                        flow_id: None,
+                        error: Some(e.to_string()),
+                        key_share: None,
+                    };
+                }
+            }
+        } else {
+            None
         };
+        let (key, key_share) = negotiated.unzip();
+        match self.insert_flow(&request, key, false) {
+            Ok(allocated_flow) => FlowAllocResponse {
+                request_id: request.request_id,
+                success: true,
+                flow_id: Some(allocated_flow.flow_id),
+                error: None,
+                key_share,
+            },
+            Err(e) => FlowAllocResponse {
+                request_id: request.request_id,
+                success: false,
+                flow_id: None,
+                error: Some(e.to_string()),
+                key_share: None,
+            },
+        }
+    }
 
-        let mut flows = self.flows.write().unwrap();
-        flows.insert(flow_id, allocated_flow);
+    /// Derives the responder's flow key from a request's key share
+    fn answer_key_share(request: &FlowAllocRequest) -> Result<(FlowKey, [u8; 32]), EfcpError> {
+        let peer_share = request.key_share.ok_or_else(|| {
+            EfcpError::KeyNegotiationFailed("encrypted flow requested without a key share".into())
+        })?;
+        let exchange = KeyExchange::new()?;
+        let share = exchange.public_share();
+        Ok((exchange.derive(peer_share, KeyRole::Responder)?, share))
+    }
 
-        FlowAllocResponse {
+    /// Derives the initiator's flow key once the peer has answered
+    fn complete_key_exchange(
+        &self,
+        request: &FlowAllocRequest,
+        response: &FlowAllocResponse,
+    ) -> Result<Option<FlowKey>, FlowError> {
+        let exchange = self
+            .key_exchanges
+            .lock()
+            .unwrap()
+            .remove(&request.request_id);
+        if !request.qos.encryption.is_enabled() {
+            return Ok(None);
+        }
+
+        let failed = |reason: String| FlowError::KeyNegotiationFailed {
             request_id: request.request_id,
-            success: true,
-            flow_id: Some(flow_id),
-            error: None,
+            reason,
+        };
+        let exchange = exchange.ok_or_else(|| failed("no local key share".to_string()))?;
+        let peer_share = response
+            .key_share
+            .ok_or_else(|| failed("peer sent no key share".to_string()))?;
+        exchange
+            .derive(peer_share, KeyRole::Initiator)
+            .map(Some)
+            .map_err(|e| failed(e.to_string()))
+    }
+
+    /// Allocates a flow by issuing the request to the peer and awaiting its answer
+    ///
+    /// The request is sent over the channel set with `set_request_sender`
+    /// and stays pending until `complete_request` is called with the peer's
+    /// response. If no response arrives within `timeout`, the pending
+    /// allocation is cancelled and `FlowError::Timeout` is returned.
+    ///
+    /// The flow is admitted before the request is sent, and its slot stays
+    /// reserved while the peer answers.
+    pub async fn allocate_flow(
+        &self,
+        request: FlowAllocRequest,
+        timeout: Duration,
+    ) -> Result<AllocatedFlow, FlowError> {
+        let request_id = request.request_id;
+        let tx = self.request_tx.as_ref().ok_or(FlowError::NoTransport)?;
+        self.reserve(&request)?;
+        let result = self.await_allocation(tx, &request, timeout).await;
+        self.reservations.lock().unwrap().remove(&request_id);
+        result
+    }
+
+    /// Sends a reserved request to the peer and turns its answer into a flow
+    async fn await_allocation(
+        &self,
+        tx: &mpsc::Sender<FlowAllocRequest>,
+        request: &FlowAllocRequest,
+        timeout: Duration,
+    ) -> Result<AllocatedFlow, FlowError> {
+        let request_id = request.request_id;
+        let (resp_tx, resp_rx) = oneshot::channel();
+        self.pending_requests
+            .write()
+            .unwrap()
+            .insert(request_id, request.clone());
+        self.waiters.lock().unwrap().insert(request_id, resp_tx);
+
+        if let Err(e) = tx.send(request.clone()).await {
+            self.cancel_request(request_id);
+            return Err(FlowError::SendFailed(e.to_string()));
+        }
+
+        match tokio::time::timeout(timeout, resp_rx).await {
+            Ok(Ok(response)) if response.success => {
+                let key = self.complete_key_exchange(request, &response)?;
+                self.insert_flow(request, key, true)
+            }
+            Ok(Ok(response)) => Err(FlowError::Rejected {
+                request_id,
+                reason: response
+                    .error
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            }),
+            Ok(Err(_)) => Err(FlowError::Cancelled(request_id)),
+            Err(_) => {
+                self.cancel_request(request_id);
+                Err(FlowError::Timeout {
+                    request_id,
+                    timeout_ms: timeout.as_millis() as u64,
+                })
+            }
         }
     }
 
+    /// Allocates a flow from a local application to a named remote one
+    ///
+    /// The remote name is resolved through the directory; when it is
+    /// registered at several addresses the first registration wins.
+    pub async fn request_flow_by_name(
+        &self,
+        local_app: &str,
+        remote_app_name: &str,
+        config: FlowConfig,
+    ) -> Result<AllocatedFlow, FlowError> {
+        let unresolved = || FlowError::UnresolvedName(remote_app_name.to_string());
+        let directory = self.directory.as_ref().ok_or_else(unresolved)?;
+        let dst_addr = directory
+            .resolve(remote_app_name)
+            .await
+            .and_then(|addresses| addresses.first().copied())
+            .ok_or_else(unresolved)?;
+
+        let request = self.create_request(
+            local_app.to_string(),
+            remote_app_name.to_string(),
+            self.local_addr,
+            dst_addr,
+            config,
+        )?;
+        self.allocate_flow(request, self.allocation_timeout).await
+    }
+
+    /// Cancels a pending request, waking any caller awaiting it
+    pub fn cancel_request(&self, request_id: u64) -> bool {
+        let waiter = self.waiters.lock().unwrap().remove(&request_id);
+        let pending = self.pending_requests.write().unwrap().remove(&request_id);
+        self.key_exchanges.lock().unwrap().remove(&request_id);
+        self.reservations.lock().unwrap().remove(&request_id);
+        waiter.is_some() || pending.is_some()
+    }
+
     /// Completes a pending request with a response
+    ///
+    /// If a caller is awaiting the request in `allocate_flow`, the response
+    /// is handed to it.
     pub fn complete_request(&self, response: FlowAllocResponse) -> Result<(), String> {
+        if let Some(waiter) = self.waiters.lock().unwrap().remove(&response.request_id) {
+            let _ = waiter.send(response.clone());
+        } else {
+            // Nobody is waiting to finish the key exchange
+            self.key_exchanges
+                .lock()
+                .unwrap()
+                .remove(&response.request_id);
+        }
+
         let mut pending = self.pending_requests.write().unwrap();
         pending.remove(&response.request_id);
 
@@ -201,6 +610,45 @@ impl FlowAllocator {
         let pending = self.pending_requests.read().unwrap();
         pending.len()
     }
+
+    /// Stores a new allocated flow for a request, if the admission limits allow it
+    ///
+    /// A `reserved` request is one of ours that was admitted before being
+    /// sent; its reservation is turned into the flow.
+    fn insert_flow(
+        &self,
+        request: &FlowAllocRequest,
+        key: Option<FlowKey>,
+        reserved: bool,
+    ) -> Result<AllocatedFlow, FlowError> {
+        let mut flows = self.flows.write().unwrap();
+        let mut reservations = self.reservations.lock().unwrap();
+        if !reserved || reservations.remove(&request.request_id).is_none() {
+            self.admission_limits.admit(
+                &flows,
+                &reservations,
+                request.qos.bandwidth_bps.unwrap_or(0),
+            )?;
+        }
+
+        let mut flow_id_lock = self.next_flow_id.write().unwrap();
+        let flow_id = *flow_id_lock;
+        *flow_id_lock += 1;
+
+        let allocated_flow = AllocatedFlow {
+            flow_id,
+            src_app_name: request.src_app_name.clone(),
+            dst_app_name: request.dst_app_name.clone(),
+            src_addr: request.src_addr,
+            dst_addr: request.dst_addr,
+            config: request.qos.clone(),
+            state: FlowState::Allocated,
+            key,
+        };
+
+        flows.insert(flow_id, allocated_flow.clone());
+        Ok(allocated_flow)
+    }
 }
 
 impl Default for FlowAllocator {
@@ -212,18 +660,21 @@ impl Default for FlowAllocator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pdu::QoSParameters;
 
     #[test]
     fn test_fal_create_request() {
         let fal = FlowAllocator::new();
 
-        let request = fal.create_request(
-            "app1".to_string(),
-            "app2".to_string(),
-            1000,
-            2000,
-            FlowConfig::default(),
-        );
+        let request = fal
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                FlowConfig::default(),
+            )
+            .unwrap();
 
         assert_eq!(request.request_id, 1);
         assert_eq!(fal.pending_count(), 1);
@@ -239,7 +690,9 @@ mod tests {
             src_addr: 1000,
             dst_addr: 2000,
             qos: FlowConfig::default(),
+            qos_class: None,
             request_id: 1,
+            key_share: None,
         };
 
         let response = fal.process_request(request);
@@ -259,7 +712,9 @@ mod tests {
             src_addr: 1000,
             dst_addr: 2000,
             qos: FlowConfig::default(),
+            qos_class: None,
             request_id: 1,
+            key_share: None,
         };
 
         let response = fal.process_request(request);
@@ -279,7 +734,9 @@ mod tests {
             src_addr: 1000,
             dst_addr: 2000,
             qos: FlowConfig::default(),
+            qos_class: None,
             request_id: 1,
+            key_share: None,
         };
 
         let response = fal.process_request(request);
@@ -289,4 +746,455 @@ mod tests {
         assert!(flow.is_some());
         assert_eq!(flow.unwrap().src_app_name, "app1");
     }
+
+    #[test]
+    fn test_fal_request_with_qos_class() {
+        let fal = FlowAllocator::new();
+        fal.register_qos_class(
+            "realtime",
+            FlowConfig {
+                window_size: 4,
+                retransmit_timeout_ms: 50,
+                ..Default::default()
+            },
+        );
+
+        let request = fal
+            .create_request_with_class(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                "realtime",
+            )
+            .unwrap();
+        let flow_id = fal.process_request(request).flow_id.unwrap();
+
+        let config = fal.get_flow(flow_id).unwrap().config;
+        assert_eq!(config.window_size, 4);
+        assert_eq!(config.retransmit_timeout_ms, 50);
+
+        let result = fal.create_request_with_class(
+            "app1".to_string(),
+            "app2".to_string(),
+            1000,
+            2000,
+            "bulk",
+        );
+        assert!(result.is_err());
+        assert_eq!(fal.pending_count(), 1);
+    }
+
+    #[test]
+    fn test_fal_rejects_classes_missing_from_qos_cube() {
+        let mut fal = FlowAllocator::new();
+        fal.register_qos_class("gold", FlowConfig::default());
+        fal.register_qos_class(
+            "silver",
+            FlowConfig {
+                reliable: false,
+                ..Default::default()
+            },
+        );
+        fal.set_qos_cube(QoSCube::new().with_class(
+            "gold",
+            QoSParameters {
+                priority: 250,
+                ..Default::default()
+            },
+        ));
+
+        // Known locally but not advertised by the DIF
+        let err = fal
+            .create_request_with_class("app1".to_string(), "app2".to_string(), 1000, 2000, "silver")
+            .unwrap_err();
+        assert!(err.contains("not offered"), "{}", err);
+        assert_eq!(fal.pending_count(), 0);
+
+        let request = fal
+            .create_request_with_class("app1".to_string(), "app2".to_string(), 1000, 2000, "gold")
+            .unwrap();
+        assert_eq!(request.qos_class.as_deref(), Some("gold"));
+        assert!(fal.process_request(request).success);
+
+        // A peer that does not check still gets refused by the responder
+        let unchecked = FlowAllocator::new();
+        unchecked.register_qos_class("silver", FlowConfig::default());
+        let request = unchecked
+            .create_request_with_class("app1".to_string(), "app2".to_string(), 1000, 2000, "silver")
+            .unwrap();
+        let response = fal.process_request(request);
+        assert!(!response.success);
+        assert!(response.error.unwrap().contains("silver"));
+        assert_eq!(fal.flow_count(), 1);
+
+        // Neither side lets a flow bypass the cube by naming no class
+        let err = fal
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                FlowConfig::default(),
+            )
+            .unwrap_err();
+        assert_eq!(err, FlowError::QoSClassRequired);
+        let request = unchecked
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                FlowConfig::default(),
+            )
+            .unwrap();
+        let response = fal.process_request(request);
+        assert!(!response.success);
+        assert_eq!(fal.flow_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fal_allocate_flow_times_out_without_response() {
+        let mut fal = FlowAllocator::new();
+        // The peer end of the channel never answers
+        let (tx, mut peer_rx) = mpsc::channel(4);
+        fal.set_request_sender(tx);
+
+        let request = fal
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                FlowConfig::default(),
+            )
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let result = fal.allocate_flow(request, Duration::from_millis(100)).await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            FlowError::Timeout {
+                request_id: 1,
+                timeout_ms: 100
+            }
+        );
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(peer_rx.recv().await.unwrap().request_id, 1);
+        assert_eq!(fal.pending_count(), 0);
+        assert_eq!(fal.flow_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fal_allocate_flow_rejected_by_peer() {
+        let mut fal = FlowAllocator::new();
+        let (tx, mut peer_rx) = mpsc::channel(4);
+        fal.set_request_sender(tx);
+        let fal = Arc::new(fal);
+
+        let peer_fal = fal.clone();
+        tokio::spawn(async move {
+            let request = peer_rx.recv().await.unwrap();
+            let _ = peer_fal.complete_request(FlowAllocResponse {
+                request_id: request.request_id,
+                success: false,
+                flow_id: None,
+                error: Some("no such application".to_string()),
+                key_share: None,
+            });
+        });
+
+        let request = fal
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                FlowConfig::default(),
+            )
+            .unwrap();
+        let result = fal.allocate_flow(request, Duration::from_secs(2)).await;
+
+        assert!(matches!(
+            result,
+            Err(FlowError::Rejected { request_id: 1, ref reason }) if reason == "no such application"
+        ));
+        assert_eq!(fal.pending_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fal_request_flow_by_name() {
+        let directory = Directory::new();
+        directory.register("app2".to_string(), 2000).await.unwrap();
+
+        let mut fal = FlowAllocator::new();
+        let (tx, mut peer_rx) = mpsc::channel(4);
+        fal.set_request_sender(tx);
+        fal.set_directory(directory);
+        fal.set_local_addr(1000);
+        let fal = Arc::new(fal);
+
+        let peer_fal = fal.clone();
+        tokio::spawn(async move {
+            let request = peer_rx.recv().await.unwrap();
+            assert_eq!(request.dst_addr, 2000);
+            let _ = peer_fal.complete_request(FlowAllocResponse {
+                request_id: request.request_id,
+                success: true,
+                flow_id: None,
+                error: None,
+                key_share: None,
+            });
+        });
+
+        let flow = fal
+            .request_flow_by_name("app1", "app2", FlowConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(flow.src_addr, 1000);
+        assert_eq!(flow.dst_addr, 2000);
+        assert_eq!(flow.dst_app_name, "app2");
+
+        let result = fal
+            .request_flow_by_name("app1", "app3", FlowConfig::default())
+            .await;
+        assert_eq!(
+            result.unwrap_err(),
+            FlowError::UnresolvedName("app3".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_flow_negotiates_key_during_allocation() {
+        use crate::crypto::EncryptionMode;
+        use crate::efcp::Flow;
+
+        let mut fal = FlowAllocator::new();
+        let (tx, mut peer_rx) = mpsc::channel(4);
+        fal.set_request_sender(tx);
+        let fal = Arc::new(fal);
+
+        // The peer allocates its end and answers with its key share
+        let peer = FlowAllocator::new();
+        let initiator_fal = fal.clone();
+        let responder = tokio::spawn(async move {
+            let request = peer_rx.recv().await.unwrap();
+            assert!(request.key_share.is_some());
+            let response = peer.process_request(request);
+            let flow = peer.get_flow(response.flow_id.unwrap()).unwrap();
+            let _ = initiator_fal.complete_request(response);
+            flow
+        });
+
+        let config = FlowConfig {
+            encryption: EncryptionMode::ChaCha20Poly1305,
+            ..Default::default()
+        };
+        let request = fal
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                config.clone(),
+            )
+            .unwrap();
+        let local = fal
+            .allocate_flow(request, Duration::from_secs(2))
+            .await
+            .unwrap();
+        let remote = responder.await.unwrap();
+
+        let mut sender = Flow::new(1, 10, 20, 1000, 2000, config.clone());
+        sender.set_key(local.key.unwrap());
+        let mut receiver = Flow::new(1, 20, 10, 2000, 1000, config.clone());
+        receiver.set_key(remote.key.unwrap());
+
+        let pdu = sender.send_data(b"confidential".to_vec()).unwrap();
+        assert_ne!(pdu.payload, b"confidential");
+        assert_eq!(
+            receiver.receive_pdu(pdu.clone()).unwrap(),
+            Some(b"confidential".to_vec())
+        );
+
+        // An encrypted flow without the negotiated key cannot read it
+        let keyless = Flow::new(1, 20, 10, 2000, 1000, config);
+        assert_eq!(
+            keyless.open_payload(&pdu),
+            Err(EfcpError::KeyNotNegotiated(1))
+        );
+
+        // Nor can one holding a key from another allocation
+        let other = FlowAllocator::new();
+        let mut stranger_request = fal
+            .create_request(
+                "app3".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                local.config.clone(),
+            )
+            .unwrap();
+        stranger_request.request_id = 99;
+        let stranger = other.process_request(stranger_request);
+        let mut eavesdropper = Flow::new(1, 20, 10, 2000, 1000, local.config);
+        eavesdropper.set_key(
+            other
+                .get_flow(stranger.flow_id.unwrap())
+                .unwrap()
+                .key
+                .unwrap(),
+        );
+        assert_eq!(
+            eavesdropper.open_payload(&pdu),
+            Err(EfcpError::DecryptionFailed(pdu.sequence_num))
+        );
+        assert!(eavesdropper.receive_pdu(pdu).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_request_without_key_share_is_refused() {
+        let fal = FlowAllocator::new();
+        let mut request = fal
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                FlowConfig {
+                    encryption: crate::crypto::EncryptionMode::ChaCha20Poly1305,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        request.key_share = None;
+
+        let response = fal.process_request(request);
+        assert!(!response.success);
+        assert_eq!(fal.flow_count(), 0);
+    }
+
+    #[test]
+    fn test_admission_rejects_flows_beyond_the_limit_until_one_is_freed() {
+        let mut fal = FlowAllocator::new();
+        fal.set_admission_limits(AdmissionLimits {
+            max_flows: Some(2),
+            bandwidth_budget_bps: None,
+        });
+        let request = |fal: &FlowAllocator| {
+            fal.create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                FlowConfig::default(),
+            )
+            .unwrap()
+        };
+
+        let first = fal.process_request(request(&fal));
+        assert!(fal.process_request(request(&fal)).success);
+        let refused = fal.process_request(request(&fal));
+        assert!(!refused.success);
+        assert_eq!(
+            refused.error.as_deref(),
+            Some(FlowError::FlowLimitReached(2).to_string().as_str())
+        );
+        assert!(matches!(
+            fal.check_admission(&FlowConfig::default()),
+            Err(FlowError::FlowLimitReached(2))
+        ));
+        assert_eq!(fal.flow_count(), 2);
+
+        fal.deallocate_flow(first.flow_id.unwrap()).unwrap();
+        assert!(fal.process_request(request(&fal)).success);
+        assert_eq!(fal.flow_count(), 2);
+    }
+
+    #[test]
+    fn test_admission_enforces_bandwidth_budget() {
+        let mut fal = FlowAllocator::new();
+        fal.set_admission_limits(AdmissionLimits {
+            max_flows: None,
+            bandwidth_budget_bps: Some(1_000),
+        });
+        let reserving = |bps| FlowConfig {
+            bandwidth_bps: Some(bps),
+            ..Default::default()
+        };
+
+        assert!(fal.check_admission(&reserving(600)).is_ok());
+        let request = fal
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                reserving(600),
+            )
+            .unwrap();
+        assert!(fal.process_request(request).success);
+
+        assert!(matches!(
+            fal.check_admission(&reserving(500)),
+            Err(FlowError::BandwidthExhausted {
+                requested_bps: 500,
+                available_bps: 400
+            })
+        ));
+        assert!(fal.check_admission(&reserving(400)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_admission_counts_requests_awaiting_the_peer() {
+        let mut fal = FlowAllocator::new();
+        let (tx, mut peer_rx) = mpsc::channel(4);
+        fal.set_request_sender(tx);
+        fal.set_admission_limits(AdmissionLimits {
+            max_flows: Some(1),
+            bandwidth_budget_bps: None,
+        });
+        let fal = Arc::new(fal);
+
+        let peer_fal = fal.clone();
+        let peer = tokio::spawn(async move {
+            let request = peer_rx.recv().await.unwrap();
+
+            // The slot is held while we answer, so nothing else gets in
+            assert!(matches!(
+                peer_fal.check_admission(&FlowConfig::default()),
+                Err(FlowError::FlowLimitReached(1))
+            ));
+            let incoming = FlowAllocRequest {
+                request_id: 99,
+                ..request.clone()
+            };
+            assert!(!peer_fal.process_request(incoming).success);
+
+            let _ = peer_fal.complete_request(FlowAllocResponse {
+                request_id: request.request_id,
+                success: true,
+                flow_id: None,
+                error: None,
+                key_share: None,
+            });
+        });
+
+        let request = fal
+            .create_request(
+                "app1".to_string(),
+                "app2".to_string(),
+                1000,
+                2000,
+                FlowConfig::default(),
+            )
+            .unwrap();
+        let flow = fal.allocate_flow(request, Duration::from_secs(2)).await;
+        peer.await.unwrap();
+
+        assert!(flow.is_ok());
+        assert_eq!(fal.flow_count(), 1);
+        assert!(fal.reservations.lock().unwrap().is_empty());
+    }
 }
@@ -9,12 +9,19 @@
 
 use crate::pdu::Pdu;
 use crate::rib::Rib;
+use crate::routing::Route;
 use crate::shim::Shim;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+/// Default number of route re-resolution attempts when recovering a failed flow
+pub const DEFAULT_RECOVERY_ATTEMPTS: u32 = 3;
+
+/// Default delay before the second recovery attempt (doubled on each retry)
+pub const DEFAULT_RECOVERY_BACKOFF: Duration = Duration::from_millis(100);
+
 /// State of an Inter-IPCP flow
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InterIpcpFlowState {
@@ -110,6 +117,12 @@ pub struct InterIpcpFlowAllocator {
 
     /// Timeout for marking flows as stale
     stale_timeout: Duration,
+
+    /// Maximum route re-resolution attempts when recovering a failed flow
+    recovery_attempts: u32,
+
+    /// Initial backoff between recovery attempts
+    recovery_backoff: Duration,
 }
 
 impl InterIpcpFlowAllocator {
@@ -120,6 +133,8 @@ impl InterIpcpFlowAllocator {
             rib,
             shim,
             stale_timeout: Duration::from_secs(300), // 5 minutes default
+            recovery_attempts: DEFAULT_RECOVERY_ATTEMPTS,
+            recovery_backoff: DEFAULT_RECOVERY_BACKOFF,
         }
     }
 
@@ -128,21 +143,30 @@ impl InterIpcpFlowAllocator {
         self.stale_timeout = timeout;
     }
 
+    /// Sets how many times, and with what initial backoff, a failed flow is
+    /// re-resolved before giving up
+    pub fn set_recovery_policy(&mut self, attempts: u32, initial_backoff: Duration) {
+        self.recovery_attempts = attempts.max(1);
+        self.recovery_backoff = initial_backoff;
+    }
+
     /// Gets or creates a flow to the specified neighbor
     ///
     /// This is the main entry point for RMT to obtain connectivity.
     /// If no flow exists, it will be created lazily by looking up the
-    /// route in the RIB.
+    /// route in the RIB. A failed flow is recovered instead.
     pub async fn get_or_create_flow(&self, remote_addr: u64) -> Result<(), String> {
         // Check if flow already exists
-        {
+        let state = {
             let flows = self.flows.lock().unwrap();
-            if let Some(flow) = flows.get(&remote_addr)
-                && flow.state == InterIpcpFlowState::Active
-            {
-                return Ok(());
-            }
-        } // Lock is dropped here before await
+            flows.get(&remote_addr).map(|flow| flow.state)
+        }; // Lock is dropped here before await
+
+        match state {
+            Some(InterIpcpFlowState::Active) => return Ok(()),
+            Some(InterIpcpFlowState::Failed) => return self.recover_flow(remote_addr).await,
+            _ => {}
+        }
 
         // Need to create new flow - lookup route in RIB
         let socket_addr = self.lookup_route(remote_addr).await?;
@@ -183,6 +207,60 @@ impl InterIpcpFlowAllocator {
         Ok(())
     }
 
+    /// Sends a PDU, recovering the flow to the next hop if it has failed
+    ///
+    /// A flow that is already `Failed` is recovered before sending. If the
+    /// send itself fails, the flow is recovered once more and the send is
+    /// retried before the error is returned.
+    pub async fn send_pdu_with_recovery(&self, next_hop: u64, pdu: &Pdu) -> Result<(), String> {
+        self.get_or_create_flow(next_hop).await?;
+
+        match self.send_pdu(next_hop, pdu) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                eprintln!("  ⚠ {}, attempting flow recovery", e);
+                self.recover_flow(next_hop).await?;
+                self.send_pdu(next_hop, pdu)
+            }
+        }
+    }
+
+    /// Recovers a flow by re-resolving its route from the RIB
+    ///
+    /// Re-registers the peer with the shim and marks the flow `Active` again,
+    /// keeping its statistics. Route lookups are retried up to the configured
+    /// number of attempts, doubling the backoff after each failure.
+    pub async fn recover_flow(&self, remote_addr: u64) -> Result<(), String> {
+        let mut backoff = self.recovery_backoff;
+        let mut attempt = 1;
+
+        let socket_addr = loop {
+            match self.lookup_route(remote_addr).await {
+                Ok(addr) => break addr,
+                Err(e) if attempt >= self.recovery_attempts => {
+                    return Err(format!(
+                        "Failed to recover flow to {} after {} attempts: {}",
+                        remote_addr, attempt, e
+                    ));
+                }
+                Err(_) => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+        };
+
+        self.update_peer_address(remote_addr, socket_addr);
+        progress!(
+            "  ✓ Recovered flow to {} via {} (attempt {})",
+            remote_addr,
+            socket_addr,
+            attempt
+        );
+        Ok(())
+    }
+
     /// Updates the socket address for a neighbor
     ///
     /// Called when a peer's underlay address changes (e.g., DHCP renewal).
@@ -264,26 +342,18 @@ impl InterIpcpFlowAllocator {
     async fn lookup_route(&self, remote_addr: u64) -> Result<SocketAddr, String> {
         // Try dynamic routes first
         let route_name = format!("/routing/dynamic/{}", remote_addr);
-        if let Some(route_obj) = self.rib.read(&route_name).await
-            && let crate::rib::RibValue::Struct(route_struct) = &route_obj.value
-            && let Some(next_hop_box) = route_struct.get("next_hop_address")
-            && let Some(addr_str) = next_hop_box.as_string()
-        {
-            return addr_str
-                .parse::<SocketAddr>()
-                .map_err(|e| format!("Invalid socket address: {}", e));
+        if let Ok(route_obj) = self.rib.read(&route_name).await {
+            return Route::try_from(&route_obj.value)
+                .map(|route| route.next_hop)
+                .map_err(|e| e.to_string());
         }
 
         // Try static routes as fallback
         let static_route_name = format!("/routing/static/{}", remote_addr);
-        if let Some(route_obj) = self.rib.read(&static_route_name).await
-            && let crate::rib::RibValue::Struct(route_struct) = &route_obj.value
-            && let Some(next_hop_box) = route_struct.get("next_hop_address")
-            && let Some(addr_str) = next_hop_box.as_string()
-        {
-            return addr_str
-                .parse::<SocketAddr>()
-                .map_err(|e| format!("Invalid socket address: {}", e));
+        if let Ok(route_obj) = self.rib.read(&static_route_name).await {
+            return Route::try_from(&route_obj.value)
+                .map(|route| route.next_hop)
+                .map_err(|e| e.to_string());
         }
 
         Err(format!("No route found for RINA address {}", remote_addr))
@@ -296,6 +366,7 @@ impl std::fmt::Debug for InterIpcpFlowAllocator {
         f.debug_struct("InterIpcpFlowAllocator")
             .field("flow_count", &flows.len())
             .field("stale_timeout", &self.stale_timeout)
+            .field("recovery_attempts", &self.recovery_attempts)
             .finish()
     }
 }
@@ -303,9 +374,62 @@ impl std::fmt::Debug for InterIpcpFlowAllocator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::shim::UdpShim;
+    use crate::rib::RibValue;
+    use crate::shim::{ShimError, UdpShim};
+    use std::sync::atomic::{AtomicU32, Ordering};
     use std::thread;
 
+    /// UDP shim whose next `failures` sends fail
+    struct FlakyShim {
+        inner: UdpShim,
+        failures: AtomicU32,
+    }
+
+    impl Shim for FlakyShim {
+        fn bind(&self, addr: &str) -> Result<(), ShimError> {
+            self.inner.bind(addr)
+        }
+
+        fn send_pdu(&self, pdu: &Pdu) -> Result<usize, ShimError> {
+            if self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(ShimError::SendError("simulated failure".to_string()));
+            }
+            self.inner.send_pdu(pdu)
+        }
+
+        fn receive_pdu(&self) -> Result<Option<(Pdu, SocketAddr)>, ShimError> {
+            self.inner.receive_pdu()
+        }
+
+        fn register_peer(&self, rina_addr: u64, socket_addr: SocketAddr) {
+            self.inner.register_peer(rina_addr, socket_addr)
+        }
+
+        fn lookup_peer(&self, rina_addr: u64) -> Option<SocketAddr> {
+            self.inner.lookup_peer(rina_addr)
+        }
+
+        fn local_rina_addr(&self) -> u64 {
+            self.inner.local_rina_addr()
+        }
+    }
+
+    async fn set_static_route(rib: &Rib, remote_addr: u64, socket_addr: SocketAddr) {
+        let name = format!("/routing/static/{}", remote_addr);
+        let route = RibValue::from(Route::new(socket_addr));
+        if rib.read(&name).await.is_ok() {
+            rib.update(&name, route).await.unwrap();
+        } else {
+            rib.create(name, "static_route".to_string(), route)
+                .await
+                .unwrap();
+        }
+    }
+
     #[tokio::test]
     async fn test_inter_ipcp_flow_creation() {
         let flow = InterIpcpFlow::new(1002, "127.0.0.1:7001".parse().unwrap());
@@ -414,4 +538,52 @@ mod tests {
         assert_eq!(cleaned, 1);
         assert_eq!(fal.active_flow_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_failed_flow_recovers_via_route_reresolution() {
+        let old_peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let new_peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        new_peer
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+
+        let rib = Rib::new();
+        set_static_route(&rib, 1002, old_peer.local_addr().unwrap()).await;
+
+        let shim = Arc::new(FlakyShim {
+            inner: UdpShim::new(1001),
+            failures: AtomicU32::new(1),
+        });
+        shim.bind("127.0.0.1:0").unwrap();
+        let mut fal = InterIpcpFlowAllocator::new(rib.clone(), shim.clone());
+        fal.set_recovery_policy(3, Duration::from_millis(10));
+
+        let pdu = Pdu::new_data(1001, 1002, 0, 0, 0, b"ping".to_vec());
+        fal.get_or_create_flow(1002).await.unwrap();
+        assert!(fal.send_pdu(1002, &pdu).is_err());
+        assert_eq!(fal.get_flow_stats()[0].1, InterIpcpFlowState::Failed);
+        assert_eq!(fal.active_flow_count(), 0);
+
+        // The peer moved; recovery must pick up the new route from the RIB
+        set_static_route(&rib, 1002, new_peer.local_addr().unwrap()).await;
+        fal.send_pdu_with_recovery(1002, &pdu).await.unwrap();
+
+        assert_eq!(fal.get_flow_stats()[0].1, InterIpcpFlowState::Active);
+        assert_eq!(shim.lookup_peer(1002), Some(new_peer.local_addr().unwrap()));
+
+        let mut buf = [0u8; 1024];
+        let (len, _) = new_peer.recv_from(&mut buf).unwrap();
+        assert_eq!(Pdu::deserialize(&buf[..len]).unwrap().payload, b"ping");
+
+        // Without a route, recovery gives up after the bounded attempts
+        rib.delete("/routing/static/1002").await.unwrap();
+        fal.flows
+            .lock()
+            .unwrap()
+            .get_mut(&1002)
+            .unwrap()
+            .record_send_error();
+        let err = fal.send_pdu_with_recovery(1002, &pdu).await.unwrap_err();
+        assert!(err.contains("after 3 attempts"));
+    }
 }
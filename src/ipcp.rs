@@ -5,15 +5,26 @@
 //!
 //! Manages IPCP lifecycle, state, and component coordination.
 
+use crate::actors::{
+    EfcpActor, EfcpHandle, EfcpMessage, RibActor, RibHandle, RmtActor, RmtHandle, RmtMessage,
+    ShimActor, ShimHandle,
+};
 use crate::cdap::CdapSession;
+use crate::crypto::FlowKey;
 use crate::directory::Directory;
-use crate::efcp::Efcp;
+use crate::efcp::{Efcp, FlowConfig};
 use crate::enrollment::{EnrollmentManager, EnrollmentState};
-use crate::fal::FlowAllocator;
+use crate::error::{AriError, EfcpError};
+use crate::fal::{AllocatedFlow, FlowAllocator};
+use crate::pdu::Pdu;
 use crate::rib::Rib;
-use crate::rmt::Rmt;
+use crate::rmt::{ForwardingEntry, Rmt};
 use crate::shim::UdpShim;
 use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Mailbox capacity for each component actor spawned by `IpcProcess::start`
+const ACTOR_CHANNEL_CAPACITY: usize = 32;
 
 /// IPCP operational state
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -34,6 +45,47 @@ pub enum IpcpState {
     Error(String),
 }
 
+impl IpcpState {
+    /// Checks whether the lifecycle allows moving from this state to `next`
+    ///
+    /// Re-entering the current state is always allowed. An IPCP in the
+    /// `Error` state must enroll again before it can become operational,
+    /// and nothing leaves `Shutdown`.
+    pub fn can_transition_to(&self, next: &IpcpState) -> bool {
+        use IpcpState::*;
+
+        if std::mem::discriminant(self) == std::mem::discriminant(next) {
+            return !matches!(self, Shutdown) || matches!(next, Shutdown);
+        }
+
+        matches!(
+            (self, next),
+            (Initializing, Ready)
+                | (Ready, Enrolling)
+                | (Ready, Operational)
+                | (Enrolling, Operational)
+                | (Enrolling, Ready)
+                | (Operational, Enrolling)
+                | (Error(_), Enrolling)
+                | (ShuttingDown, Shutdown)
+                | (Initializing | Ready | Enrolling | Operational, Error(_))
+                | (
+                    Initializing | Ready | Enrolling | Operational | Error(_),
+                    ShuttingDown
+                )
+        )
+    }
+}
+
+/// Handles to the component actors of a started IPCP
+#[derive(Debug, Clone)]
+pub struct IpcpActors {
+    pub rib: RibHandle,
+    pub efcp: EfcpHandle,
+    pub rmt: RmtHandle,
+    pub shim: ShimHandle,
+}
+
 /// Complete IPC Process with all components
 #[derive(Debug)]
 pub struct IpcProcess {
@@ -61,6 +113,8 @@ pub struct IpcProcess {
     pub directory: Directory,
     /// Enrollment manager
     pub enrollment: EnrollmentManager,
+    /// Component actors, once started
+    actors: Option<IpcpActors>,
 }
 
 impl IpcProcess {
@@ -70,10 +124,15 @@ impl IpcProcess {
         let address = 0;
         let shim = UdpShim::new(address);
         let shim_for_enrollment = Arc::new(UdpShim::new(address));
+        let directory = Directory::with_rib(rib.clone());
+        let mut fal = FlowAllocator::new();
+        fal.set_directory(directory.clone());
+        fal.set_local_addr(address);
 
         Self {
             cdap: CdapSession::new(rib.clone()),
             enrollment: EnrollmentManager::new(rib.clone(), shim_for_enrollment, address),
+            directory,
             rib,
             name: None,
             address: None,
@@ -82,8 +141,8 @@ impl IpcProcess {
             efcp: Efcp::new(),
             rmt: Rmt::new(address),
             shim,
-            fal: FlowAllocator::new(),
-            directory: Directory::new(),
+            fal,
+            actors: None,
         }
     }
 
@@ -92,10 +151,15 @@ impl IpcProcess {
         let rib = Rib::new();
         let shim = UdpShim::new(address);
         let shim_for_enrollment = Arc::new(UdpShim::new(address));
+        let directory = Directory::with_rib(rib.clone());
+        let mut fal = FlowAllocator::new();
+        fal.set_directory(directory.clone());
+        fal.set_local_addr(address);
 
         Self {
             cdap: CdapSession::new(rib.clone()),
             enrollment: EnrollmentManager::new(rib.clone(), shim_for_enrollment, address),
+            directory,
             rib,
             name: Some(name),
             address: Some(address),
@@ -104,8 +168,8 @@ impl IpcProcess {
             efcp: Efcp::new(),
             rmt: Rmt::new(address),
             shim,
-            fal: FlowAllocator::new(),
-            directory: Directory::new(),
+            fal,
+            actors: None,
         }
     }
 
@@ -119,6 +183,7 @@ impl IpcProcess {
         self.address = Some(address);
         self.rmt = Rmt::new(address);
         self.shim = UdpShim::new(address);
+        self.fal.set_local_addr(address);
     }
 
     /// Sets the DIF name
@@ -126,7 +191,22 @@ impl IpcProcess {
         self.dif_name = Some(dif_name);
     }
 
-    /// Transitions to a new state
+    /// Transitions to a new state, rejecting moves the lifecycle forbids
+    pub fn transition_to(&mut self, state: IpcpState) -> Result<(), AriError> {
+        if !self.state.can_transition_to(&state) {
+            return Err(AriError::InvalidState(format!(
+                "Illegal transition from {:?} to {:?}",
+                self.state, state
+            )));
+        }
+        self.state = state;
+        Ok(())
+    }
+
+    /// Overwrites the state without validating the transition
+    ///
+    /// Only meant for tests that need to put the IPCP into an arbitrary
+    /// state; everything else should go through `transition_to`.
     pub fn set_state(&mut self, state: IpcpState) {
         self.state = state;
     }
@@ -138,19 +218,165 @@ impl IpcProcess {
 
     /// Checks if IPCP is enrolled
     pub fn is_enrolled(&self) -> bool {
-        *self.enrollment.state() == EnrollmentState::Enrolled
+        self.enrollment.state() == EnrollmentState::Enrolled
     }
 
     /// Starts the IPCP
-    pub fn start(&mut self) -> Result<(), String> {
+    ///
+    /// Spawns the RIB actor over this IPCP's RIB and the EFCP, RMT and Shim
+    /// actors, wires EFCP to the RMT and
+    /// the RMT to the Shim actor, and keeps their handles for the async
+    /// methods below. The shim is bound through the Shim actor's handle. Starting an already
+    /// started IPCP leaves its actors untouched.
+    pub async fn start(&mut self) -> Result<(), AriError> {
         if self.state == IpcpState::Shutdown {
-            return Err("Cannot start a shutdown IPCP".to_string());
+            return Err(AriError::InvalidState(
+                "Cannot start a shutdown IPCP".to_string(),
+            ));
         }
 
-        self.state = IpcpState::Ready;
+        if self.actors.is_none() {
+            let local_addr = self.address.unwrap_or(0);
+
+            let (rib_tx, rib_rx) = mpsc::channel(ACTOR_CHANNEL_CAPACITY);
+            let mut rib_actor = RibActor::with_rib(self.rib.clone(), rib_rx);
+            rib_actor.set_sync_source(self.enrollment.shared());
+            tokio::spawn(rib_actor.run());
+
+            let (shim_tx, shim_rx) = mpsc::channel(ACTOR_CHANNEL_CAPACITY);
+            tokio::spawn(ShimActor::new(local_addr, shim_rx).run());
+            let shim = ShimHandle::new(shim_tx);
+
+            let (management_tx, management_rx) = mpsc::channel(ACTOR_CHANNEL_CAPACITY);
+            self.enrollment.start_management_handler(management_rx);
+
+            let (rmt_tx, rmt_rx) = mpsc::channel(ACTOR_CHANNEL_CAPACITY);
+            let mut rmt_actor = RmtActor::new(local_addr, rmt_rx);
+            rmt_actor.set_management_sender(management_tx);
+            rmt_actor.set_shim_handle(shim.clone());
+            tokio::spawn(rmt_actor.run());
+            let rmt = RmtHandle::new(rmt_tx);
+
+            let (efcp_tx, efcp_rx) = mpsc::channel(ACTOR_CHANNEL_CAPACITY);
+            let mut efcp_actor = EfcpActor::new(efcp_rx);
+            efcp_actor.set_rmt_handle(rmt.clone());
+            tokio::spawn(efcp_actor.run());
+
+            self.actors = Some(IpcpActors {
+                rib: RibHandle::new(rib_tx),
+                efcp: EfcpHandle::new(efcp_tx),
+                rmt,
+                shim,
+            });
+        }
+
+        if self.state == IpcpState::Initializing {
+            self.transition_to(IpcpState::Ready)?;
+        }
         Ok(())
     }
 
+    /// Returns the component actor handles, if the IPCP has been started
+    pub fn actors(&self) -> Option<&IpcpActors> {
+        self.actors.as_ref()
+    }
+
+    fn started_actors(&self) -> Result<&IpcpActors, AriError> {
+        self.actors
+            .as_ref()
+            .ok_or_else(|| AriError::InvalidState("IPCP has not been started".to_string()))
+    }
+
+    /// Allocates an EFCP flow to a remote address
+    ///
+    /// Encrypted flows are refused here; their key has to be negotiated by
+    /// the flow allocator first, see `allocate_negotiated_flow`.
+    pub async fn allocate_flow(
+        &self,
+        remote_addr: u64,
+        config: FlowConfig,
+    ) -> Result<u32, AriError> {
+        if config.encryption.is_enabled() {
+            return Err(EfcpError::KeyNegotiationFailed(
+                "encrypted flows must be allocated through the flow allocator".to_string(),
+            )
+            .into());
+        }
+        self.request_efcp_flow(remote_addr, config, None).await
+    }
+
+    /// Allocates the EFCP flow for a flow the flow allocator has granted
+    ///
+    /// The key negotiated during allocation, if any, seals the flow's
+    /// payloads. The remote end is whichever of the flow's addresses is
+    /// not ours.
+    pub async fn allocate_negotiated_flow(&self, flow: &AllocatedFlow) -> Result<u32, AriError> {
+        let remote_addr = if Some(flow.src_addr) == self.address {
+            flow.dst_addr
+        } else {
+            flow.src_addr
+        };
+        self.request_efcp_flow(remote_addr, flow.config.clone(), flow.key.clone())
+            .await
+    }
+
+    async fn request_efcp_flow(
+        &self,
+        remote_addr: u64,
+        config: FlowConfig,
+        key: Option<FlowKey>,
+    ) -> Result<u32, AriError> {
+        let local_addr = self.address.unwrap_or(0);
+        self.started_actors()?
+            .efcp
+            .request(|response| EfcpMessage::AllocateFlow {
+                local_addr,
+                remote_addr,
+                config,
+                key,
+                remote_cep_id: None,
+                response,
+            })
+            .await
+    }
+
+    /// Sends data on a flow, returning the PDUs handed to the RMT
+    ///
+    /// Data larger than the flow's path MTU allows goes out in fragments.
+    pub async fn send(&self, flow_id: u32, data: Vec<u8>) -> Result<Vec<Pdu>, AriError> {
+        self.started_actors()?
+            .efcp
+            .request(|response| EfcpMessage::SendData {
+                flow_id,
+                data,
+                response,
+            })
+            .await?
+    }
+
+    /// Returns the number of EFCP flows
+    pub async fn flow_count(&self) -> Result<usize, AriError> {
+        self.started_actors()?
+            .efcp
+            .request(|response| EfcpMessage::GetFlowCount { response })
+            .await
+    }
+
+    /// Installs a forwarding entry in the RMT
+    pub async fn add_route(&self, dst_addr: u64, next_hop: u64, cost: u32) -> Result<(), AriError> {
+        self.started_actors()?
+            .rmt
+            .request(|response| RmtMessage::AddForwardingEntry {
+                entry: ForwardingEntry {
+                    dst_addr,
+                    next_hop,
+                    cost,
+                },
+                response,
+            })
+            .await?
+    }
+
     /// Shuts down the IPCP
     pub fn shutdown(&mut self) -> Result<(), String> {
         self.state = IpcpState::ShuttingDown;
@@ -174,6 +400,7 @@ impl Default for IpcProcess {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::actors::ShimMessage;
 
     #[test]
     fn test_ipcp_creation() {
@@ -197,11 +424,11 @@ mod tests {
         assert_eq!(ipcp.address, Some(2000));
     }
 
-    #[test]
-    fn test_ipcp_state_transitions() {
+    #[tokio::test]
+    async fn test_ipcp_state_transitions() {
         let mut ipcp = IpcProcess::new();
 
-        ipcp.start().unwrap();
+        ipcp.start().await.unwrap();
         assert_eq!(ipcp.state, IpcpState::Ready);
 
         ipcp.set_state(IpcpState::Operational);
@@ -211,15 +438,177 @@ mod tests {
         assert_eq!(ipcp.state, IpcpState::Shutdown);
     }
 
-    #[test]
-    fn test_ipcp_cannot_start_after_shutdown() {
+    #[tokio::test]
+    async fn test_ipcp_cannot_start_after_shutdown() {
         let mut ipcp = IpcProcess::new();
         ipcp.shutdown().unwrap();
 
-        let result = ipcp.start();
+        let result = ipcp.start().await;
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_ipcp_legal_transition() {
+        let mut ipcp = IpcProcess::with_name_and_address("member".to_string(), 1002);
+
+        ipcp.transition_to(IpcpState::Enrolling).unwrap();
+        ipcp.transition_to(IpcpState::Operational).unwrap();
+        assert!(ipcp.is_operational());
+    }
+
+    #[test]
+    fn test_ipcp_rejects_illegal_transition() {
+        let mut ipcp = IpcProcess::with_name_and_address("member".to_string(), 1002);
+        ipcp.transition_to(IpcpState::Error("link down".to_string()))
+            .unwrap();
+
+        let result = ipcp.transition_to(IpcpState::Operational);
+        assert!(matches!(result, Err(AriError::InvalidState(_))));
+        assert_eq!(ipcp.state, IpcpState::Error("link down".to_string()));
+
+        // Recovering from an error has to go through enrollment again
+        ipcp.transition_to(IpcpState::Enrolling).unwrap();
+        ipcp.transition_to(IpcpState::Operational).unwrap();
+    }
+
+    /// Binds a started IPCP's shim and routes `peer` to a fresh local socket
+    async fn connect(ipcp: &IpcProcess, peer: u64) -> UdpShim {
+        let shim = &ipcp.actors().unwrap().shim;
+        shim.request(|response| ShimMessage::Bind {
+            addr: "127.0.0.1:0".to_string(),
+            response,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        let wire = UdpShim::new(peer);
+        wire.bind("127.0.0.1:0").unwrap();
+        shim.request(|response| ShimMessage::RegisterPeer {
+            rina_addr: peer,
+            socket_addr: wire.local_addr().unwrap(),
+            response,
+        })
+        .await
+        .unwrap();
+        ipcp.add_route(peer, peer, 1).await.unwrap();
+        wire
+    }
+
+    #[tokio::test]
+    async fn test_ipcp_facade_allocates_flow() {
+        let mut ipcp = IpcProcess::with_name_and_address("facade".to_string(), 1001);
+        assert!(ipcp.flow_count().await.is_err());
+
+        ipcp.start().await.unwrap();
+        let flow_id = ipcp
+            .allocate_flow(1002, FlowConfig::default())
+            .await
+            .unwrap();
+
+        // Without a bound shim the RMT cannot transmit, and the send fails
+        assert!(ipcp.send(flow_id, b"lost".to_vec()).await.is_err());
+
+        let peer = connect(&ipcp, 1002).await;
+        let pdus = ipcp.send(flow_id, b"hello".to_vec()).await.unwrap();
+        let [pdu] = pdus.as_slice() else {
+            panic!("expected one PDU, got {}", pdus.len());
+        };
+        assert_eq!(pdu.dst_addr, 1002);
+        assert_eq!(ipcp.flow_count().await.unwrap(), 1);
+
+        let (received, _) = peer
+            .receive_pdu_within(std::time::Duration::from_secs(2))
+            .await
+            .unwrap()
+            .expect("PDU on the wire");
+        assert_eq!(received.payload, b"hello");
+        assert_eq!(received.sequence_num, pdu.sequence_num);
+    }
+
+    #[tokio::test]
+    async fn test_ipcp_sends_on_negotiated_encrypted_flow() {
+        use crate::crypto::EncryptionMode;
+
+        let mut initiator = IpcProcess::with_name_and_address("a".to_string(), 1001);
+        let mut responder = IpcProcess::with_name_and_address("b".to_string(), 1002);
+        let (tx, mut requests) = mpsc::channel(4);
+        initiator.fal.set_request_sender(tx);
+
+        let config = FlowConfig {
+            encryption: EncryptionMode::ChaCha20Poly1305,
+            ..Default::default()
+        };
+        assert!(initiator.allocate_flow(1002, config.clone()).await.is_err());
+
+        // The responder answers over the request channel like a peer would
+        let request = initiator
+            .fal
+            .create_request("a".to_string(), "b".to_string(), 1001, 1002, config)
+            .unwrap();
+        let (local, remote) = tokio::join!(
+            initiator
+                .fal
+                .allocate_flow(request, std::time::Duration::from_secs(2)),
+            async {
+                let response = responder
+                    .fal
+                    .process_request(requests.recv().await.unwrap());
+                let flow = responder.fal.get_flow(response.flow_id.unwrap()).unwrap();
+                initiator.fal.complete_request(response).unwrap();
+                flow
+            }
+        );
+
+        initiator.start().await.unwrap();
+        responder.start().await.unwrap();
+        let sending = initiator
+            .allocate_negotiated_flow(&local.unwrap())
+            .await
+            .unwrap();
+        let receiving = responder.allocate_negotiated_flow(&remote).await.unwrap();
+        for (ipcp, flow_id, remote_cep_id) in [
+            (&initiator, sending, receiving),
+            (&responder, receiving, sending),
+        ] {
+            ipcp.actors()
+                .unwrap()
+                .efcp
+                .request(|response| EfcpMessage::BindRemoteCep {
+                    flow_id,
+                    remote_cep_id,
+                    response,
+                })
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        let wire = connect(&initiator, 1002).await;
+        let pdu = initiator
+            .send(sending, b"confidential".to_vec())
+            .await
+            .unwrap()
+            .remove(0);
+        assert_ne!(pdu.payload, b"confidential");
+        let (sent, _) = wire
+            .receive_pdu_within(std::time::Duration::from_secs(2))
+            .await
+            .unwrap()
+            .expect("PDU on the wire");
+        assert_eq!(sent.payload, pdu.payload);
+
+        let delivered = responder
+            .actors()
+            .unwrap()
+            .efcp
+            .request(|response| EfcpMessage::ReceivePdu { pdu, response })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(delivered, Some(b"confidential".to_vec()));
+    }
+
     #[test]
     fn test_ipcp_dif_name() {
         let mut ipcp = IpcProcess::new();
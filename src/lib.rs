@@ -7,17 +7,25 @@
 //! Recursive InterNetwork Architecture, including concepts like
 //! DIFs (Distributed IPC Facilities) and IPC Processes.
 
-// Public module declarations
+// Public module declarations; `progress` comes first so its macro is
+// visible to every module below
+#[macro_use]
+pub mod progress;
+
 pub mod actors;
 pub mod cdap;
 pub mod config;
+pub mod crypto;
 pub mod directory;
+pub mod discovery;
 pub mod efcp;
 pub mod enrollment;
 pub mod error;
 pub mod fal;
 pub mod inter_ipcp_fal;
 pub mod ipcp;
+pub mod neighbor;
+pub mod node;
 pub mod pdu;
 pub mod policies;
 pub mod rib;
@@ -27,32 +35,56 @@ pub mod shim;
 
 // Re-export commonly used types
 pub use actors::{
-    EfcpActor, EfcpHandle, EfcpMessage, RibActor, RibHandle, RibMessage, RmtActor, RmtHandle,
-    RmtMessage, ShimActor, ShimHandle, ShimMessage,
+    EfcpActor, EfcpHandle, EfcpMessage, InterIpcpFalActor, InterIpcpFalHandle, InterIpcpFalMessage,
+    RibActor, RibHandle, RibMessage, RmtActor, RmtHandle, RmtMessage, ShimActor, ShimHandle,
+    ShimMessage,
+};
+pub use cdap::{
+    CdapCapabilities, CdapMessage, CdapOp, CdapOpCode, CdapOpResult, CdapReassembler, CdapResult,
+    CdapSession, InvokeIds,
 };
-pub use cdap::{CdapMessage, CdapOpCode, CdapSession};
-pub use directory::{AddressPool, Directory};
-pub use efcp::{Efcp, Flow, FlowConfig};
+pub use crypto::{EncryptionMode, FlowKey, KeyExchange, KeyRole};
+pub use directory::{
+    ADDRESS_POOL_RIB_NAME, AddressPool, AllocationStrategy, DIRECTORY_RIB_PREFIX, Directory,
+    DirectoryEvent, DirectoryWatch, NameMatching,
+};
+pub use discovery::{Beacon, BeaconListener, DiscoveredPeer, DiscoveryEndpoint};
+pub use efcp::{ConnectionEndpoint, Efcp, Flow, FlowConfig, FlowStats};
 pub use enrollment::{
-    DifConfiguration, EnrollmentManager, EnrollmentRequest, EnrollmentResponse, EnrollmentState,
-    NeighborInfo,
+    AddressState, Backoff, BackoffJitter, BootstrapHello, DifConfiguration, DifNameRules,
+    EnrollmentEvent, EnrollmentManager, EnrollmentRequest, EnrollmentResponse, EnrollmentState,
+    NeighborInfo, SyncStatus,
 };
 pub use error::{
-    AriError, CdapError, EfcpError, EnrollmentError, RibError, RmtError, SerializationError,
-    ShimError,
+    ActorError, AriError, CdapError, EfcpError, EnrollmentError, FlowError, PduError, RibError,
+    RmtError, SerializationError, ShimError,
 };
-pub use fal::{AllocatedFlow, FlowAllocator, FlowState};
+pub use fal::{AdmissionLimits, AllocatedFlow, FlowAllocator, FlowState};
 pub use inter_ipcp_fal::{InterIpcpFlow, InterIpcpFlowAllocator, InterIpcpFlowState};
-pub use ipcp::{IpcProcess, IpcpState};
-pub use pdu::{Pdu, PduType, QoSParameters};
+pub use ipcp::{IpcProcess, IpcpActors, IpcpState};
+pub use neighbor::{NeighborEntry, NeighborSeed, NeighborTable};
+pub use node::{BootstrapNode, BootstrapStats, MemberNode};
+pub use pdu::{
+    DEFAULT_HOP_LIMIT, DEFAULT_MAX_PAYLOAD_SIZE, PDU_VERSION, Pdu, PduBuilder, PduType,
+    QoSParameters,
+};
 pub use policies::{
-    FifoScheduling, PriorityScheduling, QoSPolicy, RoutingPolicy, SchedulingPolicy,
-    ShortestPathRouting, SimpleQoSPolicy,
+    DistanceVectorRouting, FifoScheduling, PriorityScheduling, QoSCube, QoSPolicy, QoSPolicyKind,
+    QoSProfile, RoutingPolicy, RoutingPolicyKind, SchedulingPolicy, SchedulingPolicyKind,
+    ShortestPathRouting, SimpleQoSPolicy, WfqScheduling,
+};
+pub use rib::{
+    CapacityPolicy, CausalOrder, ConflictResolver, Rib, RibChange, RibChangeLog, RibName,
+    RibObject, RibValue, SerializationFormat, SnapshotGuard, StructBuilder, VectorClock,
+};
+pub use rmt::{
+    DEFAULT_CONGESTION_THRESHOLD_PERCENT, DropReason, ForwardingEntry, IncomingDisposition,
+    QoSClass, Rmt, RmtStats, SchedulerFactory,
+};
+pub use routing::{
+    Route, RouteEvent, RouteMetadata, RouteResolver, RouteResolverConfig, RouteSnapshot, RouteStats,
 };
-pub use rib::{Rib, RibChange, RibChangeLog, RibObject, RibValue};
-pub use rmt::{ForwardingEntry, Rmt};
-pub use routing::{RouteMetadata, RouteResolver, RouteResolverConfig, RouteSnapshot, RouteStats};
-pub use shim::{AddressMapper, Shim, UdpShim};
+pub use shim::{AddressMapper, Shim, ShimStats, UdpShim};
 
 /// Represents a Distributed IPC Facility (DIF).
 ///
@@ -73,10 +105,11 @@ pub struct Dif {
 impl Dif {
     /// Creates a new DIF with the given name
     pub fn new_with_name(name: String) -> Self {
+        let rib = Rib::new();
         Self {
             name,
-            rib: Rib::new(),
-            directory: Directory::new(),
+            directory: Directory::with_rib(rib.clone()),
+            rib,
             member_addresses: Vec::new(),
         }
     }
@@ -2,23 +2,27 @@
 // Copyright © 2026-present ARI Contributors
 
 use ari::{
-    Dif, Directory, EfcpActor, EfcpHandle, EfcpMessage, EnrollmentManager, FlowAllocator,
-    FlowConfig, ForwardingEntry, InterIpcpFlowAllocator, IpcProcess, IpcpState, PriorityScheduling,
-    Rib, RibActor, RibHandle, RibMessage, RibValue, RmtActor, RmtHandle, RmtMessage, RouteResolver,
-    RouteResolverConfig, RoutingPolicy, ShimActor, ShimHandle, ShimMessage, ShortestPathRouting,
-    UdpShim,
-    config::{CliArgs, IpcpConfiguration, IpcpMode},
+    BootstrapNode, Dif, Directory, EfcpActor, EfcpHandle, EfcpMessage, EnrollmentManager,
+    FlowAllocator, FlowConfig, ForwardingEntry, IpcProcess, IpcpState, MemberNode, RibActor,
+    RibHandle, RibMessage, RibValue, RmtActor, RmtHandle, RmtMessage, ShimActor, ShimHandle,
+    ShimMessage,
+    config::{CliArgs, IpcpConfiguration, IpcpMode, OutputFormat},
 };
 use clap::Parser;
-use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::io::Write;
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::{mpsc, oneshot};
 
 #[tokio::main]
 async fn main() {
     // Parse command-line arguments
     let args = CliArgs::parse();
+    let check_only = args.check;
+    let output = args.output;
+    if output == OutputFormat::Json {
+        // Stdout only carries the JSON lines
+        ari::progress::send_to_stderr(true);
+    }
 
     // Load configuration from CLI args or config file
     let config = match IpcpConfiguration::from_cli(args) {
@@ -40,6 +44,8 @@ async fn main() {
             eprintln!("\n  From config file:");
             eprintln!("    cargo run -- --config config/bootstrap.toml");
             eprintln!("    cargo run -- --config config/member.toml");
+            eprintln!("\n  Validate a config file without starting:");
+            eprintln!("    cargo run -- --config config/member.toml --check");
             std::process::exit(1);
         }
     };
@@ -51,18 +57,70 @@ async fn main() {
     }
 
     // Print configuration summary
-    config.print_summary();
+    match output {
+        OutputFormat::Text => config.print_summary(),
+        OutputFormat::Json => emit_json_line(&config.summary_json()),
+    }
+
+    if check_only {
+        match output {
+            OutputFormat::Text => {
+                for warning in config.warnings() {
+                    println!("⚠ {}", warning);
+                }
+                println!("✓ Configuration is valid");
+            }
+            OutputFormat::Json => emit_event(
+                "check",
+                serde_json::json!({ "valid": true, "warnings": config.warnings() }),
+            ),
+        }
+        return;
+    }
 
     // Run appropriate mode
     match config.mode {
-        IpcpMode::Demo => run_demo_mode().await,
-        IpcpMode::Bootstrap => run_bootstrap_mode(config).await,
-        IpcpMode::Member => run_member_mode(config).await,
+        IpcpMode::Demo => run_demo_mode(config).await,
+        IpcpMode::Bootstrap => run_bootstrap_mode(config, output).await,
+        IpcpMode::Member => run_member_mode(config, output).await,
+    }
+}
+
+/// Prints a runtime event as one JSON line, merging `fields` into the object
+fn emit_event(event: &str, fields: serde_json::Value) {
+    let mut line = serde_json::json!({ "event": event });
+    if let (Some(line), serde_json::Value::Object(fields)) = (line.as_object_mut(), fields) {
+        line.extend(fields);
     }
+    emit_json_line(&line);
+}
+
+/// Writes one JSON line to stdout
+fn emit_json_line(line: &impl std::fmt::Display) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+/// Sends on `shutdown` when Ctrl-C is pressed
+///
+/// The signal listener is registered before this returns, so an interrupt
+/// arriving right after the node reports it has started is not lost.
+fn forward_ctrl_c(shutdown: mpsc::Sender<()>) -> std::io::Result<()> {
+    #[cfg(unix)]
+    let mut interrupt = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())?;
+    #[cfg(not(unix))]
+    let mut interrupt = tokio::signal::windows::ctrl_c()?;
+
+    tokio::spawn(async move {
+        interrupt.recv().await;
+        let _ = shutdown.send(()).await;
+    });
+    Ok(())
 }
 
 /// Runs the original demo mode
-async fn run_demo_mode() {
+async fn run_demo_mode(config: IpcpConfiguration) {
     println!("=== RINA (Recursive InterNetwork Architecture) ===");
     println!("=== Enhanced with Modular Extensions ===\n");
     println!("Initializing a new Distributed IPC Facility (DIF).\n");
@@ -77,16 +135,23 @@ async fn run_demo_mode() {
     dif.add_member(1002);
     println!("✓ Added {} members to DIF\n", dif.member_count());
 
-    // Spawn actor tasks for each component
+    // The enhanced IPCP owns the RIB the RIB actor serves
     let local_addr = 1001;
+    let mut ipcp = IpcProcess::with_name_and_address("ipcp-0".to_string(), local_addr);
+    ipcp.set_dif_name("test-dif".to_string());
+    ipcp.transition_to(IpcpState::Ready)
+        .expect("Freshly created IPCP must be able to become ready");
+
+    // Spawn actor tasks for each component
     println!("✓ Spawning RINA component actors...\n");
 
     // RIB Actor
     let (rib_tx, rib_rx) = mpsc::channel(32);
     let rib_handle = RibHandle::new(rib_tx);
+    let mut rib_actor = RibActor::with_rib(ipcp.rib.clone(), rib_rx);
+    rib_actor.set_sync_source(ipcp.enrollment.shared());
     tokio::spawn(async move {
-        let actor = RibActor::new(rib_rx);
-        actor.run().await;
+        rib_actor.run().await;
     });
     println!("  → RIB Actor spawned");
 
@@ -102,8 +167,10 @@ async fn run_demo_mode() {
     // RMT Actor
     let (rmt_tx, rmt_rx) = mpsc::channel(32);
     let rmt_handle = RmtHandle::new(rmt_tx);
+    let (management_tx, management_rx) = mpsc::channel(32);
     tokio::spawn(async move {
-        let actor = RmtActor::new(local_addr, rmt_rx);
+        let mut actor = RmtActor::new(local_addr, rmt_rx);
+        actor.set_management_sender(management_tx);
         actor.run().await;
     });
     println!("  → RMT Actor spawned");
@@ -119,10 +186,7 @@ async fn run_demo_mode() {
 
     println!("\n✓ All actors running concurrently\n");
 
-    // Also create enhanced IPCP with all new components
-    let mut ipcp = IpcProcess::with_name_and_address("ipcp-0".to_string(), local_addr);
-    ipcp.set_dif_name("test-dif".to_string());
-    ipcp.set_state(IpcpState::Ready);
+    ipcp.enrollment.start_management_handler(management_rx);
 
     println!(
         "✓ Created Enhanced IPCP: {:?} with address {} in DIF {:?}",
@@ -136,73 +200,54 @@ async fn run_demo_mode() {
     println!("=== 1. Resource Information Base (RIB Actor) ===");
 
     // Create objects via RIB actor
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
     rib_handle
-        .send(RibMessage::Create {
+        .request(|response| RibMessage::Create {
             name: "neighbor/ipcp-1".to_string(),
             class: "neighbor".to_string(),
             value: RibValue::Integer(1002),
-            response: resp_tx,
+            response,
         })
         .await
-        .unwrap();
-    resp_rx
-        .recv()
-        .await
         .unwrap()
         .expect("Failed to create neighbor");
 
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
     rib_handle
-        .send(RibMessage::Create {
+        .request(|response| RibMessage::Create {
             name: "flow/app-1".to_string(),
             class: "flow".to_string(),
             value: RibValue::String("allocated".to_string()),
-            response: resp_tx,
+            response,
         })
         .await
-        .unwrap();
-    resp_rx
-        .recv()
-        .await
         .unwrap()
         .expect("Failed to create flow");
 
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
     rib_handle
-        .send(RibMessage::Create {
+        .request(|response| RibMessage::Create {
             name: "config/max-flows".to_string(),
             class: "config".to_string(),
             value: RibValue::Integer(100),
-            response: resp_tx,
+            response,
         })
         .await
-        .unwrap();
-    resp_rx
-        .recv()
-        .await
         .unwrap()
         .expect("Failed to create config");
 
     // Query RIB count
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
-    rib_handle
-        .send(RibMessage::Count { response: resp_tx })
+    let count = rib_handle
+        .request(|response| RibMessage::Count { response })
         .await
         .unwrap();
-    let count = resp_rx.recv().await.unwrap();
     println!("  Added {} objects to RIB (via actor)", count);
 
     // List flows
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
-    rib_handle
-        .send(RibMessage::ListByClass {
+    let flows = rib_handle
+        .request(|response| RibMessage::ListByClass {
             class: "flow".to_string(),
-            response: resp_tx,
+            response,
         })
         .await
         .unwrap();
-    let flows = resp_rx.recv().await.unwrap();
     println!("  Flows in RIB: {:?}\n", flows);
 
     // === CDAP Operations ===
@@ -219,22 +264,24 @@ async fn run_demo_mode() {
     println!("=== 3. Error and Flow Control Protocol (EFCP Actor) ===");
 
     // Allocate flow via actor
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
+    let (resp_tx, resp_rx) = oneshot::channel();
     efcp_handle
         .send(EfcpMessage::AllocateFlow {
             local_addr: 1001,
             remote_addr: 1002,
             config: FlowConfig::default(),
+            key: None,
+            remote_cep_id: None,
             response: resp_tx,
         })
         .await
         .unwrap();
-    let flow_id = resp_rx.recv().await.unwrap();
+    let flow_id = resp_rx.await.unwrap();
     println!("  Allocated flow with ID: {} (via actor)", flow_id);
 
     // Send data via actor
     let test_data = b"Hello from RINA!".to_vec();
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
+    let (resp_tx, resp_rx) = oneshot::channel();
     efcp_handle
         .send(EfcpMessage::SendData {
             flow_id,
@@ -244,28 +291,30 @@ async fn run_demo_mode() {
         .await
         .unwrap();
 
-    match resp_rx.recv().await.unwrap() {
-        Ok(pdu) => {
-            println!("  Sent PDU with seq_num: {}", pdu.sequence_num);
-            println!("  Payload: {:?}", String::from_utf8_lossy(&pdu.payload));
+    match resp_rx.await.unwrap() {
+        Ok(pdus) => {
+            for pdu in pdus {
+                println!("  Sent PDU with seq_num: {}", pdu.sequence_num);
+                println!("  Payload: {:?}", String::from_utf8_lossy(&pdu.payload));
+            }
         }
         Err(e) => println!("  Error sending: {}", e),
     }
 
     // Get flow count
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
+    let (resp_tx, resp_rx) = oneshot::channel();
     efcp_handle
         .send(EfcpMessage::GetFlowCount { response: resp_tx })
         .await
         .unwrap();
-    let flow_count = resp_rx.recv().await.unwrap();
+    let flow_count = resp_rx.await.unwrap();
     println!("  Active flows: {} (via actor)\n", flow_count);
 
     // === RMT Operations (Actor-based) ===
     println!("=== 4. Relaying and Multiplexing Task (RMT Actor) ===");
 
     // Add forwarding entries via actor
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
+    let (resp_tx, resp_rx) = oneshot::channel();
     rmt_handle
         .send(RmtMessage::AddForwardingEntry {
             entry: ForwardingEntry {
@@ -277,9 +326,11 @@ async fn run_demo_mode() {
         })
         .await
         .unwrap();
-    resp_rx.recv().await.unwrap();
+    if let Err(e) = resp_rx.await.unwrap() {
+        println!("  Error adding forwarding entry: {}", e);
+    }
 
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
+    let (resp_tx, resp_rx) = oneshot::channel();
     rmt_handle
         .send(RmtMessage::AddForwardingEntry {
             entry: ForwardingEntry {
@@ -291,44 +342,55 @@ async fn run_demo_mode() {
         })
         .await
         .unwrap();
-    resp_rx.recv().await.unwrap();
+    if let Err(e) = resp_rx.await.unwrap() {
+        println!("  Error adding forwarding entry: {}", e);
+    }
 
     // Get forwarding table size
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
+    let (resp_tx, resp_rx) = oneshot::channel();
     rmt_handle
         .send(RmtMessage::GetForwardingTableSize { response: resp_tx })
         .await
         .unwrap();
-    let table_size = resp_rx.recv().await.unwrap();
+    let table_size = resp_rx.await.unwrap();
     println!("  Added {} forwarding entries (via actor)", table_size);
 
     // Also update synchronous IPCP for demonstration
-    ipcp.rmt.add_forwarding_entry(ForwardingEntry {
-        dst_addr: 1002,
-        next_hop: 1002,
-        cost: 1,
-    });
-    ipcp.rmt.add_forwarding_entry(ForwardingEntry {
-        dst_addr: 1003,
-        next_hop: 1002,
-        cost: 2,
-    });
+    ipcp.rmt
+        .add_forwarding_entry(ForwardingEntry {
+            dst_addr: 1002,
+            next_hop: 1002,
+            cost: 1,
+        })
+        .unwrap();
+    ipcp.rmt
+        .add_forwarding_entry(ForwardingEntry {
+            dst_addr: 1003,
+            next_hop: 1002,
+            cost: 2,
+        })
+        .unwrap();
     println!("  Next hop for addr 1002: {:?}", ipcp.rmt.lookup(1002));
     println!("  Next hop for addr 1003: {:?}\n", ipcp.rmt.lookup(1003));
 
     // === Directory Service ===
     println!("=== 6. Directory Service ===");
     let directory = Directory::new();
-    directory.register("app.example".to_string(), 1001).unwrap();
+    directory
+        .register("app.example".to_string(), 1001)
+        .await
+        .unwrap();
     directory
         .register("service.example".to_string(), 1002)
+        .await
         .unwrap();
     directory
         .register("service.example".to_string(), 1003)
+        .await
         .unwrap(); // Multiple addresses
 
     println!("  Registered {} names in directory", directory.count());
-    if let Some(addrs) = directory.resolve("service.example") {
+    if let Some(addrs) = directory.resolve("service.example").await {
         println!("  'service.example' resolves to addresses: {:?}", addrs);
     }
     println!();
@@ -336,13 +398,15 @@ async fn run_demo_mode() {
     // === Flow Allocator ===
     println!("=== 7. Flow Allocator (FAL) ===");
     let fal = FlowAllocator::new();
-    let request = fal.create_request(
-        "app1".to_string(),
-        "app2".to_string(),
-        1001,
-        1002,
-        FlowConfig::default(),
-    );
+    let request = fal
+        .create_request(
+            "app1".to_string(),
+            "app2".to_string(),
+            1001,
+            1002,
+            FlowConfig::default(),
+        )
+        .unwrap();
     println!("  Created flow allocation request #{}", request.request_id);
 
     let response = fal.process_request(request);
@@ -361,21 +425,22 @@ async fn run_demo_mode() {
     // === Pluggable Policies ===
     println!("=== 9. Pluggable Policies ===");
 
-    // Routing policy
-    let routing = ShortestPathRouting::new();
+    // Policies are chosen by the [policies] config section
+    let routing = config.policies.routing.build();
     println!("  Routing policy: {}", routing.name());
 
-    // Scheduling policy
-    let _sched = PriorityScheduling::default();
-    println!("  Scheduling policy: Priority");
-    println!("  Queue capacity: {} PDUs per priority level\n", 250);
+    let sched = config.policies.scheduling.build();
+    println!("  Scheduling policy: {}", sched.name());
+
+    let qos = config.policies.qos.build();
+    println!("  QoS policy: {}\n", qos.name());
 
     // === 5. UDP/IP Shim Layer (Shim Actor) ===
     println!("=== 5. UDP/IP Shim Layer (Shim Actor) ===");
     println!("  Shim layer ready for RINA address: {}", local_addr);
 
     // Bind via actor
-    let (resp_tx, mut resp_rx) = mpsc::channel(1);
+    let (resp_tx, resp_rx) = oneshot::channel();
     shim_handle
         .send(ShimMessage::Bind {
             addr: "127.0.0.1:0".to_string(),
@@ -384,15 +449,15 @@ async fn run_demo_mode() {
         .await
         .unwrap();
 
-    match resp_rx.recv().await.unwrap() {
+    match resp_rx.await.unwrap() {
         Ok(_) => {
-            let (resp_tx, mut resp_rx) = mpsc::channel(1);
+            let (resp_tx, resp_rx) = oneshot::channel();
             shim_handle
                 .send(ShimMessage::GetLocalAddr { response: resp_tx })
                 .await
                 .unwrap();
 
-            if let Ok(addr) = resp_rx.recv().await.unwrap() {
+            if let Ok(addr) = resp_rx.await.unwrap() {
                 println!("  Bound to UDP socket: {} (via actor)", addr);
             }
         }
@@ -419,485 +484,54 @@ async fn run_demo_mode() {
 }
 
 /// Runs bootstrap IPCP mode
-async fn run_bootstrap_mode(config: IpcpConfiguration) {
-    println!("=== RINA Bootstrap IPCP ===\n");
-
-    let local_addr = config.address.expect("Bootstrap mode requires an address");
-
-    // Initialize RIB first
-    println!("✓ Initializing RIB...");
-    let rib = ari::rib::Rib::new();
-    rib.create(
-        "/dif/name".to_string(),
-        "dif_info".to_string(),
-        RibValue::String(config.dif_name.clone()),
-    )
-    .await
-    .unwrap();
-
-    // Load RIB snapshot if persistence is enabled
-    if config.enable_rib_persistence {
-        let rib_snapshot_path = std::path::Path::new(&config.rib_snapshot_path);
-        match rib.load_snapshot_from_file(rib_snapshot_path).await {
-            Ok(count) if count > 0 => {
-                println!("  ✓ Loaded {} RIB objects from snapshot", count);
+async fn run_bootstrap_mode(config: IpcpConfiguration, output: OutputFormat) {
+    match BootstrapNode::start(config).await {
+        Ok(node) => {
+            // Ctrl-C stops the receive loop so the final counters are reported
+            let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+            if let Err(e) = forward_ctrl_c(shutdown_tx.clone()) {
+                eprintln!("⚠ Ctrl-C will not stop the node cleanly: {}", e);
             }
-            Ok(_) => {
-                println!("  ℹ️  No RIB objects to load from snapshot");
+            if output == OutputFormat::Json {
+                emit_event("started", serde_json::json!({ "mode": "bootstrap" }));
             }
-            Err(e) => {
-                eprintln!("  ⚠️  Failed to load RIB snapshot: {}", e);
+            node.run(shutdown_rx).await;
+            if output == OutputFormat::Json {
+                emit_event("stopped", serde_json::json!({ "mode": "bootstrap" }));
             }
         }
-    }
-
-    // Load static routes into RIB
-    println!("\n✓ Loading static routes into RIB...");
-
-    // Load static routes into RIB
-    for route in &config.static_routes {
-        let route_name = format!("/routing/static/{}", route.destination);
-        let route_value = ari::rib::RibValue::Struct({
-            let mut map = std::collections::HashMap::new();
-            map.insert(
-                "next_hop_address".to_string(),
-                Box::new(ari::rib::RibValue::String(route.next_hop_address.clone())),
-            );
-            map.insert(
-                "next_hop_rina_addr".to_string(),
-                Box::new(ari::rib::RibValue::Integer(route.next_hop_rina_addr as i64)),
-            );
-            map
-        });
-
-        rib.create(route_name.clone(), "static_route".to_string(), route_value)
-            .await
-            .unwrap();
-
-        println!(
-            "  Route: {} → {} ({})",
-            route.destination, route.next_hop_address, route.next_hop_rina_addr
-        );
-    }
-    println!("  Loaded {} static routes\n", config.static_routes.len());
-
-    // Initialize RouteResolver
-    println!("✓ Initializing RouteResolver...");
-    let rib_arc = Arc::new(RwLock::new(rib));
-    let resolver_config = RouteResolverConfig {
-        enable_persistence: config.enable_route_persistence,
-        snapshot_path: PathBuf::from(&config.route_snapshot_path),
-        default_ttl_seconds: config.route_ttl_seconds,
-        snapshot_interval_seconds: config.route_snapshot_interval_seconds,
-    };
-    let route_resolver = Arc::new(RouteResolver::new(rib_arc.clone(), resolver_config));
-
-    // Load dynamic routes from snapshot
-    if config.enable_route_persistence {
-        match route_resolver.load_snapshot().await {
-            Ok(count) if count > 0 => {
-                println!("  Loaded {} dynamic routes from snapshot", count);
-            }
-            Ok(_) => {
-                println!("  No dynamic routes to load from snapshot");
-            }
-            Err(e) => {
-                eprintln!("  ⚠ Failed to load route snapshot: {}", e);
-            }
-        }
-    }
-
-    // Start snapshot task for periodic saves
-    if config.enable_route_persistence && config.route_snapshot_interval_seconds > 0 {
-        let resolver_clone = route_resolver.clone();
-        let _snapshot_task = resolver_clone.start_snapshot_task();
-        println!(
-            "  Route snapshot task started (interval: {}s)",
-            config.route_snapshot_interval_seconds
-        );
-    }
-
-    // Start RIB snapshot task for periodic saves
-    if config.enable_rib_persistence && config.rib_snapshot_interval_seconds > 0 {
-        // Clone RIB for snapshot task
-        let rib_for_snapshot = {
-            let rib_lock = rib_arc.read().await;
-            rib_lock.clone()
-        };
-        let rib_snapshot_path = std::path::PathBuf::from(&config.rib_snapshot_path);
-        let rib_snapshot_interval = config.rib_snapshot_interval_seconds;
-        let _rib_snapshot_task = std::sync::Arc::new(rib_for_snapshot)
-            .start_snapshot_task(rib_snapshot_path, rib_snapshot_interval);
-        println!(
-            "  RIB snapshot task started (interval: {}s)",
-            config.rib_snapshot_interval_seconds
-        );
-    }
-    println!();
-
-    // Initialize Shim and Flow Allocator BEFORE spawning actors
-    println!("✓ Initializing Shim and Flow Allocator...");
-    let shim = Arc::new(UdpShim::new(local_addr));
-
-    // Bind shim to UDP socket
-    if let Err(e) = shim.bind(&config.bind_address) {
-        eprintln!("  Failed to bind shim: {}", e);
-        return;
-    }
-    println!("  Bound to: {}", config.bind_address);
-
-    // Initialize InterIpcpFlowAllocator
-    let rib_for_fal = {
-        let rib_lock = rib_arc.read().await;
-        rib_lock.clone()
-    };
-    let flow_allocator = Arc::new(InterIpcpFlowAllocator::new(rib_for_fal, shim.clone()));
-    println!("  Flow allocator ready (stale timeout: 300s)\n");
-
-    // Spawn actor tasks
-    println!("✓ Spawning RINA component actors...");
-
-    // RIB Actor
-    let (rib_tx, rib_rx) = mpsc::channel(32);
-    let rib_handle = RibHandle::new(rib_tx);
-    tokio::spawn(async move {
-        let actor = RibActor::new(rib_rx);
-        actor.run().await;
-    });
-    println!("  → RIB Actor spawned");
-
-    // Create all channels first
-    let (efcp_tx, efcp_rx) = mpsc::channel(32);
-    let _efcp_handle = EfcpHandle::new(efcp_tx);
-
-    let (rmt_tx, rmt_rx) = mpsc::channel(32);
-    let rmt_handle = RmtHandle::new(rmt_tx);
-
-    // Spawn EFCP Actor with RMT handle
-    let rmt_for_efcp = rmt_handle.clone();
-    tokio::spawn(async move {
-        let mut actor = EfcpActor::new(efcp_rx);
-        actor.set_rmt_handle(rmt_for_efcp);
-        actor.run().await;
-    });
-    println!("  → EFCP Actor spawned");
-
-    // Spawn RMT Actor with FlowAllocator and RouteResolver
-    let fal_for_rmt = flow_allocator.clone();
-    let resolver_for_rmt = route_resolver.clone();
-    tokio::spawn(async move {
-        let mut actor = RmtActor::new(local_addr, rmt_rx);
-        actor.set_flow_allocator(fal_for_rmt);
-        actor.set_route_resolver(resolver_for_rmt);
-        actor.run().await;
-    });
-    println!("  → RMT Actor spawned\n");
-
-    // Create IPCP
-    let mut ipcp = IpcProcess::with_name_and_address(config.name.clone(), local_addr);
-    ipcp.set_dif_name(config.dif_name.clone());
-    ipcp.set_state(IpcpState::Operational);
-
-    println!("✓ Created Bootstrap IPCP: {}", config.name);
-    println!("  RINA Address: {}", local_addr);
-    println!("  DIF: {}", config.dif_name);
-
-    // Initialize RIB with address pool
-    println!("✓ Initializing address pool...");
-    for addr in config.address_pool_start..=config.address_pool_end {
-        let (resp_tx, mut resp_rx) = mpsc::channel(1);
-        rib_handle
-            .send(RibMessage::Create {
-                name: format!("address-pool/{}", addr),
-                class: "address-pool".to_string(),
-                value: RibValue::Boolean(true), // true = available
-                response: resp_tx,
-            })
-            .await
-            .unwrap();
-        let _ = resp_rx.recv().await.unwrap();
-    }
-    println!(
-        "  Address pool: {}-{}\n",
-        config.address_pool_start, config.address_pool_end
-    );
-
-    // Set up async enrollment manager
-    println!("✓ Setting up enrollment manager...");
-    // Clone the RIB for enrollment (we already created it earlier)
-    let rib_for_enrollment = {
-        let rib_lock = rib_arc.read().await;
-        rib_lock.clone()
-    };
-
-    let mut enrollment_mgr = EnrollmentManager::new_bootstrap(
-        rib_for_enrollment,
-        shim.clone(),
-        local_addr,
-        config.address_pool_start,
-        config.address_pool_end,
-    );
-    enrollment_mgr.set_ipcp_name(config.name.clone());
-    enrollment_mgr.set_route_resolver(route_resolver.clone());
-    println!(
-        "  Enrollment manager ready (timeout: {}s, retries: {})",
-        config.enrollment_timeout_secs, config.enrollment_max_retries
-    );
-
-    println!("\n🎉 Bootstrap IPCP operational!");
-    println!("   Waiting for enrollment requests from member IPCPs...\n");
-
-    // Listen for incoming enrollment requests
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
-        if let Ok(Some((pdu, src_addr))) = shim.receive_pdu() {
-            println!(
-                "  Received PDU from address {} ({})",
-                pdu.src_addr, src_addr
-            );
-            if let Err(e) = enrollment_mgr.handle_cdap_message(&pdu, src_addr).await {
-                eprintln!("  Failed to handle CDAP message: {}", e);
+        Err(e) => {
+            if output == OutputFormat::Json {
+                emit_event(
+                    "failed",
+                    serde_json::json!({ "mode": "bootstrap", "error": e.to_string() }),
+                );
             }
+            eprintln!("\n❌ Bootstrap IPCP failed to start: {}", e);
+            std::process::exit(1);
         }
     }
 }
 
 /// Runs member IPCP mode
-async fn run_member_mode(config: IpcpConfiguration) {
-    println!("=== RINA Member IPCP ===\n");
-
-    // Validate configuration: Route persistence is not applicable to members
-    if config.enable_route_persistence {
-        eprintln!("⚠️  WARNING: Route persistence is IGNORED in member mode!");
-        eprintln!("    Members learn routes dynamically from bootstrap during enrollment.");
-        eprintln!("    Only bootstrap IPCPs should enable route persistence.");
-        eprintln!(
-            "    Set enable_route_persistence=false in the member configuration to remove this warning.\n"
-        );
-    }
-
-    // Member starts with address 0 (will request dynamic assignment during enrollment)
-    let local_addr = config.address.unwrap_or(0);
-
-    // Initialize RIB for member IPCP
-    println!("✓ Initializing RIB...");
-    let rib = ari::rib::Rib::new();
-    rib.create(
-        "/dif/name".to_string(),
-        "dif_info".to_string(),
-        RibValue::String(config.dif_name.clone()),
-    )
-    .await
-    .unwrap();
-    let rib_arc = Arc::new(RwLock::new(rib));
-
-    // Initialize Shim and Flow Allocator
-    println!("✓ Initializing Shim and Flow Allocator...");
-    let shim = Arc::new(UdpShim::new(local_addr));
-
-    // Bind shim to UDP socket
-    if let Err(e) = shim.bind(&config.bind_address) {
-        eprintln!("  Failed to bind shim: {}", e);
-        return;
-    }
-    println!("  Bound to: {}", config.bind_address);
-
-    // Initialize InterIpcpFlowAllocator
-    let rib_for_fal = {
-        let rib_lock = rib_arc.read().await;
-        rib_lock.clone()
-    };
-    let flow_allocator = Arc::new(InterIpcpFlowAllocator::new(rib_for_fal, shim.clone()));
-    println!("  Flow allocator ready\n");
-
-    // Spawn actor tasks
-    println!("✓ Spawning RINA component actors...\n");
-
-    // RIB Actor
-    let (rib_tx, rib_rx) = mpsc::channel(32);
-    let _rib_handle = RibHandle::new(rib_tx);
-    tokio::spawn(async move {
-        let actor = RibActor::new(rib_rx);
-        actor.run().await;
-    });
-    println!("  → RIB Actor spawned");
-
-    // EFCP Actor
-    let (efcp_tx, efcp_rx) = mpsc::channel(32);
-    let _efcp_handle = EfcpHandle::new(efcp_tx);
-    tokio::spawn(async move {
-        let actor = EfcpActor::new(efcp_rx);
-        actor.run().await;
-    });
-    println!("  → EFCP Actor spawned");
-
-    // RMT Actor with FlowAllocator
-    let (rmt_tx, rmt_rx) = mpsc::channel(32);
-    let _rmt_handle = RmtHandle::new(rmt_tx);
-    let fal_for_rmt = flow_allocator.clone();
-    tokio::spawn(async move {
-        let mut actor = RmtActor::new(local_addr, rmt_rx);
-        actor.set_flow_allocator(fal_for_rmt);
-        actor.run().await;
-    });
-    println!("  → RMT Actor spawned\n");
-
-    // Create IPCP
-    let mut ipcp = IpcProcess::with_name_and_address(config.name.clone(), local_addr);
-    ipcp.set_dif_name(config.dif_name.clone());
-    ipcp.set_state(IpcpState::Enrolling);
-
-    println!("✓ Created Member IPCP: {}", config.name);
-    println!("  DIF: {}", config.dif_name);
-    if local_addr == 0 {
-        println!("  Status: Enrolling (will request dynamic address)");
-    } else {
-        println!(
-            "  Status: Enrolling with pre-configured address: {}",
-            local_addr
-        );
-    }
-
-    // Set up async enrollment manager
-    println!("\n✓ Setting up enrollment manager...");
-    let rib = Rib::new();
-
-    // Load RIB snapshot if persistence is enabled
-    if config.enable_rib_persistence {
-        let rib_snapshot_path = std::path::Path::new(&config.rib_snapshot_path);
-        match rib.load_snapshot_from_file(rib_snapshot_path).await {
-            Ok(count) if count > 0 => {
-                println!("  ✓ Loaded {} RIB objects from snapshot", count);
-            }
-            Ok(_) => {
-                println!("  ℹ️  No RIB objects to load from snapshot");
-            }
-            Err(e) => {
-                eprintln!("  ⚠️  Failed to load RIB snapshot: {}", e);
+async fn run_member_mode(config: IpcpConfiguration, output: OutputFormat) {
+    let result = match MemberNode::start(config).await {
+        Ok(node) => {
+            if output == OutputFormat::Json {
+                emit_event("started", serde_json::json!({ "mode": "member" }));
             }
+            node.run().await
         }
-    }
-
-    // Load static routes into RIB (before enrollment)
-    println!("\n✓ Loading static routes into RIB...");
-    for route in &config.static_routes {
-        let route_name = format!("/routing/static/{}", route.destination);
-        let route_value = ari::rib::RibValue::Struct({
-            let mut map = std::collections::HashMap::new();
-            map.insert(
-                "destination".to_string(),
-                Box::new(ari::rib::RibValue::String(route.destination.to_string())),
-            );
-            map.insert(
-                "next_hop_address".to_string(),
-                Box::new(ari::rib::RibValue::String(route.next_hop_address.clone())),
-            );
-            map.insert(
-                "next_hop_rina_addr".to_string(),
-                Box::new(ari::rib::RibValue::Integer(route.next_hop_rina_addr as i64)),
-            );
-            map
-        });
-
-        rib.create(route_name.clone(), "static_route".to_string(), route_value)
-            .await
-            .unwrap();
-
-        println!(
-            "  Route: {} → {} ({})",
-            route.destination, route.next_hop_address, route.next_hop_rina_addr
-        );
-    }
-    println!("  Loaded {} static routes", config.static_routes.len());
-
-    // Clone RIB for snapshot task (if enabled) before moving it to enrollment manager
-    let rib_for_snapshot =
-        if config.enable_rib_persistence && config.rib_snapshot_interval_seconds > 0 {
-            Some(rib.clone())
-        } else {
-            None
-        };
-
-    // Reuse the shim that was already created and bound earlier (no need to bind again)
-
-    let enrollment_config = ari::enrollment::EnrollmentConfig {
-        timeout: std::time::Duration::from_secs(config.enrollment_timeout_secs),
-        max_retries: config.enrollment_max_retries,
-        initial_backoff_ms: config.enrollment_initial_backoff_ms,
-        heartbeat_interval_secs: 30, // Default: heartbeat every 30 seconds
-        connection_timeout_secs: 90, // Default: re-enroll if no heartbeat for 90 seconds
+        Err(e) => Err(e),
     };
-    let mut enrollment_mgr =
-        EnrollmentManager::with_config(rib, shim.clone(), local_addr, enrollment_config);
-    enrollment_mgr.set_ipcp_name(config.name.clone());
-    println!(
-        "  Enrollment manager ready (timeout: {}s, retries: {})",
-        config.enrollment_timeout_secs, config.enrollment_max_retries
-    );
-
-    // Start RIB snapshot task if enabled
-    if let Some(rib_snapshot) = rib_for_snapshot {
-        let rib_snapshot_path = std::path::PathBuf::from(&config.rib_snapshot_path);
-        let rib_snapshot_interval = config.rib_snapshot_interval_seconds;
-        let _rib_snapshot_task = std::sync::Arc::new(rib_snapshot)
-            .start_snapshot_task(rib_snapshot_path, rib_snapshot_interval);
-        println!(
-            "  RIB snapshot task started (interval: {}s)",
-            config.rib_snapshot_interval_seconds
-        );
-    }
-
-    // Attempt enrollment with bootstrap peers
-    println!("\n✓ Initiating enrollment with bootstrap IPCP...");
-    println!("  Bootstrap peers: {:?}", config.bootstrap_peers);
-
-    // Parse bootstrap peer address and map to RINA address
-    let bootstrap_peer: SocketAddr = config.bootstrap_peers[0]
-        .parse()
-        .expect("Invalid bootstrap peer address");
-
-    // For now, use a fixed RINA address for bootstrap (from config)
-    // In a real system, this would come from DNS/discovery
-    let bootstrap_rina_addr = 1001; // Bootstrap IPCP address from config
-
-    // Register bootstrap peer in shim's address mapper
-    shim.register_peer(bootstrap_rina_addr, bootstrap_peer);
-    println!(
-        "  Registered bootstrap peer: {} -> {}",
-        bootstrap_rina_addr, bootstrap_peer
-    );
-
-    println!("\n  Attempting enrollment...");
-    match enrollment_mgr
-        .enrol_with_bootstrap(bootstrap_rina_addr)
-        .await
-    {
-        Ok(dif_name) => {
-            // Get the assigned address (may have been updated during enrollment)
-            let assigned_addr = enrollment_mgr.local_addr();
-            ipcp.address = Some(assigned_addr);
-            ipcp.set_state(IpcpState::Operational);
-
-            println!("\n🎉 Successfully enrolled in DIF: {}", dif_name);
-            if assigned_addr != local_addr {
-                println!("   Assigned RINA address: {}", assigned_addr);
-            }
-            println!("   Member IPCP is now operational!\n");
-
-            // Keep running
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-                println!(
-                    "  [Member IPCP operational in DIF: {} with address: {}]",
-                    dif_name, assigned_addr
-                );
-            }
-        }
-        Err(e) => {
-            eprintln!("\n❌ Enrollment failed: {}", e);
-            ipcp.set_state(IpcpState::Error("Enrollment failed".to_string()));
-            std::process::exit(1);
+    if let Err(e) = result {
+        if output == OutputFormat::Json {
+            emit_event(
+                "failed",
+                serde_json::json!({ "mode": "member", "error": e.to_string() }),
+            );
         }
+        eprintln!("\n❌ Enrollment failed: {}", e);
+        std::process::exit(1);
     }
 }
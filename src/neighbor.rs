@@ -0,0 +1,329 @@
+// SPDX-License-Identifier: EUPL-1.2-or-later
+// Copyright © 2026-present ARI Contributors
+
+//! Neighbor Table
+//!
+//! Tracks the IPCPs this process exchanges messages with, when each was last
+//! heard from, and whether it is still considered reachable. Every entry is
+//! mirrored into the RIB under `/local/neighbors/<name>` so routing can
+//! consume it. Reachability is this IPCP's own view, so the mirror lives in
+//! the local namespace and is never synchronized to peers.
+//!
+//! Neighbors can also be declared up front with `[[neighbor]]` entries in the
+//! configuration file, so a static mesh forms without discovery.
+
+use crate::efcp::MIN_PATH_MTU;
+use crate::enrollment::NeighborInfo;
+use crate::rib::{Rib, RibValue};
+use crate::shim::UdpShim;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// RIB name prefix for mirrored neighbor objects
+pub const NEIGHBOR_RIB_PREFIX: &str = "/local/neighbors/";
+
+/// RIB object class for mirrored neighbor objects
+const NEIGHBOR_CLASS: &str = "neighbor";
+
+/// A neighbor declared in configuration rather than learned at runtime
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NeighborSeed {
+    /// Neighbor IPCP name
+    pub name: String,
+    /// Neighbor RINA address
+    pub address: u64,
+    /// Neighbor shim socket address (host:port)
+    pub socket: String,
+    /// Path MTU towards this neighbor (bytes); EFCP fragments to fit it
+    #[serde(default)]
+    pub mtu: Option<usize>,
+}
+
+impl NeighborSeed {
+    /// Parses the socket address
+    pub fn socket_addr(&self) -> Result<SocketAddr, String> {
+        self.socket.parse().map_err(|e| {
+            format!(
+                "Neighbor '{}' has invalid socket '{}': {}",
+                self.name, self.socket, e
+            )
+        })
+    }
+}
+
+/// Checks seeds for zero addresses, bad sockets and duplicate names or addresses
+pub fn validate_seeds(seeds: &[NeighborSeed]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    let mut addresses = HashSet::new();
+
+    for seed in seeds {
+        if seed.address == 0 {
+            return Err(format!("Neighbor '{}' has address 0", seed.name));
+        }
+        seed.socket_addr()?;
+        if let Some(mtu) = seed.mtu
+            && mtu < MIN_PATH_MTU
+        {
+            return Err(format!(
+                "Neighbor '{}' has MTU {}, below the minimum of {}",
+                seed.name, mtu, MIN_PATH_MTU
+            ));
+        }
+        if !names.insert(seed.name.as_str()) {
+            return Err(format!("Duplicate neighbor name '{}'", seed.name));
+        }
+        if !addresses.insert(seed.address) {
+            return Err(format!("Duplicate neighbor address {}", seed.address));
+        }
+    }
+    Ok(())
+}
+
+/// A single neighbor tracked by the table
+#[derive(Debug, Clone)]
+pub struct NeighborEntry {
+    /// Neighbor IPCP name
+    pub name: String,
+    /// Neighbor RINA address
+    pub address: u64,
+    /// When a message was last received from this neighbor
+    pub last_seen: Instant,
+    /// Whether this neighbor is currently reachable
+    pub reachable: bool,
+}
+
+impl NeighborEntry {
+    /// Converts the entry into the wire-level neighbor description
+    pub fn to_info(&self) -> NeighborInfo {
+        NeighborInfo {
+            name: self.name.clone(),
+            address: self.address,
+            reachable: self.reachable,
+        }
+    }
+
+    fn to_rib_value(&self) -> RibValue {
+        RibValue::struct_builder()
+            .field("name", self.name.clone())
+            .field("address", self.address as i64)
+            .field("reachable", self.reachable)
+            .build()
+    }
+}
+
+/// Live table of neighbor IPCPs keyed by RINA address
+#[derive(Debug, Clone)]
+pub struct NeighborTable {
+    /// Neighbors keyed by address
+    entries: Arc<RwLock<HashMap<u64, NeighborEntry>>>,
+    /// RIB that neighbor objects are mirrored into
+    rib: Rib,
+    /// How long a neighbor may stay silent before it is marked unreachable
+    reachability_timeout: Duration,
+}
+
+impl NeighborTable {
+    /// Creates an empty neighbor table mirroring into `rib`
+    pub fn new(rib: Rib, reachability_timeout: Duration) -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            rib,
+            reachability_timeout,
+        }
+    }
+
+    /// Returns the silence threshold after which neighbors become unreachable
+    pub fn reachability_timeout(&self) -> Duration {
+        self.reachability_timeout
+    }
+
+    /// Records that a message was just received from a neighbor
+    ///
+    /// Inserts the neighbor if it is new and marks it reachable. The RIB is
+    /// only touched when the neighbor's name or reachability changes, so
+    /// frequent heartbeats do not churn the change log.
+    pub async fn record_seen(&self, name: &str, address: u64) {
+        if address == 0 {
+            return;
+        }
+
+        let (entry, stale_name) = {
+            let mut entries = self.entries.write().await;
+            match entries.get_mut(&address) {
+                Some(existing) => {
+                    existing.last_seen = Instant::now();
+                    let renamed = existing.name != name;
+                    if !renamed && existing.reachable {
+                        return;
+                    }
+                    let stale_name =
+                        renamed.then(|| std::mem::replace(&mut existing.name, name.to_string()));
+                    existing.reachable = true;
+                    (existing.clone(), stale_name)
+                }
+                None => {
+                    let entry = NeighborEntry {
+                        name: name.to_string(),
+                        address,
+                        last_seen: Instant::now(),
+                        reachable: true,
+                    };
+                    entries.insert(address, entry.clone());
+                    (entry, None)
+                }
+            }
+        };
+
+        if let Some(old_name) = stale_name {
+            let _ = self
+                .rib
+                .delete(&format!("{}{}", NEIGHBOR_RIB_PREFIX, old_name))
+                .await;
+        }
+
+        progress!(
+            "  ✓ Neighbor {} ({}) is reachable",
+            entry.name,
+            entry.address
+        );
+        self.mirror(&entry).await;
+    }
+
+    /// Loads configured neighbors into the table and `shim`'s address mapper
+    ///
+    /// The seeds are validated first; nothing is loaded if any is invalid.
+    /// Returns the number of neighbors loaded.
+    pub async fn seed(&self, seeds: &[NeighborSeed], shim: &UdpShim) -> Result<usize, String> {
+        validate_seeds(seeds)?;
+
+        for seed in seeds {
+            shim.register_peer(seed.address, seed.socket_addr()?);
+            self.record_seen(&seed.name, seed.address).await;
+        }
+        Ok(seeds.len())
+    }
+
+    /// Marks neighbors silent for longer than the timeout as unreachable
+    ///
+    /// Returns the addresses that changed from reachable to unreachable.
+    pub async fn expire_stale(&self) -> Vec<u64> {
+        let expired: Vec<NeighborEntry> = {
+            let mut entries = self.entries.write().await;
+            entries
+                .values_mut()
+                .filter(|e| e.reachable && e.last_seen.elapsed() > self.reachability_timeout)
+                .map(|e| {
+                    e.reachable = false;
+                    e.clone()
+                })
+                .collect()
+        };
+
+        for entry in &expired {
+            progress!(
+                "  ⚠ Neighbor {} ({}) is unreachable ({}s since last seen)",
+                entry.name,
+                entry.address,
+                entry.last_seen.elapsed().as_secs()
+            );
+            self.mirror(entry).await;
+        }
+
+        expired.iter().map(|e| e.address).collect()
+    }
+
+    /// Returns the addresses of all currently reachable neighbors
+    pub async fn reachable_neighbors(&self) -> Vec<u64> {
+        self.expire_stale().await;
+
+        let entries = self.entries.read().await;
+        let mut addresses: Vec<u64> = entries
+            .values()
+            .filter(|e| e.reachable)
+            .map(|e| e.address)
+            .collect();
+        addresses.sort_unstable();
+        addresses
+    }
+
+    /// Looks up a neighbor by address
+    pub async fn get(&self, address: u64) -> Option<NeighborEntry> {
+        self.entries.read().await.get(&address).cloned()
+    }
+
+    /// Returns a description of every known neighbor
+    pub async fn neighbors(&self) -> Vec<NeighborInfo> {
+        let entries = self.entries.read().await;
+        let mut neighbors: Vec<NeighborInfo> = entries.values().map(|e| e.to_info()).collect();
+        neighbors.sort_by_key(|n| n.address);
+        neighbors
+    }
+
+    /// Writes the entry to its `/local/neighbors/<name>` RIB object
+    async fn mirror(&self, entry: &NeighborEntry) {
+        let name = format!("{}{}", NEIGHBOR_RIB_PREFIX, entry.name);
+        let value = entry.to_rib_value();
+
+        if self.rib.read(&name).await.is_ok() {
+            let _ = self.rib.update(&name, value).await;
+        } else {
+            let _ = self
+                .rib
+                .create(name, NEIGHBOR_CLASS.to_string(), value)
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_neighbor_becomes_unreachable_after_timeout() {
+        let rib = Rib::new();
+        let table = NeighborTable::new(rib.clone(), Duration::from_millis(50));
+
+        table.record_seen("ipcp-a", 2001).await;
+        assert_eq!(table.reachable_neighbors().await, vec![2001]);
+
+        let obj = rib.read("/local/neighbors/ipcp-a").await.unwrap();
+        assert_eq!(obj.class, "neighbor");
+        assert_eq!(
+            obj.value.as_struct().unwrap()["reachable"].as_boolean(),
+            Some(true)
+        );
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(table.reachable_neighbors().await.is_empty());
+        assert!(!table.get(2001).await.unwrap().reachable);
+        let obj = rib.read("/local/neighbors/ipcp-a").await.unwrap();
+        assert_eq!(
+            obj.value.as_struct().unwrap()["reachable"].as_boolean(),
+            Some(false)
+        );
+
+        // Hearing from it again restores reachability
+        table.record_seen("ipcp-a", 2001).await;
+        assert_eq!(table.reachable_neighbors().await, vec![2001]);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_does_not_churn_rib() {
+        let rib = Rib::new();
+        let table = NeighborTable::new(rib.clone(), Duration::from_secs(60));
+
+        table.record_seen("ipcp-b", 2002).await;
+        let version = rib.current_version().await;
+
+        table.record_seen("ipcp-b", 2002).await;
+        table.record_seen("ipcp-b", 2002).await;
+        assert_eq!(rib.current_version().await, version);
+        assert_eq!(table.neighbors().await.len(), 1);
+    }
+}
@@ -0,0 +1,780 @@
+// SPDX-License-Identifier: EUPL-1.2-or-later
+// Copyright © 2026-present ARI Contributors
+
+//! IPCP Nodes
+//!
+//! Wires a complete bootstrap or member IPCP together from an
+//! `IpcpConfiguration`: RIB, static routes, shim, component actors and the
+//! enrollment manager. `start()` builds and binds everything, `run()` serves
+//! (bootstrap) or enrolls and stays operational (member), so embedding an
+//! IPCP takes the same two calls the binary makes.
+
+use crate::actors::{
+    EfcpActor, EfcpHandle, InterIpcpFalActor, InterIpcpFalHandle, RibActor, RibHandle, RmtActor,
+    RmtHandle,
+};
+use crate::config::IpcpConfiguration;
+use crate::discovery::Beacon;
+use crate::efcp::Efcp;
+use crate::enrollment::{EnrollmentConfig, EnrollmentManager, SyncStatus};
+use crate::error::AriError;
+use crate::inter_ipcp_fal::InterIpcpFlowAllocator;
+use crate::ipcp::{IpcProcess, IpcpState};
+use crate::pdu::Pdu;
+use crate::rib::{Rib, RibValue};
+use crate::rmt::Rmt;
+use crate::routing::{Route, RouteResolver, RouteResolverConfig};
+use crate::shim::{ShimError, UdpShim};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{RwLock, mpsc};
+
+/// Snapshot of a bootstrap's receive loop counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BootstrapStats {
+    /// PDUs received and handled without error
+    pub pdus_handled: u64,
+    /// Receives or handlers that failed
+    pub errors: u64,
+    /// Receive waits that ended without a PDU
+    pub idle_waits: u64,
+}
+
+/// Live receive loop counters, readable while the loop runs
+#[derive(Debug, Default)]
+struct BootstrapCounters {
+    pdus_handled: AtomicU64,
+    errors: AtomicU64,
+    idle_waits: AtomicU64,
+}
+
+impl BootstrapCounters {
+    fn snapshot(&self) -> BootstrapStats {
+        BootstrapStats {
+            pdus_handled: self.pdus_handled.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            idle_waits: self.idle_waits.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A bootstrap IPCP: owns the DIF's address pool and enrolls members
+pub struct BootstrapNode {
+    config: IpcpConfiguration,
+    ipcp: IpcProcess,
+    rib: Rib,
+    shim: Arc<UdpShim>,
+    enrollment_mgr: EnrollmentManager,
+    counters: BootstrapCounters,
+}
+
+impl BootstrapNode {
+    /// Builds the bootstrap IPCP and binds its shim
+    ///
+    /// Background tasks (actors, snapshots, route expiry and discovery
+    /// announcements) are spawned here; enrollment requests are only
+    /// served once `run()` is called.
+    pub async fn start(config: IpcpConfiguration) -> Result<Self, AriError> {
+        progress!("=== RINA Bootstrap IPCP ===\n");
+
+        let local_addr = config
+            .address
+            .ok_or_else(|| AriError::Config("Bootstrap mode requires an address".to_string()))?;
+
+        // Initialize RIB first
+        progress!("✓ Initializing RIB...");
+        let rib = configured_rib(&config);
+        rib.create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String(config.dif_name.clone()),
+        )
+        .await?;
+        load_rib_snapshot(&rib, &config).await;
+
+        progress!("\n✓ Loading static routes into RIB...");
+        load_static_routes(&rib, &config).await?;
+        progress!();
+
+        // Initialize RouteResolver
+        progress!("✓ Initializing RouteResolver...");
+        let rib_arc = Arc::new(RwLock::new(rib.clone()));
+        let resolver_config = RouteResolverConfig {
+            enable_persistence: config.enable_route_persistence,
+            snapshot_path: PathBuf::from(&config.route_snapshot_path),
+            default_ttl_seconds: config.route_ttl_seconds,
+            snapshot_interval_seconds: config.route_snapshot_interval_seconds,
+            ..RouteResolverConfig::default()
+        };
+        let route_resolver = Arc::new(RouteResolver::new(rib_arc, resolver_config));
+
+        // Load dynamic routes from snapshot
+        if config.enable_route_persistence {
+            match route_resolver.load_snapshot().await {
+                Ok(count) if count > 0 => {
+                    progress!("  Loaded {} dynamic routes from snapshot", count);
+                }
+                Ok(_) => {
+                    progress!("  No dynamic routes to load from snapshot");
+                }
+                Err(e) => {
+                    eprintln!("  ⚠ Failed to load route snapshot: {}", e);
+                }
+            }
+        }
+
+        // Start snapshot task for periodic saves
+        if config.enable_route_persistence && config.route_snapshot_interval_seconds > 0 {
+            let _snapshot_task = route_resolver.clone().start_snapshot_task();
+            progress!(
+                "  Route snapshot task started (interval: {}s)",
+                config.route_snapshot_interval_seconds
+            );
+        }
+
+        // Start expiry sweep so unqueried expired routes are dropped too
+        let _expiry_task = route_resolver.clone().start_expiry_task();
+
+        if config.enable_rib_persistence && config.rib_snapshot_interval_seconds > 0 {
+            start_rib_snapshot_task(rib.clone(), &config);
+        }
+        progress!();
+
+        // Initialize Shim and Flow Allocator BEFORE spawning actors
+        progress!("✓ Initializing Shim and Flow Allocator...");
+        let shim = Arc::new(configured_shim(&config, local_addr));
+        bind_shim(&shim, &config)?;
+
+        let flow_allocator = Arc::new(InterIpcpFlowAllocator::new(rib.clone(), shim.clone()));
+        progress!("  Flow allocator ready (stale timeout: 300s)\n");
+
+        // Create IPCP
+        let mut ipcp = IpcProcess::with_name_and_address(config.name.clone(), local_addr);
+        ipcp.set_dif_name(config.dif_name.clone());
+        ipcp.fal.set_admission_limits(config.flow_limits);
+        if let Some(cube) = config.qos_cube() {
+            // Published before any member can enrol, so each one receives it
+            if let Err(e) = cube.publish(&rib).await {
+                eprintln!("  ⚠ Failed to publish QoS cube: {}", e);
+            }
+            progress!("  QoS classes offered: {}", cube.profiles().len());
+            ipcp.fal.set_qos_cube(cube);
+        }
+        ipcp.transition_to(IpcpState::Operational)?;
+
+        progress!("✓ Created Bootstrap IPCP: {}", config.name);
+        progress!("  RINA Address: {}", local_addr);
+        progress!("  DIF: {}", config.dif_name);
+
+        progress!("✓ Setting up enrollment manager...");
+        let mut enrollment_mgr = EnrollmentManager::new_bootstrap(
+            rib.clone(),
+            shim.clone(),
+            local_addr,
+            config.address_pool_start,
+            config.address_pool_end,
+        );
+        enrollment_mgr.set_ipcp_name(config.name.clone());
+        enrollment_mgr.set_route_resolver(route_resolver.clone());
+        enrollment_mgr.set_request_rate_limit(
+            config.enrollment_request_rate_limit,
+            config.enrollment_request_burst,
+        );
+        enrollment_mgr.set_rebind_secret(config.enrollment_rebind_secret.clone());
+        enrollment_mgr.set_dif_name_rules(config.dif_name_rules);
+        if let Some(fraction) = config.address_pool_low_watermark {
+            enrollment_mgr.set_address_pool_low_watermark(fraction);
+        }
+        progress!(
+            "  Enrollment manager ready (timeout: {}s, retries: {})",
+            config.enrollment_timeout_secs,
+            config.enrollment_max_retries
+        );
+        seed_neighbors(&config, &enrollment_mgr, &shim).await;
+
+        progress!("✓ Spawning RINA component actors...");
+        spawn_actors(
+            &config,
+            local_addr,
+            flow_allocator,
+            Some(route_resolver),
+            &enrollment_mgr,
+        );
+
+        // The pool itself is the source of truth; the RIB only gets a summary
+        if let Some(pool) = enrollment_mgr.address_pool() {
+            if let Err(e) = pool.mirror_to_rib(&rib).await {
+                eprintln!("  ⚠ Failed to mirror address pool: {}", e);
+            }
+            progress!(
+                "  Address pool: {}-{} ({} available)",
+                config.address_pool_start,
+                config.address_pool_end,
+                pool.available_count()
+            );
+        }
+
+        let node = Self {
+            config,
+            ipcp,
+            rib,
+            shim,
+            enrollment_mgr,
+            counters: BootstrapCounters::default(),
+        };
+        node.start_announcer();
+        Ok(node)
+    }
+
+    /// Announces this bootstrap to members that rely on discovery
+    fn start_announcer(&self) {
+        let discovery = &self.config.discovery;
+        if !discovery.enabled {
+            return;
+        }
+
+        let beacon = Beacon {
+            dif_name: self.config.dif_name.clone(),
+            rina_addr: self.shim.local_rina_addr(),
+            shim_port: self.shim.local_addr().map(|addr| addr.port()).unwrap_or(0),
+        };
+        let interval = Duration::from_secs(discovery.announce_interval_secs.max(1));
+        match discovery.endpoint().spawn_announcer(beacon, interval) {
+            Ok(_) => progress!(
+                "✓ Announcing on {}:{} every {}s",
+                discovery.group,
+                discovery.port,
+                interval.as_secs()
+            ),
+            Err(e) => eprintln!("  ⚠ Discovery announcements disabled: {}", e),
+        }
+    }
+
+    /// Returns the IPC process this node runs
+    pub fn ipcp(&self) -> &IpcProcess {
+        &self.ipcp
+    }
+
+    /// Returns the node's RIB
+    pub fn rib(&self) -> &Rib {
+        &self.rib
+    }
+
+    /// Returns the socket address the shim is bound to
+    pub fn local_socket_addr(&self) -> Result<SocketAddr, ShimError> {
+        self.shim.local_addr()
+    }
+
+    /// Returns the enrollment manager serving members
+    pub fn enrollment_manager(&self) -> &EnrollmentManager {
+        &self.enrollment_mgr
+    }
+
+    /// Returns the receive loop counters accumulated so far
+    pub fn stats(&self) -> BootstrapStats {
+        self.counters.snapshot()
+    }
+
+    /// Serves enrollment and other CDAP requests until `shutdown` fires
+    ///
+    /// The loop also stops if every sender of `shutdown` is dropped.
+    /// Returns the final counters.
+    pub async fn run(&self, mut shutdown: mpsc::Receiver<()>) -> BootstrapStats {
+        progress!("\n🎉 Bootstrap IPCP operational!");
+        progress!("   Waiting for enrollment requests from member IPCPs...\n");
+
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => break,
+                received = self.shim.receive_pdu_async() => self.handle_received(received).await,
+            }
+        }
+
+        let stats = self.stats();
+        progress!(
+            "✓ Bootstrap receive loop stopped ({} PDUs handled, {} errors)",
+            stats.pdus_handled,
+            stats.errors
+        );
+        stats
+    }
+
+    async fn handle_received(&self, received: Result<Option<(Pdu, SocketAddr)>, ShimError>) {
+        let (pdu, src_addr) = match received {
+            Ok(Some(received)) => received,
+            Ok(None) => {
+                self.counters.idle_waits.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            Err(e) => {
+                if let ShimError::Truncated { .. } = e {
+                    eprintln!("  ⚠ Dropped datagram: {}", e);
+                }
+                self.counters.errors.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+
+        progress!(
+            "  Received PDU from address {} ({})",
+            pdu.src_addr,
+            src_addr
+        );
+        match self
+            .enrollment_mgr
+            .handle_cdap_message(&pdu, src_addr)
+            .await
+        {
+            Ok(()) => self.counters.pdus_handled.fetch_add(1, Ordering::Relaxed),
+            Err(e) => {
+                eprintln!("  Failed to handle CDAP message: {}", e);
+                self.counters.errors.fetch_add(1, Ordering::Relaxed)
+            }
+        };
+    }
+}
+
+/// A member IPCP: joins a DIF by enrolling through a bootstrap
+pub struct MemberNode {
+    config: IpcpConfiguration,
+    ipcp: IpcProcess,
+    shim: Arc<UdpShim>,
+    enrollment_mgr: EnrollmentManager,
+}
+
+impl MemberNode {
+    /// Builds the member IPCP and binds its shim, without enrolling yet
+    pub async fn start(config: IpcpConfiguration) -> Result<Self, AriError> {
+        progress!("=== RINA Member IPCP ===\n");
+
+        if config.enable_route_persistence {
+            eprintln!("⚠️  WARNING: Route persistence is IGNORED in member mode!");
+            eprintln!("    Members learn routes dynamically from bootstrap during enrollment.");
+            eprintln!("    Only bootstrap IPCPs should enable route persistence.");
+            eprintln!(
+                "    Set enable_route_persistence=false in the member configuration to remove this warning.\n"
+            );
+        }
+
+        // Member starts with address 0 (will request dynamic assignment during enrollment)
+        let local_addr = config.address.unwrap_or(0);
+
+        progress!("✓ Initializing RIB...");
+        let fal_rib = Rib::new();
+        fal_rib
+            .create(
+                "/dif/name".to_string(),
+                "dif_info".to_string(),
+                RibValue::String(config.dif_name.clone()),
+            )
+            .await?;
+
+        progress!("✓ Initializing Shim and Flow Allocator...");
+        let shim = Arc::new(configured_shim(&config, local_addr));
+        bind_shim(&shim, &config)?;
+
+        let flow_allocator = Arc::new(InterIpcpFlowAllocator::new(fal_rib, shim.clone()));
+        progress!("  Flow allocator ready\n");
+
+        let mut ipcp = IpcProcess::with_name_and_address(config.name.clone(), local_addr);
+        ipcp.set_dif_name(config.dif_name.clone());
+        ipcp.fal.set_admission_limits(config.flow_limits);
+        ipcp.transition_to(IpcpState::Enrolling)?;
+
+        progress!("✓ Created Member IPCP: {}", config.name);
+        progress!("  DIF: {}", config.dif_name);
+        if local_addr == 0 {
+            progress!("  Status: Enrolling (will request dynamic address)");
+        } else {
+            progress!(
+                "  Status: Enrolling with pre-configured address: {}",
+                local_addr
+            );
+        }
+
+        progress!("\n✓ Setting up enrollment manager...");
+        let rib = configured_rib(&config);
+        load_rib_snapshot(&rib, &config).await;
+
+        // Static routes are loaded before enrollment adds the learned ones
+        progress!("\n✓ Loading static routes into RIB...");
+        load_static_routes(&rib, &config).await?;
+
+        if config.enable_rib_persistence && config.rib_snapshot_interval_seconds > 0 {
+            start_rib_snapshot_task(rib.clone(), &config);
+        }
+
+        let enrollment_config = EnrollmentConfig {
+            timeout: Duration::from_secs(config.enrollment_timeout_secs),
+            max_retries: config.enrollment_max_retries,
+            initial_backoff_ms: config.enrollment_initial_backoff_ms,
+            backoff_jitter: config.enrollment_backoff_jitter,
+            heartbeat_interval_secs: 30, // Default: heartbeat every 30 seconds
+            connection_timeout_secs: config.connection_timeout_secs(),
+            poll_interval: Duration::from_millis(config.enrollment_poll_interval_ms),
+        };
+        let mut enrollment_mgr =
+            EnrollmentManager::with_config(rib, shim.clone(), local_addr, enrollment_config);
+        enrollment_mgr.set_ipcp_name(config.name.clone());
+        let address_state_path = config
+            .address_state_path
+            .clone()
+            .unwrap_or_else(|| format!("{}-address.toml", config.name));
+        enrollment_mgr.set_address_state_path(address_state_path.into());
+        enrollment_mgr.set_rebind_secret(config.enrollment_rebind_secret.clone());
+        enrollment_mgr.set_dif_name(config.dif_name.clone());
+        progress!(
+            "  Enrollment manager ready (timeout: {}s, retries: {})",
+            config.enrollment_timeout_secs,
+            config.enrollment_max_retries
+        );
+        seed_neighbors(&config, &enrollment_mgr, &shim).await;
+
+        progress!("✓ Spawning RINA component actors...\n");
+        spawn_actors(&config, local_addr, flow_allocator, None, &enrollment_mgr);
+
+        Ok(Self {
+            config,
+            ipcp,
+            shim,
+            enrollment_mgr,
+        })
+    }
+
+    /// Returns the IPC process this node runs
+    pub fn ipcp(&self) -> &IpcProcess {
+        &self.ipcp
+    }
+
+    /// Returns the socket address the shim is bound to
+    pub fn local_socket_addr(&self) -> Result<SocketAddr, ShimError> {
+        self.shim.local_addr()
+    }
+
+    /// Returns the enrollment manager
+    pub fn enrollment_manager(&self) -> &EnrollmentManager {
+        &self.enrollment_mgr
+    }
+
+    /// Reports how far this member's RIB is in step with the bootstrap
+    pub async fn sync_status(&self) -> SyncStatus {
+        self.enrollment_mgr.sync_status().await
+    }
+
+    /// Enrolls through the configured bootstrap peers, or a discovered one
+    ///
+    /// Returns the name of the DIF joined. On success the IPCP is
+    /// operational with the address the bootstrap assigned; on failure it
+    /// is left in the error state.
+    pub async fn enrol(&mut self) -> Result<String, AriError> {
+        progress!("\n✓ Initiating enrollment with bootstrap IPCP...");
+        progress!("  Bootstrap peers: {:?}", self.config.bootstrap_peers);
+
+        let enrollment = match self.enrol_with_configured_peers().await {
+            Ok(dif_name) => dif_name,
+            Err(e) => {
+                let _ = self
+                    .ipcp
+                    .transition_to(IpcpState::Error("Enrollment failed".to_string()));
+                return Err(e);
+            }
+        };
+
+        let assigned_addr = self.enrollment_mgr.local_addr();
+        self.ipcp.address = Some(assigned_addr);
+        if let Some(cube) = self.enrollment_mgr.qos_cube().await {
+            self.ipcp.fal.set_qos_cube(cube);
+        }
+        if let Err(e) = self.ipcp.transition_to(IpcpState::Operational) {
+            eprintln!("  ⚠ {}", e);
+        }
+
+        progress!("\n🎉 Successfully enrolled in DIF: {}", enrollment);
+        if let Some(peer) = self.enrollment_mgr.bootstrap_peer() {
+            progress!("   Enrolled via bootstrap peer: {}", peer);
+        }
+        if assigned_addr != self.config.address.unwrap_or(0) {
+            progress!("   Assigned RINA address: {}", assigned_addr);
+        }
+        progress!("   Member IPCP is now operational!\n");
+        Ok(enrollment)
+    }
+
+    async fn enrol_with_configured_peers(&mut self) -> Result<String, AriError> {
+        if !self.config.bootstrap_peers.is_empty() {
+            // Each peer is asked for its RINA address before enrolling through it
+            let bootstrap_peers = self
+                .config
+                .bootstrap_peers
+                .iter()
+                .map(|peer| {
+                    peer.parse().map_err(|e| {
+                        AriError::Config(format!("Invalid bootstrap peer '{}': {}", peer, e))
+                    })
+                })
+                .collect::<Result<Vec<SocketAddr>, _>>()?;
+
+            progress!("\n  Attempting enrollment...");
+            return Ok(self
+                .enrollment_mgr
+                .enrol_with_peers(&bootstrap_peers)
+                .await?);
+        }
+
+        // No configured peer: wait for a bootstrap of our DIF to announce itself
+        let discovery = &self.config.discovery;
+        progress!(
+            "  Listening for bootstrap announcements on {}:{}...",
+            discovery.group,
+            discovery.port
+        );
+        let timeout = Duration::from_secs(discovery.timeout_secs);
+        let peer = discovery
+            .endpoint()
+            .listen()?
+            .discover_into(&self.shim, &self.config.dif_name, timeout)
+            .await
+            .inspect_err(|e| eprintln!("\n❌ Bootstrap discovery failed: {}", e))?;
+        progress!(
+            "  Discovered bootstrap peer: {} -> {}",
+            peer.rina_addr,
+            peer.socket_addr
+        );
+
+        progress!("\n  Attempting enrollment...");
+        Ok(self
+            .enrollment_mgr
+            .enrol_with_bootstrap(peer.rina_addr)
+            .await?)
+    }
+
+    /// Enrolls, then keeps the member synchronised with its bootstrap
+    ///
+    /// Only returns if enrollment fails.
+    pub async fn run(mut self) -> Result<(), AriError> {
+        let dif_name = self.enrol().await?;
+
+        // Periodic RIB sync doubles as the bootstrap heartbeat, so the
+        // watchdog only runs alongside it
+        let sync_interval = self.config.rib_sync_interval_secs;
+        if sync_interval > 0 {
+            let _watchdog_task = self.enrollment_mgr.start_connection_monitoring();
+            progress!(
+                "✓ Connection watchdog started (re-enrolls after {}s of silence)",
+                self.config.connection_timeout_secs()
+            );
+        }
+
+        let enrollment_mgr = Arc::new(self.enrollment_mgr);
+        if sync_interval > 0 {
+            let _sync_task = enrollment_mgr.clone().start_sync_task(sync_interval);
+            progress!("✓ RIB sync task started (interval: {}s)\n", sync_interval);
+        }
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            progress!(
+                "  [Member IPCP {:?} in DIF: {} with address: {}]",
+                enrollment_mgr.state(),
+                dif_name,
+                enrollment_mgr.local_addr()
+            );
+        }
+    }
+}
+
+/// Spawns the RIB, EFCP, inter-IPCP FAL and RMT actors
+///
+/// The RIB actor serves `enrollment`'s RIB and reports its sync status. The
+/// RMT resolves next hops through `route_resolver` when one is given and
+/// hands the management PDUs it receives to `enrollment`.
+fn spawn_actors(
+    config: &IpcpConfiguration,
+    local_addr: u64,
+    flow_allocator: Arc<InterIpcpFlowAllocator>,
+    route_resolver: Option<Arc<RouteResolver>>,
+    enrollment: &EnrollmentManager,
+) {
+    let (rib_tx, rib_rx) = mpsc::channel(32);
+    let _rib_handle = RibHandle::new(rib_tx);
+    let mut rib_actor = RibActor::with_rib(enrollment.rib().clone(), rib_rx);
+    rib_actor.set_sync_source(enrollment.shared());
+    tokio::spawn(async move {
+        rib_actor.run().await;
+    });
+    progress!("  → RIB Actor spawned");
+
+    let (efcp_tx, efcp_rx) = mpsc::channel(32);
+    let _efcp_handle = EfcpHandle::new(efcp_tx);
+    let (rmt_tx, rmt_rx) = mpsc::channel(32);
+    let rmt_handle = RmtHandle::new(rmt_tx);
+
+    let mut efcp = Efcp::new();
+    for seed in &config.neighbors {
+        if let Some(mtu) = seed.mtu {
+            efcp.set_peer_mtu(seed.address, mtu);
+        }
+    }
+    let rmt_for_efcp = rmt_handle.clone();
+    tokio::spawn(async move {
+        let mut actor = EfcpActor::with_efcp(efcp, efcp_rx);
+        actor.set_rmt_handle(rmt_for_efcp);
+        actor.run().await;
+    });
+    progress!("  → EFCP Actor spawned");
+
+    let (fal_tx, fal_rx) = mpsc::channel(32);
+    let fal_handle = InterIpcpFalHandle::new(fal_tx);
+    tokio::spawn(async move {
+        let actor = InterIpcpFalActor::new(flow_allocator, fal_rx);
+        actor.run().await;
+    });
+    progress!("  → Inter-IPCP FAL Actor spawned");
+
+    let (management_tx, management_rx) = mpsc::channel(32);
+    enrollment.start_management_handler(management_rx);
+
+    let scheduler_factory = config.policies.scheduling.factory();
+    tokio::spawn(async move {
+        let mut rmt = Rmt::new(local_addr);
+        rmt.set_scheduler_factory(scheduler_factory);
+        let mut actor = RmtActor::with_rmt(rmt, rmt_rx);
+        actor.set_fal_handle(fal_handle);
+        actor.set_management_sender(management_tx);
+        if let Some(resolver) = route_resolver {
+            actor.set_route_resolver(resolver);
+        }
+        actor.run().await;
+    });
+    progress!("  → RMT Actor spawned\n");
+}
+
+/// Creates an unbound shim with the socket settings from `[shim]`
+fn configured_shim(config: &IpcpConfiguration, local_addr: u64) -> UdpShim {
+    let mut shim = UdpShim::new(local_addr);
+    shim.set_max_buffer_size(config.shim_recv_buffer_bytes);
+    shim.set_read_timeout(Duration::from_millis(config.shim_read_timeout_ms));
+    shim.set_so_rcvbuf(config.shim_so_rcvbuf);
+    shim.set_so_sndbuf(config.shim_so_sndbuf);
+    shim.set_max_payload_size(config.shim_max_payload_bytes);
+    shim
+}
+
+/// Binds `shim` to the configured address
+fn bind_shim(shim: &UdpShim, config: &IpcpConfiguration) -> Result<(), AriError> {
+    shim.bind(&config.bind_address).map_err(|e| {
+        AriError::Shim(crate::error::ShimError::BindFailed(format!(
+            "{}: {}",
+            config.bind_address, e
+        )))
+    })?;
+    progress!("  Bound to: {}", config.bind_address);
+    Ok(())
+}
+
+/// Creates the RIB with the snapshot and capacity settings from `[rib]`
+fn configured_rib(config: &IpcpConfiguration) -> Rib {
+    let rib = Rib::new()
+        .with_snapshot_format(config.rib_snapshot_format)
+        .with_snapshot_backups(config.rib_snapshot_backups);
+    if config.rib_max_objects == 0 {
+        return rib;
+    }
+    progress!(
+        "  RIB capped at {} objects ({})",
+        config.rib_max_objects,
+        config.rib_capacity_policy
+    );
+    rib.with_max_objects(config.rib_max_objects, config.rib_capacity_policy)
+}
+
+/// Restores the RIB from its snapshot file if persistence is enabled
+async fn load_rib_snapshot(rib: &Rib, config: &IpcpConfiguration) {
+    if !config.enable_rib_persistence {
+        return;
+    }
+    match rib
+        .load_snapshot_from_file(Path::new(&config.rib_snapshot_path))
+        .await
+    {
+        Ok(count) if count > 0 => {
+            progress!("  ✓ Loaded {} RIB objects from snapshot", count);
+        }
+        Ok(_) => {
+            progress!("  ℹ️  No RIB objects to load from snapshot");
+        }
+        Err(e) => {
+            eprintln!("  ⚠️  Failed to load RIB snapshot: {}", e);
+        }
+    }
+}
+
+/// Periodically saves `rib` to the configured snapshot file
+fn start_rib_snapshot_task(rib: Rib, config: &IpcpConfiguration) {
+    let _rib_snapshot_task = Arc::new(rib).start_snapshot_task(
+        PathBuf::from(&config.rib_snapshot_path),
+        config.rib_snapshot_interval_seconds,
+    );
+    progress!(
+        "  RIB snapshot task started (interval: {}s)",
+        config.rib_snapshot_interval_seconds
+    );
+}
+
+/// Writes the configured static routes into the RIB
+///
+/// Routes with an unparseable next hop are skipped with a warning.
+async fn load_static_routes(rib: &Rib, config: &IpcpConfiguration) -> Result<(), AriError> {
+    for route in &config.static_routes {
+        let Ok(next_hop) = route.next_hop_address.parse() else {
+            eprintln!(
+                "  ⚠ Skipping route to {}: invalid next hop '{}'",
+                route.destination, route.next_hop_address
+            );
+            continue;
+        };
+        let route_value = RibValue::from(
+            Route::new(next_hop)
+                .with_next_hop_rina_addr(route.next_hop_rina_addr)
+                .with_cost(route.cost),
+        );
+
+        rib.create(
+            format!("/routing/static/{}", route.destination),
+            "static_route".to_string(),
+            route_value,
+        )
+        .await?;
+
+        progress!(
+            "  Route: {} → {} ({})",
+            route.destination,
+            route.next_hop_address,
+            route.next_hop_rina_addr
+        );
+    }
+    progress!("  Loaded {} static routes", config.static_routes.len());
+    Ok(())
+}
+
+/// Loads the configured `[[neighbor]]` entries into the neighbor table and shim
+async fn seed_neighbors(
+    config: &IpcpConfiguration,
+    enrollment_mgr: &EnrollmentManager,
+    shim: &UdpShim,
+) {
+    if config.neighbors.is_empty() {
+        return;
+    }
+    match enrollment_mgr
+        .neighbor_table()
+        .seed(&config.neighbors, shim)
+        .await
+    {
+        Ok(count) => progress!("  Seeded {} neighbors from configuration", count),
+        Err(e) => eprintln!("  ⚠ Failed to seed neighbors: {}", e),
+    }
+}
@@ -5,13 +5,46 @@
 //!
 //! Common PDU structures used across RINA components.
 //! Consolidated from various modules for consistency.
+//!
+//! With the `compression` feature (enabled by default), payloads of at least
+//! [`COMPRESSION_THRESHOLD`] bytes are deflate-compressed by
+//! [`Pdu::serialize`] when that makes them smaller, and transparently
+//! decompressed by [`Pdu::deserialize`].
+//!
+//! Every PDU starts with a header version byte. Nodes reject PDUs carrying
+//! a version they do not know instead of misreading the fields behind it.
+//! postcard encodes fields by position and ignores serde defaults, so a
+//! header field cannot be added compatibly: any change to the header needs
+//! a new [`PDU_VERSION`].
 
+use crate::error::{PduError, SerializationError};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// Smallest payload worth compressing; below this the deflate overhead dominates
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Header version written into every PDU this build creates
+pub const PDU_VERSION: u8 = 1;
+
+/// Number of relays a freshly created PDU may traverse before it is dropped
+pub const DEFAULT_HOP_LIMIT: u8 = 64;
+
+/// Upper bound on the bytes a serialized PDU takes besides its payload
+pub const PDU_HEADER_OVERHEAD: usize = 72;
+
+/// Largest payload [`PduBuilder::build`] accepts unless overridden
+pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = 64 * 1024;
+
+/// Upper bound on a decompressed payload, guarding against decompression bombs
+#[cfg(feature = "compression")]
+const MAX_DECOMPRESSED_SIZE: u64 = 16 * 1024 * 1024;
+
 /// Protocol Data Unit (PDU) - the basic unit of data transfer
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Pdu {
+    /// Header version; must stay the first field so it leads the wire format
+    pub version: u8,
     /// Source address
     pub src_addr: u64,
     /// Destination address
@@ -28,6 +61,15 @@ pub struct Pdu {
     pub payload: Vec<u8>,
     /// Quality of Service (QoS) parameters
     pub qos: QoSParameters,
+    /// Whether the payload is compressed (only ever set on the wire)
+    pub compressed: bool,
+    /// Remaining number of relays before the PDU is discarded
+    pub hop_limit: u8,
+    /// Congestion experienced: set by an RMT on a congested data PDU, and
+    /// echoed back to the sender on the receiver's ACK
+    pub congestion_experienced: bool,
+    /// Set on every fragment of an SDU but the last, so EFCP can reassemble it
+    pub more_fragments: bool,
 }
 
 /// Types of PDUs
@@ -78,8 +120,128 @@ impl Default for QoSParameters {
     }
 }
 
+/// Builds PDUs field by field instead of through positional constructors
+#[derive(Debug, Clone)]
+pub struct PduBuilder {
+    pdu: Pdu,
+    max_payload_size: usize,
+}
+
+impl PduBuilder {
+    /// Starts a PDU of the given type with zeroed addressing and default QoS
+    pub fn new(pdu_type: PduType) -> Self {
+        Self {
+            pdu: Pdu {
+                version: PDU_VERSION,
+                src_addr: 0,
+                dst_addr: 0,
+                src_cep_id: 0,
+                dst_cep_id: 0,
+                sequence_num: 0,
+                pdu_type,
+                payload: Vec::new(),
+                qos: QoSParameters::default(),
+                compressed: false,
+                hop_limit: DEFAULT_HOP_LIMIT,
+                congestion_experienced: false,
+                more_fragments: false,
+            },
+            max_payload_size: DEFAULT_MAX_PAYLOAD_SIZE,
+        }
+    }
+
+    /// Sets the source address
+    pub fn src_addr(mut self, addr: u64) -> Self {
+        self.pdu.src_addr = addr;
+        self
+    }
+
+    /// Sets the destination address
+    pub fn dst_addr(mut self, addr: u64) -> Self {
+        self.pdu.dst_addr = addr;
+        self
+    }
+
+    /// Sets the source connection endpoint ID
+    pub fn src_cep_id(mut self, cep_id: u32) -> Self {
+        self.pdu.src_cep_id = cep_id;
+        self
+    }
+
+    /// Sets the destination connection endpoint ID
+    pub fn dst_cep_id(mut self, cep_id: u32) -> Self {
+        self.pdu.dst_cep_id = cep_id;
+        self
+    }
+
+    /// Sets the sequence number
+    pub fn sequence_num(mut self, sequence_num: u64) -> Self {
+        self.pdu.sequence_num = sequence_num;
+        self
+    }
+
+    /// Sets the payload
+    pub fn payload(mut self, payload: Vec<u8>) -> Self {
+        self.pdu.payload = payload;
+        self
+    }
+
+    /// Sets the QoS parameters
+    pub fn qos(mut self, qos: QoSParameters) -> Self {
+        self.pdu.qos = qos;
+        self
+    }
+
+    /// Sets how many relays the PDU may traverse
+    pub fn hop_limit(mut self, hop_limit: u8) -> Self {
+        self.pdu.hop_limit = hop_limit;
+        self
+    }
+
+    /// Sets the largest payload `build` accepts
+    pub fn max_payload_size(mut self, max: usize) -> Self {
+        self.max_payload_size = max;
+        self
+    }
+
+    /// Validates and returns the PDU
+    ///
+    /// Data PDUs must carry non-zero source and destination addresses, and
+    /// no payload may exceed the configured maximum.
+    pub fn build(self) -> Result<Pdu, PduError> {
+        if self.pdu.pdu_type == PduType::Data {
+            if self.pdu.src_addr == 0 {
+                return Err(PduError::ZeroAddress("Source"));
+            }
+            if self.pdu.dst_addr == 0 {
+                return Err(PduError::ZeroAddress("Destination"));
+            }
+        }
+
+        if self.pdu.payload.len() > self.max_payload_size {
+            return Err(PduError::PayloadTooLarge {
+                size: self.pdu.payload.len(),
+                max: self.max_payload_size,
+            });
+        }
+
+        Ok(self.pdu)
+    }
+
+    /// Returns the PDU without validating it
+    ///
+    /// For traffic that legitimately precedes address assignment, such as
+    /// enrollment messages from a member that is still at address 0.
+    pub fn build_unchecked(self) -> Pdu {
+        self.pdu
+    }
+}
+
 impl Pdu {
     /// Creates a new data PDU
+    ///
+    /// Unvalidated shorthand for [`PduBuilder`]; prefer the builder where
+    /// the addresses could be mixed up.
     pub fn new_data(
         src_addr: u64,
         dst_addr: u64,
@@ -88,16 +250,14 @@ impl Pdu {
         sequence_num: u64,
         payload: Vec<u8>,
     ) -> Self {
-        Self {
-            src_addr,
-            dst_addr,
-            src_cep_id,
-            dst_cep_id,
-            sequence_num,
-            pdu_type: PduType::Data,
-            payload,
-            qos: QoSParameters::default(),
-        }
+        PduBuilder::new(PduType::Data)
+            .src_addr(src_addr)
+            .dst_addr(dst_addr)
+            .src_cep_id(src_cep_id)
+            .dst_cep_id(dst_cep_id)
+            .sequence_num(sequence_num)
+            .payload(payload)
+            .build_unchecked()
     }
 
     /// Creates a new data PDU with QoS parameters
@@ -111,6 +271,7 @@ impl Pdu {
         qos: QoSParameters,
     ) -> Self {
         Self {
+            version: PDU_VERSION,
             src_addr,
             dst_addr,
             src_cep_id,
@@ -119,6 +280,10 @@ impl Pdu {
             pdu_type: PduType::Data,
             payload,
             qos,
+            compressed: false,
+            hop_limit: DEFAULT_HOP_LIMIT,
+            congestion_experienced: false,
+            more_fragments: false,
         }
     }
 
@@ -131,6 +296,7 @@ impl Pdu {
         ack_num: u64,
     ) -> Self {
         Self {
+            version: PDU_VERSION,
             src_addr,
             dst_addr,
             src_cep_id,
@@ -139,12 +305,17 @@ impl Pdu {
             pdu_type: PduType::Ack,
             payload: Vec::new(),
             qos: QoSParameters::default(),
+            compressed: false,
+            hop_limit: DEFAULT_HOP_LIMIT,
+            congestion_experienced: false,
+            more_fragments: false,
         }
     }
 
     /// Creates a new management PDU
     pub fn new_management(src_addr: u64, dst_addr: u64, payload: Vec<u8>) -> Self {
         Self {
+            version: PDU_VERSION,
             src_addr,
             dst_addr,
             src_cep_id: 0,
@@ -153,14 +324,18 @@ impl Pdu {
             pdu_type: PduType::Management,
             payload,
             qos: QoSParameters::default(),
+            compressed: false,
+            hop_limit: DEFAULT_HOP_LIMIT,
+            congestion_experienced: false,
+            more_fragments: false,
         }
     }
 
     /// Returns the total size of the PDU in bytes
     pub fn size(&self) -> usize {
         // Header size + payload size
-        // Simplified: 8 + 8 + 4 + 4 + 8 + 1 (type) + payload
-        33 + self.payload.len()
+        // Simplified: 1 (version) + 8 + 8 + 4 + 4 + 8 + 1 (type) + 1 (hop limit) + payload
+        35 + self.payload.len()
     }
 
     /// Checks if this is a data PDU
@@ -179,13 +354,112 @@ impl Pdu {
     }
 
     /// Serializes the PDU to bytes using postcard
+    ///
+    /// Large payloads are compressed when the `compression` feature is enabled.
     pub fn serialize(&self) -> Result<Vec<u8>, String> {
+        #[cfg(feature = "compression")]
+        if !self.compressed
+            && self.payload.len() >= COMPRESSION_THRESHOLD
+            && let Some(payload) = compression::compress(&self.payload)
+        {
+            let wire = Pdu {
+                version: self.version,
+                src_addr: self.src_addr,
+                dst_addr: self.dst_addr,
+                src_cep_id: self.src_cep_id,
+                dst_cep_id: self.dst_cep_id,
+                sequence_num: self.sequence_num,
+                pdu_type: self.pdu_type.clone(),
+                payload,
+                qos: self.qos.clone(),
+                compressed: true,
+                hop_limit: self.hop_limit,
+                congestion_experienced: self.congestion_experienced,
+                more_fragments: self.more_fragments,
+            };
+            return postcard::to_allocvec(&wire)
+                .map_err(|e| format!("Failed to serialize PDU: {}", e));
+        }
+
+        self.serialize_uncompressed()
+    }
+
+    /// Serializes the PDU without compressing it, for peers that cannot
+    /// decompress payloads
+    pub fn serialize_uncompressed(&self) -> Result<Vec<u8>, String> {
         postcard::to_allocvec(self).map_err(|e| format!("Failed to serialize PDU: {}", e))
     }
 
     /// Deserializes a PDU from bytes using postcard
-    pub fn deserialize(data: &[u8]) -> Result<Self, String> {
-        postcard::from_bytes(data).map_err(|e| format!("Failed to deserialize PDU: {}", e))
+    ///
+    /// Compressed payloads are decompressed, so the returned PDU always
+    /// carries the original bytes. The version byte is checked before the
+    /// rest of the header is decoded, as a newer header may not parse at all.
+    pub fn deserialize(data: &[u8]) -> Result<Self, SerializationError> {
+        match data.first() {
+            None => {
+                return Err(SerializationError::InvalidFormat("Empty PDU".to_string()));
+            }
+            Some(&version) if version != PDU_VERSION => {
+                return Err(SerializationError::UnsupportedVersion {
+                    version,
+                    supported: PDU_VERSION,
+                });
+            }
+            Some(_) => {}
+        }
+
+        let pdu: Pdu = postcard::from_bytes(data)?;
+
+        if !pdu.compressed {
+            return Ok(pdu);
+        }
+
+        #[cfg(feature = "compression")]
+        {
+            let payload =
+                compression::decompress(&pdu.payload).map_err(SerializationError::InvalidFormat)?;
+            Ok(Pdu {
+                payload,
+                compressed: false,
+                ..pdu
+            })
+        }
+        #[cfg(not(feature = "compression"))]
+        Err(SerializationError::InvalidFormat(
+            "Received compressed PDU but compression support is disabled".to_string(),
+        ))
+    }
+}
+
+#[cfg(feature = "compression")]
+mod compression {
+    use super::MAX_DECOMPRESSED_SIZE;
+    use flate2::Compression;
+    use flate2::read::DeflateDecoder;
+    use flate2::write::DeflateEncoder;
+    use std::io::{Read, Write};
+
+    /// Deflates `payload`, returning `None` if that would not make it smaller
+    pub(super) fn compress(payload: &[u8]) -> Option<Vec<u8>> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(payload).ok()?;
+        let compressed = encoder.finish().ok()?;
+        (compressed.len() < payload.len()).then_some(compressed)
+    }
+
+    /// Inflates a payload produced by [`compress`]
+    pub(super) fn decompress(payload: &[u8]) -> Result<Vec<u8>, String> {
+        let mut decompressed = Vec::new();
+        DeflateDecoder::new(payload)
+            .take(MAX_DECOMPRESSED_SIZE + 1)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| format!("Failed to decompress PDU payload: {}", e))?;
+
+        if decompressed.len() as u64 > MAX_DECOMPRESSED_SIZE {
+            return Err("Decompressed PDU payload exceeds size limit".to_string());
+        }
+        Ok(decompressed)
     }
 }
 
@@ -193,6 +467,23 @@ impl Pdu {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_header_overhead_bounds_worst_case_header() {
+        let payload = vec![0; 64 * 1024];
+        let mut pdu = Pdu::new_data(u64::MAX, u64::MAX, u32::MAX, u32::MAX, u64::MAX, payload);
+        pdu.qos = QoSParameters {
+            priority: u8::MAX,
+            max_delay_ms: Some(u32::MAX),
+            min_bandwidth_bps: Some(u64::MAX),
+            max_loss_rate: Some(u8::MAX),
+        };
+        pdu.more_fragments = true;
+        pdu.congestion_experienced = true;
+
+        let wire = pdu.serialize_uncompressed().unwrap();
+        assert!(wire.len() - pdu.payload.len() <= PDU_HEADER_OVERHEAD);
+    }
+
     #[test]
     fn test_pdu_creation() {
         let pdu = Pdu::new_data(100, 200, 1, 2, 0, vec![1, 2, 3, 4]);
@@ -227,9 +518,91 @@ mod tests {
         assert!(mgmt_pdu.is_management());
     }
 
+    #[test]
+    fn test_pdu_builder_validates_addresses() {
+        let err = PduBuilder::new(PduType::Data)
+            .src_addr(1001)
+            .payload(vec![1, 2, 3])
+            .build()
+            .unwrap_err();
+        assert_eq!(err, PduError::ZeroAddress("Destination"));
+
+        let err = PduBuilder::new(PduType::Data)
+            .src_addr(1001)
+            .dst_addr(1002)
+            .payload(vec![0; 32])
+            .max_payload_size(16)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, PduError::PayloadTooLarge { size: 32, max: 16 });
+
+        let pdu = PduBuilder::new(PduType::Data)
+            .src_addr(1001)
+            .dst_addr(1002)
+            .src_cep_id(1)
+            .dst_cep_id(2)
+            .sequence_num(7)
+            .payload(vec![1, 2, 3])
+            .build()
+            .unwrap();
+        assert_eq!(pdu, Pdu::new_data(1001, 1002, 1, 2, 7, vec![1, 2, 3]));
+    }
+
     #[test]
     fn test_pdu_size() {
         let pdu = Pdu::new_data(1, 2, 1, 2, 0, vec![0; 100]);
-        assert_eq!(pdu.size(), 133); // 33 byte header + 100 byte payload
+        assert_eq!(pdu.size(), 135); // 35 byte header + 100 byte payload
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_large_payload_is_compressed_on_the_wire() {
+        let payload: Vec<u8> = b"/directory/app -> ipcp-1001\n"
+            .iter()
+            .copied()
+            .cycle()
+            .take(64 * 1024)
+            .collect();
+        let pdu = Pdu::new_data(100, 200, 1, 2, 0, payload.clone());
+
+        let wire = pdu.serialize().unwrap();
+        assert!(
+            wire.len() < payload.len() / 10,
+            "wire size {} not substantially smaller than {}",
+            wire.len(),
+            payload.len()
+        );
+
+        let decoded = Pdu::deserialize(&wire).unwrap();
+        assert!(!decoded.compressed);
+        assert_eq!(decoded.payload, payload);
+        assert_eq!(decoded, pdu);
+    }
+
+    #[test]
+    fn test_small_payload_is_not_compressed() {
+        let pdu = Pdu::new_data(100, 200, 1, 2, 0, vec![7; 64]);
+        let wire = pdu.serialize().unwrap();
+        let raw: Pdu = postcard::from_bytes(&wire).unwrap();
+        assert!(!raw.compressed);
+        assert_eq!(Pdu::deserialize(&wire).unwrap(), pdu);
+    }
+
+    #[test]
+    fn test_unknown_header_version_is_rejected() {
+        let pdu = Pdu::new_data(100, 200, 1, 2, 0, vec![1, 2, 3]);
+        assert_eq!(pdu.version, PDU_VERSION);
+        assert_eq!(Pdu::deserialize(&pdu.serialize().unwrap()).unwrap(), pdu);
+
+        let future = Pdu {
+            version: PDU_VERSION + 1,
+            ..pdu
+        };
+        let err = Pdu::deserialize(&future.serialize().unwrap()).unwrap_err();
+        assert!(matches!(
+            err,
+            SerializationError::UnsupportedVersion { version, supported }
+                if version == PDU_VERSION + 1 && supported == PDU_VERSION
+        ));
     }
 }
@@ -12,6 +12,108 @@ pub mod qos;
 pub mod routing;
 pub mod scheduling;
 
-pub use qos::{QoSPolicy, SimpleQoSPolicy};
-pub use routing::{RoutingPolicy, ShortestPathRouting};
-pub use scheduling::{FifoScheduling, PriorityScheduling, SchedulingPolicy};
+pub use qos::{QOS_CUBE_RIB_NAME, QoSCube, QoSPolicy, QoSProfile, SimpleQoSPolicy};
+pub use routing::{DistanceVectorRouting, RoutingPolicy, ShortestPathRouting};
+pub use scheduling::{FifoScheduling, PriorityScheduling, SchedulingPolicy, WfqScheduling};
+
+use crate::rmt::SchedulerFactory;
+use serde::{Deserialize, Serialize};
+
+/// Routing policy selectable from configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingPolicyKind {
+    /// Dijkstra shortest paths
+    #[default]
+    ShortestPath,
+    /// Bellman-Ford distance vectors
+    DistanceVector,
+}
+
+impl RoutingPolicyKind {
+    /// Constructs the selected routing policy
+    pub fn build(self) -> Box<dyn RoutingPolicy> {
+        match self {
+            RoutingPolicyKind::ShortestPath => Box::new(ShortestPathRouting::new()),
+            RoutingPolicyKind::DistanceVector => Box::new(DistanceVectorRouting::new()),
+        }
+    }
+}
+
+impl std::fmt::Display for RoutingPolicyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoutingPolicyKind::ShortestPath => write!(f, "shortest_path"),
+            RoutingPolicyKind::DistanceVector => write!(f, "distance_vector"),
+        }
+    }
+}
+
+/// Scheduling policy selectable from configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedulingPolicyKind {
+    /// First in, first out
+    Fifo,
+    /// Strict priority classes
+    #[default]
+    Priority,
+    /// Weighted fair queueing across priority classes
+    Wfq,
+}
+
+impl SchedulingPolicyKind {
+    /// Constructs the selected scheduling policy with its default sizing
+    pub fn build(self) -> Box<dyn SchedulingPolicy> {
+        match self {
+            SchedulingPolicyKind::Fifo => Box::new(FifoScheduling::default()),
+            SchedulingPolicyKind::Priority => Box::new(PriorityScheduling::default()),
+            SchedulingPolicyKind::Wfq => Box::new(WfqScheduling::default()),
+        }
+    }
+
+    /// Returns a factory the RMT uses to build sized output queues of this kind
+    pub fn factory(self) -> SchedulerFactory {
+        match self {
+            SchedulingPolicyKind::Fifo => |size| Box::new(FifoScheduling::new(size)),
+            SchedulingPolicyKind::Priority => |size| Box::new(PriorityScheduling::new(4, size)),
+            SchedulingPolicyKind::Wfq => |size| Box::new(WfqScheduling::new(4, size)),
+        }
+    }
+}
+
+impl std::fmt::Display for SchedulingPolicyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchedulingPolicyKind::Fifo => write!(f, "fifo"),
+            SchedulingPolicyKind::Priority => write!(f, "priority"),
+            SchedulingPolicyKind::Wfq => write!(f, "wfq"),
+        }
+    }
+}
+
+/// QoS policy selectable from configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QoSPolicyKind {
+    /// Priority-based dropping once queues fill up
+    #[default]
+    Simple,
+}
+
+impl QoSPolicyKind {
+    /// Constructs the selected QoS policy
+    pub fn build(self) -> Box<dyn QoSPolicy> {
+        match self {
+            QoSPolicyKind::Simple => Box::new(SimpleQoSPolicy::default()),
+        }
+    }
+}
+
+impl std::fmt::Display for QoSPolicyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QoSPolicyKind::Simple => write!(f, "simple"),
+        }
+    }
+}
@@ -3,9 +3,99 @@
 
 //! QoS Policies
 //!
-//! Quality of Service management policies.
+//! Quality of Service management policies, and the QoS cube: the named
+//! classes of service a DIF offers to the flows allocated over it.
 
 use crate::pdu::{Pdu, QoSParameters};
+use crate::rib::{Rib, RibValue};
+use serde::{Deserialize, Serialize};
+
+/// RIB object holding the DIF's QoS cube
+pub const QOS_CUBE_RIB_NAME: &str = "/dif/qos_cube";
+
+/// RIB object class of the QoS cube
+const QOS_CUBE_CLASS: &str = "qos_cube";
+
+/// A named class of service, such as "gold"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QoSProfile {
+    /// Class name flows request the profile by
+    pub name: String,
+    /// Parameters PDUs of the class are sent with
+    pub parameters: QoSParameters,
+}
+
+/// The QoS classes a DIF supports, advertised to members at enrollment
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QoSCube {
+    profiles: Vec<QoSProfile>,
+}
+
+impl QoSCube {
+    /// Creates an empty cube
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a cube offering `profiles`
+    pub fn from_profiles(profiles: impl IntoIterator<Item = QoSProfile>) -> Self {
+        profiles.into_iter().fold(Self::new(), |cube, profile| {
+            cube.with_class(&profile.name, profile.parameters)
+        })
+    }
+
+    /// Adds (or replaces) the class `name`
+    pub fn with_class(mut self, name: &str, parameters: QoSParameters) -> Self {
+        self.profiles.retain(|profile| profile.name != name);
+        self.profiles.push(QoSProfile {
+            name: name.to_string(),
+            parameters,
+        });
+        self
+    }
+
+    /// Looks up the parameters of class `name`
+    pub fn get(&self, name: &str) -> Option<&QoSParameters> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .map(|profile| &profile.parameters)
+    }
+
+    /// Checks whether class `name` is offered
+    pub fn supports(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Returns the offered classes in the order they were added
+    pub fn profiles(&self) -> &[QoSProfile] {
+        &self.profiles
+    }
+
+    /// Reads the cube stored in `rib`, if any
+    pub async fn from_rib(rib: &Rib) -> Option<Self> {
+        match rib.read(QOS_CUBE_RIB_NAME).await.ok()?.value {
+            RibValue::Bytes(bytes) => postcard::from_bytes(&bytes).ok(),
+            _ => None,
+        }
+    }
+
+    /// Stores the cube in `rib`, replacing any previous one
+    pub async fn publish(&self, rib: &Rib) -> Result<(), String> {
+        let value = RibValue::Bytes(postcard::to_allocvec(self).map_err(|e| e.to_string())?);
+        let result = if rib.read(QOS_CUBE_RIB_NAME).await.is_ok() {
+            rib.update(QOS_CUBE_RIB_NAME, value).await
+        } else {
+            rib.create(
+                QOS_CUBE_RIB_NAME.to_string(),
+                QOS_CUBE_CLASS.to_string(),
+                value,
+            )
+            .await
+        };
+        result.map(|_| ()).map_err(|e| e.to_string())
+    }
+}
 
 /// Trait for QoS policies
 pub trait QoSPolicy: Send + Sync {
@@ -76,6 +166,30 @@ impl QoSPolicy for SimpleQoSPolicy {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_qos_cube_round_trips_through_rib() {
+        let rib = Rib::new();
+        assert_eq!(QoSCube::from_rib(&rib).await, None);
+
+        let cube = QoSCube::new()
+            .with_class(
+                "gold",
+                QoSParameters {
+                    priority: 250,
+                    max_delay_ms: Some(20),
+                    ..Default::default()
+                },
+            )
+            .with_class("bronze", QoSParameters::default());
+        cube.publish(&rib).await.unwrap();
+        assert_eq!(QoSCube::from_rib(&rib).await, Some(cube.clone()));
+
+        let smaller = QoSCube::new().with_class("bronze", QoSParameters::default());
+        smaller.publish(&rib).await.unwrap();
+        let stored = QoSCube::from_rib(&rib).await.unwrap();
+        assert!(stored.supports("bronze") && !stored.supports("gold"));
+    }
+
     #[test]
     fn test_qos_check() {
         let policy = SimpleQoSPolicy::default();
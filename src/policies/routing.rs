@@ -141,6 +141,75 @@ impl RoutingPolicy for ShortestPathRouting {
     }
 }
 
+/// Distance-vector routing using Bellman-Ford relaxation
+///
+/// Produces the same next hops as `ShortestPathRouting` on a static
+/// topology, but mirrors how distance-vector protocols converge: every
+/// node's distance estimate is relaxed from its neighbors' estimates until
+/// nothing changes.
+#[derive(Debug, Default)]
+pub struct DistanceVectorRouting {
+    /// Computed routing table: (src, dst) -> next_hop
+    routing_table: HashMap<(u64, u64), u64>,
+}
+
+impl DistanceVectorRouting {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn compute_vectors(&mut self, source: u64, topology: &NetworkTopology) {
+        // dst -> (distance, first hop)
+        let mut vectors: HashMap<u64, (u32, u64)> = HashMap::new();
+        vectors.insert(source, (0, source));
+
+        // At most |V| - 1 rounds are needed for estimates to settle
+        for _ in 0..topology.adjacency.len().max(1) {
+            let mut changed = false;
+            for (&node, links) in &topology.adjacency {
+                let Some(&(distance, first_hop)) = vectors.get(&node) else {
+                    continue;
+                };
+                for &(neighbor, cost) in links {
+                    let candidate = distance.saturating_add(cost);
+                    let hop = if node == source { neighbor } else { first_hop };
+                    if vectors.get(&neighbor).is_none_or(|&(d, _)| candidate < d) {
+                        vectors.insert(neighbor, (candidate, hop));
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        for (dst, (_, next_hop)) in vectors {
+            if dst != source {
+                self.routing_table.insert((source, dst), next_hop);
+            }
+        }
+    }
+}
+
+impl RoutingPolicy for DistanceVectorRouting {
+    fn compute_next_hop(&self, src: u64, dst: u64, _topology: &NetworkTopology) -> Option<u64> {
+        self.routing_table.get(&(src, dst)).copied()
+    }
+
+    fn update(&mut self, topology: &NetworkTopology) {
+        self.routing_table.clear();
+
+        for &source in topology.adjacency.keys() {
+            self.compute_vectors(source, topology);
+        }
+    }
+
+    fn name(&self) -> &str {
+        "DistanceVector"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,6 +229,22 @@ mod tests {
         assert_eq!(next_hop, Some(2));
     }
 
+    #[test]
+    fn test_distance_vector_matches_shortest_path() {
+        let mut topology = NetworkTopology::new();
+        topology.add_link(1, 2, 1);
+        topology.add_link(2, 3, 1);
+        topology.add_link(1, 3, 10);
+        topology.add_link(3, 4, 1);
+
+        let mut policy = DistanceVectorRouting::new();
+        policy.update(&topology);
+
+        assert_eq!(policy.compute_next_hop(1, 3, &topology), Some(2));
+        assert_eq!(policy.compute_next_hop(1, 4, &topology), Some(2));
+        assert_eq!(policy.compute_next_hop(4, 1, &topology), None);
+    }
+
     #[test]
     fn test_topology_neighbors() {
         let mut topology = NetworkTopology::new();
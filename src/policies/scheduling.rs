@@ -9,7 +9,7 @@ use crate::pdu::Pdu;
 use std::collections::VecDeque;
 
 /// Trait for scheduling policies
-pub trait SchedulingPolicy: Send + Sync {
+pub trait SchedulingPolicy: Send + Sync + std::fmt::Debug {
     /// Enqueues a PDU
     fn enqueue(&mut self, pdu: Pdu) -> Result<(), String>;
 
@@ -91,13 +91,18 @@ impl PriorityScheduling {
     }
 
     fn priority_to_queue_index(&self, priority: u8) -> usize {
-        // Map priority (0-255) to queue index
-        // Higher priority gets lower index (served first)
-        let normalized = priority as usize * self.num_priorities / 256;
-        self.num_priorities - 1 - normalized.min(self.num_priorities - 1)
+        priority_class(priority, self.num_priorities)
     }
 }
 
+/// Maps a priority (0-255) onto one of `classes` queue indices
+///
+/// Higher priority gets lower index (served first).
+fn priority_class(priority: u8, classes: usize) -> usize {
+    let normalized = priority as usize * classes / 256;
+    classes - 1 - normalized.min(classes - 1)
+}
+
 impl Default for PriorityScheduling {
     fn default() -> Self {
         Self::new(4, 250) // 4 priority levels, 250 PDUs per queue
@@ -136,6 +141,99 @@ impl SchedulingPolicy for PriorityScheduling {
     }
 }
 
+/// Fixed-point scale for WFQ finish tags, so small PDUs still advance them
+const WFQ_TAG_SCALE: u64 = 1000;
+
+/// Weighted fair queueing scheduling
+///
+/// PDUs are split into priority classes like `PriorityScheduling`, but
+/// instead of strict priority each class receives a share of the link
+/// proportional to its weight. Every PDU is stamped with a virtual finish
+/// time and the PDU with the earliest one is sent next, so low-priority
+/// classes are slowed down rather than starved.
+#[derive(Debug)]
+pub struct WfqScheduling {
+    /// Per-class queues of (finish tag, PDU)
+    queues: Vec<VecDeque<(u64, Pdu)>>,
+    /// Relative share of each class
+    weights: Vec<u64>,
+    /// Finish tag of the last PDU enqueued in each class
+    last_finish: Vec<u64>,
+    /// Finish tag of the last PDU dequeued
+    virtual_time: u64,
+    max_size_per_queue: usize,
+}
+
+impl WfqScheduling {
+    /// Creates a scheduler with `num_classes` classes, weighted so that the
+    /// most urgent class gets `num_classes` shares and the least urgent one
+    pub fn new(num_classes: usize, max_size_per_queue: usize) -> Self {
+        let num_classes = num_classes.max(1);
+        let weights = (1..=num_classes as u64).rev().collect();
+        Self::with_weights(weights, max_size_per_queue)
+    }
+
+    /// Creates a scheduler with explicit class weights, most urgent class first
+    pub fn with_weights(weights: Vec<u64>, max_size_per_queue: usize) -> Self {
+        let weights: Vec<u64> = if weights.is_empty() {
+            vec![1]
+        } else {
+            weights.into_iter().map(|w| w.max(1)).collect()
+        };
+
+        Self {
+            queues: weights.iter().map(|_| VecDeque::new()).collect(),
+            last_finish: vec![0; weights.len()],
+            weights,
+            virtual_time: 0,
+            max_size_per_queue,
+        }
+    }
+}
+
+impl Default for WfqScheduling {
+    fn default() -> Self {
+        Self::new(4, 250)
+    }
+}
+
+impl SchedulingPolicy for WfqScheduling {
+    fn enqueue(&mut self, pdu: Pdu) -> Result<(), String> {
+        let class = priority_class(pdu.qos.priority, self.queues.len());
+        if self.queues[class].len() >= self.max_size_per_queue {
+            return Err(format!("WFQ class {} queue is full", class));
+        }
+
+        let start = self.virtual_time.max(self.last_finish[class]);
+        let finish = start + pdu.size() as u64 * WFQ_TAG_SCALE / self.weights[class];
+        self.last_finish[class] = finish;
+        self.queues[class].push_back((finish, pdu));
+        Ok(())
+    }
+
+    fn dequeue(&mut self) -> Option<Pdu> {
+        let class = self
+            .queues
+            .iter()
+            .enumerate()
+            .filter_map(|(class, queue)| queue.front().map(|(finish, _)| (*finish, class)))
+            .min()?
+            .1;
+
+        let (finish, pdu) = self.queues[class].pop_front()?;
+        self.virtual_time = finish;
+        Some(pdu)
+    }
+
+    fn queue_length(&self) -> usize {
+        self.queues.iter().map(|q| q.len()).sum()
+    }
+
+    fn name(&self) -> &str {
+        "WFQ"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,6 +293,38 @@ mod tests {
         assert_eq!(dequeued.sequence_num, 1);
     }
 
+    #[test]
+    fn test_wfq_shares_link_between_classes() {
+        let mut sched = WfqScheduling::with_weights(vec![3, 1], 10);
+        let with_priority = |seq, priority| {
+            Pdu::new_data_with_qos(
+                1,
+                2,
+                1,
+                2,
+                seq,
+                vec![0; 100],
+                QoSParameters {
+                    priority,
+                    ..Default::default()
+                },
+            )
+        };
+
+        for seq in 0..4 {
+            sched.enqueue(with_priority(seq, 0)).unwrap();
+            sched.enqueue(with_priority(10 + seq, 255)).unwrap();
+        }
+
+        // The urgent class gets three PDUs out for every bulk one,
+        // but bulk traffic is not starved
+        let order: Vec<u64> = std::iter::from_fn(|| sched.dequeue())
+            .map(|pdu| pdu.sequence_num)
+            .take(6)
+            .collect();
+        assert_eq!(order, vec![10, 11, 12, 0, 13, 1]);
+    }
+
     #[test]
     fn test_scheduling_full_queue() {
         let mut sched = FifoScheduling::new(2);
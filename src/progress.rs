@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: EUPL-1.2-or-later
+// Copyright © 2026-present ARI Contributors
+
+//! Progress Output
+//!
+//! Nodes report what they are doing as human-readable lines through
+//! [`progress!`](crate::progress!). These go to stdout unless a binary
+//! reserves stdout for machine-readable output, in which case they go to
+//! stderr instead.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Sends progress lines to stderr (`true`) or stdout (`false`, the default)
+pub fn send_to_stderr(enabled: bool) {
+    TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether progress lines currently go to stderr
+pub fn to_stderr() -> bool {
+    TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// Prints a progress line like `println!`, honouring [`send_to_stderr`]
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if $crate::progress::to_stderr() {
+            ::std::eprintln!($($arg)*)
+        } else {
+            ::std::println!($($arg)*)
+        }
+    };
+}
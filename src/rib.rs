@@ -13,11 +13,39 @@
 //!
 //! The RIB is distributed across all IPCPs in a DIF and kept consistent through CDAP.
 
+use crate::error::{RibError, SerializationError};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, mpsc};
+
+/// Name prefix of objects describing this IPCP alone
+///
+/// Objects under it are never sent to peers, neither as sync changes nor
+/// in the snapshots handed out during enrollment.
+pub const LOCAL_NAMESPACE: &str = "/local/";
+
+/// Returns whether `name` lies in the local namespace
+pub fn is_local_name(name: &str) -> bool {
+    name.starts_with(LOCAL_NAMESPACE)
+}
+
+/// Returns whether a name received from a peer is valid, logging it if not
+fn is_valid_remote_name(name: &str) -> bool {
+    match RibName::parse(name) {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("⚠️  Skipping remote RIB object: {}", e);
+            false
+        }
+    }
+}
 
 /// Represents an object stored in the RIB with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,16 +60,170 @@ pub struct RibObject {
     pub version: u64,
     /// Last modification timestamp (Unix epoch)
     pub last_modified: u64,
+    /// Per-node logical clock (only set when the RIB has a node ID)
+    #[serde(default)]
+    pub clock: Option<VectorClock>,
+}
+
+/// Causal relationship between two vector clocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CausalOrder {
+    /// Both clocks are identical
+    Equal,
+    /// This clock happened before the other
+    Before,
+    /// This clock happened after the other
+    After,
+    /// Neither clock dominates: the edits were concurrent
+    Concurrent,
+}
+
+/// Vector clock mapping node IDs to per-node update counters
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorClock {
+    /// Counter per node ID
+    counters: BTreeMap<String, u64>,
+}
+
+impl VectorClock {
+    /// Creates an empty clock
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one local update by `node_id`
+    pub fn increment(&mut self, node_id: &str) {
+        *self.counters.entry(node_id.to_string()).or_insert(0) += 1;
+    }
+
+    /// Returns the counter for `node_id` (0 if never seen)
+    pub fn get(&self, node_id: &str) -> u64 {
+        self.counters.get(node_id).copied().unwrap_or(0)
+    }
+
+    /// Returns the element-wise maximum of both clocks
+    pub fn merged(&self, other: &VectorClock) -> VectorClock {
+        let mut counters = self.counters.clone();
+        for (node, &count) in &other.counters {
+            let entry = counters.entry(node.clone()).or_insert(0);
+            *entry = (*entry).max(count);
+        }
+        VectorClock { counters }
+    }
+
+    /// Compares this clock against `other`
+    pub fn compare(&self, other: &VectorClock) -> CausalOrder {
+        let mut less = false;
+        let mut greater = false;
+
+        for node in self.counters.keys().chain(other.counters.keys()) {
+            let (mine, theirs) = (self.get(node), other.get(node));
+            less |= mine < theirs;
+            greater |= mine > theirs;
+        }
+
+        match (less, greater) {
+            (false, false) => CausalOrder::Equal,
+            (true, false) => CausalOrder::Before,
+            (false, true) => CausalOrder::After,
+            (true, true) => CausalOrder::Concurrent,
+        }
+    }
+}
+
+/// A validated RIB object name
+///
+/// Names follow the path grammar the rest of the stack relies on when it
+/// splits names like `/routing/static/2000`: an optional leading `/`, then
+/// one or more segments separated by `/`. Each segment is non-empty and made
+/// of ASCII letters, digits, `-`, `_`, `.`, `:` or `@`. Empty segments
+/// (`//`), trailing slashes and other characters are rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RibName(String);
+
+impl RibName {
+    /// Validates `name` against the RIB name grammar
+    pub fn parse(name: impl Into<String>) -> Result<Self, RibError> {
+        let name = name.into();
+        let path = name.strip_prefix('/').unwrap_or(&name);
+        if path.is_empty() {
+            return Err(RibError::InvalidName(format!("'{}' has no segments", name)));
+        }
+
+        for segment in path.split('/') {
+            if segment.is_empty() {
+                return Err(RibError::InvalidName(format!(
+                    "'{}' contains an empty segment",
+                    name
+                )));
+            }
+            if let Some(c) = segment.chars().find(|&c| !Self::is_segment_char(c)) {
+                return Err(RibError::InvalidName(format!(
+                    "'{}' contains invalid character {:?}",
+                    name, c
+                )));
+            }
+        }
+
+        Ok(Self(name))
+    }
+
+    fn is_segment_char(c: char) -> bool {
+        c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '@')
+    }
+
+    /// Returns the name as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the `/`-separated segments of the name
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.trim_start_matches('/').split('/')
+    }
+
+    /// Consumes the name, returning the underlying string
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl std::fmt::Display for RibName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for RibName {
+    type Err = RibError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl AsRef<str> for RibName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
 }
 
 /// Represents different types of values that can be stored in the RIB
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RibValue {
     String(String),
     Integer(i64),
     Boolean(bool),
     Bytes(Vec<u8>),
-    Struct(HashMap<String, Box<RibValue>>),
+    Struct(#[serde(serialize_with = "serialize_sorted_fields")] HashMap<String, Box<RibValue>>),
+}
+
+/// Serializes struct fields in key order so equal values encode identically
+fn serialize_sorted_fields<S: serde::Serializer>(
+    fields: &HashMap<String, Box<RibValue>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(fields.iter().collect::<BTreeMap<_, _>>())
 }
 
 impl RibValue {
@@ -68,6 +250,131 @@ impl RibValue {
             _ => None,
         }
     }
+
+    /// Attempts to extract a struct value
+    pub fn as_struct(&self) -> Option<&HashMap<String, Box<RibValue>>> {
+        match self {
+            RibValue::Struct(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Looks up a field by `.`-separated path through nested structs
+    ///
+    /// `"route.next_hop_address"` reads the `next_hop_address` field of the
+    /// struct held in this struct's `route` field. Returns None if any
+    /// segment is missing or traverses a non-struct value.
+    pub fn get_path(&self, path: &str) -> Option<&RibValue> {
+        path.split('.').try_fold(self, |value, field| {
+            value.as_struct()?.get(field).map(|v| v.as_ref())
+        })
+    }
+
+    /// Reads a string field by path, or None if missing or not a string
+    pub fn get_string_path(&self, path: &str) -> Option<&str> {
+        self.get_path(path)?.as_string()
+    }
+
+    /// Reads an integer field by path, or None if missing or not an integer
+    pub fn get_integer_path(&self, path: &str) -> Option<i64> {
+        self.get_path(path)?.as_integer()
+    }
+
+    /// Starts building a struct value field by field
+    pub fn struct_builder() -> StructBuilder {
+        StructBuilder::default()
+    }
+}
+
+impl From<String> for RibValue {
+    fn from(value: String) -> Self {
+        RibValue::String(value)
+    }
+}
+
+impl From<&str> for RibValue {
+    fn from(value: &str) -> Self {
+        RibValue::String(value.to_string())
+    }
+}
+
+impl From<i64> for RibValue {
+    fn from(value: i64) -> Self {
+        RibValue::Integer(value)
+    }
+}
+
+impl From<bool> for RibValue {
+    fn from(value: bool) -> Self {
+        RibValue::Boolean(value)
+    }
+}
+
+impl From<Vec<u8>> for RibValue {
+    fn from(value: Vec<u8>) -> Self {
+        RibValue::Bytes(value)
+    }
+}
+
+/// Builds a `RibValue::Struct` without spelling out the map and boxes
+#[derive(Debug, Clone, Default)]
+pub struct StructBuilder {
+    fields: HashMap<String, Box<RibValue>>,
+}
+
+impl StructBuilder {
+    /// Sets `name` to `value`, replacing any earlier value for that field
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<RibValue>) -> Self {
+        self.fields.insert(name.into(), Box::new(value.into()));
+        self
+    }
+
+    /// Finishes the struct
+    pub fn build(self) -> RibValue {
+        RibValue::Struct(self.fields)
+    }
+}
+
+/// Encoding used when serializing the RIB
+///
+/// Postcard stays the wire format; JSON gives operators human-readable
+/// snapshots, and bincode is offered for interoperability with other tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerializationFormat {
+    /// Compact binary format (default, used on the wire)
+    #[default]
+    Postcard,
+    /// Human-readable JSON
+    Json,
+    /// Bincode binary format
+    Bincode,
+}
+
+impl std::fmt::Display for SerializationFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SerializationFormat::Postcard => write!(f, "postcard"),
+            SerializationFormat::Json => write!(f, "json"),
+            SerializationFormat::Bincode => write!(f, "bincode"),
+        }
+    }
+}
+
+impl std::str::FromStr for SerializationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "postcard" => Ok(SerializationFormat::Postcard),
+            "json" => Ok(SerializationFormat::Json),
+            "bincode" => Ok(SerializationFormat::Bincode),
+            _ => Err(format!(
+                "Invalid serialization format: {}. Use 'postcard', 'json', or 'bincode'",
+                s
+            )),
+        }
+    }
 }
 
 /// Represents a single change to the RIB for incremental synchronization
@@ -103,20 +410,73 @@ impl RibChange {
             RibChange::Deleted { name, .. } => name,
         }
     }
+
+    /// Returns whether the change concerns this IPCP only and stays off the wire
+    pub fn is_local(&self) -> bool {
+        is_local_name(self.object_name())
+    }
+}
+
+/// Strategy for resolving conflicting copies of the same RIB object
+///
+/// Invoked by `merge_objects` and `apply_changes` when an incoming object's
+/// version is not strictly greater than the local one but its value differs,
+/// i.e. when a plain version comparison would silently drop one of the edits.
+/// When both copies carry a vector clock, it is invoked for concurrent edits.
+pub trait ConflictResolver: Send + Sync + std::fmt::Debug {
+    /// Returns the object to keep, given the local and the incoming copy
+    ///
+    /// The RIB stamps the result with the higher of the two versions.
+    fn resolve(&self, local: &RibObject, incoming: &RibObject) -> RibObject;
+}
+
+/// Default resolver: the most recent write wins
+///
+/// Compares `(version, last_modified)`; ties keep the local copy.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LastWriterWins;
+
+impl ConflictResolver for LastWriterWins {
+    fn resolve(&self, local: &RibObject, incoming: &RibObject) -> RibObject {
+        if (incoming.version, incoming.last_modified) > (local.version, local.last_modified) {
+            incoming.clone()
+        } else {
+            local.clone()
+        }
+    }
+}
+
+/// Resolver that always keeps the local copy on conflict
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreferLocal;
+
+impl ConflictResolver for PreferLocal {
+    fn resolve(&self, local: &RibObject, _incoming: &RibObject) -> RibObject {
+        local.clone()
+    }
 }
 
 /// Change log for incremental RIB synchronization
 ///
 /// Maintains a bounded circular buffer of recent RIB changes to enable
-/// efficient delta-based synchronization between IPCPs.
+/// efficient delta-based synchronization between IPCPs. The buffer is
+/// bounded by entry count and, optionally, by the total serialized size
+/// of the retained changes.
 #[derive(Debug, Clone)]
 pub struct RibChangeLog {
-    /// Ordered list of changes (bounded by max_size)
+    /// Ordered list of changes (bounded by max_size and max_bytes)
     changes: Arc<RwLock<VecDeque<RibChange>>>,
     /// Maximum number of changes to retain
     max_size: usize,
+    /// Maximum total serialized size of retained changes (None = unbounded)
+    max_bytes: Option<usize>,
+    /// Total serialized size of the retained changes
+    total_bytes: Arc<AtomicUsize>,
     /// Oldest version available in change log
     oldest_version: Arc<RwLock<u64>>,
+    /// Highest version received from a remote IPCP, tracked apart from the
+    /// entries so that remote versions never show up as local changes
+    synced_version: Arc<RwLock<u64>>,
 }
 
 impl RibChangeLog {
@@ -125,26 +485,52 @@ impl RibChangeLog {
         Self {
             changes: Arc::new(RwLock::new(VecDeque::with_capacity(max_size))),
             max_size,
+            max_bytes: None,
+            total_bytes: Arc::new(AtomicUsize::new(0)),
             oldest_version: Arc::new(RwLock::new(0)),
+            synced_version: Arc::new(RwLock::new(0)),
         }
     }
 
+    /// Also trims the oldest changes once their total serialized size exceeds `max_bytes`
+    ///
+    /// The newest change is always kept, even if it alone is over budget.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Returns the total serialized size of the retained changes
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+
     /// Add a change to the log
     ///
-    /// If at capacity, removes the oldest change and updates oldest_version
+    /// If over either limit, removes the oldest changes and updates oldest_version
     pub async fn log_change(&self, change: RibChange) {
         let mut changes = self.changes.write().await;
+        self.push(&mut changes, change).await;
+    }
+
+    /// Appends `change`, then trims from the front until both limits hold
+    async fn push(&self, changes: &mut VecDeque<RibChange>, change: RibChange) {
+        let mut total = self.total_bytes.load(Ordering::Relaxed) + serialized_size(&change);
+        changes.push_back(change);
 
-        // Remove oldest if at capacity
-        if changes.len() >= self.max_size
+        let mut trimmed_through = None;
+        while changes.len() > 1
+            && (changes.len() > self.max_size || self.max_bytes.is_some_and(|max| total > max))
             && let Some(removed) = changes.pop_front()
         {
-            let version = removed.version();
-            let mut oldest = self.oldest_version.write().await;
-            *oldest = version + 1;
+            total -= serialized_size(&removed);
+            trimmed_through = Some(removed.version());
         }
+        self.total_bytes.store(total, Ordering::Relaxed);
 
-        changes.push_back(change);
+        if let Some(version) = trimmed_through {
+            *self.oldest_version.write().await = version + 1;
+        }
     }
 
     /// Get all changes since a specific version
@@ -171,10 +557,13 @@ impl RibChangeLog {
             .collect())
     }
 
-    /// Get the current version (latest change)
+    /// Get the current version (latest change or synced remote version)
     pub async fn current_version(&self) -> u64 {
-        let changes = self.changes.read().await;
-        changes.back().map(|change| change.version()).unwrap_or(0)
+        let latest_change = {
+            let changes = self.changes.read().await;
+            changes.back().map(|change| change.version()).unwrap_or(0)
+        };
+        latest_change.max(*self.synced_version.read().await)
     }
 
     /// Get the number of changes currently in the log
@@ -188,42 +577,150 @@ impl RibChangeLog {
         let changes = self.changes.read().await;
         changes.is_empty()
     }
+
+    /// Drops every retained change and moves the baseline to the current version
+    ///
+    /// Peers at the current version keep receiving deltas; anyone older is
+    /// told to do a full sync. Returns the number of changes dropped.
+    pub async fn compact(&self) -> usize {
+        let mut changes = self.changes.write().await;
+        let latest_change = changes.back().map(|change| change.version()).unwrap_or(0);
+        let dropped = changes.len();
+        changes.clear();
+        self.total_bytes.store(0, Ordering::Relaxed);
+
+        // The marker keeps current_version() from falling back to zero
+        let mut synced = self.synced_version.write().await;
+        *synced = (*synced).max(latest_change);
+        *self.oldest_version.write().await = *synced;
+        dropped
+    }
+
     /// Update version tracker when applying remote changes (for sync)
+    ///
     /// This ensures current_version() reflects the latest synced version
+    /// without adding an entry to the log.
     pub async fn update_version_marker(&self, version: u64) {
-        // Add a synthetic marker change to track remote sync version
-        // This doesn't represent a local change but keeps version tracking accurate
-        let mut changes = self.changes.write().await;
+        let mut synced = self.synced_version.write().await;
+        *synced = (*synced).max(version);
+    }
+}
 
-        // Only update if new version is higher
-        if let Some(last) = changes.back()
-            && version <= last.version()
-        {
-            return;
-        }
+/// Path of the `index`th most recent backup of the snapshot at `path`
+fn snapshot_backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
 
-        // Remove oldest if at capacity
-        if changes.len() >= self.max_size
-            && let Some(removed) = changes.pop_front()
-        {
-            let removed_version = removed.version();
-            let mut oldest = self.oldest_version.write().await;
-            *oldest = removed_version + 1;
+/// Writes `data` to `path` through a temporary file renamed over it on success
+///
+/// A crash or failed write leaves the previous file at `path` untouched.
+pub(crate) fn write_file_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    write_atomically_with(path, |file| file.write_all(data))
+}
+
+/// Runs `write` against a temporary file next to `path`, then renames it into place
+fn write_atomically_with(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    // Same directory, so the rename never crosses file systems
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Serializes objects in name order, so equal contents give equal bytes
+fn encode_objects<'a>(
+    objects: impl IntoIterator<Item = &'a RibObject>,
+    format: SerializationFormat,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut all_objects: Vec<&RibObject> = objects.into_iter().collect();
+    all_objects.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(match format {
+        SerializationFormat::Postcard => postcard::to_allocvec(&all_objects)?,
+        SerializationFormat::Json => serde_json::to_vec_pretty(&all_objects)?,
+        SerializationFormat::Bincode => bincode::serialize(&all_objects)?,
+    })
+}
+
+/// Size of a change as it would be sent in a sync response
+fn serialized_size(change: &RibChange) -> usize {
+    postcard::to_allocvec(change).map_or(0, |bytes| bytes.len())
+}
+
+/// What happens when an object is created in a store that is already full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapacityPolicy {
+    /// Refuse the new object with `RibError::CapacityExceeded`
+    #[default]
+    Reject,
+    /// Remove the least recently modified object to make room
+    EvictOldest,
+}
+
+impl std::fmt::Display for CapacityPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CapacityPolicy::Reject => write!(f, "reject"),
+            CapacityPolicy::EvictOldest => write!(f, "evict_oldest"),
         }
+    }
+}
 
-        // Add a marker indicating sync to this version
-        // Use a dummy deleted entry as a version marker
-        changes.push_back(RibChange::Deleted {
-            name: format!("__sync_marker_{}", version),
-            version,
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        });
+/// Lets only one snapshot save run at a time
+///
+/// Clones share the same lock. On-demand saves wait their turn with `run`,
+/// while periodic saves use `try_run` so a tick that lands during a slow
+/// save is dropped instead of piling up behind it.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotGuard {
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl SnapshotGuard {
+    /// Waits for any in-progress save, then runs `save`
+    pub async fn run<F: Future>(&self, save: F) -> F::Output {
+        let _held = self.lock.lock().await;
+        save.await
+    }
+
+    /// Runs `save` unless another save is in progress, in which case returns `None`
+    pub async fn try_run<F: Future>(&self, save: F) -> Option<F::Output> {
+        let _held = self.lock.try_lock().ok()?;
+        Some(save.await)
     }
 }
 
+/// Buffered changes per RIB subscriber before new changes are dropped
+const RIB_CHANGE_CHANNEL_CAPACITY: usize = 64;
+
+/// A receiver of changes to objects under a name prefix
+#[derive(Debug)]
+struct RibSubscriber {
+    prefix: String,
+    tx: mpsc::Sender<RibChange>,
+}
+
 /// The Resource Information Base
 ///
 /// Thread-safe storage for all IPC Process state information.
@@ -236,6 +733,22 @@ pub struct Rib {
     version_counter: Arc<RwLock<u64>>,
     /// Change log for incremental synchronization
     change_log: RibChangeLog,
+    /// Strategy for conflicting remote updates
+    conflict_resolver: Arc<dyn ConflictResolver>,
+    /// Node ID used to stamp vector clocks (None = scalar versions only)
+    node_id: Option<String>,
+    /// Format used for snapshot files
+    snapshot_format: SerializationFormat,
+    /// Number of previous snapshots kept as `<path>.1` ... `<path>.N`
+    snapshot_backups: usize,
+    /// Keeps periodic and on-demand snapshot saves from overlapping
+    snapshot_guard: SnapshotGuard,
+    /// Maximum number of objects `create` admits (None = unbounded)
+    max_objects: Option<usize>,
+    /// What `create` does once `max_objects` is reached
+    capacity_policy: CapacityPolicy,
+    /// Receivers of changes, local or synchronized
+    subscribers: Arc<std::sync::Mutex<Vec<RibSubscriber>>>,
 }
 
 impl Rib {
@@ -250,6 +763,126 @@ impl Rib {
             objects: Arc::new(RwLock::new(HashMap::new())),
             version_counter: Arc::new(RwLock::new(0)),
             change_log: RibChangeLog::new(change_log_size),
+            conflict_resolver: Arc::new(LastWriterWins),
+            node_id: None,
+            snapshot_format: SerializationFormat::default(),
+            snapshot_backups: 0,
+            snapshot_guard: SnapshotGuard::default(),
+            max_objects: None,
+            capacity_policy: CapacityPolicy::default(),
+            subscribers: Arc::new(std::sync::Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Subscribes to changes of objects whose name starts with `prefix`
+    ///
+    /// Local changes and changes applied by synchronization are both
+    /// delivered. A subscriber that falls behind misses changes rather than
+    /// stalling the RIB, and closed receivers are pruned.
+    pub fn subscribe(&self, prefix: &str) -> mpsc::Receiver<RibChange> {
+        let (tx, rx) = mpsc::channel(RIB_CHANGE_CHANNEL_CAPACITY);
+        self.subscribers.lock().unwrap().push(RibSubscriber {
+            prefix: prefix.to_string(),
+            tx,
+        });
+        rx
+    }
+
+    /// Delivers a change to every live subscriber whose prefix matches
+    fn notify(&self, change: &RibChange) {
+        let name = change.object_name();
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            if !name.starts_with(&subscriber.prefix) {
+                return !subscriber.tx.is_closed();
+            }
+            match subscriber.tx.try_send(change.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    eprintln!("⚠️  RIB subscriber lagging, dropped change to {}", name);
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            }
+        });
+    }
+
+    /// Logs a local change for incremental sync and tells subscribers about it
+    async fn record(&self, change: RibChange) {
+        self.notify(&change);
+        self.change_log.log_change(change).await;
+    }
+
+    /// Caps the number of objects `create` admits, applying `policy` once full
+    ///
+    /// Objects arriving through synchronization are not counted against the
+    /// cap, so every IPCP in a DIF converges on the same state.
+    pub fn with_max_objects(mut self, max: usize, policy: CapacityPolicy) -> Self {
+        self.max_objects = Some(max);
+        self.capacity_policy = policy;
+        self
+    }
+
+    /// Keeps the previous `count` snapshots as backups to fall back on when loading
+    pub fn with_snapshot_backups(mut self, count: usize) -> Self {
+        self.snapshot_backups = count;
+        self
+    }
+
+    /// Bounds the change log by the total serialized size of its entries as well
+    pub fn with_change_log_byte_budget(mut self, max_bytes: usize) -> Self {
+        self.change_log = self.change_log.with_max_bytes(max_bytes);
+        self
+    }
+
+    /// Sets the format used by `save_snapshot_to_file`/`load_snapshot_from_file`
+    pub fn with_snapshot_format(mut self, format: SerializationFormat) -> Self {
+        self.snapshot_format = format;
+        self
+    }
+
+    /// Enables vector clocks, stamping local writes with `node_id`
+    pub fn with_node_id(mut self, node_id: impl Into<String>) -> Self {
+        self.node_id = Some(node_id.into());
+        self
+    }
+
+    /// Replaces the conflict resolver used when merging remote objects
+    pub fn with_conflict_resolver(mut self, resolver: Arc<dyn ConflictResolver>) -> Self {
+        self.conflict_resolver = resolver;
+        self
+    }
+
+    /// Decides what to store when `incoming` meets an existing local object
+    ///
+    /// Returns `None` when the local object should be left untouched.
+    fn reconcile(&self, local: &RibObject, incoming: RibObject) -> Option<RibObject> {
+        // With clocks on both sides, causality decides instead of versions
+        if let (Some(local_clock), Some(incoming_clock)) = (&local.clock, &incoming.clock) {
+            return match incoming_clock.compare(local_clock) {
+                CausalOrder::After => Some(incoming),
+                CausalOrder::Before | CausalOrder::Equal => None,
+                CausalOrder::Concurrent => {
+                    let mut resolved = self.conflict_resolver.resolve(local, &incoming);
+                    resolved.version = local.version.max(incoming.version);
+                    resolved.clock = Some(local_clock.merged(incoming_clock));
+                    Some(resolved)
+                }
+            };
+        }
+
+        if incoming.version > local.version {
+            return Some(incoming);
+        }
+        if incoming.value == local.value {
+            return None;
+        }
+
+        let mut resolved = self.conflict_resolver.resolve(local, &incoming);
+        resolved.version = local.version.max(incoming.version);
+        if resolved.value == local.value && resolved.class == local.class {
+            None
+        } else {
+            Some(resolved)
         }
     }
 
@@ -262,12 +895,46 @@ impl Rib {
     ///
     /// # Returns
     /// * `Ok(())` if the object was created successfully
-    /// * `Err(String)` if an object with that name already exists
-    pub async fn create(&self, name: String, class: String, value: RibValue) -> Result<(), String> {
+    /// * `Err(RibError::AlreadyExists)` if an object with that name already exists
+    /// * `Err(RibError::InvalidName)` if the name does not follow the `RibName` grammar
+    pub async fn create(
+        &self,
+        name: String,
+        class: String,
+        value: RibValue,
+    ) -> Result<(), RibError> {
+        let name = RibName::parse(name)?.into_string();
         let mut objects = self.objects.write().await;
 
         if objects.contains_key(&name) {
-            return Err(format!("Object '{}' already exists", name));
+            return Err(RibError::AlreadyExists(name));
+        }
+
+        if let Some(max) = self.max_objects
+            && objects.len() >= max
+        {
+            let stalest = match self.capacity_policy {
+                CapacityPolicy::Reject => None,
+                CapacityPolicy::EvictOldest => objects
+                    .values()
+                    .min_by_key(|obj| (obj.last_modified, obj.version))
+                    .map(|obj| obj.name.clone()),
+            };
+            let Some(evicted) = stalest else {
+                return Err(RibError::CapacityExceeded { name, max });
+            };
+
+            objects.remove(&evicted);
+            let version = self.next_version().await;
+            self.record(RibChange::Deleted {
+                name: evicted,
+                version,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            })
+            .await;
         }
 
         let version = self.next_version().await;
@@ -276,18 +943,23 @@ impl Rib {
             .unwrap()
             .as_secs();
 
+        let clock = self.node_id.as_deref().map(|node_id| {
+            let mut clock = VectorClock::new();
+            clock.increment(node_id);
+            clock
+        });
+
         let obj = RibObject {
             name: name.clone(),
             class,
             value,
             version,
             last_modified: now,
+            clock,
         };
 
         // Log the change for incremental sync
-        self.change_log
-            .log_change(RibChange::Created(obj.clone()))
-            .await;
+        self.record(RibChange::Created(obj.clone())).await;
 
         objects.insert(name, obj);
         Ok(())
@@ -299,11 +971,14 @@ impl Rib {
     /// * `name` - The name of the object to retrieve
     ///
     /// # Returns
-    /// * `Some(RibObject)` if found
-    /// * `None` if not found
-    pub async fn read(&self, name: &str) -> Option<RibObject> {
+    /// * `Ok(RibObject)` if found
+    /// * `Err(RibError::NotFound)` if not found
+    pub async fn read(&self, name: &str) -> Result<RibObject, RibError> {
         let objects = self.objects.read().await;
-        objects.get(name).cloned()
+        objects
+            .get(name)
+            .cloned()
+            .ok_or_else(|| RibError::NotFound(name.to_string()))
     }
 
     /// Updates an existing RIB object
@@ -314,14 +989,55 @@ impl Rib {
     ///
     /// # Returns
     /// * `Ok(())` if updated successfully
-    /// * `Err(String)` if the object doesn't exist
-    pub async fn update(&self, name: &str, value: RibValue) -> Result<(), String> {
+    /// * `Err(RibError::NotFound)` if the object doesn't exist
+    pub async fn update(&self, name: &str, value: RibValue) -> Result<(), RibError> {
+        self.update_inner(name, value, None).await
+    }
+
+    /// Updates an existing RIB object only if it is still at `expected_version`
+    ///
+    /// Lets a caller that read an object apply its edit without silently
+    /// overwriting a concurrent change.
+    ///
+    /// # Returns
+    /// * `Ok(())` if updated successfully
+    /// * `Err(RibError::NotFound)` if the object doesn't exist
+    /// * `Err(RibError::VersionConflict)` if the object has moved on
+    pub async fn update_if_version(
+        &self,
+        name: &str,
+        value: RibValue,
+        expected_version: u64,
+    ) -> Result<(), RibError> {
+        self.update_inner(name, value, Some(expected_version)).await
+    }
+
+    async fn update_inner(
+        &self,
+        name: &str,
+        value: RibValue,
+        expected_version: Option<u64>,
+    ) -> Result<(), RibError> {
         let mut objects = self.objects.write().await;
 
         match objects.get_mut(name) {
             Some(obj) => {
+                if let Some(expected) = expected_version
+                    && obj.version != expected
+                {
+                    return Err(RibError::VersionConflict {
+                        expected,
+                        actual: obj.version,
+                    });
+                }
+
                 obj.value = value;
                 obj.version = self.next_version().await;
+                if let Some(node_id) = &self.node_id {
+                    obj.clock
+                        .get_or_insert_with(VectorClock::new)
+                        .increment(node_id);
+                }
                 obj.last_modified = SystemTime::now()
                     .duration_since(UNIX_EPOCH)
                     .unwrap()
@@ -330,13 +1046,11 @@ impl Rib {
                 // Log the change for incremental sync
                 let updated_obj = obj.clone();
                 drop(objects); // Release lock before logging
-                self.change_log
-                    .log_change(RibChange::Updated(updated_obj))
-                    .await;
+                self.record(RibChange::Updated(updated_obj)).await;
 
                 Ok(())
             }
-            None => Err(format!("Object '{}' not found", name)),
+            None => Err(RibError::NotFound(name.to_string())),
         }
     }
 
@@ -347,8 +1061,8 @@ impl Rib {
     ///
     /// # Returns
     /// * `Ok(())` if deleted successfully
-    /// * `Err(String)` if the object doesn't exist
-    pub async fn delete(&self, name: &str) -> Result<(), String> {
+    /// * `Err(RibError::NotFound)` if the object doesn't exist
+    pub async fn delete(&self, name: &str) -> Result<(), RibError> {
         let mut objects = self.objects.write().await;
 
         match objects.remove(name) {
@@ -359,20 +1073,19 @@ impl Rib {
                 // Increment version for this deletion
                 let new_version = self.next_version().await;
 
-                self.change_log
-                    .log_change(RibChange::Deleted {
-                        name: deleted_name,
-                        version: new_version,
-                        timestamp: SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                    })
-                    .await;
+                self.record(RibChange::Deleted {
+                    name: deleted_name,
+                    version: new_version,
+                    timestamp: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs(),
+                })
+                .await;
 
                 Ok(())
             }
-            None => Err(format!("Object '{}' not found", name)),
+            None => Err(RibError::NotFound(name.to_string())),
         }
     }
 
@@ -392,6 +1105,29 @@ impl Rib {
             .collect()
     }
 
+    /// Returns the objects of a given class whose value satisfies `predicate`
+    ///
+    /// # Arguments
+    /// * `class` - The object class to filter by
+    /// * `predicate` - Test applied to each candidate's value
+    ///
+    /// # Returns
+    /// Full copies of the matching objects, ordered by name
+    pub async fn query(
+        &self,
+        class: &str,
+        predicate: impl Fn(&RibValue) -> bool,
+    ) -> Vec<RibObject> {
+        let objects = self.objects.read().await;
+        let mut matches: Vec<RibObject> = objects
+            .values()
+            .filter(|obj| obj.class == class && predicate(&obj.value))
+            .cloned()
+            .collect();
+        matches.sort_by(|a, b| a.name.cmp(&b.name));
+        matches
+    }
+
     /// Lists all object names in the RIB
     pub async fn list_all(&self) -> Vec<String> {
         let objects = self.objects.read().await;
@@ -412,24 +1148,64 @@ impl Rib {
 
     /// Serializes the entire RIB into a byte vector for synchronization
     ///
-    /// Uses bincode for efficient binary serialization
+    /// Uses postcard for efficient binary serialization
     ///
     /// # Returns
     /// A serialized representation of all RIB objects
     pub async fn serialize(&self) -> Vec<u8> {
-        let objects = self.objects.read().await;
+        self.serialize_as(SerializationFormat::Postcard)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to serialize RIB: {}", e);
+                Vec::new()
+            })
+    }
 
-        // Collect all objects into a vector
-        let all_objects: Vec<RibObject> = objects.values().cloned().collect();
+    /// Serializes the entire RIB using the given format
+    ///
+    /// Objects are written in name order, so RIBs with the same content
+    /// produce the same bytes regardless of insertion order.
+    pub async fn serialize_as(
+        &self,
+        format: SerializationFormat,
+    ) -> Result<Vec<u8>, SerializationError> {
+        let objects = self.objects.read().await;
+        encode_objects(objects.values(), format)
+    }
 
-        // Serialize using postcard
-        postcard::to_allocvec(&all_objects).unwrap_or_else(|e| {
+    /// Serializes the objects peers may see, leaving out the local namespace
+    ///
+    /// This is the snapshot handed to members during enrollment and sync.
+    pub async fn serialize_shared(&self) -> Vec<u8> {
+        let objects = self.objects.read().await;
+        let shared = objects.values().filter(|obj| !is_local_name(&obj.name));
+        encode_objects(shared, SerializationFormat::Postcard).unwrap_or_else(|e| {
             eprintln!("Failed to serialize RIB: {}", e);
             Vec::new()
         })
     }
 
-    /// Deserializes a RIB snapshot and merges it into this RIB
+    /// Discards the change history and returns a snapshot of the live objects
+    ///
+    /// Deleted objects only survive as `RibChange::Deleted` entries in the
+    /// change log, so after heavy churn the log is what keeps growing. This
+    /// drops it and resets its baseline to the current version; object
+    /// versions are preserved so peers can keep comparing against them.
+    /// The snapshot uses the configured snapshot format.
+    pub async fn compact(&self) -> Result<Vec<u8>, SerializationError> {
+        // Held throughout so no change lands between the snapshot and the reset
+        let objects = self.objects.write().await;
+        let dropped = self.change_log.compact().await;
+        let snapshot = encode_objects(objects.values(), self.snapshot_format)?;
+        progress!(
+            "  ✓ Compacted RIB: {} live objects, {} logged changes dropped",
+            objects.len(),
+            dropped
+        );
+        Ok(snapshot)
+    }
+
+    /// Deserializes a RIB snapshot and merges it into this RIB
     ///
     /// Uses postcard for deserialization
     ///
@@ -440,17 +1216,29 @@ impl Rib {
     /// * `Ok(usize)` with the number of objects synchronized
     /// * `Err(String)` if deserialization fails
     pub async fn deserialize(&self, data: &[u8]) -> Result<usize, String> {
+        self.deserialize_as(data, SerializationFormat::Postcard)
+            .await
+            .map_err(|e| format!("Failed to deserialize RIB: {}", e))
+    }
+
+    /// Deserializes a RIB snapshot in the given format and merges it into this RIB
+    pub async fn deserialize_as(
+        &self,
+        data: &[u8],
+        format: SerializationFormat,
+    ) -> Result<usize, SerializationError> {
         if data.is_empty() {
             return Ok(0);
         }
 
-        // Deserialize using postcard
-        let objects: Vec<RibObject> =
-            postcard::from_bytes(data).map_err(|e| format!("Failed to deserialize RIB: {}", e))?;
+        let objects: Vec<RibObject> = match format {
+            SerializationFormat::Postcard => postcard::from_bytes(data)?,
+            SerializationFormat::Json => serde_json::from_slice(data)?,
+            SerializationFormat::Bincode => bincode::deserialize(data)?,
+        };
 
         // Merge objects into RIB
-        let count = self.merge_objects(objects).await;
-        Ok(count)
+        Ok(self.merge_objects(objects).await)
     }
 
     /// Gets all objects from the RIB (for synchronization)
@@ -461,6 +1249,11 @@ impl Rib {
 
     /// Merges objects from another RIB, using version numbers to resolve conflicts
     ///
+    /// Objects whose version is not newer but whose value differs are handed
+    /// to the configured `ConflictResolver`. Objects whose name does not
+    /// follow the `RibName` grammar are skipped. Subscribers are told about
+    /// every object that is created or updated.
+    ///
     /// # Arguments
     /// * `objects` - Objects to merge into this RIB
     ///
@@ -476,18 +1269,23 @@ impl Rib {
             if obj.version > max_version {
                 max_version = obj.version;
             }
+            if !is_valid_remote_name(&obj.name) {
+                continue;
+            }
 
             match local_objects.get(&obj.name) {
                 Some(existing) => {
-                    // Only update if incoming version is newer
-                    if obj.version > existing.version {
-                        local_objects.insert(obj.name.clone(), obj);
+                    // Newer versions win; conflicts go to the resolver
+                    if let Some(merged) = self.reconcile(existing, obj) {
+                        local_objects.insert(merged.name.clone(), merged.clone());
+                        self.notify(&RibChange::Updated(merged));
                         merged_count += 1;
                     }
                 }
                 None => {
                     // New object, add it
-                    local_objects.insert(obj.name.clone(), obj);
+                    local_objects.insert(obj.name.clone(), obj.clone());
+                    self.notify(&RibChange::Created(obj));
                     merged_count += 1;
                 }
             }
@@ -525,7 +1323,8 @@ impl Rib {
     /// Apply incremental changes to RIB (for members receiving sync from bootstrap)
     ///
     /// Note: This method does NOT log changes to the change log, as these changes
-    /// originated from a remote IPCP and should not be re-propagated.
+    /// originated from a remote IPCP and should not be re-propagated. Changes
+    /// to names that do not follow the `RibName` grammar are skipped.
     ///
     /// # Returns
     /// The number of changes successfully applied
@@ -539,33 +1338,33 @@ impl Rib {
             if change_version > max_version {
                 max_version = change_version;
             }
+            if !is_valid_remote_name(change.object_name()) {
+                continue;
+            }
 
+            // Don't log these changes (they came from remote), but do
+            // tell subscribers about the ones that took effect
             match change {
-                RibChange::Created(obj) => {
-                    // Don't log this change (it came from remote)
+                RibChange::Created(obj) | RibChange::Updated(obj) => {
                     let mut objects = self.objects.write().await;
-                    if !objects.contains_key(&obj.name) {
-                        objects.insert(obj.name.clone(), obj);
-                        applied += 1;
-                    }
-                }
-                RibChange::Updated(obj) => {
-                    let mut objects = self.objects.write().await;
-                    if let Some(existing) = objects.get_mut(&obj.name) {
-                        // Only apply if version is newer
-                        if obj.version > existing.version {
-                            *existing = obj;
+                    if let Some(existing) = objects.get(&obj.name) {
+                        // Newer versions win; conflicts go to the resolver
+                        if let Some(merged) = self.reconcile(existing, obj) {
+                            objects.insert(merged.name.clone(), merged.clone());
+                            self.notify(&RibChange::Updated(merged));
                             applied += 1;
                         }
                     } else {
                         // Object doesn't exist locally, create it
-                        objects.insert(obj.name.clone(), obj);
+                        objects.insert(obj.name.clone(), obj.clone());
+                        self.notify(&RibChange::Created(obj));
                         applied += 1;
                     }
                 }
-                RibChange::Deleted { name, .. } => {
+                deleted @ RibChange::Deleted { .. } => {
                     let mut objects = self.objects.write().await;
-                    if objects.remove(&name).is_some() {
+                    if objects.remove(deleted.object_name()).is_some() {
+                        self.notify(&deleted);
                         applied += 1;
                     }
                 }
@@ -586,6 +1385,60 @@ impl Rib {
         Ok(applied)
     }
 
+    /// Applies changes pushed by a peer IPCP (bootstrap side of bidirectional sync)
+    ///
+    /// Unlike `apply_changes`, changes that take effect are re-stamped with a
+    /// local version and logged, so they propagate to other peers on their
+    /// next incremental sync. As there, badly named objects are skipped.
+    ///
+    /// # Returns
+    /// The number of changes that modified the RIB
+    pub async fn apply_peer_changes(&self, changes: Vec<RibChange>) -> usize {
+        let mut applied = 0;
+
+        for change in changes {
+            if !is_valid_remote_name(change.object_name()) {
+                continue;
+            }
+            match change {
+                RibChange::Created(obj) | RibChange::Updated(obj) => {
+                    let mut objects = self.objects.write().await;
+                    let existing = objects.get(&obj.name);
+                    let is_new = existing.is_none();
+
+                    let winner = match existing {
+                        // Identical content: nothing to propagate
+                        Some(local) if local.value == obj.value && local.class == obj.class => None,
+                        Some(local) => self.reconcile(local, obj),
+                        None => Some(obj),
+                    };
+
+                    if let Some(mut winner) = winner {
+                        winner.version = self.next_version().await;
+                        objects.insert(winner.name.clone(), winner.clone());
+                        drop(objects); // Release lock before logging
+
+                        let change = if is_new {
+                            RibChange::Created(winner)
+                        } else {
+                            RibChange::Updated(winner)
+                        };
+                        self.record(change).await;
+                        applied += 1;
+                    }
+                }
+                RibChange::Deleted { name, .. } => {
+                    // Deletes go through the regular path so they get logged
+                    if self.delete(&name).await.is_ok() {
+                        applied += 1;
+                    }
+                }
+            }
+        }
+
+        applied
+    }
+
     /// Generates the next version number
     async fn next_version(&self) -> u64 {
         let mut counter = self.version_counter.write().await;
@@ -593,15 +1446,45 @@ impl Rib {
         *counter
     }
 
-    /// Load RIB from snapshot file (binary format)
+    /// Load RIB from snapshot file (in the configured snapshot format)
+    ///
+    /// If the snapshot cannot be read or decoded, the backups kept by
+    /// `save_snapshot_to_file` are tried from newest to oldest.
     ///
     /// # Arguments
     /// * `path` - Path to the snapshot file
     ///
     /// # Returns
     /// * `Ok(usize)` - Number of objects loaded
-    /// * `Err(String)` - If file read or deserialization fails
+    /// * `Err(String)` - If the snapshot and every backup fail to load
     pub async fn load_snapshot_from_file(&self, path: &std::path::Path) -> Result<usize, String> {
+        let primary_error = match self.load_snapshot_file(path).await {
+            Ok(count) => return Ok(count),
+            Err(e) => e,
+        };
+
+        for index in 1..=self.snapshot_backups {
+            let backup = snapshot_backup_path(path, index);
+            if !backup.exists() {
+                continue;
+            }
+            match self.load_snapshot_file(&backup).await {
+                Ok(count) => {
+                    eprintln!(
+                        "  ⚠️  {}; loaded backup {:?} instead",
+                        primary_error, backup
+                    );
+                    return Ok(count);
+                }
+                Err(e) => eprintln!("  ⚠️  Skipping unreadable backup: {}", e),
+            }
+        }
+
+        Err(primary_error)
+    }
+
+    /// Loads a single snapshot file without trying backups
+    async fn load_snapshot_file(&self, path: &std::path::Path) -> Result<usize, String> {
         if !path.exists() {
             return Err(format!("Snapshot file not found: {:?}", path));
         }
@@ -613,11 +1496,12 @@ impl Rib {
             return Ok(0);
         }
 
-        let count = self.deserialize(&data).await?;
-        Ok(count)
+        self.deserialize_as(&data, self.snapshot_format)
+            .await
+            .map_err(|e| format!("Failed to deserialize RIB snapshot {:?}: {}", path, e))
     }
 
-    /// Save RIB to snapshot file (binary format)
+    /// Save RIB to snapshot file (in the configured snapshot format)
     ///
     /// # Arguments
     /// * `path` - Path where snapshot should be saved
@@ -626,7 +1510,15 @@ impl Rib {
     /// * `Ok(usize)` - Number of objects saved
     /// * `Err(String)` - If serialization or file write fails
     pub async fn save_snapshot_to_file(&self, path: &std::path::Path) -> Result<usize, String> {
-        let data = self.serialize().await;
+        self.snapshot_guard.run(self.write_snapshot(path)).await
+    }
+
+    /// Serializes and writes the snapshot; callers hold the snapshot guard
+    async fn write_snapshot(&self, path: &std::path::Path) -> Result<usize, String> {
+        let data = self
+            .serialize_as(self.snapshot_format)
+            .await
+            .map_err(|e| format!("Failed to serialize RIB snapshot: {}", e))?;
 
         if data.is_empty() {
             return Ok(0);
@@ -638,13 +1530,37 @@ impl Rib {
                 .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
         }
 
-        std::fs::write(path, &data)
+        self.rotate_snapshot_backups(path)
+            .map_err(|e| format!("Failed to rotate snapshot backups for {:?}: {}", path, e))?;
+
+        write_file_atomically(path, &data)
             .map_err(|e| format!("Failed to write snapshot file {:?}: {}", path, e))?;
 
         let object_count = self.count().await;
         Ok(object_count)
     }
 
+    /// Shifts `<path>.1` ... `<path>.N-1` up by one and copies `path` to `<path>.1`
+    ///
+    /// The current snapshot is copied rather than moved, so a valid file
+    /// stays at `path` until the new one replaces it.
+    fn rotate_snapshot_backups(&self, path: &std::path::Path) -> io::Result<()> {
+        if self.snapshot_backups == 0 || !path.exists() {
+            return Ok(());
+        }
+
+        for index in (1..self.snapshot_backups).rev() {
+            let older = snapshot_backup_path(path, index);
+            if older.exists() {
+                std::fs::rename(&older, snapshot_backup_path(path, index + 1))?;
+            }
+        }
+
+        let newest = snapshot_backup_path(path, 1);
+        std::fs::copy(path, &newest)?;
+        Ok(())
+    }
+
     /// Start background task for periodic RIB snapshots
     ///
     /// # Arguments
@@ -660,29 +1576,41 @@ impl Rib {
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
             if interval_seconds == 0 {
-                println!("⚠️  RIB snapshot interval is 0 - snapshot task not started");
+                progress!("⚠️  RIB snapshot interval is 0 - snapshot task not started");
                 return;
             }
 
-            println!(
+            progress!(
                 "✅ Starting RIB snapshot task (interval: {}s, path: {:?})",
-                interval_seconds, snapshot_path
+                interval_seconds,
+                snapshot_path
             );
 
             let mut ticker =
                 tokio::time::interval(tokio::time::Duration::from_secs(interval_seconds));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
             loop {
                 ticker.tick().await;
 
                 let count = self.count().await;
-                println!("🔄 RIB snapshot task tick: {} objects", count);
-
-                match self.save_snapshot_to_file(&snapshot_path).await {
+                progress!("🔄 RIB snapshot task tick: {} objects", count);
+
+                let Some(result) = self
+                    .snapshot_guard
+                    .try_run(self.write_snapshot(&snapshot_path))
+                    .await
+                else {
+                    progress!("⏭️  RIB snapshot already in progress, skipping tick");
+                    continue;
+                };
+
+                match result {
                     Ok(saved_count) => {
-                        println!(
+                        progress!(
                             "💾 Saved {} RIB objects to snapshot: {:?}",
-                            saved_count, snapshot_path
+                            saved_count,
+                            snapshot_path
                         );
                     }
                     Err(e) => {
@@ -704,6 +1632,107 @@ impl Default for Rib {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_query_filters_by_class_and_value() {
+        let rib = Rib::new();
+        for (addr, available) in [(1002, true), (1003, false), (1004, true), (1005, false)] {
+            rib.create(
+                format!("/address-pool/{}", addr),
+                "address-pool".to_string(),
+                RibValue::Boolean(available),
+            )
+            .await
+            .unwrap();
+        }
+        // Same value, different class
+        rib.create(
+            "/flags/enabled".to_string(),
+            "flag".to_string(),
+            RibValue::Boolean(true),
+        )
+        .await
+        .unwrap();
+
+        let available = rib
+            .query("address-pool", |value| value.as_boolean() == Some(true))
+            .await;
+        let names: Vec<&str> = available.iter().map(|obj| obj.name.as_str()).collect();
+        assert_eq!(names, ["/address-pool/1002", "/address-pool/1004"]);
+        assert!(available.iter().all(|obj| obj.class == "address-pool"));
+
+        assert!(rib.query("missing", |_| true).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_validates_object_names() {
+        let name = RibName::parse("/routing/static/2000").unwrap();
+        assert_eq!(
+            name.segments().collect::<Vec<_>>(),
+            ["routing", "static", "2000"]
+        );
+
+        let rib = Rib::new();
+        rib.create(
+            "/routing/static/2000".to_string(),
+            "static_route".to_string(),
+            RibValue::Integer(2000),
+        )
+        .await
+        .unwrap();
+
+        for bad in ["//bad//name", "", "/", "/routing/static/", "/with space"] {
+            let result = rib
+                .create(bad.to_string(), "test".to_string(), RibValue::Integer(0))
+                .await;
+            assert!(
+                matches!(result, Err(RibError::InvalidName(_))),
+                "{:?} was accepted",
+                bad
+            );
+        }
+        assert_eq!(rib.count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_remote_objects_with_invalid_names_are_skipped() {
+        let source = Rib::new();
+        source
+            .create(
+                "/dif/good".to_string(),
+                "test".to_string(),
+                RibValue::Integer(1),
+            )
+            .await
+            .unwrap();
+        let good = source.read("/dif/good").await.unwrap();
+        let bad = RibObject {
+            name: "//dif/bad name".to_string(),
+            ..good.clone()
+        };
+
+        let merged = Rib::new();
+        assert_eq!(
+            merged.merge_objects(vec![good.clone(), bad.clone()]).await,
+            1
+        );
+
+        let member = Rib::new();
+        let changes = vec![
+            RibChange::Created(bad.clone()),
+            RibChange::Created(good.clone()),
+        ];
+        assert_eq!(member.apply_changes(changes.clone()).await.unwrap(), 1);
+
+        let bootstrap = Rib::new();
+        assert_eq!(bootstrap.apply_peer_changes(changes).await, 1);
+
+        for rib in [merged, member, bootstrap] {
+            assert_eq!(rib.count().await, 1);
+            assert!(rib.read("/dif/good").await.is_ok());
+            assert!(rib.read(&bad.name).await.is_err());
+        }
+    }
+
     #[tokio::test]
     async fn test_rib_create_and_read() {
         let rib = Rib::new();
@@ -719,7 +1748,7 @@ mod tests {
         assert!(result.is_ok());
 
         let obj = rib.read("test-object").await;
-        assert!(obj.is_some());
+        assert!(obj.is_ok());
 
         let obj = obj.unwrap();
         assert_eq!(obj.name, "test-object");
@@ -759,7 +1788,7 @@ mod tests {
         .unwrap();
 
         assert!(rib.delete("test").await.is_ok());
-        assert!(rib.read("test").await.is_none());
+        assert!(rib.read("test").await.is_err());
     }
 
     #[tokio::test]
@@ -1141,4 +2170,801 @@ mod tests {
         // Clean up
         let _ = std::fs::remove_file(&snapshot_path);
     }
+
+    /// Resolver that unions the fields of two struct objects
+    #[derive(Debug)]
+    struct MergeStructFields;
+
+    impl ConflictResolver for MergeStructFields {
+        fn resolve(&self, local: &RibObject, incoming: &RibObject) -> RibObject {
+            match (&local.value, &incoming.value) {
+                (RibValue::Struct(local_fields), RibValue::Struct(incoming_fields)) => {
+                    let mut fields = local_fields.clone();
+                    for (key, value) in incoming_fields {
+                        fields.entry(key.clone()).or_insert_with(|| value.clone());
+                    }
+                    RibObject {
+                        value: RibValue::Struct(fields),
+                        ..local.clone()
+                    }
+                }
+                _ => local.clone(),
+            }
+        }
+    }
+
+    fn struct_object(name: &str, fields: &[(&str, i64)], version: u64) -> RibObject {
+        RibObject {
+            name: name.to_string(),
+            class: "neighbor".to_string(),
+            value: RibValue::Struct(
+                fields
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), Box::new(RibValue::Integer(*v))))
+                    .collect(),
+            ),
+            version,
+            last_modified: 0,
+            clock: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rib_custom_conflict_resolver_merges_struct_fields() {
+        let rib = Rib::new().with_conflict_resolver(Arc::new(MergeStructFields));
+        rib.merge_objects(vec![struct_object("/neighbor/a", &[("cost", 1)], 5)])
+            .await;
+
+        // Concurrent edit with the same version but a different field
+        let merged = rib
+            .merge_objects(vec![struct_object("/neighbor/a", &[("hops", 2)], 5)])
+            .await;
+        assert_eq!(merged, 1);
+
+        let obj = rib.read("/neighbor/a").await.unwrap();
+        assert_eq!(obj.version, 5);
+        match obj.value {
+            RibValue::Struct(fields) => {
+                assert_eq!(fields.len(), 2);
+                assert_eq!(fields["cost"].as_integer(), Some(1));
+                assert_eq!(fields["hops"].as_integer(), Some(2));
+            }
+            _ => panic!("Expected struct value"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rib_default_resolver_keeps_local_on_tie() {
+        let rib = Rib::new();
+        rib.merge_objects(vec![struct_object("/neighbor/a", &[("cost", 1)], 5)])
+            .await;
+
+        let merged = rib
+            .merge_objects(vec![struct_object("/neighbor/a", &[("hops", 2)], 5)])
+            .await;
+        assert_eq!(merged, 0);
+
+        let obj = rib.read("/neighbor/a").await.unwrap();
+        assert_eq!(
+            obj.value,
+            struct_object("/neighbor/a", &[("cost", 1)], 5).value
+        );
+    }
+
+    /// Resolver that records every conflict it sees and keeps the local copy
+    #[derive(Debug, Default)]
+    struct RecordingResolver {
+        conflicts: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl ConflictResolver for RecordingResolver {
+        fn resolve(&self, local: &RibObject, _incoming: &RibObject) -> RibObject {
+            self.conflicts.lock().unwrap().push(local.name.clone());
+            local.clone()
+        }
+    }
+
+    #[test]
+    fn test_vector_clock_compare() {
+        let mut a = VectorClock::new();
+        a.increment("a");
+        let mut b = a.clone();
+        assert_eq!(a.compare(&b), CausalOrder::Equal);
+
+        b.increment("b");
+        assert_eq!(a.compare(&b), CausalOrder::Before);
+        assert_eq!(b.compare(&a), CausalOrder::After);
+
+        a.increment("a");
+        assert_eq!(a.compare(&b), CausalOrder::Concurrent);
+        assert_eq!(a.merged(&b).get("a"), 2);
+        assert_eq!(a.merged(&b).get("b"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rib_vector_clock_detects_concurrent_update() {
+        let resolver = Arc::new(RecordingResolver::default());
+        let node_a = Rib::new()
+            .with_node_id("a")
+            .with_conflict_resolver(resolver.clone());
+        let node_b = Rib::new().with_node_id("b");
+
+        node_a
+            .create(
+                "/shared".to_string(),
+                "test".to_string(),
+                RibValue::Integer(0),
+            )
+            .await
+            .unwrap();
+        node_b.merge_objects(node_a.get_all_objects().await).await;
+
+        // Node B's unrelated write pushes its scalar version ahead of A's
+        node_b
+            .create(
+                "/other".to_string(),
+                "test".to_string(),
+                RibValue::Integer(0),
+            )
+            .await
+            .unwrap();
+
+        // Each node updates the shared object once
+        node_a
+            .update("/shared", RibValue::Integer(1))
+            .await
+            .unwrap();
+        node_b
+            .update("/shared", RibValue::Integer(2))
+            .await
+            .unwrap();
+
+        let incoming = node_b.read("/shared").await.unwrap();
+        assert!(incoming.version > node_a.read("/shared").await.unwrap().version);
+        node_a.merge_objects(vec![incoming]).await;
+
+        // A higher scalar version alone must not discard A's edit
+        assert_eq!(*resolver.conflicts.lock().unwrap(), vec!["/shared"]);
+        let merged = node_a.read("/shared").await.unwrap();
+        assert_eq!(merged.value.as_integer(), Some(1));
+        let clock = merged.clock.unwrap();
+        assert_eq!(clock.get("a"), 2);
+        assert_eq!(clock.get("b"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rib_vector_clock_sequential_update_applies() {
+        let node_a = Rib::new().with_node_id("a");
+        let node_b = Rib::new().with_node_id("b");
+
+        node_a
+            .create(
+                "/shared".to_string(),
+                "test".to_string(),
+                RibValue::Integer(0),
+            )
+            .await
+            .unwrap();
+        node_b.merge_objects(node_a.get_all_objects().await).await;
+        node_b
+            .update("/shared", RibValue::Integer(5))
+            .await
+            .unwrap();
+
+        node_a
+            .merge_objects(vec![node_b.read("/shared").await.unwrap()])
+            .await;
+        let obj = node_a.read("/shared").await.unwrap();
+        assert_eq!(obj.value.as_integer(), Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_rib_serialization_formats_roundtrip() {
+        let rib = Rib::new();
+        rib.create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("test-dif".to_string()),
+        )
+        .await
+        .unwrap();
+        rib.create(
+            "/neighbors/n1".to_string(),
+            "neighbor".to_string(),
+            RibValue::Struct(HashMap::from([(
+                "address".to_string(),
+                Box::new(RibValue::Integer(2001)),
+            )])),
+        )
+        .await
+        .unwrap();
+        rib.create(
+            "/blob".to_string(),
+            "data".to_string(),
+            RibValue::Bytes(vec![1, 2, 3]),
+        )
+        .await
+        .unwrap();
+
+        for format in [
+            SerializationFormat::Postcard,
+            SerializationFormat::Json,
+            SerializationFormat::Bincode,
+        ] {
+            let data = rib.serialize_as(format).await.unwrap();
+            let restored = Rib::new();
+            assert_eq!(restored.deserialize_as(&data, format).await.unwrap(), 3);
+
+            for name in ["/dif/name", "/neighbors/n1", "/blob"] {
+                let original = rib.read(name).await.unwrap();
+                let copy = restored.read(name).await.unwrap();
+                assert_eq!(copy.value, original.value, "{} via {}", name, format);
+                assert_eq!(copy.version, original.version);
+            }
+        }
+
+        // JSON snapshots are human-readable
+        let json = rib.serialize_as(SerializationFormat::Json).await.unwrap();
+        assert!(String::from_utf8(json).unwrap().contains("test-dif"));
+    }
+
+    #[tokio::test]
+    async fn test_rib_errors_are_typed() {
+        let rib = Rib::new();
+        rib.create(
+            "/test/obj".to_string(),
+            "test".to_string(),
+            RibValue::Integer(1),
+        )
+        .await
+        .unwrap();
+
+        let err = rib
+            .create(
+                "/test/obj".to_string(),
+                "test".to_string(),
+                RibValue::Integer(2),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RibError::AlreadyExists(ref name) if name == "/test/obj"));
+
+        assert!(matches!(
+            rib.read("/test/missing").await,
+            Err(RibError::NotFound(ref name)) if name == "/test/missing"
+        ));
+        assert!(matches!(
+            rib.update("/test/missing", RibValue::Integer(3)).await,
+            Err(RibError::NotFound(_))
+        ));
+        assert!(matches!(
+            rib.delete("/test/missing").await,
+            Err(RibError::NotFound(_))
+        ));
+
+        let version = rib.read("/test/obj").await.unwrap().version;
+        rib.update_if_version("/test/obj", RibValue::Integer(4), version)
+            .await
+            .unwrap();
+        let err = rib
+            .update_if_version("/test/obj", RibValue::Integer(5), version)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RibError::VersionConflict { expected, actual } if expected == version && actual > version
+        ));
+        assert_eq!(
+            rib.read("/test/obj").await.unwrap().value.as_integer(),
+            Some(4)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_change_log_byte_budget_trims_before_count_cap() {
+        let rib = Rib::with_change_log_size(100).with_change_log_byte_budget(4096);
+
+        for i in 0..5 {
+            rib.create(
+                format!("/small/{}", i),
+                "test".to_string(),
+                RibValue::Integer(i),
+            )
+            .await
+            .unwrap();
+        }
+        let before_large = rib.current_version().await;
+        assert!(rib.get_changes_since(0).await.is_ok());
+
+        // Two large values together exceed the budget long before 100 entries
+        for i in 0..2 {
+            rib.create(
+                format!("/large/{}", i),
+                "test".to_string(),
+                RibValue::Bytes(vec![0xAB; 3000]),
+            )
+            .await
+            .unwrap();
+        }
+
+        assert!(rib.change_log.len().await < 7);
+        assert!(rib.change_log.total_bytes() <= 4096);
+        assert!(rib.get_changes_since(0).await.is_err());
+        assert!(rib.get_changes_since(before_large).await.is_err());
+
+        // The newest change survives, so the version is still tracked
+        assert_eq!(rib.change_log.len().await, 1);
+        let newest = rib.current_version().await;
+        assert_eq!(rib.read("/large/1").await.unwrap().version, newest);
+        assert!(rib.get_changes_since(newest).await.unwrap().is_empty());
+
+        // Without a budget only the count cap applies
+        let unbounded = Rib::with_change_log_size(100);
+        for i in 0..2 {
+            unbounded
+                .create(
+                    format!("/large/{}", i),
+                    "test".to_string(),
+                    RibValue::Bytes(vec![0xAB; 3000]),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(unbounded.change_log.len().await, 2);
+        assert!(unbounded.get_changes_since(0).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_version_marker_is_not_a_change() {
+        let rib = Rib::new();
+        rib.create(
+            "/local".to_string(),
+            "test".to_string(),
+            RibValue::Integer(1),
+        )
+        .await
+        .unwrap();
+
+        let remote = RibObject {
+            name: "/remote".to_string(),
+            class: "test".to_string(),
+            value: RibValue::Integer(2),
+            version: 50,
+            last_modified: 0,
+            clock: None,
+        };
+        rib.apply_changes(vec![RibChange::Created(remote)])
+            .await
+            .unwrap();
+
+        assert_eq!(rib.current_version().await, 50);
+        assert_eq!(rib.change_log.len().await, 1);
+        let changes = rib.get_changes_since(0).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].object_name(), "/local");
+
+        // Later local writes are still reported after the synced version
+        rib.create(
+            "/after".to_string(),
+            "test".to_string(),
+            RibValue::Integer(3),
+        )
+        .await
+        .unwrap();
+        let changes = rib.get_changes_since(50).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].object_name(), "/after");
+        assert_eq!(rib.current_version().await, 51);
+    }
+
+    #[tokio::test]
+    async fn test_serialization_is_independent_of_insertion_order() {
+        let objects: Vec<RibObject> = (0..20)
+            .map(|i| {
+                let mut fields = HashMap::new();
+                for field in ["address", "name", "reachable", "port", "weight"] {
+                    fields.insert(
+                        field.to_string(),
+                        Box::new(RibValue::String(format!("{}-{}", field, i))),
+                    );
+                }
+                RibObject {
+                    name: format!("/objects/{:02}", i),
+                    class: "test".to_string(),
+                    value: RibValue::Struct(fields),
+                    version: i + 1,
+                    last_modified: 1_700_000_000,
+                    clock: None,
+                }
+            })
+            .collect();
+
+        let forward = Rib::new();
+        forward.merge_objects(objects.clone()).await;
+        let backward = Rib::new();
+        backward
+            .merge_objects(objects.into_iter().rev().collect())
+            .await;
+
+        assert_eq!(forward.serialize().await, backward.serialize().await);
+        for format in [SerializationFormat::Json, SerializationFormat::Bincode] {
+            assert_eq!(
+                forward.serialize_as(format).await.unwrap(),
+                backward.serialize_as(format).await.unwrap()
+            );
+        }
+
+        // The sorted encoding still round-trips
+        let restored = Rib::new();
+        restored
+            .deserialize(&forward.serialize().await)
+            .await
+            .unwrap();
+        assert_eq!(restored.serialize().await, forward.serialize().await);
+    }
+
+    #[tokio::test]
+    async fn test_failed_snapshot_write_preserves_previous_file() {
+        let snapshot_path = std::env::temp_dir().join("test_atomic_rib_snapshot.bin");
+        let _ = std::fs::remove_file(&snapshot_path);
+
+        let rib = Rib::new();
+        rib.create(
+            "/kept".to_string(),
+            "test".to_string(),
+            RibValue::Integer(1),
+        )
+        .await
+        .unwrap();
+        rib.save_snapshot_to_file(&snapshot_path).await.unwrap();
+        let good = std::fs::read(&snapshot_path).unwrap();
+
+        // A write that dies halfway through
+        let result = write_atomically_with(&snapshot_path, |file| {
+            file.write_all(&[0xFF; 16])?;
+            Err(io::Error::other("disk full"))
+        });
+        assert!(result.is_err());
+
+        assert_eq!(std::fs::read(&snapshot_path).unwrap(), good);
+        assert!(
+            !std::env::temp_dir()
+                .join(".test_atomic_rib_snapshot.bin.tmp")
+                .exists()
+        );
+        let restored = Rib::new();
+        assert_eq!(
+            restored
+                .load_snapshot_from_file(&snapshot_path)
+                .await
+                .unwrap(),
+            1
+        );
+
+        let _ = std::fs::remove_file(&snapshot_path);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_snapshot_falls_back_to_backup() {
+        let snapshot_path = std::env::temp_dir().join("test_rotating_rib_snapshot.bin");
+        let cleanup = || {
+            for path in [
+                snapshot_path.clone(),
+                snapshot_backup_path(&snapshot_path, 1),
+                snapshot_backup_path(&snapshot_path, 2),
+                snapshot_backup_path(&snapshot_path, 3),
+            ] {
+                let _ = std::fs::remove_file(path);
+            }
+        };
+        cleanup();
+
+        let rib = Rib::new().with_snapshot_backups(2);
+        for i in 0..3 {
+            rib.create(
+                format!("/gen/{}", i),
+                "test".to_string(),
+                RibValue::Integer(i),
+            )
+            .await
+            .unwrap();
+            rib.save_snapshot_to_file(&snapshot_path).await.unwrap();
+        }
+
+        // Only the configured number of backups is kept
+        assert!(snapshot_backup_path(&snapshot_path, 1).exists());
+        assert!(snapshot_backup_path(&snapshot_path, 2).exists());
+        assert!(!snapshot_backup_path(&snapshot_path, 3).exists());
+
+        std::fs::write(&snapshot_path, b"definitely not a snapshot").unwrap();
+
+        let restored = Rib::new().with_snapshot_backups(2);
+        assert_eq!(
+            restored
+                .load_snapshot_from_file(&snapshot_path)
+                .await
+                .unwrap(),
+            2
+        );
+        assert!(restored.read("/gen/1").await.is_ok());
+        assert!(restored.read("/gen/2").await.is_err());
+
+        // Without backups the corrupt snapshot is an error
+        assert!(
+            Rib::new()
+                .load_snapshot_from_file(&snapshot_path)
+                .await
+                .is_err()
+        );
+
+        cleanup();
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_guard_never_overlaps_saves() {
+        let guard = SnapshotGuard::default();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let save = |in_flight: Arc<AtomicUsize>, max_in_flight: Arc<AtomicUsize>| async move {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        };
+
+        let tasks: Vec<_> = (0..20)
+            .map(|_| {
+                let guard = guard.clone();
+                let save = save(in_flight.clone(), max_in_flight.clone());
+                tokio::spawn(async move { guard.run(save).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 1);
+
+        // A periodic save landing during an in-progress one is skipped
+        let slow = guard.clone();
+        let holder = tokio::spawn(async move {
+            slow.run(tokio::time::sleep(tokio::time::Duration::from_millis(100)))
+                .await
+        });
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+        let skipped = guard
+            .try_run(save(in_flight.clone(), max_in_flight.clone()))
+            .await;
+        assert!(skipped.is_none());
+        holder.await.unwrap();
+        assert_eq!(guard.try_run(async { 42 }).await, Some(42));
+    }
+
+    #[tokio::test]
+    async fn test_max_objects_rejects_or_evicts() {
+        let rib = Rib::new().with_max_objects(2, CapacityPolicy::Reject);
+        rib.create("/a".to_string(), "test".to_string(), RibValue::Integer(1))
+            .await
+            .unwrap();
+        rib.create("/b".to_string(), "test".to_string(), RibValue::Integer(2))
+            .await
+            .unwrap();
+
+        let err = rib
+            .create("/c".to_string(), "test".to_string(), RibValue::Integer(3))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RibError::CapacityExceeded { max: 2, .. }));
+        assert_eq!(rib.count().await, 2);
+        // Updates to existing objects are unaffected
+        rib.update("/a", RibValue::Integer(10)).await.unwrap();
+
+        let rib = Rib::new().with_max_objects(2, CapacityPolicy::EvictOldest);
+        rib.create("/a".to_string(), "test".to_string(), RibValue::Integer(1))
+            .await
+            .unwrap();
+        rib.create("/b".to_string(), "test".to_string(), RibValue::Integer(2))
+            .await
+            .unwrap();
+        // Touching /a leaves /b as the stalest object
+        rib.update("/a", RibValue::Integer(10)).await.unwrap();
+        let before = rib.current_version().await;
+
+        rib.create("/c".to_string(), "test".to_string(), RibValue::Integer(3))
+            .await
+            .unwrap();
+        assert_eq!(rib.count().await, 2);
+        assert!(rib.read("/b").await.is_err());
+        assert!(rib.read("/a").await.is_ok());
+        assert!(rib.read("/c").await.is_ok());
+
+        // Peers learn about the eviction like any other delete
+        let changes = rib.get_changes_since(before).await.unwrap();
+        assert!(matches!(&changes[0], RibChange::Deleted { name, .. } if name == "/b"));
+    }
+
+    #[test]
+    fn test_get_path_reads_nested_struct_fields() {
+        let mut route = HashMap::new();
+        route.insert(
+            "next_hop_address".to_string(),
+            Box::new(RibValue::String("127.0.0.1:7000".to_string())),
+        );
+        route.insert("cost".to_string(), Box::new(RibValue::Integer(3)));
+        let mut outer = HashMap::new();
+        outer.insert("route".to_string(), Box::new(RibValue::Struct(route)));
+        let value = RibValue::Struct(outer);
+
+        assert_eq!(
+            value.get_string_path("route.next_hop_address"),
+            Some("127.0.0.1:7000")
+        );
+        assert_eq!(value.get_integer_path("route.cost"), Some(3));
+        assert!(value.get_path("route").unwrap().as_struct().is_some());
+
+        // Missing fields, and paths running through non-structs
+        assert!(value.get_path("route.next_hop_rina_addr").is_none());
+        assert!(value.get_path("route.cost.value").is_none());
+        assert!(value.get_path("route.").is_none());
+
+        // Present but of the wrong type
+        assert!(value.get_integer_path("route.next_hop_address").is_none());
+        assert!(value.get_string_path("route.cost").is_none());
+    }
+
+    #[test]
+    fn test_struct_builder_matches_hand_built_struct() {
+        let mut fields = HashMap::new();
+        fields.insert(
+            "next_hop_address".to_string(),
+            Box::new(RibValue::String("127.0.0.1:7000".to_string())),
+        );
+        fields.insert(
+            "next_hop_rina_addr".to_string(),
+            Box::new(RibValue::Integer(1002)),
+        );
+        fields.insert("reachable".to_string(), Box::new(RibValue::Boolean(true)));
+        fields.insert("key".to_string(), Box::new(RibValue::Bytes(vec![1, 2])));
+        let hand_built = RibValue::Struct(fields);
+
+        let built = RibValue::struct_builder()
+            .field("next_hop_address", "127.0.0.1:7000")
+            .field("next_hop_rina_addr", 1001)
+            .field("reachable", true)
+            .field("key", vec![1u8, 2])
+            // Later values replace earlier ones
+            .field("next_hop_rina_addr", 1002)
+            .build();
+
+        assert_eq!(built, hand_built);
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_see_local_and_synced_changes_under_prefix() {
+        let rib = Rib::new();
+        let mut changes = rib.subscribe("/directory/");
+
+        rib.create(
+            "/other/x".to_string(),
+            "test".to_string(),
+            RibValue::Integer(1),
+        )
+        .await
+        .unwrap();
+        rib.create(
+            "/directory/a".to_string(),
+            "test".to_string(),
+            RibValue::Integer(1),
+        )
+        .await
+        .unwrap();
+        let change = changes.recv().await.unwrap();
+        assert!(matches!(change, RibChange::Created(_)));
+        assert_eq!(change.object_name(), "/directory/a");
+
+        // Changes from a peer are not logged but still reach subscribers
+        let remote = Rib::new();
+        remote
+            .create(
+                "/directory/b".to_string(),
+                "test".to_string(),
+                RibValue::Integer(2),
+            )
+            .await
+            .unwrap();
+        let synced = remote.change_log.get_changes_since(0).await.unwrap();
+        rib.apply_changes(synced).await.unwrap();
+        assert_eq!(changes.recv().await.unwrap().object_name(), "/directory/b");
+        assert!(changes.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_see_objects_from_a_full_sync() {
+        let rib = Rib::new();
+        rib.create(
+            "/directory/a".to_string(),
+            "test".to_string(),
+            RibValue::Integer(1),
+        )
+        .await
+        .unwrap();
+        let mut changes = rib.subscribe("/directory/");
+
+        let remote = Rib::new();
+        for (name, value) in [("/directory/a", 2), ("/directory/b", 3), ("/other/x", 4)] {
+            remote
+                .create(
+                    name.to_string(),
+                    "test".to_string(),
+                    RibValue::Integer(value),
+                )
+                .await
+                .unwrap();
+        }
+        remote
+            .update("/directory/a", RibValue::Integer(5))
+            .await
+            .unwrap();
+        let snapshot = remote.serialize().await;
+        rib.deserialize(&snapshot).await.unwrap();
+
+        let mut seen = Vec::new();
+        while let Ok(change) = changes.try_recv() {
+            seen.push((
+                change.object_name().to_string(),
+                matches!(change, RibChange::Created(_)),
+            ));
+        }
+        seen.sort();
+        assert_eq!(
+            seen,
+            vec![
+                ("/directory/a".to_string(), false),
+                ("/directory/b".to_string(), true)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compact_drops_tombstones_after_churn() {
+        let rib = Rib::with_change_log_size(10_000);
+        for i in 0..5 {
+            rib.create(
+                format!("/live/{}", i),
+                "test".to_string(),
+                RibValue::Integer(i),
+            )
+            .await
+            .unwrap();
+        }
+        for i in 0..500 {
+            let name = format!("/churn/{}", i);
+            rib.create(name.clone(), "test".to_string(), RibValue::Integer(i))
+                .await
+                .unwrap();
+            rib.delete(&name).await.unwrap();
+        }
+
+        let version = rib.current_version().await;
+        let history = postcard::to_allocvec(&rib.get_changes_since(0).await.unwrap()).unwrap();
+
+        let snapshot = rib.compact().await.unwrap();
+        assert!(
+            snapshot.len() * 10 < history.len(),
+            "compacted {} bytes vs {} bytes of history",
+            snapshot.len(),
+            history.len()
+        );
+        assert!(rib.change_log.is_empty().await);
+
+        // Versions survive; only peers older than the baseline need a full sync
+        assert_eq!(rib.current_version().await, version);
+        assert!(rib.get_changes_since(version).await.unwrap().is_empty());
+        assert!(rib.get_changes_since(version - 1).await.is_err());
+
+        let restored = Rib::new();
+        assert_eq!(restored.deserialize(&snapshot).await.unwrap(), 5);
+        assert_eq!(restored.read("/live/4").await.unwrap().version, 5);
+        assert!(restored.read("/churn/0").await.is_err());
+    }
 }
@@ -9,11 +9,23 @@
 //! - PDU forwarding based on destination addresses
 //! - Queueing and scheduling
 
-use crate::pdu::Pdu;
+use crate::error::RmtError;
+use crate::pdu::{Pdu, PduType, QoSParameters};
+use crate::policies::{FifoScheduling, SchedulingPolicy};
 use std::collections::{HashMap, VecDeque};
 
+/// Default upper bound on forwarding table entries
+pub const DEFAULT_MAX_FORWARDING_ENTRIES: usize = 10_000;
+
+/// Default number of QoS classes each next hop's traffic is split into
+pub const DEFAULT_QOS_CLASSES: usize = 4;
+
+/// Default class queue occupancy, in percent, at which data PDUs are marked
+/// as having experienced congestion
+pub const DEFAULT_CONGESTION_THRESHOLD_PERCENT: usize = 50;
+
 /// Forwarding table entry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ForwardingEntry {
     /// Destination address or prefix
     pub dst_addr: u64,
@@ -23,41 +35,160 @@ pub struct ForwardingEntry {
     pub cost: u32,
 }
 
-/// PDU queue for a specific output port/flow
+/// Why the RMT discarded a PDU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// No forwarding entry or route for the destination
+    NoRoute,
+    /// The output queue for the next hop was full
+    QueueFull,
+    /// The PDU ran out of hops while being relayed
+    HopLimitExceeded,
+    /// The PDU was dequeued but could not be handed to the next hop
+    SendFailed,
+}
+
+/// Counters of PDUs dropped by the RMT per reason, and of congestion marks
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RmtStats {
+    /// PDUs dropped because no route to the destination was known
+    pub dropped_no_route: u64,
+    /// PDUs dropped because the next hop's output queue was full
+    pub dropped_queue_full: u64,
+    /// Transit PDUs dropped because their hop limit reached zero
+    pub dropped_hop_limit: u64,
+    /// PDUs dropped because sending them to the next hop failed
+    pub dropped_send_failed: u64,
+    /// Data PDUs marked as having experienced congestion (not dropped)
+    pub congestion_marked: u64,
+}
+
+impl RmtStats {
+    /// Total number of dropped PDUs across all reasons
+    pub fn total_dropped(&self) -> u64 {
+        self.dropped_no_route
+            + self.dropped_queue_full
+            + self.dropped_hop_limit
+            + self.dropped_send_failed
+    }
+}
+
+/// Where an incoming PDU goes once the RMT has looked at it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncomingDisposition {
+    /// Addressed to this IPCP; hand it to EFCP
+    DeliverLocally,
+    /// Management traffic (enrollment, RIB sync, heartbeats); hand it to the
+    /// local management handler wherever it was addressed
+    DeliverToManagement,
+    /// Queued for relaying towards the given next hop
+    Forward(u64),
+}
+
+/// Builds the scheduling policy for a new next-hop output queue
+///
+/// Called with the RMT's default queue size.
+pub type SchedulerFactory = fn(usize) -> Box<dyn SchedulingPolicy>;
+
+fn fifo_scheduler(max_size: usize) -> Box<dyn SchedulingPolicy> {
+    Box::new(FifoScheduling::new(max_size))
+}
+
+/// QoS class of a PDU within an output queue; class 0 is the most urgent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QoSClass(pub usize);
+
+impl QoSClass {
+    /// Derives the class of a PDU out of `classes` from its QoS parameters
+    ///
+    /// Priorities are split evenly across the classes, higher priorities
+    /// landing in more urgent classes. A PDU with a delay bound is always
+    /// put in the most urgent class.
+    pub fn from_qos(qos: &QoSParameters, classes: usize) -> Self {
+        let classes = classes.max(1);
+        if qos.max_delay_ms.is_some() {
+            return QoSClass(0);
+        }
+        let band = qos.priority as usize * classes / 256;
+        QoSClass(classes - 1 - band.min(classes - 1))
+    }
+}
+
+/// Output queues towards one next hop, one per QoS class
+///
+/// Each class has its own bounded FIFO, so a full bulk class never blocks
+/// latency-sensitive traffic. The head of every non-empty class waits in the
+/// configured scheduling policy, which decides the class served next.
 #[derive(Debug)]
-struct PduQueue {
-    /// Queue of PDUs waiting to be sent
-    queue: VecDeque<Pdu>,
-    /// Maximum queue size
+struct ClassQueues {
+    /// PDUs waiting behind each class head
+    classes: Vec<VecDeque<Pdu>>,
+    /// Whether each class currently has its head in the scheduler
+    staged: Vec<bool>,
+    /// Chooses between the class heads
+    scheduler: Box<dyn SchedulingPolicy>,
+    /// Capacity of each class queue
     max_size: usize,
 }
 
-impl PduQueue {
-    fn new(max_size: usize) -> Self {
+impl ClassQueues {
+    fn new(classes: usize, max_size: usize, factory: SchedulerFactory) -> Self {
+        let classes = classes.max(1);
         Self {
-            queue: VecDeque::new(),
+            classes: (0..classes).map(|_| VecDeque::new()).collect(),
+            staged: vec![false; classes],
+            scheduler: factory(max_size.max(classes)),
             max_size,
         }
     }
 
+    fn class_of(&self, pdu: &Pdu) -> QoSClass {
+        QoSClass::from_qos(&pdu.qos, self.classes.len())
+    }
+
+    /// Whether the class `pdu` would join is filled to at least `percent`
+    fn is_congested_for(&self, pdu: &Pdu, percent: usize) -> bool {
+        let threshold = (self.max_size * percent / 100).max(1);
+        self.class_length(self.class_of(pdu)) >= threshold
+    }
+
     fn enqueue(&mut self, pdu: Pdu) -> Result<(), String> {
-        if self.queue.len() >= self.max_size {
-            return Err("Queue is full".to_string());
+        let class = self.class_of(&pdu).0;
+        if !self.staged[class] {
+            self.scheduler.enqueue(pdu)?;
+            self.staged[class] = true;
+            return Ok(());
+        }
+        if self.class_length(QoSClass(class)) >= self.max_size {
+            return Err(format!("QoS class {} queue is full", class));
         }
-        self.queue.push_back(pdu);
+        self.classes[class].push_back(pdu);
         Ok(())
     }
 
     fn dequeue(&mut self) -> Option<Pdu> {
-        self.queue.pop_front()
+        let pdu = self.scheduler.dequeue()?;
+        let class = self.class_of(&pdu).0;
+        self.staged[class] = false;
+        if let Some(next) = self.classes[class].pop_front() {
+            // The scheduler holds at most one head per class and is sized
+            // for all of them, so the next head always fits
+            let staged = self.scheduler.enqueue(next).is_ok();
+            debug_assert!(staged, "scheduler too small for every class head");
+            self.staged[class] = staged;
+        }
+        Some(pdu)
     }
 
-    fn len(&self) -> usize {
-        self.queue.len()
+    fn class_length(&self, class: QoSClass) -> usize {
+        self.classes
+            .get(class.0)
+            .map(|queue| queue.len() + usize::from(self.staged[class.0]))
+            .unwrap_or(0)
     }
 
-    fn is_empty(&self) -> bool {
-        self.queue.is_empty()
+    fn queue_length(&self) -> usize {
+        self.scheduler.queue_length() + self.classes.iter().map(VecDeque::len).sum::<usize>()
     }
 }
 
@@ -68,10 +199,22 @@ pub struct Rmt {
     local_addr: u64,
     /// Forwarding table: dst_addr -> ForwardingEntry
     forwarding_table: HashMap<u64, ForwardingEntry>,
-    /// Output queues for each next hop
-    output_queues: HashMap<u64, PduQueue>,
+    /// Group table: group address -> member next hops
+    groups: HashMap<u64, Vec<u64>>,
+    /// Output queues for each next hop, split by QoS class
+    output_queues: HashMap<u64, ClassQueues>,
+    /// Builds the scheduling policy of each newly created class queue
+    scheduler_factory: SchedulerFactory,
+    /// Number of QoS classes of newly created output queues
+    qos_classes: usize,
     /// Default queue size
     default_queue_size: usize,
+    /// Maximum number of forwarding table entries
+    max_forwarding_entries: usize,
+    /// Class queue occupancy (percent) from which data PDUs are marked
+    congestion_threshold_percent: usize,
+    /// Drop counters
+    stats: RmtStats,
 }
 
 impl Rmt {
@@ -80,8 +223,14 @@ impl Rmt {
         Self {
             local_addr,
             forwarding_table: HashMap::new(),
+            groups: HashMap::new(),
             output_queues: HashMap::new(),
+            scheduler_factory: fifo_scheduler,
+            qos_classes: DEFAULT_QOS_CLASSES,
             default_queue_size: 100,
+            max_forwarding_entries: DEFAULT_MAX_FORWARDING_ENTRIES,
+            congestion_threshold_percent: DEFAULT_CONGESTION_THRESHOLD_PERCENT,
+            stats: RmtStats::default(),
         }
     }
 
@@ -90,15 +239,55 @@ impl Rmt {
         self.default_queue_size = size;
     }
 
+    /// Sets the scheduling policy used by output queues created from now on
+    pub fn set_scheduler_factory(&mut self, factory: SchedulerFactory) {
+        self.scheduler_factory = factory;
+    }
+
+    /// Sets how many QoS classes output queues created from now on are split into
+    pub fn set_qos_classes(&mut self, classes: usize) {
+        self.qos_classes = classes.max(1);
+    }
+
+    /// Sets the class queue occupancy, in percent, at which data PDUs are
+    /// marked as having experienced congestion
+    pub fn set_congestion_threshold(&mut self, percent: usize) {
+        self.congestion_threshold_percent = percent.min(100);
+    }
+
+    /// Returns the local address of this IPCP
+    pub fn local_addr(&self) -> u64 {
+        self.local_addr
+    }
+
+    /// Sets the maximum number of forwarding table entries
+    pub fn set_max_forwarding_entries(&mut self, max: usize) {
+        self.max_forwarding_entries = max;
+    }
+
     /// Adds a forwarding table entry
-    pub fn add_forwarding_entry(&mut self, entry: ForwardingEntry) {
+    ///
+    /// Replacing the entry for an existing destination always succeeds; a new
+    /// destination is rejected once the table is full.
+    pub fn add_forwarding_entry(&mut self, entry: ForwardingEntry) -> Result<(), RmtError> {
+        if !self.forwarding_table.contains_key(&entry.dst_addr)
+            && self.forwarding_table.len() >= self.max_forwarding_entries
+        {
+            return Err(RmtError::TableFull(self.max_forwarding_entries));
+        }
+
         let next_hop = entry.next_hop;
         self.forwarding_table.insert(entry.dst_addr, entry);
 
         // Ensure output queue exists for this next hop
-        self.output_queues
-            .entry(next_hop)
-            .or_insert_with(|| PduQueue::new(self.default_queue_size));
+        self.output_queues.entry(next_hop).or_insert_with(|| {
+            ClassQueues::new(
+                self.qos_classes,
+                self.default_queue_size,
+                self.scheduler_factory,
+            )
+        });
+        Ok(())
     }
 
     /// Removes a forwarding table entry
@@ -106,6 +295,57 @@ impl Rmt {
         self.forwarding_table.remove(&dst_addr);
     }
 
+    /// Installs a group address that fans out to several next hops
+    ///
+    /// PDUs sent to `group_addr` are copied once onto each member's queue.
+    /// Groups share the forwarding table's size limit, and a group address
+    /// cannot also be a unicast destination.
+    pub fn add_group(&mut self, group_addr: u64, next_hops: Vec<u64>) -> Result<(), RmtError> {
+        if next_hops.is_empty() {
+            return Err(RmtError::InvalidRoute(format!(
+                "Group {} has no next hops",
+                group_addr
+            )));
+        }
+        if self.forwarding_table.contains_key(&group_addr) {
+            return Err(RmtError::InvalidRoute(format!(
+                "Address {} is already a unicast destination",
+                group_addr
+            )));
+        }
+        if !self.groups.contains_key(&group_addr)
+            && self.forwarding_table.len() + self.groups.len() >= self.max_forwarding_entries
+        {
+            return Err(RmtError::TableFull(self.max_forwarding_entries));
+        }
+
+        let mut members = Vec::with_capacity(next_hops.len());
+        for next_hop in next_hops {
+            if !members.contains(&next_hop) {
+                members.push(next_hop);
+            }
+            self.output_queues.entry(next_hop).or_insert_with(|| {
+                ClassQueues::new(
+                    self.qos_classes,
+                    self.default_queue_size,
+                    self.scheduler_factory,
+                )
+            });
+        }
+        self.groups.insert(group_addr, members);
+        Ok(())
+    }
+
+    /// Removes a group address
+    pub fn remove_group(&mut self, group_addr: u64) {
+        self.groups.remove(&group_addr);
+    }
+
+    /// Returns the member next hops of a group address
+    pub fn group_members(&self, group_addr: u64) -> Option<&[u64]> {
+        self.groups.get(&group_addr).map(Vec::as_slice)
+    }
+
     /// Looks up the next hop for a destination address
     pub fn lookup(&self, dst_addr: u64) -> Option<u64> {
         self.forwarding_table
@@ -113,28 +353,93 @@ impl Rmt {
             .map(|entry| entry.next_hop)
     }
 
+    /// Counts a PDU dropped for `reason`
+    pub fn record_drop(&mut self, reason: DropReason) {
+        match reason {
+            DropReason::NoRoute => self.stats.dropped_no_route += 1,
+            DropReason::QueueFull => self.stats.dropped_queue_full += 1,
+            DropReason::HopLimitExceeded => self.stats.dropped_hop_limit += 1,
+            DropReason::SendFailed => self.stats.dropped_send_failed += 1,
+        }
+    }
+
+    /// Returns a snapshot of the drop counters
+    pub fn stats(&self) -> RmtStats {
+        self.stats.clone()
+    }
+
     /// Processes an outgoing PDU (from local EFCP)
     ///
-    /// Returns the next hop address if forwarding is needed
+    /// Returns the next hop address if forwarding is needed. For a group
+    /// destination this is the first member; use
+    /// [`Rmt::process_outgoing_all`] to learn every queue that got a copy.
     pub fn process_outgoing(&mut self, pdu: Pdu) -> Result<u64, String> {
+        self.process_outgoing_all(pdu).map(|next_hops| next_hops[0])
+    }
+
+    /// Processes an outgoing PDU, returning every next hop it was queued for
+    ///
+    /// A unicast destination yields a single next hop. A group destination
+    /// queues one copy per member and fails only if no member accepted it.
+    pub fn process_outgoing_all(&mut self, pdu: Pdu) -> Result<Vec<u64>, String> {
         // Check if this is a local delivery
         if pdu.dst_addr == self.local_addr {
             return Err("PDU destination is local address".to_string());
         }
 
-        // Lookup next hop
-        let next_hop = self
-            .lookup(pdu.dst_addr)
-            .ok_or_else(|| format!("No route to destination {}", pdu.dst_addr))?;
+        let Some(members) = self.groups.get(&pdu.dst_addr).cloned() else {
+            return self.enqueue_towards(pdu).map(|next_hop| vec![next_hop]);
+        };
 
-        // Enqueue to output queue
+        let mut queued = Vec::with_capacity(members.len());
+        let mut last_error = None;
+        for next_hop in members {
+            match self.enqueue_on(next_hop, pdu.clone()) {
+                Ok(()) => queued.push(next_hop),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(e) if queued.is_empty() => Err(e),
+            _ => Ok(queued),
+        }
+    }
+
+    /// Looks up the next hop for `pdu` and queues it there, counting drops
+    ///
+    /// Data PDUs joining a class queue that is past the congestion threshold
+    /// are marked, so the receiver can echo the mark back to the sender.
+    fn enqueue_towards(&mut self, pdu: Pdu) -> Result<u64, String> {
+        let Some(next_hop) = self.lookup(pdu.dst_addr) else {
+            self.record_drop(DropReason::NoRoute);
+            return Err(format!("No route to destination {}", pdu.dst_addr));
+        };
+
+        self.enqueue_on(next_hop, pdu).map(|()| next_hop)
+    }
+
+    /// Queues `pdu` for `next_hop`, marking and counting as described above
+    fn enqueue_on(&mut self, next_hop: u64, mut pdu: Pdu) -> Result<(), String> {
         let queue = self
             .output_queues
             .get_mut(&next_hop)
             .ok_or_else(|| format!("No output queue for next hop {}", next_hop))?;
 
-        queue.enqueue(pdu)?;
-        Ok(next_hop)
+        let mark = pdu.is_data()
+            && !pdu.congestion_experienced
+            && queue.is_congested_for(&pdu, self.congestion_threshold_percent);
+        if mark {
+            pdu.congestion_experienced = true;
+        }
+
+        if let Err(e) = queue.enqueue(pdu) {
+            self.record_drop(DropReason::QueueFull);
+            return Err(e);
+        }
+        if mark {
+            self.stats.congestion_marked += 1;
+        }
+        Ok(())
     }
 
     /// Processes an incoming PDU (from network/shim)
@@ -143,25 +448,46 @@ impl Rmt {
     /// - Ok(None) if PDU is for local delivery (should go to EFCP)
     /// - Ok(Some(next_hop)) if PDU should be forwarded
     /// - Err if there's an error
+    ///
+    /// Management PDUs are reported as local deliveries too; use
+    /// [`Rmt::dispatch_incoming`] to tell them apart.
     pub fn process_incoming(&mut self, pdu: Pdu) -> Result<Option<u64>, String> {
-        // Check if this is for us
-        if pdu.dst_addr == self.local_addr {
-            // Local delivery - will be handled by EFCP
-            return Ok(None);
+        match self.dispatch_incoming(pdu)? {
+            IncomingDisposition::Forward(next_hop) => Ok(Some(next_hop)),
+            _ => Ok(None),
         }
+    }
 
-        // Forward the PDU
-        let next_hop = self
-            .lookup(pdu.dst_addr)
-            .ok_or_else(|| format!("No route to destination {}", pdu.dst_addr))?;
+    /// Decides where an incoming PDU goes based on its type and destination
+    ///
+    /// Management PDUs never leave this IPCP: they concern the layer itself
+    /// and are processed here even when their destination address is stale
+    /// or not yet assigned. All other PDUs are delivered if addressed to us
+    /// and otherwise follow the forwarding table.
+    pub fn dispatch_incoming(&mut self, mut pdu: Pdu) -> Result<IncomingDisposition, String> {
+        if pdu.pdu_type == PduType::Management {
+            return Ok(IncomingDisposition::DeliverToManagement);
+        }
 
-        let queue = self
-            .output_queues
-            .get_mut(&next_hop)
-            .ok_or_else(|| format!("No output queue for next hop {}", next_hop))?;
+        if pdu.dst_addr == self.local_addr {
+            return Ok(IncomingDisposition::DeliverLocally);
+        }
 
-        queue.enqueue(pdu)?;
-        Ok(Some(next_hop))
+        // Forward the PDU, spending one hop
+        self.consume_hop(&mut pdu)?;
+        self.enqueue_towards(pdu).map(IncomingDisposition::Forward)
+    }
+
+    /// Decrements the hop limit of a transit PDU
+    ///
+    /// Fails, and counts the drop, once the PDU has no hops left.
+    pub fn consume_hop(&mut self, pdu: &mut Pdu) -> Result<(), String> {
+        if pdu.hop_limit == 0 {
+            self.record_drop(DropReason::HopLimitExceeded);
+            return Err(format!("Hop limit exceeded for PDU to {}", pdu.dst_addr));
+        }
+        pdu.hop_limit -= 1;
+        Ok(())
     }
 
     /// Dequeues a PDU from the output queue for a specific next hop
@@ -175,7 +501,15 @@ impl Rmt {
     pub fn queue_length(&self, next_hop: u64) -> usize {
         self.output_queues
             .get(&next_hop)
-            .map(|queue| queue.len())
+            .map(|queue| queue.queue_length())
+            .unwrap_or(0)
+    }
+
+    /// Returns the queue length of one QoS class for a next hop
+    pub fn class_queue_length(&self, next_hop: u64, class: QoSClass) -> usize {
+        self.output_queues
+            .get(&next_hop)
+            .map(|queues| queues.class_length(class))
             .unwrap_or(0)
     }
 
@@ -183,28 +517,67 @@ impl Rmt {
     pub fn has_queued_pdus(&self, next_hop: u64) -> bool {
         self.output_queues
             .get(&next_hop)
-            .map(|queue| !queue.is_empty())
+            .map(|queue| queue.queue_length() > 0)
             .unwrap_or(false)
     }
 
     /// Returns the total number of queued PDUs across all queues
     pub fn total_queued(&self) -> usize {
-        self.output_queues.values().map(|queue| queue.len()).sum()
+        self.output_queues
+            .values()
+            .map(|queue| queue.queue_length())
+            .sum()
     }
 
     /// Returns the number of forwarding table entries
     pub fn forwarding_table_size(&self) -> usize {
         self.forwarding_table.len()
     }
+
+    /// Returns a copy of the installed forwarding table, ordered by destination
+    pub fn export_table(&self) -> Vec<ForwardingEntry> {
+        let mut entries: Vec<ForwardingEntry> = self.forwarding_table.values().cloned().collect();
+        entries.sort_by_key(|entry| entry.dst_addr);
+        entries
+    }
+
+    /// Replaces the whole forwarding table with `entries`
+    ///
+    /// The table is left untouched if `entries` exceeds the configured
+    /// maximum. Output queues of next hops that disappear are kept so that
+    /// already queued PDUs can still be drained.
+    pub fn import_table(&mut self, entries: Vec<ForwardingEntry>) -> Result<(), RmtError> {
+        let table: HashMap<u64, ForwardingEntry> = entries
+            .into_iter()
+            .map(|entry| (entry.dst_addr, entry))
+            .collect();
+        if table.len() > self.max_forwarding_entries {
+            return Err(RmtError::TableFull(self.max_forwarding_entries));
+        }
+
+        for entry in table.values() {
+            self.output_queues.entry(entry.next_hop).or_insert_with(|| {
+                ClassQueues::new(
+                    self.qos_classes,
+                    self.default_queue_size,
+                    self.scheduler_factory,
+                )
+            });
+        }
+        self.forwarding_table = table;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pdu::{PduType, QoSParameters};
+    use crate::pdu::{DEFAULT_HOP_LIMIT, PDU_VERSION, PduType, QoSParameters};
+    use crate::policies::PriorityScheduling;
 
     fn create_test_pdu(src: u64, dst: u64, seq: u64) -> Pdu {
         Pdu {
+            version: PDU_VERSION,
             src_addr: src,
             dst_addr: dst,
             src_cep_id: 1,
@@ -213,6 +586,10 @@ mod tests {
             pdu_type: PduType::Data,
             payload: vec![1, 2, 3],
             qos: QoSParameters::default(),
+            compressed: false,
+            hop_limit: DEFAULT_HOP_LIMIT,
+            congestion_experienced: false,
+            more_fragments: false,
         }
     }
 
@@ -223,6 +600,92 @@ mod tests {
         assert_eq!(rmt.forwarding_table_size(), 0);
     }
 
+    #[test]
+    fn test_forwarding_table_full() {
+        let mut rmt = Rmt::new(100);
+        rmt.set_max_forwarding_entries(1);
+
+        let entry = |dst_addr, next_hop| ForwardingEntry {
+            dst_addr,
+            next_hop,
+            cost: 1,
+        };
+
+        rmt.add_forwarding_entry(entry(200, 150)).unwrap();
+        assert!(matches!(
+            rmt.add_forwarding_entry(entry(300, 150)),
+            Err(RmtError::TableFull(1))
+        ));
+
+        // Replacing an existing destination is still allowed
+        rmt.add_forwarding_entry(entry(200, 160)).unwrap();
+        assert_eq!(rmt.lookup(200), Some(160));
+    }
+
+    #[test]
+    fn test_no_route_drops_are_counted() {
+        let mut rmt = Rmt::new(100);
+
+        for seq in 0..5 {
+            assert!(
+                rmt.process_outgoing(create_test_pdu(100, 999, seq))
+                    .is_err()
+            );
+        }
+
+        let stats = rmt.stats();
+        assert_eq!(stats.dropped_no_route, 5);
+        assert_eq!(stats.dropped_queue_full, 0);
+        assert_eq!(stats.total_dropped(), 5);
+    }
+
+    #[test]
+    fn test_transit_pdu_without_hops_is_dropped() {
+        let mut rmt = Rmt::new(100);
+        rmt.add_forwarding_entry(ForwardingEntry {
+            dst_addr: 200,
+            next_hop: 150,
+            cost: 1,
+        })
+        .unwrap();
+
+        let mut pdu = create_test_pdu(50, 200, 0);
+        pdu.hop_limit = 0;
+        assert!(rmt.process_incoming(pdu).is_err());
+        assert_eq!(rmt.stats().dropped_hop_limit, 1);
+
+        rmt.process_incoming(create_test_pdu(50, 200, 1)).unwrap();
+        let relayed = rmt.dequeue_for_next_hop(150).unwrap();
+        assert_eq!(relayed.hop_limit, DEFAULT_HOP_LIMIT - 1);
+    }
+
+    #[test]
+    fn test_forwarding_table_export_import_round_trip() {
+        let mut rmt = Rmt::new(100);
+        for (dst_addr, next_hop) in [(300, 160), (200, 150), (400, 150)] {
+            rmt.add_forwarding_entry(ForwardingEntry {
+                dst_addr,
+                next_hop,
+                cost: 1,
+            })
+            .unwrap();
+        }
+
+        let exported = rmt.export_table();
+        let dsts: Vec<u64> = exported.iter().map(|e| e.dst_addr).collect();
+        assert_eq!(dsts, vec![200, 300, 400]);
+
+        rmt.import_table(Vec::new()).unwrap();
+        assert_eq!(rmt.forwarding_table_size(), 0);
+        assert_eq!(rmt.lookup(200), None);
+
+        rmt.import_table(exported).unwrap();
+        assert_eq!(rmt.lookup(200), Some(150));
+        assert_eq!(rmt.lookup(300), Some(160));
+        assert_eq!(rmt.lookup(400), Some(150));
+        assert_eq!(rmt.lookup(500), None);
+    }
+
     #[test]
     fn test_add_forwarding_entry() {
         let mut rmt = Rmt::new(100);
@@ -233,7 +696,7 @@ mod tests {
             cost: 1,
         };
 
-        rmt.add_forwarding_entry(entry);
+        rmt.add_forwarding_entry(entry).unwrap();
         assert_eq!(rmt.forwarding_table_size(), 1);
         assert_eq!(rmt.lookup(200), Some(150));
     }
@@ -247,7 +710,8 @@ mod tests {
             dst_addr: 200,
             next_hop: 150,
             cost: 1,
-        });
+        })
+        .unwrap();
 
         // Create and process PDU
         let pdu = create_test_pdu(100, 200, 0);
@@ -279,7 +743,8 @@ mod tests {
             dst_addr: 300,
             next_hop: 200,
             cost: 1,
-        });
+        })
+        .unwrap();
 
         // PDU that needs forwarding
         let pdu = create_test_pdu(50, 300, 0);
@@ -290,6 +755,84 @@ mod tests {
         assert_eq!(rmt.queue_length(200), 1);
     }
 
+    #[test]
+    fn test_management_pdu_is_not_forwarded() {
+        let mut rmt = Rmt::new(100);
+        rmt.add_forwarding_entry(ForwardingEntry {
+            dst_addr: 300,
+            next_hop: 200,
+            cost: 1,
+        })
+        .unwrap();
+
+        let mgmt = Pdu::new_management(50, 300, vec![1, 2, 3]);
+        assert_eq!(
+            rmt.dispatch_incoming(mgmt).unwrap(),
+            IncomingDisposition::DeliverToManagement
+        );
+        assert_eq!(rmt.queue_length(200), 0);
+
+        let data = create_test_pdu(50, 300, 0);
+        assert_eq!(
+            rmt.dispatch_incoming(data).unwrap(),
+            IncomingDisposition::Forward(200)
+        );
+        assert_eq!(rmt.queue_length(200), 1);
+
+        let local = create_test_pdu(50, 100, 0);
+        assert_eq!(
+            rmt.dispatch_incoming(local).unwrap(),
+            IncomingDisposition::DeliverLocally
+        );
+    }
+
+    #[test]
+    fn test_queue_pressure_marks_pdus_and_shrinks_sender_window() {
+        use crate::efcp::{Flow, FlowConfig};
+
+        let mut rmt = Rmt::new(100);
+        rmt.set_default_queue_size(8);
+        rmt.add_forwarding_entry(ForwardingEntry {
+            dst_addr: 300,
+            next_hop: 200,
+            cost: 1,
+        })
+        .unwrap();
+
+        let config = FlowConfig {
+            window_size: 16,
+            initial_congestion_window: 16,
+            ..Default::default()
+        };
+        let mut sender = Flow::new(1, 10, 20, 100, 300, config.clone());
+        let mut receiver = Flow::new(2, 20, 10, 300, 100, config);
+
+        // The queue towards 200 is not drained, so it keeps building up
+        for i in 0..8 {
+            let pdu = sender.send_data(vec![i]).unwrap();
+            rmt.process_outgoing(pdu).unwrap();
+        }
+        assert_eq!(rmt.stats().congestion_marked, 4);
+
+        let mut marked = Vec::new();
+        while let Some(pdu) = rmt.dequeue_for_next_hop(200) {
+            marked.push(pdu.congestion_experienced);
+            receiver.receive_pdu(pdu).unwrap();
+        }
+        assert_eq!(marked, [false, false, false, false, true, true, true, true]);
+
+        let ack = receiver.build_ack();
+        assert!(ack.congestion_experienced);
+        assert_eq!(ack.sequence_num, 7);
+
+        sender.receive_pdu(ack).unwrap();
+        assert_eq!(sender.send_window_size(), 0);
+        assert_eq!(sender.effective_window(), 8);
+
+        // The echo is only sent once per mark
+        assert!(!receiver.build_ack().congestion_experienced);
+    }
+
     #[test]
     fn test_dequeue_pdu() {
         let mut rmt = Rmt::new(100);
@@ -298,7 +841,8 @@ mod tests {
             dst_addr: 200,
             next_hop: 150,
             cost: 1,
-        });
+        })
+        .unwrap();
 
         // Enqueue PDU
         let pdu = create_test_pdu(100, 200, 42);
@@ -331,7 +875,8 @@ mod tests {
             dst_addr: 200,
             next_hop: 150,
             cost: 1,
-        });
+        })
+        .unwrap();
 
         // Fill the queue
         rmt.process_outgoing(create_test_pdu(100, 200, 0)).unwrap();
@@ -343,6 +888,67 @@ mod tests {
         assert!(result.unwrap_err().contains("full"));
     }
 
+    #[test]
+    fn test_urgent_pdu_bypasses_full_bulk_class() {
+        let mut rmt = Rmt::new(100);
+        rmt.set_default_queue_size(2);
+        rmt.add_forwarding_entry(ForwardingEntry {
+            dst_addr: 200,
+            next_hop: 150,
+            cost: 1,
+        })
+        .unwrap();
+
+        let with_priority = |seq, priority| {
+            let mut pdu = create_test_pdu(100, 200, seq);
+            pdu.qos.priority = priority;
+            pdu
+        };
+
+        // Fill the bulk class to the brim
+        rmt.process_outgoing(with_priority(0, 0)).unwrap();
+        rmt.process_outgoing(with_priority(1, 0)).unwrap();
+        assert!(rmt.process_outgoing(with_priority(2, 0)).is_err());
+
+        // The urgent PDU has its own class queue towards the same next hop
+        rmt.process_outgoing(with_priority(3, 255)).unwrap();
+        assert_eq!(rmt.class_queue_length(150, QoSClass(0)), 1);
+        assert_eq!(
+            rmt.class_queue_length(150, QoSClass(DEFAULT_QOS_CLASSES - 1)),
+            2
+        );
+
+        // The default FIFO policy serves the class heads in arrival order
+        let order: Vec<u64> = std::iter::from_fn(|| rmt.dequeue_for_next_hop(150))
+            .map(|pdu| pdu.sequence_num)
+            .collect();
+        assert_eq!(order, vec![0, 3, 1]);
+    }
+
+    #[test]
+    fn test_scheduling_policy_picks_the_class_served_next() {
+        let mut rmt = Rmt::new(100);
+        rmt.set_scheduler_factory(|size| Box::new(PriorityScheduling::new(4, size)));
+        rmt.add_forwarding_entry(ForwardingEntry {
+            dst_addr: 200,
+            next_hop: 150,
+            cost: 1,
+        })
+        .unwrap();
+
+        for (seq, priority) in [(0, 0), (1, 0), (2, 255), (3, 255)] {
+            let mut pdu = create_test_pdu(100, 200, seq);
+            pdu.qos.priority = priority;
+            rmt.process_outgoing(pdu).unwrap();
+        }
+
+        // Strict priority drains the urgent class before the earlier bulk PDUs
+        let order: Vec<u64> = std::iter::from_fn(|| rmt.dequeue_for_next_hop(150))
+            .map(|pdu| pdu.sequence_num)
+            .collect();
+        assert_eq!(order, vec![2, 3, 0, 1]);
+    }
+
     #[test]
     fn test_total_queued() {
         let mut rmt = Rmt::new(100);
@@ -351,12 +957,14 @@ mod tests {
             dst_addr: 200,
             next_hop: 150,
             cost: 1,
-        });
+        })
+        .unwrap();
         rmt.add_forwarding_entry(ForwardingEntry {
             dst_addr: 300,
             next_hop: 250,
             cost: 1,
-        });
+        })
+        .unwrap();
 
         rmt.process_outgoing(create_test_pdu(100, 200, 0)).unwrap();
         rmt.process_outgoing(create_test_pdu(100, 200, 1)).unwrap();
@@ -364,4 +972,33 @@ mod tests {
 
         assert_eq!(rmt.total_queued(), 3);
     }
+
+    #[test]
+    fn test_group_pdu_is_queued_towards_every_member() {
+        let mut rmt = Rmt::new(100);
+        rmt.add_group(9000, vec![201, 202, 203]).unwrap();
+
+        let mut next_hops = rmt
+            .process_outgoing_all(create_test_pdu(100, 9000, 0))
+            .unwrap();
+        next_hops.sort_unstable();
+        assert_eq!(next_hops, vec![201, 202, 203]);
+
+        for next_hop in [201, 202, 203] {
+            assert_eq!(rmt.queue_length(next_hop), 1);
+            let copy = rmt.dequeue_for_next_hop(next_hop).unwrap();
+            assert_eq!(copy.dst_addr, 9000);
+        }
+        assert_eq!(rmt.total_queued(), 0);
+
+        // A group cannot shadow a unicast destination
+        rmt.add_forwarding_entry(ForwardingEntry {
+            dst_addr: 200,
+            next_hop: 150,
+            cost: 1,
+        })
+        .unwrap();
+        assert!(rmt.add_group(200, vec![201]).is_err());
+        assert!(rmt.add_group(9001, Vec::new()).is_err());
+    }
 }
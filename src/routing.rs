@@ -9,20 +9,29 @@
 //! # Features
 //! - Hybrid routing: Static (config-driven) + Dynamic (learned during enrollment)
 //! - Optional persistence: Save/load dynamic routes to/from TOML snapshots
-//! - TTL-based expiration: Automatic stale route detection
+//! - TTL-based expiration: Lazy on lookup, plus an optional periodic sweep
 //! - Validation on load: Filter expired routes during startup
 //! - Periodic snapshots: Background task saves routes at configured intervals
+//! - Change notifications: Subscribers receive [`RouteEvent`]s as routes change
 
-use crate::error::AriError;
-use crate::rib::{Rib, RibValue};
+use crate::error::{AriError, RmtError};
+use crate::rib::{Rib, RibValue, SnapshotGuard, write_file_atomically};
+use crate::rmt::ForwardingEntry;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::RwLock;
-use tokio::time::{Duration, interval};
+use tokio::sync::{RwLock, mpsc};
+use tokio::time::{Duration, MissedTickBehavior, interval};
+
+/// Cost assigned to routes that don't specify one
+pub const DEFAULT_ROUTE_COST: u32 = 1;
+
+fn default_route_cost() -> u32 {
+    DEFAULT_ROUTE_COST
+}
 
 /// Metadata for a dynamic route entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +44,9 @@ pub struct RouteMetadata {
     pub created_at: u64,
     /// Time-to-live in seconds (0 = never expires)
     pub ttl_seconds: u64,
+    /// Route cost (lower is preferred)
+    #[serde(default = "default_route_cost")]
+    pub cost: u32,
 }
 
 impl RouteMetadata {
@@ -69,6 +81,114 @@ impl RouteMetadata {
     }
 }
 
+/// A route as stored in the RIB under `/routing/static/` or `/routing/dynamic/`
+///
+/// The destination is the last segment of the object name. In the RIB the
+/// next hop is a `next_hop_address` string, while `next_hop_rina_addr` and
+/// `cost` are integers; the last two may be omitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Route {
+    /// Next-hop socket address (UDP underlay)
+    pub next_hop: SocketAddr,
+    /// RINA address of the next hop, if known
+    pub next_hop_rina_addr: Option<u64>,
+    /// Route cost (lower is preferred)
+    pub cost: u32,
+}
+
+impl Route {
+    /// Creates a route via `next_hop` with the default cost
+    pub fn new(next_hop: SocketAddr) -> Self {
+        Self {
+            next_hop,
+            next_hop_rina_addr: None,
+            cost: DEFAULT_ROUTE_COST,
+        }
+    }
+
+    /// Records the RINA address of the next hop
+    pub fn with_next_hop_rina_addr(mut self, addr: u64) -> Self {
+        self.next_hop_rina_addr = Some(addr);
+        self
+    }
+
+    /// Sets the route cost
+    pub fn with_cost(mut self, cost: u32) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Forwarding table entry reaching `destination` through this route
+    ///
+    /// None if the RINA address of the next hop is unknown, as the RMT
+    /// forwards by RINA address.
+    pub fn forwarding_entry(&self, destination: u64) -> Option<ForwardingEntry> {
+        Some(ForwardingEntry {
+            dst_addr: destination,
+            next_hop: self.next_hop_rina_addr?,
+            cost: self.cost,
+        })
+    }
+}
+
+impl From<Route> for RibValue {
+    fn from(route: Route) -> Self {
+        let mut builder = RibValue::struct_builder()
+            .field("next_hop_address", route.next_hop.to_string())
+            .field("cost", route.cost as i64);
+        if let Some(addr) = route.next_hop_rina_addr {
+            builder = builder.field("next_hop_rina_addr", addr as i64);
+        }
+        builder.build()
+    }
+}
+
+impl TryFrom<&RibValue> for Route {
+    type Error = RmtError;
+
+    fn try_from(value: &RibValue) -> Result<Self, Self::Error> {
+        if value.as_struct().is_none() {
+            return Err(RmtError::InvalidRoute("not a struct".to_string()));
+        }
+
+        let next_hop = value
+            .get_string_path("next_hop_address")
+            .ok_or_else(|| RmtError::InvalidRoute("next_hop_address must be a string".to_string()))?
+            .parse()
+            .map_err(|e| RmtError::InvalidRoute(format!("invalid next_hop_address: {}", e)))?;
+
+        let next_hop_rina_addr = match value.get_path("next_hop_rina_addr") {
+            None => None,
+            Some(field) => Some(
+                field
+                    .as_integer()
+                    .and_then(|addr| u64::try_from(addr).ok())
+                    .ok_or_else(|| {
+                        RmtError::InvalidRoute(
+                            "next_hop_rina_addr must be a non-negative integer".to_string(),
+                        )
+                    })?,
+            ),
+        };
+
+        let cost = match value.get_path("cost") {
+            None => DEFAULT_ROUTE_COST,
+            Some(field) => field
+                .as_integer()
+                .and_then(|cost| u32::try_from(cost).ok())
+                .ok_or_else(|| {
+                    RmtError::InvalidRoute("cost must be an integer fitting u32".to_string())
+                })?,
+        };
+
+        Ok(Self {
+            next_hop,
+            next_hop_rina_addr,
+            cost,
+        })
+    }
+}
+
 /// Snapshot of dynamic routes for persistence
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RouteSnapshot {
@@ -96,7 +216,7 @@ impl RouteSnapshot {
     }
 
     /// Load snapshot from TOML file
-    pub fn load_from_file(path: &PathBuf) -> Result<Self, AriError> {
+    pub fn load_from_file(path: &Path) -> Result<Self, AriError> {
         let content = std::fs::read_to_string(path).map_err(|e| {
             AriError::Rib(crate::error::RibError::OperationFailed(format!(
                 "Failed to read file: {}",
@@ -115,7 +235,7 @@ impl RouteSnapshot {
     }
 
     /// Save snapshot to TOML file
-    pub fn save_to_file(&self, path: &PathBuf) -> Result<(), AriError> {
+    pub fn save_to_file(&self, path: &Path) -> Result<(), AriError> {
         let content = toml::to_string_pretty(self).map_err(|e| {
             AriError::Rib(crate::error::RibError::OperationFailed(format!(
                 "Failed to serialize: {}",
@@ -133,7 +253,7 @@ impl RouteSnapshot {
             })?;
         }
 
-        std::fs::write(path, content).map_err(|e| {
+        write_file_atomically(path, content.as_bytes()).map_err(|e| {
             AriError::Rib(crate::error::RibError::OperationFailed(format!(
                 "Failed to write file: {}",
                 e
@@ -153,6 +273,30 @@ impl RouteSnapshot {
     }
 }
 
+/// Buffered events per subscriber before new events are dropped
+const ROUTE_EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// A change to the dynamic routing table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteEvent {
+    /// A route to a new destination was added
+    Added {
+        destination: u64,
+        next_hop: SocketAddr,
+        cost: u32,
+    },
+    /// An existing route was replaced (e.g. on re-enrollment)
+    Updated {
+        destination: u64,
+        next_hop: SocketAddr,
+        cost: u32,
+    },
+    /// A route was removed explicitly
+    Removed { destination: u64 },
+    /// A route was removed because its TTL elapsed
+    Expired { destination: u64 },
+}
+
 /// Configuration for route resolution
 #[derive(Debug, Clone)]
 pub struct RouteResolverConfig {
@@ -164,6 +308,8 @@ pub struct RouteResolverConfig {
     pub default_ttl_seconds: u64,
     /// Interval between automatic snapshots (seconds)
     pub snapshot_interval_seconds: u64,
+    /// Interval between expired-route sweeps (seconds, 0 = lazy expiry only)
+    pub expiry_sweep_interval_seconds: u64,
 }
 
 impl Default for RouteResolverConfig {
@@ -173,6 +319,7 @@ impl Default for RouteResolverConfig {
             snapshot_path: PathBuf::from("dynamic-routes.toml"),
             default_ttl_seconds: 3600,      // 1 hour default
             snapshot_interval_seconds: 300, // 5 minutes
+            expiry_sweep_interval_seconds: 60,
         }
     }
 }
@@ -186,6 +333,10 @@ pub struct RouteResolver {
     config: RouteResolverConfig,
     /// Cache of dynamic route metadata for efficient TTL checks
     metadata_cache: Arc<RwLock<HashMap<u64, RouteMetadata>>>,
+    /// Senders for route change subscribers
+    subscribers: Arc<std::sync::Mutex<Vec<mpsc::Sender<RouteEvent>>>>,
+    /// Keeps periodic and immediate snapshot saves from overlapping
+    snapshot_guard: SnapshotGuard,
 }
 
 impl RouteResolver {
@@ -195,69 +346,157 @@ impl RouteResolver {
             rib,
             config,
             metadata_cache: Arc::new(RwLock::new(HashMap::new())),
+            subscribers: Arc::new(std::sync::Mutex::new(Vec::new())),
+            snapshot_guard: SnapshotGuard::default(),
         }
     }
 
+    /// Subscribe to route changes
+    ///
+    /// Each subscriber gets its own bounded channel; events are dropped for
+    /// a subscriber that falls behind, and closed receivers are pruned.
+    pub fn subscribe(&self) -> mpsc::Receiver<RouteEvent> {
+        let (tx, rx) = mpsc::channel(ROUTE_EVENT_CHANNEL_CAPACITY);
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Delivers an event to every live subscriber
+    fn notify(&self, event: RouteEvent) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| match tx.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    eprintln!("⚠️  Route event subscriber lagging, dropped {:?}", event);
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            });
+    }
+
     /// Resolve the next-hop socket address for a destination RINA address
     ///
-    /// Lookup order:
-    /// 1. Static routes (highest priority)
-    /// 2. Dynamic routes (check TTL expiration)
-    /// 3. Error if no route found
+    /// See [`resolve_next_hop_with_cost`](Self::resolve_next_hop_with_cost)
+    /// for how competing routes are chosen.
     pub async fn resolve_next_hop(&self, dst_addr: u64) -> Result<SocketAddr, AriError> {
-        // Try static route first (highest priority)
+        self.resolve_next_hop_with_cost(dst_addr)
+            .await
+            .map(|(next_hop, _)| next_hop)
+    }
+
+    /// Resolve the next hop and its cost for a destination RINA address
+    ///
+    /// Both the static and the (unexpired) dynamic route are considered and
+    /// the lowest-cost one wins. On equal cost the static route is preferred.
+    /// A route that does not parse is skipped, so it cannot shadow a valid
+    /// one. Expired dynamic routes are removed as a side effect.
+    pub async fn resolve_next_hop_with_cost(
+        &self,
+        dst_addr: u64,
+    ) -> Result<(SocketAddr, u32), AriError> {
         let static_route_name = format!("/routing/static/{}", dst_addr);
+        let dynamic_route_name = format!("/routing/dynamic/{}", dst_addr);
+
         let rib = self.rib.read().await;
+        let static_route = rib.read(&static_route_name).await;
+        let dynamic_route = rib.read(&dynamic_route_name).await;
+        drop(rib);
 
-        if let Some(obj) = rib.read(&static_route_name).await
-            && let RibValue::Struct(fields) = &obj.value
-            && let Some(socket_addr_box) = fields.get("next_hop_address")
-            && let RibValue::String(socket_addr) = socket_addr_box.as_ref()
-        {
-            return socket_addr.parse().map_err(|e| {
-                AriError::Rmt(crate::error::RmtError::Network(format!(
-                    "Invalid socket address: {}",
-                    e
-                )))
-            });
+        let mut candidates = Vec::new();
+
+        if let Ok(obj) = static_route {
+            candidates.extend(Self::route_candidate(&static_route_name, &obj.value));
         }
 
-        // Try dynamic route (check TTL)
-        let dynamic_route_name = format!("/routing/dynamic/{}", dst_addr);
-        if let Some(obj) = rib.read(&dynamic_route_name).await
-            && let RibValue::Struct(fields) = &obj.value
-        {
+        if let Ok(obj) = dynamic_route {
             // Check if route has expired
-            let metadata_cache = self.metadata_cache.read().await;
-            if let Some(metadata) = metadata_cache.get(&dst_addr)
-                && metadata.is_expired()
-            {
+            let expired = self
+                .metadata_cache
+                .read()
+                .await
+                .get(&dst_addr)
+                .is_some_and(RouteMetadata::is_expired);
+
+            if expired {
                 // Route expired - remove it
-                drop(metadata_cache);
-                drop(rib);
-                self.remove_dynamic_route(dst_addr).await?;
-                return Err(AriError::Rmt(crate::error::RmtError::RouteNotFound(
-                    dst_addr,
-                )));
+                self.delete_dynamic_route(dst_addr).await?;
+                progress!("⌛ Dynamic route expired: {}", dst_addr);
+                self.notify(RouteEvent::Expired {
+                    destination: dst_addr,
+                });
+            } else {
+                candidates.extend(Self::route_candidate(&dynamic_route_name, &obj.value));
             }
+        }
 
-            // Route is valid
-            if let Some(socket_addr_box) = fields.get("next_hop_address")
-                && let RibValue::String(socket_addr) = socket_addr_box.as_ref()
-            {
-                return socket_addr.parse().map_err(|e| {
-                    AriError::Rmt(crate::error::RmtError::Network(format!(
-                        "Invalid socket address: {}",
-                        e
-                    )))
-                });
+        // min_by_key keeps the first of equal elements, so static wins ties
+        candidates
+            .into_iter()
+            .min_by_key(|(_, cost)| *cost)
+            .ok_or(AriError::Rmt(crate::error::RmtError::RouteNotFound(
+                dst_addr,
+            )))
+    }
+
+    /// Extracts `(next_hop, cost)` from the RIB value of route `name`
+    ///
+    /// Logs and returns `None` if the value is not a valid route.
+    fn route_candidate(name: &str, value: &RibValue) -> Option<(SocketAddr, u32)> {
+        match Route::try_from(value) {
+            Ok(route) => Some((route.next_hop, route.cost)),
+            Err(e) => {
+                eprintln!("⚠️  Skipping invalid route {}: {}", name, e);
+                None
             }
         }
+    }
+
+    /// Returns a forwarding entry for every destination with a usable route
+    ///
+    /// Static and dynamic routes are parsed the same way. As when resolving,
+    /// the cheaper of the two wins with ties going to the static route, and
+    /// expired dynamic routes are skipped.
+    pub async fn forwarding_entries(&self) -> Vec<ForwardingEntry> {
+        let rib = self.rib.read().await;
+        let static_routes = rib.query("static_route", |_| true).await;
+        let dynamic_routes = rib.query("route", |_| true).await;
+        drop(rib);
 
-        // No route found
-        Err(AriError::Rmt(crate::error::RmtError::RouteNotFound(
-            dst_addr,
-        )))
+        let metadata = self.metadata_cache.read().await;
+        let mut entries: BTreeMap<u64, ForwardingEntry> = BTreeMap::new();
+        let candidates = static_routes
+            .iter()
+            .map(|obj| (obj, false))
+            .chain(dynamic_routes.iter().map(|obj| (obj, true)));
+
+        for (obj, dynamic) in candidates {
+            let Some(destination) = obj.name.rsplit('/').next().and_then(|d| d.parse().ok()) else {
+                continue;
+            };
+            if dynamic
+                && metadata
+                    .get(&destination)
+                    .is_some_and(RouteMetadata::is_expired)
+            {
+                continue;
+            }
+            let Some(entry) = Route::try_from(&obj.value)
+                .ok()
+                .and_then(|route| route.forwarding_entry(destination))
+            else {
+                continue;
+            };
+
+            if entries
+                .get(&destination)
+                .is_none_or(|existing| entry.cost < existing.cost)
+            {
+                entries.insert(destination, entry);
+            }
+        }
+        entries.into_values().collect()
     }
 
     /// Add a dynamic route (typically during enrollment)
@@ -270,6 +509,18 @@ impl RouteResolver {
         dst_addr: u64,
         next_hop: SocketAddr,
         ttl_seconds: Option<u64>,
+    ) -> Result<(), AriError> {
+        self.add_dynamic_route_with_cost(dst_addr, next_hop, ttl_seconds, DEFAULT_ROUTE_COST)
+            .await
+    }
+
+    /// Add a dynamic route with an explicit cost
+    pub async fn add_dynamic_route_with_cost(
+        &self,
+        dst_addr: u64,
+        next_hop: SocketAddr,
+        ttl_seconds: Option<u64>,
+        cost: u32,
     ) -> Result<(), AriError> {
         let ttl = ttl_seconds.unwrap_or(self.config.default_ttl_seconds);
 
@@ -282,47 +533,51 @@ impl RouteResolver {
                 .unwrap()
                 .as_secs(),
             ttl_seconds: ttl,
+            cost,
         };
 
         // Check if route already exists
         let route_name = format!("/routing/dynamic/{}", dst_addr);
-        let mut route_data = HashMap::new();
-        route_data.insert(
-            "next_hop_address".to_string(),
-            Box::new(RibValue::String(next_hop.to_string())),
-        );
-        route_data.insert(
-            "next_hop_rina_addr".to_string(),
-            Box::new(RibValue::Integer(dst_addr as i64)),
+        let route_data = RibValue::from(
+            Route::new(next_hop)
+                .with_next_hop_rina_addr(dst_addr)
+                .with_cost(cost),
         );
 
         let rib = self.rib.read().await;
-        let route_exists = rib.read(&route_name).await.is_some();
+        let route_exists = rib.read(&route_name).await.is_ok();
 
         if route_exists {
             // Update existing route
-            rib.update(&route_name, RibValue::Struct(route_data))
-                .await
-                .map_err(|e| AriError::Rib(crate::error::RibError::OperationFailed(e)))?;
+            rib.update(&route_name, route_data).await?;
 
-            println!(
+            progress!(
                 "🔄 Updated dynamic route: {} -> {} (TTL: {}s)",
-                dst_addr, next_hop, ttl
+                dst_addr,
+                next_hop,
+                ttl
             );
+            self.notify(RouteEvent::Updated {
+                destination: dst_addr,
+                next_hop,
+                cost,
+            });
         } else {
             // Create new route
-            rib.create(
-                route_name.clone(),
-                "route".to_string(),
-                RibValue::Struct(route_data),
-            )
-            .await
-            .map_err(|e| AriError::Rib(crate::error::RibError::OperationFailed(e)))?;
+            rib.create(route_name.clone(), "route".to_string(), route_data)
+                .await?;
 
-            println!(
+            progress!(
                 "🛣️  Added dynamic route: {} -> {} (TTL: {}s)",
-                dst_addr, next_hop, ttl
+                dst_addr,
+                next_hop,
+                ttl
             );
+            self.notify(RouteEvent::Added {
+                destination: dst_addr,
+                next_hop,
+                cost,
+            });
         }
 
         // Update metadata cache
@@ -335,7 +590,7 @@ impl RouteResolver {
             if let Err(e) = self.save_snapshot().await {
                 eprintln!("⚠️  Failed to save snapshot after adding route: {}", e);
             } else {
-                println!("  ✓ Snapshot saved immediately");
+                progress!("  ✓ Snapshot saved immediately");
             }
         }
 
@@ -344,21 +599,67 @@ impl RouteResolver {
 
     /// Remove a dynamic route (e.g., on disconnection or expiration)
     pub async fn remove_dynamic_route(&self, dst_addr: u64) -> Result<(), AriError> {
+        self.delete_dynamic_route(dst_addr).await?;
+
+        progress!("🗑️  Removed dynamic route: {}", dst_addr);
+        self.notify(RouteEvent::Removed {
+            destination: dst_addr,
+        });
+
+        Ok(())
+    }
+
+    /// Deletes a dynamic route from the RIB and metadata cache
+    async fn delete_dynamic_route(&self, dst_addr: u64) -> Result<(), AriError> {
         let route_name = format!("/routing/dynamic/{}", dst_addr);
 
         let rib = self.rib.read().await;
-        rib.delete(&route_name)
-            .await
-            .map_err(|e| AriError::Rib(crate::error::RibError::OperationFailed(e)))?;
+        rib.delete(&route_name).await?;
 
         let mut cache = self.metadata_cache.write().await;
         cache.remove(&dst_addr);
 
-        println!("🗑️  Removed dynamic route: {}", dst_addr);
-
         Ok(())
     }
 
+    /// Remove every expired dynamic route
+    ///
+    /// Unlike the lazy check in [`resolve_next_hop`](Self::resolve_next_hop),
+    /// this also catches routes that are never looked up. Returns the number
+    /// of routes removed; the snapshot is rewritten if any were.
+    pub async fn sweep_expired(&self) -> usize {
+        let expired: Vec<u64> = self
+            .metadata_cache
+            .read()
+            .await
+            .values()
+            .filter(|metadata| metadata.is_expired())
+            .map(|metadata| metadata.destination)
+            .collect();
+
+        let mut removed = 0;
+        for dst_addr in expired {
+            match self.delete_dynamic_route(dst_addr).await {
+                Ok(()) => {
+                    progress!("⌛ Dynamic route expired: {}", dst_addr);
+                    self.notify(RouteEvent::Expired {
+                        destination: dst_addr,
+                    });
+                    removed += 1;
+                }
+                Err(e) => eprintln!("⚠️  Failed to remove expired route {}: {}", dst_addr, e),
+            }
+        }
+
+        if removed > 0
+            && let Err(e) = self.save_snapshot().await
+        {
+            eprintln!("⚠️  Failed to save snapshot after expiry sweep: {}", e);
+        }
+
+        removed
+    }
+
     /// Load routes from snapshot file (called on startup)
     pub async fn load_snapshot(&self) -> Result<usize, AriError> {
         if !self.config.enable_persistence {
@@ -366,7 +667,7 @@ impl RouteResolver {
         }
 
         if !self.config.snapshot_path.exists() {
-            println!(
+            progress!(
                 "📂 No route snapshot found at {:?}",
                 self.config.snapshot_path
             );
@@ -387,13 +688,18 @@ impl RouteResolver {
 
             let remaining_ttl = metadata.remaining_ttl();
             if remaining_ttl > 0 {
-                self.add_dynamic_route(metadata.destination, next_hop, Some(remaining_ttl))
-                    .await?;
+                self.add_dynamic_route_with_cost(
+                    metadata.destination,
+                    next_hop,
+                    Some(remaining_ttl),
+                    metadata.cost,
+                )
+                .await?;
                 loaded_count += 1;
             }
         }
 
-        println!(
+        progress!(
             "✅ Loaded {} valid dynamic routes from snapshot (filtered {} expired)",
             loaded_count,
             snapshot.routes.len() - loaded_count
@@ -403,24 +709,32 @@ impl RouteResolver {
     }
 
     /// Save current dynamic routes to snapshot file
+    ///
+    /// Waits for a save already in progress to finish first.
     pub async fn save_snapshot(&self) -> Result<(), AriError> {
         if !self.config.enable_persistence {
             return Ok(());
         }
 
+        self.snapshot_guard.run(self.write_snapshot()).await
+    }
+
+    /// Writes the snapshot file; callers hold the snapshot guard
+    async fn write_snapshot(&self) -> Result<(), AriError> {
         let cache = self.metadata_cache.read().await;
         let routes: Vec<RouteMetadata> = cache.values().cloned().collect();
         let route_count = routes.len();
 
-        if route_count == 0 {
-            println!("ℹ️  No dynamic routes to save (cache is empty)");
+        // An existing snapshot is still overwritten so removed routes don't linger
+        if route_count == 0 && !self.config.snapshot_path.exists() {
+            progress!("ℹ️  No dynamic routes to save (cache is empty)");
             return Ok(());
         }
 
         let snapshot = RouteSnapshot::new(routes);
         snapshot.save_to_file(&self.config.snapshot_path)?;
 
-        println!(
+        progress!(
             "💾 Saved {} dynamic routes to snapshot: {:?}",
             snapshot.routes.len(),
             self.config.snapshot_path
@@ -434,36 +748,73 @@ impl RouteResolver {
         let resolver = self.clone();
         tokio::spawn(async move {
             if !resolver.config.enable_persistence {
-                println!("⚠️  Route persistence disabled - snapshot task not started");
+                progress!("⚠️  Route persistence disabled - snapshot task not started");
                 return;
             }
 
             if resolver.config.snapshot_interval_seconds == 0 {
-                println!("⚠️  Snapshot interval is 0 - snapshot task not started");
+                progress!("⚠️  Snapshot interval is 0 - snapshot task not started");
                 return;
             }
 
-            println!(
+            progress!(
                 "✅ Starting route snapshot task (interval: {}s, path: {:?})",
-                resolver.config.snapshot_interval_seconds, resolver.config.snapshot_path
+                resolver.config.snapshot_interval_seconds,
+                resolver.config.snapshot_path
             );
 
             let mut ticker = interval(Duration::from_secs(
                 resolver.config.snapshot_interval_seconds,
             ));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
             loop {
                 ticker.tick().await;
 
                 // Log before attempting save
                 let stats = resolver.get_stats().await;
-                println!(
+                progress!(
                     "🔄 Snapshot task tick: {} dynamic routes",
                     stats.total_dynamic_routes
                 );
 
-                if let Err(e) = resolver.save_snapshot().await {
-                    eprintln!("⚠️  Failed to save route snapshot: {}", e);
+                match resolver
+                    .snapshot_guard
+                    .try_run(resolver.write_snapshot())
+                    .await
+                {
+                    Some(Err(e)) => eprintln!("⚠️  Failed to save route snapshot: {}", e),
+                    Some(Ok(())) => {}
+                    None => progress!("⏭️  Route snapshot already in progress, skipping tick"),
+                }
+            }
+        })
+    }
+
+    /// Start background task that periodically sweeps expired routes
+    pub fn start_expiry_task(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let resolver = self.clone();
+        tokio::spawn(async move {
+            if resolver.config.expiry_sweep_interval_seconds == 0 {
+                progress!("⚠️  Expiry sweep interval is 0 - expiry task not started");
+                return;
+            }
+
+            progress!(
+                "✅ Starting route expiry task (interval: {}s)",
+                resolver.config.expiry_sweep_interval_seconds
+            );
+
+            let mut ticker = interval(Duration::from_secs(
+                resolver.config.expiry_sweep_interval_seconds,
+            ));
+
+            loop {
+                ticker.tick().await;
+
+                let removed = resolver.sweep_expired().await;
+                if removed > 0 {
+                    progress!("🧹 Expiry sweep removed {} dynamic routes", removed);
                 }
             }
         })
@@ -509,6 +860,7 @@ mod tests {
             next_hop_address: "127.0.0.1:8000".to_string(),
             created_at: now,
             ttl_seconds: 0,
+            cost: DEFAULT_ROUTE_COST,
         };
         assert!(!route1.is_expired());
 
@@ -518,6 +870,7 @@ mod tests {
             next_hop_address: "127.0.0.1:8001".to_string(),
             created_at: now - 5,
             ttl_seconds: 10,
+            cost: DEFAULT_ROUTE_COST,
         };
         assert!(!route2.is_expired());
 
@@ -527,6 +880,7 @@ mod tests {
             next_hop_address: "127.0.0.1:8002".to_string(),
             created_at: now - 15,
             ttl_seconds: 10,
+            cost: DEFAULT_ROUTE_COST,
         };
         assert!(route3.is_expired());
     }
@@ -544,12 +898,14 @@ mod tests {
                 next_hop_address: "192.168.1.1:7000".to_string(),
                 created_at: now,
                 ttl_seconds: 3600,
+                cost: DEFAULT_ROUTE_COST,
             },
             RouteMetadata {
                 destination: 200,
                 next_hop_address: "192.168.1.2:7000".to_string(),
                 created_at: now,
                 ttl_seconds: 0,
+                cost: DEFAULT_ROUTE_COST,
             },
         ];
 
@@ -576,6 +932,7 @@ mod tests {
                 next_hop_address: "127.0.0.1:8000".to_string(),
                 created_at: now - 5,
                 ttl_seconds: 10,
+                cost: DEFAULT_ROUTE_COST,
             },
             // Expired route
             RouteMetadata {
@@ -583,6 +940,7 @@ mod tests {
                 next_hop_address: "127.0.0.1:8001".to_string(),
                 created_at: now - 20,
                 ttl_seconds: 10,
+                cost: DEFAULT_ROUTE_COST,
             },
             // Never expires
             RouteMetadata {
@@ -590,6 +948,7 @@ mod tests {
                 next_hop_address: "127.0.0.1:8002".to_string(),
                 created_at: now - 100,
                 ttl_seconds: 0,
+                cost: DEFAULT_ROUTE_COST,
             },
         ];
 
@@ -601,4 +960,243 @@ mod tests {
         assert!(valid.iter().any(|r| r.destination == 100));
         assert!(valid.iter().any(|r| r.destination == 300));
     }
+
+    #[tokio::test]
+    async fn test_lowest_cost_route_is_selected() {
+        let rib = Rib::new();
+        let resolver = RouteResolver::new(
+            Arc::new(RwLock::new(rib.clone())),
+            RouteResolverConfig::default(),
+        );
+
+        // Expensive static route
+        let static_route =
+            RibValue::from(Route::new("127.0.0.1:9000".parse().unwrap()).with_cost(10));
+        rib.create(
+            "/routing/static/500".to_string(),
+            "static_route".to_string(),
+            static_route,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            resolver.resolve_next_hop_with_cost(500).await.unwrap(),
+            ("127.0.0.1:9000".parse().unwrap(), 10)
+        );
+
+        // Cheaper dynamic route to the same destination
+        let cheap_hop: SocketAddr = "127.0.0.1:9001".parse().unwrap();
+        resolver
+            .add_dynamic_route_with_cost(500, cheap_hop, Some(3600), 2)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            resolver.resolve_next_hop_with_cost(500).await.unwrap(),
+            (cheap_hop, 2)
+        );
+        assert_eq!(resolver.resolve_next_hop(500).await.unwrap(), cheap_hop);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_route_is_skipped_when_resolving() {
+        let rib = Rib::new();
+        let resolver = RouteResolver::new(
+            Arc::new(RwLock::new(rib.clone())),
+            RouteResolverConfig::default(),
+        );
+
+        // A static route with a malformed next hop
+        let broken = RibValue::struct_builder()
+            .field("next_hop_address", "not-a-socket")
+            .build();
+        rib.create(
+            "/routing/static/600".to_string(),
+            "static_route".to_string(),
+            broken,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            resolver.resolve_next_hop(600).await,
+            Err(AriError::Rmt(RmtError::RouteNotFound(600)))
+        ));
+
+        // It does not hide a valid dynamic route to the same destination
+        let hop: SocketAddr = "127.0.0.1:9002".parse().unwrap();
+        resolver
+            .add_dynamic_route_with_cost(600, hop, Some(3600), 5)
+            .await
+            .unwrap();
+        assert_eq!(
+            resolver.resolve_next_hop_with_cost(600).await.unwrap(),
+            (hop, 5)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_cost_does_not_wrap_into_a_cheap_route() {
+        let rib = Rib::new();
+        let resolver = RouteResolver::new(
+            Arc::new(RwLock::new(rib.clone())),
+            RouteResolverConfig::default(),
+        );
+        let hop: SocketAddr = "127.0.0.1:9003".parse().unwrap();
+        resolver
+            .add_dynamic_route_with_cost(700, hop, Some(3600), 5)
+            .await
+            .unwrap();
+
+        // 2^32 + 1 would truncate to a cost of 1, and -1 to u32::MAX
+        for (dst, cost) in [(700, (1i64 << 32) + 1), (701, -1)] {
+            let route = RibValue::struct_builder()
+                .field("next_hop_address", "127.0.0.1:9004")
+                .field("cost", cost)
+                .build();
+            rib.create(
+                format!("/routing/static/{}", dst),
+                "static_route".to_string(),
+                route,
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(
+            resolver.resolve_next_hop_with_cost(700).await.unwrap(),
+            (hop, 5)
+        );
+        assert!(resolver.resolve_next_hop(701).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_sees_added_then_expired() {
+        let resolver = RouteResolver::new(
+            Arc::new(RwLock::new(Rib::new())),
+            RouteResolverConfig::default(),
+        );
+        let mut events = resolver.subscribe();
+
+        let next_hop: SocketAddr = "127.0.0.1:9100".parse().unwrap();
+        resolver
+            .add_dynamic_route(600, next_hop, Some(1))
+            .await
+            .unwrap();
+
+        // TTL is checked with one-second granularity
+        tokio::time::sleep(Duration::from_millis(2100)).await;
+        assert!(resolver.resolve_next_hop(600).await.is_err());
+
+        assert_eq!(
+            events.recv().await,
+            Some(RouteEvent::Added {
+                destination: 600,
+                next_hop,
+                cost: DEFAULT_ROUTE_COST,
+            })
+        );
+        assert_eq!(
+            events.recv().await,
+            Some(RouteEvent::Expired { destination: 600 })
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_removes_unqueried_expired_routes() {
+        let snapshot_path = std::env::temp_dir().join("test_sweep_expired_routes.toml");
+        let _ = std::fs::remove_file(&snapshot_path);
+
+        let rib = Rib::new();
+        let resolver = RouteResolver::new(
+            Arc::new(RwLock::new(rib.clone())),
+            RouteResolverConfig {
+                enable_persistence: true,
+                snapshot_path: snapshot_path.clone(),
+                ..RouteResolverConfig::default()
+            },
+        );
+
+        let next_hop: SocketAddr = "127.0.0.1:9200".parse().unwrap();
+        resolver
+            .add_dynamic_route(700, next_hop, Some(1))
+            .await
+            .unwrap();
+        resolver
+            .add_dynamic_route(701, next_hop, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            RouteSnapshot::load_from_file(&snapshot_path)
+                .unwrap()
+                .routes
+                .len(),
+            2
+        );
+
+        // Nothing expired yet
+        assert_eq!(resolver.sweep_expired().await, 0);
+
+        // TTL is checked with one-second granularity
+        tokio::time::sleep(Duration::from_millis(2100)).await;
+        assert_eq!(resolver.sweep_expired().await, 2);
+
+        assert_eq!(resolver.get_stats().await.total_dynamic_routes, 0);
+        assert!(rib.read("/routing/dynamic/700").await.is_err());
+        assert!(rib.read("/routing/dynamic/701").await.is_err());
+        assert!(
+            RouteSnapshot::load_from_file(&snapshot_path)
+                .unwrap()
+                .routes
+                .is_empty()
+        );
+
+        let _ = std::fs::remove_file(&snapshot_path);
+    }
+
+    #[test]
+    fn test_route_round_trips_through_rib_value() {
+        let route = Route::new("127.0.0.1:7000".parse().unwrap())
+            .with_next_hop_rina_addr(1002)
+            .with_cost(4);
+        let value = RibValue::from(route);
+
+        assert_eq!(value.get_integer_path("next_hop_rina_addr"), Some(1002));
+        assert_eq!(Route::try_from(&value).unwrap(), route);
+
+        // Optional fields fall back to their defaults
+        let bare = RibValue::struct_builder()
+            .field("next_hop_address", "127.0.0.1:7001")
+            .build();
+        assert_eq!(
+            Route::try_from(&bare).unwrap(),
+            Route::new("127.0.0.1:7001".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_route_parse_rejects_wrong_field_types() {
+        let stringly_rina_addr = RibValue::struct_builder()
+            .field("next_hop_address", "127.0.0.1:7000")
+            .field("next_hop_rina_addr", "1002")
+            .build();
+        assert!(matches!(
+            Route::try_from(&stringly_rina_addr),
+            Err(RmtError::InvalidRoute(_))
+        ));
+
+        let integer_next_hop = RibValue::struct_builder()
+            .field("next_hop_address", 7000)
+            .build();
+        assert!(Route::try_from(&integer_next_hop).is_err());
+
+        let negative_cost = RibValue::struct_builder()
+            .field("next_hop_address", "127.0.0.1:7000")
+            .field("cost", -1)
+            .build();
+        assert!(Route::try_from(&negative_cost).is_err());
+
+        assert!(Route::try_from(&RibValue::String("127.0.0.1:7000".to_string())).is_err());
+    }
 }
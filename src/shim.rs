@@ -12,10 +12,42 @@
 //! allows for future implementations using TCP, QUIC, Unix sockets, etc.
 
 use crate::pdu::Pdu;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::{SocketAddr, UdpSocket};
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Kernel receive buffer (SO_RCVBUF) requested for shim sockets by default
+///
+/// Fragmented CDAP messages (e.g. large RIB snapshots) arrive as a burst of
+/// datagrams; the default buffer is small enough to drop the tail of a burst.
+pub const DEFAULT_SO_RCVBUF: usize = 1024 * 1024;
+
+/// Default size of the buffer a single datagram is received into
+pub const DEFAULT_MAX_BUFFER_SIZE: usize = 65536;
+
+/// Largest payload a single UDP datagram can carry over IPv4
+///
+/// Bigger payloads fail in the kernel with an error that does not say why,
+/// so they are refused before sending.
+pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = 65507;
+
+/// How long a receive waits for a datagram before reporting none, by default
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Pause between polls when receiving across several sockets
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Datagrams the background receive thread may read ahead of the async receivers
+const RECEIVE_QUEUE_CAPACITY: usize = 1024;
+
+/// A datagram, or the error met reading one, from the background receive thread
+type Received = Result<(Vec<u8>, SocketAddr), ShimError>;
+
+/// Datagrams read by the background receive thread, in arrival order
+type ReceiveQueue = Arc<tokio::sync::Mutex<mpsc::Receiver<Received>>>;
 
 /// Shim layer trait - abstraction for underlay protocols
 ///
@@ -56,6 +88,10 @@ pub enum ShimError {
     AddressError(String),
     /// Socket not bound
     NotBound,
+    /// A datagram from `from` did not fit the receive buffer of `capacity` bytes
+    Truncated { from: SocketAddr, capacity: usize },
+    /// A payload of `size` bytes exceeds the `limit` a datagram may carry
+    PayloadTooLarge { size: usize, limit: usize },
 }
 
 impl std::fmt::Display for ShimError {
@@ -66,6 +102,16 @@ impl std::fmt::Display for ShimError {
             ShimError::ReceiveError(msg) => write!(f, "Receive error: {}", msg),
             ShimError::AddressError(msg) => write!(f, "Address error: {}", msg),
             ShimError::NotBound => write!(f, "Socket not bound"),
+            ShimError::Truncated { from, capacity } => write!(
+                f,
+                "Datagram from {} exceeds the {}-byte receive buffer",
+                from, capacity
+            ),
+            ShimError::PayloadTooLarge { size, limit } => write!(
+                f,
+                "Payload of {} bytes exceeds the {}-byte datagram limit; fragment it first",
+                size, limit
+            ),
         }
     }
 }
@@ -87,92 +133,472 @@ pub struct AddressMapping {
     pub socket_addr: SocketAddr,
 }
 
+/// Snapshot of a shim's datagram and byte counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShimStats {
+    /// Datagrams successfully handed to the socket
+    pub datagrams_sent: u64,
+    /// Bytes successfully handed to the socket
+    pub bytes_sent: u64,
+    /// Datagrams read from the socket
+    pub datagrams_received: u64,
+    /// Bytes read from the socket
+    pub bytes_received: u64,
+}
+
+/// Live I/O counters, updated without taking the socket lock
+#[derive(Debug, Default)]
+struct ShimCounters {
+    datagrams_sent: AtomicU64,
+    bytes_sent: AtomicU64,
+    datagrams_received: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl ShimCounters {
+    fn record_sent(&self, bytes: usize) {
+        self.datagrams_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn record_received(&self, bytes: usize) {
+        self.datagrams_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> ShimStats {
+        ShimStats {
+            datagrams_sent: self.datagrams_sent.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            datagrams_received: self.datagrams_received.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Locks `mutex`, recovering the data if a previous holder panicked
+///
+/// Every critical section in this module leaves the data consistent at each
+/// step, so a panic elsewhere must not take networking down with it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Waits up to `timeout` for a datagram on any of `sockets`
+///
+/// A single socket blocks in its own receive, which returns as soon as a
+/// datagram arrives; several are polled in turn. Each blocking receive
+/// lasts up to the socket's read timeout, so `timeout` is rounded up to a
+/// multiple of it. Blocks the calling thread.
+fn receive_any(
+    sockets: &[UdpSocket],
+    max_buffer_size: usize,
+    timeout: Duration,
+) -> Result<Option<(Vec<u8>, SocketAddr)>, ShimError> {
+    let deadline = Instant::now() + timeout;
+    // One spare byte tells a datagram that exactly fits from one that was cut off
+    let mut buffer = vec![0u8; max_buffer_size + 1];
+
+    loop {
+        for socket in sockets {
+            match socket.recv_from(&mut buffer) {
+                Ok((size, src_addr)) if size > max_buffer_size => {
+                    return Err(ShimError::Truncated {
+                        from: src_addr,
+                        capacity: max_buffer_size,
+                    });
+                }
+                Ok((size, src_addr)) => {
+                    buffer.truncate(size);
+                    return Ok(Some((buffer, src_addr)));
+                }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    // No data available on this socket
+                }
+                Err(e) => {
+                    return Err(ShimError::ReceiveError(format!("Failed to receive: {}", e)));
+                }
+            }
+        }
+
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        // A single socket has just waited in its receive; no need to pause
+        if sockets.len() > 1 {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Reads datagrams from `sockets` into `tx` until every receiver is gone
+///
+/// Stops early after a receive error other than a truncated datagram; the
+/// error is queued first so a receiver sees it.
+fn run_receive_thread(
+    sockets: Vec<UdpSocket>,
+    max_buffer_size: usize,
+    read_timeout: Duration,
+    tx: mpsc::Sender<Received>,
+) {
+    while !tx.is_closed() {
+        let received = match receive_any(&sockets, max_buffer_size, read_timeout) {
+            Ok(None) => continue,
+            Ok(Some(datagram)) => Ok(datagram),
+            Err(e) => Err(e),
+        };
+        let fatal = matches!(&received, Err(e) if !matches!(e, ShimError::Truncated { .. }));
+        if tx.blocking_send(received).is_err() || fatal {
+            break;
+        }
+    }
+}
+
 /// UDP/IP Shim Layer
 ///
 /// Provides abstraction over UDP sockets for RINA communication
 pub struct UdpShim {
-    /// The underlying UDP socket
-    socket: Arc<Mutex<Option<UdpSocket>>>,
+    /// The underlying UDP sockets, one per bound local address
+    ///
+    /// This and `address_mapper` are std mutexes: every critical section is
+    /// short and synchronous, so neither is ever held across an `.await` or
+    /// while blocked waiting for a datagram.
+    sockets: Arc<Mutex<Vec<UdpSocket>>>,
     /// Local RINA address
     local_rina_addr: u64,
     /// Maximum receive buffer size
     max_buffer_size: usize,
+    /// Largest payload a send accepts
+    max_payload_size: usize,
+    /// How long a receive waits for a datagram
+    read_timeout: Duration,
+    /// Kernel receive buffer requested at bind (0 = OS default)
+    so_rcvbuf: usize,
+    /// Kernel send buffer requested at bind (0 = OS default)
+    so_sndbuf: usize,
     /// Address mapper for RINA to socket address translation
     address_mapper: Arc<Mutex<HashMap<u64, SocketAddr>>>,
+    /// Peers that negotiated no compression and get uncompressed PDUs
+    uncompressed_peers: Mutex<HashSet<u64>>,
+    /// Datagram and byte counters
+    counters: ShimCounters,
+    /// Queue of the receive thread serving async receives, once started
+    ///
+    /// A receive that is cancelled while waiting leaves any datagram it
+    /// would have returned in the queue for the next one.
+    receive_queue: Mutex<Option<ReceiveQueue>>,
 }
 
 impl UdpShim {
     /// Creates a new UDP shim layer
     pub fn new(local_rina_addr: u64) -> Self {
         Self {
-            socket: Arc::new(Mutex::new(None)),
+            sockets: Arc::new(Mutex::new(Vec::new())),
             local_rina_addr,
-            max_buffer_size: 65536,
+            max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+            max_payload_size: DEFAULT_MAX_PAYLOAD_SIZE,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            so_rcvbuf: DEFAULT_SO_RCVBUF,
+            so_sndbuf: 0,
             address_mapper: Arc::new(Mutex::new(HashMap::new())),
+            uncompressed_peers: Mutex::new(HashSet::new()),
+            counters: ShimCounters::default(),
+            receive_queue: Mutex::new(None),
         }
     }
 
     /// Binds the shim to a UDP socket address
+    ///
+    /// Replaces any sockets bound before.
     pub fn bind(&self, addr: &str) -> Result<(), ShimError> {
+        self.bind_all(&[addr])
+    }
+
+    /// Binds the shim to several local addresses, e.g. one per interface
+    ///
+    /// Either every address is bound and replaces the sockets bound before,
+    /// or none is. Datagrams are received on all of them; sends go out of
+    /// the socket best matching the destination (see [`UdpShim::send_to`]).
+    pub fn bind_all(&self, addrs: &[&str]) -> Result<(), ShimError> {
+        if addrs.is_empty() {
+            return Err(ShimError::BindError("No address to bind".to_string()));
+        }
+
+        let sockets = addrs
+            .iter()
+            .map(|addr| self.open_socket(addr, addrs.len() > 1))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut sock_guard = lock(&self.sockets);
+        *sock_guard = sockets;
+        // The receive thread still reads the old sockets; the next async
+        // receive starts one on the new ones
+        lock(&self.receive_queue).take();
+
+        Ok(())
+    }
+
+    /// Opens one socket; sockets that are polled together must not block
+    fn open_socket(&self, addr: &str, nonblocking: bool) -> Result<UdpSocket, ShimError> {
         let socket = UdpSocket::bind(addr)
             .map_err(|e| ShimError::BindError(format!("Failed to bind to {}: {}", addr, e)))?;
 
-        // Set non-blocking mode with a timeout
-        socket
-            .set_read_timeout(Some(Duration::from_millis(100)))
-            .map_err(|e| ShimError::BindError(format!("Failed to set read timeout: {}", e)))?;
+        if nonblocking {
+            socket.set_nonblocking(true).map_err(|e| {
+                ShimError::BindError(format!("Failed to set non-blocking mode: {}", e))
+            })?;
+        } else {
+            // Set non-blocking mode with a timeout
+            socket
+                .set_read_timeout(Some(self.read_timeout))
+                .map_err(|e| ShimError::BindError(format!("Failed to set read timeout: {}", e)))?;
+        }
 
-        let mut sock_guard = self.socket.lock().unwrap();
-        *sock_guard = Some(socket);
+        // Enlarge the kernel buffers so fragment bursts are not dropped
+        let sock_ref = socket2::SockRef::from(&socket);
+        if self.so_rcvbuf > 0 {
+            sock_ref.set_recv_buffer_size(self.so_rcvbuf).map_err(|e| {
+                ShimError::BindError(format!("Failed to set receive buffer: {}", e))
+            })?;
+        }
+        if self.so_sndbuf > 0 {
+            sock_ref
+                .set_send_buffer_size(self.so_sndbuf)
+                .map_err(|e| ShimError::BindError(format!("Failed to set send buffer: {}", e)))?;
+        }
 
-        Ok(())
+        Ok(socket)
+    }
+
+    /// Picks the bound socket to send towards `dest` from
+    ///
+    /// Only sockets of the destination's address family qualify. Among those
+    /// a socket bound to the destination's own IP wins, then one on the same
+    /// side of the loopback boundary or bound to the wildcard address.
+    fn select_socket(sockets: &[UdpSocket], dest: &SocketAddr) -> Option<usize> {
+        sockets
+            .iter()
+            .enumerate()
+            .filter_map(|(index, socket)| {
+                let local = socket.local_addr().ok()?;
+                if local.is_ipv4() != dest.is_ipv4() {
+                    return None;
+                }
+                let score = if local.ip() == dest.ip() {
+                    2
+                } else if local.ip().is_unspecified()
+                    || local.ip().is_loopback() == dest.ip().is_loopback()
+                {
+                    1
+                } else {
+                    0
+                };
+                Some((score, index))
+            })
+            .max_by_key(|&(score, index)| (score, std::cmp::Reverse(index)))
+            .map(|(_, index)| index)
     }
 
     /// Sends data to a destination UDP address
+    ///
+    /// Payloads over the configured limit are refused with
+    /// [`ShimError::PayloadTooLarge`] rather than handed to the kernel.
     pub fn send_to(&self, data: &[u8], dest_addr: &str) -> Result<usize, ShimError> {
-        let sock_guard = self.socket.lock().unwrap();
-        let socket = sock_guard.as_ref().ok_or(ShimError::NotBound)?;
+        if data.len() > self.max_payload_size {
+            return Err(ShimError::PayloadTooLarge {
+                size: data.len(),
+                limit: self.max_payload_size,
+            });
+        }
+
+        let sock_guard = lock(&self.sockets);
+        if sock_guard.is_empty() {
+            return Err(ShimError::NotBound);
+        }
 
         let dest: SocketAddr = dest_addr.parse().map_err(|e| {
             ShimError::AddressError(format!("Invalid address {}: {}", dest_addr, e))
         })?;
 
-        socket
+        let socket = Self::select_socket(&sock_guard, &dest)
+            .map(|index| &sock_guard[index])
+            .ok_or_else(|| {
+                ShimError::SendError(format!("No bound socket can reach {}", dest_addr))
+            })?;
+
+        let sent = socket
             .send_to(data, dest)
-            .map_err(|e| ShimError::SendError(format!("Failed to send: {}", e)))
+            .map_err(|e| ShimError::SendError(format!("Failed to send: {}", e)))?;
+        self.counters.record_sent(sent);
+        Ok(sent)
     }
 
     /// Receives data from the socket
     ///
     /// Returns (data, source_address) if data was received,
     /// or None if no data is available (non-blocking)
+    ///
+    /// With several bound sockets all of them are polled until the receive
+    /// timeout elapses. This blocks the calling thread for up to the read
+    /// timeout; async code should use [`UdpShim::recv_from_async`]. Once an
+    /// async receive has started the receive thread, datagrams are taken
+    /// from its queue instead.
     pub fn recv_from(&self) -> Result<Option<(Vec<u8>, SocketAddr)>, ShimError> {
-        let sock_guard = self.socket.lock().unwrap();
-        let socket = sock_guard.as_ref().ok_or(ShimError::NotBound)?;
-
-        let mut buffer = vec![0u8; self.max_buffer_size];
+        let queue = lock(&self.receive_queue).clone();
+        let received = match queue {
+            Some(queue) => self.poll_queue(&queue)?,
+            None => {
+                let sockets = self.socket_handles()?;
+                receive_any(&sockets, self.max_buffer_size, self.read_timeout)?
+            }
+        };
+        if let Some((data, _)) = &received {
+            self.counters.record_received(data.len());
+        }
+        Ok(received)
+    }
 
-        match socket.recv_from(&mut buffer) {
-            Ok((size, src_addr)) => {
-                buffer.truncate(size);
-                Ok(Some((buffer, src_addr)))
+    /// Polls the receive thread's queue until the read timeout elapses
+    fn poll_queue(&self, queue: &ReceiveQueue) -> Result<Option<(Vec<u8>, SocketAddr)>, ShimError> {
+        let deadline = Instant::now() + self.read_timeout;
+        loop {
+            if let Ok(mut rx) = queue.try_lock() {
+                match rx.try_recv() {
+                    Ok(received) => return received.map(Some),
+                    Err(mpsc::error::TryRecvError::Disconnected) => {
+                        self.reset_receive_queue(queue);
+                        return Ok(None);
+                    }
+                    Err(mpsc::error::TryRecvError::Empty) => {}
+                }
             }
-            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                // No data available (timeout)
-                Ok(None)
+            if Instant::now() >= deadline {
+                return Ok(None);
             }
-            Err(e) => Err(ShimError::ReceiveError(format!("Failed to receive: {}", e))),
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Receives data without blocking the async runtime
+    ///
+    /// The sockets are read by a long-lived thread, so other tasks keep
+    /// running meanwhile, including on a single-threaded runtime where the
+    /// datagram being waited for may be sent by a task on the same thread.
+    /// Cancel safe: dropping the future loses no datagram.
+    pub async fn recv_from_async(&self) -> Result<Option<(Vec<u8>, SocketAddr)>, ShimError> {
+        self.recv_from_within(self.read_timeout).await
+    }
+
+    /// Waits up to `timeout` for data without blocking the async runtime
+    ///
+    /// Returns as soon as a datagram arrives rather than on a polling tick.
+    /// Cancel safe, like [`UdpShim::recv_from_async`].
+    pub async fn recv_from_within(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<(Vec<u8>, SocketAddr)>, ShimError> {
+        let queue = self.receive_queue()?;
+        let next = {
+            let mut rx = queue.lock().await;
+            tokio::time::timeout(timeout, rx.recv()).await
+        };
+        let received = match next {
+            Ok(Some(received)) => received?,
+            Ok(None) => {
+                // The receive thread stopped; the next receive starts another
+                self.reset_receive_queue(&queue);
+                return Ok(None);
+            }
+            Err(_) => return Ok(None),
+        };
+
+        self.counters.record_received(received.0.len());
+        Ok(Some(received))
+    }
+
+    /// Returns the queue of the receive thread, starting the thread if needed
+    fn receive_queue(&self) -> Result<ReceiveQueue, ShimError> {
+        let mut queue = lock(&self.receive_queue);
+        if let Some(queue) = queue.as_ref() {
+            return Ok(queue.clone());
+        }
+
+        let sockets = self.socket_handles()?;
+        let (tx, rx) = mpsc::channel(RECEIVE_QUEUE_CAPACITY);
+        let (max_buffer_size, read_timeout) = (self.max_buffer_size, self.read_timeout);
+        std::thread::Builder::new()
+            .name(format!("shim-rx-{}", self.local_rina_addr))
+            .spawn(move || run_receive_thread(sockets, max_buffer_size, read_timeout, tx))
+            .map_err(|e| {
+                ShimError::ReceiveError(format!("Failed to start receive thread: {}", e))
+            })?;
+
+        let started = Arc::new(tokio::sync::Mutex::new(rx));
+        *queue = Some(started.clone());
+        Ok(started)
+    }
+
+    /// Forgets `queue` if it is still the current one
+    fn reset_receive_queue(&self, queue: &ReceiveQueue) {
+        let mut current = lock(&self.receive_queue);
+        if current
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, queue))
+        {
+            current.take();
         }
     }
 
+    /// Duplicates the bound sockets so they can be waited on without the lock
+    ///
+    /// Receiving blocks for up to the read timeout; doing it on handles
+    /// rather than under the socket lock keeps sends and binds from stalling.
+    fn socket_handles(&self) -> Result<Vec<UdpSocket>, ShimError> {
+        let sock_guard = lock(&self.sockets);
+        if sock_guard.is_empty() {
+            return Err(ShimError::NotBound);
+        }
+        sock_guard
+            .iter()
+            .map(|socket| {
+                socket
+                    .try_clone()
+                    .map_err(|e| ShimError::ReceiveError(format!("Failed to clone socket: {}", e)))
+            })
+            .collect()
+    }
+
     /// Returns the local socket address if bound
+    ///
+    /// With several bound sockets, this is the first one's address.
     pub fn local_addr(&self) -> Result<SocketAddr, ShimError> {
-        let sock_guard = self.socket.lock().unwrap();
-        let socket = sock_guard.as_ref().ok_or(ShimError::NotBound)?;
+        self.local_addrs()?
+            .into_iter()
+            .next()
+            .ok_or(ShimError::NotBound)
+    }
 
-        socket
-            .local_addr()
-            .map_err(|e| ShimError::ReceiveError(format!("Failed to get local address: {}", e)))
+    /// Returns the local addresses of all bound sockets
+    pub fn local_addrs(&self) -> Result<Vec<SocketAddr>, ShimError> {
+        let sock_guard = lock(&self.sockets);
+        if sock_guard.is_empty() {
+            return Err(ShimError::NotBound);
+        }
+
+        sock_guard
+            .iter()
+            .map(|socket| {
+                socket.local_addr().map_err(|e| {
+                    ShimError::ReceiveError(format!("Failed to get local address: {}", e))
+                })
+            })
+            .collect()
     }
 
     /// Returns the local RINA address
@@ -180,36 +606,97 @@ impl UdpShim {
         self.local_rina_addr
     }
 
+    /// Returns the datagram and byte counters accumulated so far
+    pub fn stats(&self) -> ShimStats {
+        self.counters.snapshot()
+    }
+
     /// Sets the maximum receive buffer size
     pub fn set_max_buffer_size(&mut self, size: usize) {
         self.max_buffer_size = size;
     }
 
+    /// Sets the largest payload a send accepts, e.g. derived from the path MTU
+    pub fn set_max_payload_size(&mut self, size: usize) {
+        self.max_payload_size = size;
+    }
+
+    /// Returns the largest payload a send accepts
+    pub fn max_payload_size(&self) -> usize {
+        self.max_payload_size
+    }
+
+    /// Sets how long a receive waits for a datagram (applied at bind)
+    ///
+    /// Zero is raised to one millisecond, as sockets cannot wait for no time.
+    pub fn set_read_timeout(&mut self, timeout: Duration) {
+        self.read_timeout = timeout.max(Duration::from_millis(1));
+    }
+
+    /// Returns how long a receive waits for a datagram
+    pub fn read_timeout(&self) -> Duration {
+        self.read_timeout
+    }
+
+    /// Sets the kernel receive buffer (SO_RCVBUF) requested at bind, 0 for the OS default
+    pub fn set_so_rcvbuf(&mut self, size: usize) {
+        self.so_rcvbuf = size;
+    }
+
+    /// Sets the kernel send buffer (SO_SNDBUF) requested at bind, 0 for the OS default
+    pub fn set_so_sndbuf(&mut self, size: usize) {
+        self.so_sndbuf = size;
+    }
+
     /// Registers a RINA address to socket address mapping
     pub fn register_peer(&self, rina_addr: u64, socket_addr: SocketAddr) {
-        let mut mapper = self.address_mapper.lock().unwrap();
+        let mut mapper = lock(&self.address_mapper);
         mapper.insert(rina_addr, socket_addr);
     }
 
     /// Looks up socket address for a RINA address
     pub fn lookup_peer(&self, rina_addr: u64) -> Option<SocketAddr> {
-        let mapper = self.address_mapper.lock().unwrap();
+        let mapper = lock(&self.address_mapper);
         mapper.get(&rina_addr).copied()
     }
 
+    /// Records whether the peer at `rina_addr` can decompress PDUs
+    ///
+    /// Peers compress by default; a peer that negotiated no compression
+    /// only ever gets uncompressed PDUs.
+    pub fn set_peer_compression(&self, rina_addr: u64, enabled: bool) {
+        let mut peers = lock(&self.uncompressed_peers);
+        if enabled {
+            peers.remove(&rina_addr);
+        } else {
+            peers.insert(rina_addr);
+        }
+    }
+
+    /// Serializes `pdu` the way the peer at `rina_addr` can decode it
+    pub fn serialize_for(&self, pdu: &Pdu, rina_addr: u64) -> Result<Vec<u8>, ShimError> {
+        let compress = !lock(&self.uncompressed_peers).contains(&rina_addr);
+        let data = if compress {
+            pdu.serialize()
+        } else {
+            pdu.serialize_uncompressed()
+        };
+        data.map_err(|e| ShimError::SendError(format!("PDU serialization failed: {}", e)))
+    }
+
     /// Sends a PDU over the network
     pub fn send_pdu(&self, pdu: &Pdu) -> Result<usize, ShimError> {
+        self.send_pdu_via(pdu, pdu.dst_addr)
+    }
+
+    /// Sends a PDU to the peer registered for `next_hop`, which may be a relay
+    pub fn send_pdu_via(&self, pdu: &Pdu, next_hop: u64) -> Result<usize, ShimError> {
         // Serialize the PDU
-        let data = pdu
-            .serialize()
-            .map_err(|e| ShimError::SendError(format!("PDU serialization failed: {}", e)))?;
+        let data = self.serialize_for(pdu, next_hop)?;
 
         // Look up destination socket address
-        let dest_socket = self.lookup_peer(pdu.dst_addr).ok_or_else(|| {
-            ShimError::SendError(format!(
-                "No mapping found for RINA address {}",
-                pdu.dst_addr
-            ))
+        let dest_socket = self.lookup_peer(next_hop).ok_or_else(|| {
+            ShimError::SendError(format!("No mapping found for RINA address {}", next_hop))
         })?;
 
         // Send via UDP
@@ -234,6 +721,27 @@ impl UdpShim {
             None => Ok(None),
         }
     }
+
+    /// Receives and deserializes a PDU without blocking the async runtime
+    pub async fn receive_pdu_async(&self) -> Result<Option<(Pdu, SocketAddr)>, ShimError> {
+        self.receive_pdu_within(self.read_timeout).await
+    }
+
+    /// Waits up to `timeout` for a PDU without blocking the async runtime
+    pub async fn receive_pdu_within(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<(Pdu, SocketAddr)>, ShimError> {
+        match self.recv_from_within(timeout).await? {
+            Some((data, src_addr)) => {
+                let pdu = Pdu::deserialize(&data).map_err(|e| {
+                    ShimError::ReceiveError(format!("PDU deserialization failed: {}", e))
+                })?;
+                Ok(Some((pdu, src_addr)))
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 impl Shim for UdpShim {
@@ -267,7 +775,8 @@ impl std::fmt::Debug for UdpShim {
         f.debug_struct("UdpShim")
             .field("local_rina_addr", &self.local_rina_addr)
             .field("max_buffer_size", &self.max_buffer_size)
-            .field("bound", &self.socket.lock().unwrap().is_some())
+            .field("read_timeout", &self.read_timeout)
+            .field("bound_sockets", &lock(&self.sockets).len())
             .finish()
     }
 }
@@ -288,25 +797,25 @@ impl AddressMapper {
 
     /// Adds a mapping
     pub fn add_mapping(&self, rina_addr: u64, socket_addr: SocketAddr) {
-        let mut mappings = self.mappings.lock().unwrap();
+        let mut mappings = lock(&self.mappings);
         mappings.insert(rina_addr, socket_addr);
     }
 
     /// Looks up a socket address for a RINA address
     pub fn lookup(&self, rina_addr: u64) -> Option<SocketAddr> {
-        let mappings = self.mappings.lock().unwrap();
+        let mappings = lock(&self.mappings);
         mappings.get(&rina_addr).copied()
     }
 
     /// Removes a mapping
     pub fn remove_mapping(&self, rina_addr: u64) {
-        let mut mappings = self.mappings.lock().unwrap();
+        let mut mappings = lock(&self.mappings);
         mappings.remove(&rina_addr);
     }
 
     /// Returns the number of mappings
     pub fn mapping_count(&self) -> usize {
-        let mappings = self.mappings.lock().unwrap();
+        let mappings = lock(&self.mappings);
         mappings.len()
     }
 }
@@ -327,6 +836,21 @@ mod tests {
         assert_eq!(shim.local_rina_addr(), 1000);
     }
 
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_peers_without_compression_get_uncompressed_pdus() {
+        let shim = UdpShim::new(1000);
+        shim.set_peer_compression(2000, false);
+        let pdu = Pdu::new_management(1000, 2000, vec![7; 4096]);
+
+        let plain = shim.serialize_for(&pdu, 2000).unwrap();
+        assert_eq!(plain, pdu.serialize_uncompressed().unwrap());
+        assert!(shim.serialize_for(&pdu, 3000).unwrap().len() < plain.len());
+
+        shim.set_peer_compression(2000, true);
+        assert!(shim.serialize_for(&pdu, 2000).unwrap().len() < plain.len());
+    }
+
     #[test]
     fn test_shim_bind() {
         let shim = UdpShim::new(1000);
@@ -364,6 +888,79 @@ mod tests {
         assert_eq!(src, addr1);
     }
 
+    #[test]
+    fn test_shim_stats_count_datagrams_and_bytes() {
+        let sender = UdpShim::new(1000);
+        let receiver = UdpShim::new(2000);
+        sender.bind("127.0.0.1:0").unwrap();
+        receiver.bind("127.0.0.1:0").unwrap();
+        let dest = receiver.local_addr().unwrap().to_string();
+
+        sender.send_to(b"first", &dest).unwrap();
+        sender.send_to(b"second datagram", &dest).unwrap();
+
+        // Each receive waits at most for the socket's read timeout
+        let received = (0..20)
+            .filter_map(|_| receiver.recv_from().unwrap())
+            .take(2)
+            .count();
+        assert_eq!(received, 2);
+
+        assert_eq!(
+            sender.stats(),
+            ShimStats {
+                datagrams_sent: 2,
+                bytes_sent: 20,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            receiver.stats(),
+            ShimStats {
+                datagrams_received: 2,
+                bytes_received: 20,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_shim_receives_on_all_bound_addresses() {
+        let multi = UdpShim::new(1000);
+        multi.bind_all(&["127.0.0.1:0", "127.0.0.2:0"]).unwrap();
+        let local = multi.local_addrs().unwrap();
+        assert_eq!(local.len(), 2);
+
+        let peer_a = UdpShim::new(2000);
+        let peer_b = UdpShim::new(3000);
+        peer_a.bind("127.0.0.1:0").unwrap();
+        peer_b.bind("127.0.0.2:0").unwrap();
+        let addr_a = peer_a.local_addr().unwrap();
+        let addr_b = peer_b.local_addr().unwrap();
+
+        peer_a.send_to(b"to first", &local[0].to_string()).unwrap();
+        peer_b.send_to(b"to second", &local[1].to_string()).unwrap();
+
+        let mut received: Vec<(Vec<u8>, SocketAddr)> = (0..20)
+            .filter_map(|_| multi.recv_from().unwrap())
+            .take(2)
+            .collect();
+        received.sort();
+        assert_eq!(
+            received,
+            vec![
+                (b"to first".to_vec(), addr_a),
+                (b"to second".to_vec(), addr_b),
+            ]
+        );
+
+        // Replies leave from the socket bound to the peer's address
+        multi.send_to(b"reply", &addr_b.to_string()).unwrap();
+        let (data, src) = (0..20).find_map(|_| peer_b.recv_from().unwrap()).unwrap();
+        assert_eq!(data, b"reply");
+        assert_eq!(src, local[1]);
+    }
+
     #[test]
     fn test_shim_recv_timeout() {
         let shim = UdpShim::new(1000);
@@ -374,6 +971,53 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_oversized_datagram_is_reported_as_truncated() {
+        let mut receiver = UdpShim::new(1000);
+        receiver.set_max_buffer_size(16);
+        receiver.bind("127.0.0.1:0").unwrap();
+        let receiver_addr = receiver.local_addr().unwrap().to_string();
+
+        let sender = UdpShim::new(1001);
+        sender.bind("127.0.0.1:0").unwrap();
+        let sender_addr = sender.local_addr().unwrap();
+
+        sender.send_to(&[0xAB; 64], &receiver_addr).unwrap();
+        match receiver.receive_pdu() {
+            Err(ShimError::Truncated { from, capacity }) => {
+                assert_eq!(from, sender_addr);
+                assert_eq!(capacity, 16);
+            }
+            other => panic!("expected truncation, got {:?}", other),
+        }
+
+        // A datagram that exactly fills the buffer is delivered whole
+        sender.send_to(&[0xCD; 16], &receiver_addr).unwrap();
+        let (data, _) = receiver.recv_from().unwrap().unwrap();
+        assert_eq!(data, vec![0xCD; 16]);
+    }
+
+    #[test]
+    fn test_custom_read_timeout_applies_to_socket() {
+        let mut shim = UdpShim::new(1000);
+        shim.set_read_timeout(Duration::from_millis(300));
+        shim.set_so_sndbuf(256 * 1024);
+        shim.bind("127.0.0.1:0").unwrap();
+
+        let timeout = shim.sockets.lock().unwrap()[0].read_timeout().unwrap();
+        assert_eq!(timeout, Some(Duration::from_millis(300)));
+
+        let started = Instant::now();
+        assert!(shim.recv_from().unwrap().is_none());
+        assert!(started.elapsed() >= Duration::from_millis(250));
+
+        // The default stays short
+        let shim = UdpShim::new(1001);
+        shim.bind("127.0.0.1:0").unwrap();
+        let timeout = shim.sockets.lock().unwrap()[0].read_timeout().unwrap();
+        assert_eq!(timeout, Some(DEFAULT_READ_TIMEOUT));
+    }
+
     #[test]
     fn test_address_mapper() {
         let mapper = AddressMapper::new();
@@ -414,4 +1058,101 @@ mod tests {
         assert_eq!(mapper.lookup(1000), Some(addr1));
         assert_eq!(mapper.lookup(2000), Some(addr2));
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_async_receive_does_not_stall_single_threaded_runtime() {
+        let mut receiver = UdpShim::new(2);
+        receiver.set_read_timeout(Duration::from_secs(5));
+        receiver.bind("127.0.0.1:0").unwrap();
+        let target = receiver.local_addr().unwrap().to_string();
+        let sender = UdpShim::new(1);
+        sender.bind("127.0.0.1:0").unwrap();
+
+        // The datagram is only sent once the receive is already waiting, by
+        // a task sharing the runtime's only thread
+        let send = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            sender.send_to(b"ping", &target).unwrap();
+        };
+
+        let started = Instant::now();
+        let (received, ()) = tokio::join!(receiver.recv_from_async(), send);
+
+        let (data, _) = received.unwrap().expect("datagram sent meanwhile");
+        assert_eq!(data, b"ping");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(receiver.stats().datagrams_received, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_async_receive_loses_no_datagram() {
+        let receiver = UdpShim::new(2);
+        receiver.bind("127.0.0.1:0").unwrap();
+        let target = receiver.local_addr().unwrap().to_string();
+        let sender = UdpShim::new(1);
+        sender.bind("127.0.0.1:0").unwrap();
+
+        // The receive is abandoned while still waiting, as in a select! loop
+        tokio::select! {
+            _ = receiver.recv_from_within(Duration::from_secs(5)) => panic!("nothing sent yet"),
+            _ = tokio::time::sleep(Duration::from_millis(50)) => {}
+        }
+        sender.send_to(b"ping", &target).unwrap();
+
+        let (data, _) = receiver
+            .recv_from_within(Duration::from_secs(2))
+            .await
+            .unwrap()
+            .expect("datagram kept for the next receive");
+        assert_eq!(data, b"ping");
+        assert_eq!(receiver.stats().datagrams_received, 1);
+    }
+
+    #[test]
+    fn test_shim_keeps_working_after_lock_poisoning() {
+        let shim = UdpShim::new(1);
+        shim.bind("127.0.0.1:0").unwrap();
+        let peer = UdpShim::new(2);
+        peer.bind("127.0.0.1:0").unwrap();
+        let peer_addr = peer.local_addr().unwrap();
+
+        // Panic while holding both locks
+        let sockets = shim.sockets.clone();
+        let mapper = shim.address_mapper.clone();
+        let poisoner = std::thread::spawn(move || {
+            let _sockets = sockets.lock().unwrap();
+            let _mapper = mapper.lock().unwrap();
+            panic!("poisoning shim locks");
+        });
+        assert!(poisoner.join().is_err());
+        assert!(shim.sockets.is_poisoned());
+
+        shim.register_peer(2, peer_addr);
+        assert_eq!(shim.lookup_peer(2), Some(peer_addr));
+        shim.send_to(b"still alive", &peer_addr.to_string())
+            .unwrap();
+        let (data, _) = peer.recv_from().unwrap().unwrap();
+        assert_eq!(data, b"still alive");
+        assert!(shim.local_addr().is_ok());
+    }
+
+    #[test]
+    fn test_oversized_payload_is_refused_with_typed_error() {
+        let mut shim = UdpShim::new(1000);
+        shim.set_max_payload_size(1200);
+        shim.bind("127.0.0.1:0").unwrap();
+        let dest = shim.local_addr().unwrap().to_string();
+
+        let err = shim.send_to(&[0u8; 1201], &dest).unwrap_err();
+        assert!(matches!(
+            err,
+            ShimError::PayloadTooLarge {
+                size: 1201,
+                limit: 1200
+            }
+        ));
+        assert_eq!(shim.stats().datagrams_sent, 0);
+
+        assert_eq!(shim.send_to(&[0u8; 1200], &dest).unwrap(), 1200);
+    }
 }
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: EUPL-1.2-or-later
+// Copyright © 2026-present ARI Contributors
+
+//! Integration test for the bootstrap hello handshake
+//!
+//! A member that only knows the bootstrap's socket address learns its RINA
+//! address and DIF name, then enrolls using them. With several configured
+//! peers, dead ones are skipped; a late bootstrap is retried.
+
+use ari::enrollment::EnrollmentConfig;
+use ari::{EnrollmentManager, Rib, RibValue, UdpShim};
+use std::sync::Arc;
+use tokio::time::{Duration, sleep};
+
+#[tokio::test]
+async fn test_member_learns_bootstrap_address_from_hello() {
+    let bootstrap_addr = 5001;
+    let bootstrap_bind = "127.0.0.1:17010";
+    let member_bind = "127.0.0.1:17011";
+
+    // === Bootstrap IPCP ===
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("hello-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+    bootstrap_shim.bind(bootstrap_bind).unwrap();
+    let bootstrap_em = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib,
+        bootstrap_shim.clone(),
+        bootstrap_addr,
+        6000,
+        6999,
+    ));
+
+    let bootstrap_listener = tokio::spawn(async move {
+        for _ in 0..30 {
+            sleep(Duration::from_millis(100)).await;
+
+            if let Ok(Some((pdu, src_addr))) = bootstrap_shim.receive_pdu()
+                && let Err(e) = bootstrap_em.handle_cdap_message(&pdu, src_addr).await
+            {
+                eprintln!("   ✗ Failed to handle CDAP: {}", e);
+            }
+        }
+    });
+
+    // === Member IPCP: knows the socket address only ===
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind(member_bind).unwrap();
+    let mut member_em = EnrollmentManager::new(Rib::new(), member_shim.clone(), 0);
+    member_em.set_ipcp_name("hello-member".to_string());
+
+    let bootstrap_socket: std::net::SocketAddr = bootstrap_bind.parse().unwrap();
+    let hello = member_em.hello_bootstrap(bootstrap_socket).await.unwrap();
+
+    assert_eq!(hello.rina_addr, bootstrap_addr);
+    assert_eq!(hello.dif_name, "hello-dif");
+    assert_eq!(member_shim.lookup_peer(5001), Some(bootstrap_socket));
+
+    // The learned address is enough to enroll
+    let dif_name = member_em
+        .enrol_with_bootstrap(hello.rina_addr)
+        .await
+        .unwrap();
+    assert_eq!(dif_name, "hello-dif");
+    assert!((6000..=6999).contains(&member_em.local_addr()));
+
+    bootstrap_listener.abort();
+}
+
+#[tokio::test]
+async fn test_enrollment_skips_dead_bootstrap_peer() {
+    let bootstrap_addr = 5002;
+    let dead_bind = "127.0.0.1:17020";
+    let bootstrap_bind = "127.0.0.1:17021";
+    let member_bind = "127.0.0.1:17022";
+
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("failover-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+    bootstrap_shim.bind(bootstrap_bind).unwrap();
+    let bootstrap_em = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib,
+        bootstrap_shim.clone(),
+        bootstrap_addr,
+        6000,
+        6999,
+    ));
+
+    let bootstrap_listener = tokio::spawn(async move {
+        for _ in 0..80 {
+            sleep(Duration::from_millis(100)).await;
+
+            if let Ok(Some((pdu, src_addr))) = bootstrap_shim.receive_pdu()
+                && let Err(e) = bootstrap_em.handle_cdap_message(&pdu, src_addr).await
+            {
+                eprintln!("   ✗ Failed to handle CDAP: {}", e);
+            }
+        }
+    });
+
+    // Nothing listens on the first peer
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind(member_bind).unwrap();
+    let config = EnrollmentConfig {
+        timeout: Duration::from_millis(800),
+        max_retries: 2,
+        initial_backoff_ms: 100,
+        ..EnrollmentConfig::default()
+    };
+    let mut member_em = EnrollmentManager::with_config(Rib::new(), member_shim, 0, config);
+    member_em.set_ipcp_name("failover-member".to_string());
+
+    let dead_peer: std::net::SocketAddr = dead_bind.parse().unwrap();
+    let live_peer: std::net::SocketAddr = bootstrap_bind.parse().unwrap();
+    let dif_name = member_em
+        .enrol_with_peers(&[dead_peer, live_peer])
+        .await
+        .unwrap();
+
+    assert_eq!(dif_name, "failover-dif");
+    assert_eq!(member_em.bootstrap_peer(), Some(live_peer));
+    assert!(member_em.is_enrolled());
+
+    bootstrap_listener.abort();
+}
+
+#[tokio::test]
+async fn test_hello_is_retried_until_a_late_bootstrap_answers() {
+    let bootstrap_addr = 5003;
+    let bootstrap_bind = "127.0.0.1:17030";
+    let member_bind = "127.0.0.1:17031";
+
+    // The only peer comes up after the member's first hello has timed out
+    let bootstrap_listener = tokio::spawn(async move {
+        sleep(Duration::from_millis(700)).await;
+
+        let bootstrap_rib = Rib::new();
+        bootstrap_rib
+            .create(
+                "/dif/name".to_string(),
+                "dif_info".to_string(),
+                RibValue::String("late-dif".to_string()),
+            )
+            .await
+            .unwrap();
+        let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+        bootstrap_shim.bind(bootstrap_bind).unwrap();
+        let bootstrap_em = EnrollmentManager::new_bootstrap(
+            bootstrap_rib,
+            bootstrap_shim.clone(),
+            bootstrap_addr,
+            6000,
+            6999,
+        );
+
+        for _ in 0..50 {
+            sleep(Duration::from_millis(100)).await;
+
+            if let Ok(Some((pdu, src_addr))) = bootstrap_shim.receive_pdu()
+                && let Err(e) = bootstrap_em.handle_cdap_message(&pdu, src_addr).await
+            {
+                eprintln!("   ✗ Failed to handle CDAP: {}", e);
+            }
+        }
+    });
+
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind(member_bind).unwrap();
+    let config = EnrollmentConfig {
+        timeout: Duration::from_millis(500),
+        max_retries: 4,
+        initial_backoff_ms: 200,
+        backoff_jitter: ari::enrollment::BackoffJitter::None,
+        ..EnrollmentConfig::default()
+    };
+    let mut member_em = EnrollmentManager::with_config(Rib::new(), member_shim, 0, config);
+    member_em.set_ipcp_name("late-member".to_string());
+
+    let peer: std::net::SocketAddr = bootstrap_bind.parse().unwrap();
+    let dif_name = member_em.enrol_with_peers(&[peer]).await.unwrap();
+
+    assert_eq!(dif_name, "late-dif");
+    assert_eq!(member_em.bootstrap_peer(), Some(peer));
+
+    bootstrap_listener.abort();
+}
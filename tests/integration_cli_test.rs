@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: EUPL-1.2-or-later
+// Copyright © 2026-present ARI Contributors
+
+//! Integration test for the binary's `--check` and `--output` modes
+//!
+//! Runs the built binary against configuration files and checks that it
+//! validates them and exits without binding or enrolling.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn run_check(config: &PathBuf) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ari"))
+        .arg("--config")
+        .arg(config)
+        .arg("--check")
+        .output()
+        .unwrap()
+}
+
+fn write_config(file_name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(file_name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_check_accepts_valid_config_and_reports_warnings() {
+    let path = write_config(
+        "ari-test-check-valid.toml",
+        r#"
+[ipcp]
+name = "ipcp-check"
+type = "normal"
+mode = "member"
+
+[dif]
+name = "check-dif"
+
+[shim]
+bind_address = "127.0.0.1"
+bind_port = 7000
+
+[enrollment]
+bootstrap_peers = [{ address = "127.0.0.1:7001" }]
+
+[routing]
+enable_route_persistence = true
+"#,
+    );
+
+    let output = run_check(&path);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {}", stdout);
+    assert!(stdout.contains("Name: ipcp-check"));
+    assert!(stdout.contains("Route persistence is ignored in member mode"));
+    assert!(stdout.contains("Configuration is valid"));
+    // Nothing past the summary was started
+    assert!(!stdout.contains("RINA Member IPCP"));
+}
+
+#[test]
+fn test_check_rejects_invalid_config() {
+    let path = write_config(
+        "ari-test-check-invalid.toml",
+        r#"
+[ipcp]
+name = "ipcp-check"
+type = "normal"
+mode = "bootstrap"
+
+[dif]
+name = "check-dif"
+
+[shim]
+bind_address = "127.0.0.1"
+bind_port = 7000
+"#,
+    );
+
+    let output = run_check(&path);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Bootstrap mode requires an address"));
+}
+
+#[test]
+fn test_json_output_summary_parses() {
+    let path = write_config(
+        "ari-test-check-json.toml",
+        r#"
+[ipcp]
+name = "ipcp-json"
+type = "normal"
+mode = "bootstrap"
+
+[dif]
+name = "json-dif"
+address = 1001
+address_pool_start = 2000
+address_pool_end = 2999
+
+[shim]
+bind_address = "127.0.0.1"
+bind_port = 7000
+"#,
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ari"))
+        .arg("--config")
+        .arg(&path)
+        .args(["--check", "--output", "json"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {}", stdout);
+
+    // Every line is a standalone JSON object; the first is the summary
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let summary = &lines[0];
+    assert_eq!(summary["event"], "config");
+    assert_eq!(summary["mode"], "bootstrap");
+    assert_eq!(summary["name"], "ipcp-json");
+    assert_eq!(summary["dif"], "json-dif");
+    assert_eq!(summary["address"], 1001);
+    assert_eq!(summary["bind"], "127.0.0.1:7000");
+    assert_eq!(summary["address_pool"]["start"], 2000);
+    assert_eq!(summary["address_pool"]["end"], 2999);
+    assert_eq!(lines.last().unwrap()["event"], "check");
+    assert_eq!(lines.last().unwrap()["valid"], true);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_json_output_keeps_progress_text_off_stdout() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let mut node = Command::new(env!("CARGO_BIN_EXE_ari"))
+        .args(["--mode", "bootstrap", "--name", "ipcp-json-run"])
+        .args(["--dif-name", "json-dif", "--address", "1001"])
+        .args(["--bind", "127.0.0.1:0", "--output", "json"])
+        .args(["--address-pool-start", "2000", "--address-pool-end", "2099"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let stdout = BufReader::new(node.stdout.take().unwrap());
+    std::thread::spawn(move || {
+        for line in stdout.lines() {
+            let _ = tx.send(line.unwrap());
+        }
+    });
+
+    // Every line, up to and after the node being stopped, is an event
+    let mut events = Vec::new();
+    while events.last() != Some(&"started".to_string()) {
+        let line = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        let event: serde_json::Value = serde_json::from_str(&line).unwrap_or_else(|e| {
+            let _ = node.kill();
+            panic!("{:?} is not JSON: {}", line, e)
+        });
+        events.push(event["event"].as_str().unwrap().to_string());
+    }
+    Command::new("kill")
+        .args(["-INT", &node.id().to_string()])
+        .status()
+        .unwrap();
+    let output = node.wait_with_output().unwrap();
+    for line in rx.iter() {
+        let event: serde_json::Value = serde_json::from_str(&line).unwrap();
+        events.push(event["event"].as_str().unwrap().to_string());
+    }
+
+    assert_eq!(events, ["config", "started", "stopped"]);
+    // The progress text went to stderr instead
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Bootstrap IPCP"));
+}
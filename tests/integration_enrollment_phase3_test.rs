@@ -9,8 +9,8 @@
 //! - RIB synchronization
 //! - Route creation
 
-use ari::routing::{RouteResolver, RouteResolverConfig};
-use ari::{EnrollmentManager, ForwardingEntry, Rib, RibValue, Rmt, UdpShim};
+use ari::routing::{Route, RouteResolver, RouteResolverConfig};
+use ari::{EnrollmentEvent, EnrollmentManager, ForwardingEntry, Rib, RibValue, Rmt, UdpShim};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -45,18 +45,7 @@ async fn test_phase3_dynamic_address_assignment() {
         .create(
             "/routing/static/2000".to_string(),
             "static_route".to_string(),
-            RibValue::Struct({
-                let mut map = std::collections::HashMap::new();
-                map.insert(
-                    "next_hop_address".to_string(),
-                    Box::new(RibValue::String(member_bind.to_string())),
-                );
-                map.insert(
-                    "next_hop_rina_addr".to_string(),
-                    Box::new(RibValue::Integer(2000)),
-                );
-                map
-            }),
+            RibValue::from(Route::new(member_bind.parse().unwrap()).with_next_hop_rina_addr(2000)),
         )
         .await
         .unwrap();
@@ -71,6 +60,7 @@ async fn test_phase3_dynamic_address_assignment() {
         snapshot_path: PathBuf::from("test-phase3-bootstrap.toml"),
         default_ttl_seconds: 3600,
         snapshot_interval_seconds: 0,
+        expiry_sweep_interval_seconds: 0,
     };
     let bootstrap_route_resolver = Arc::new(RouteResolver::new(
         bootstrap_rib_arc.clone(),
@@ -173,8 +163,8 @@ async fn test_phase3_dynamic_address_assignment() {
 
     // Check if address was stored in member's RIB
     let addr_obj = member_rib.read("/local/address").await;
-    assert!(addr_obj.is_some(), "Assigned address should be in RIB");
-    if let Some(obj) = addr_obj {
+    assert!(addr_obj.is_ok(), "Assigned address should be in RIB");
+    if let Ok(obj) = addr_obj {
         assert_eq!(
             obj.value.as_integer(),
             Some(assigned_addr as i64),
@@ -189,8 +179,8 @@ async fn test_phase3_dynamic_address_assignment() {
 
     // Check DIF name was synced
     let dif_name_obj = member_rib.read("/dif/name").await;
-    assert!(dif_name_obj.is_some(), "DIF name should be synced");
-    if let Some(obj) = dif_name_obj {
+    assert!(dif_name_obj.is_ok(), "DIF name should be synced");
+    if let Ok(obj) = dif_name_obj {
         assert_eq!(
             obj.value.as_string(),
             Some("test-dif"),
@@ -201,7 +191,7 @@ async fn test_phase3_dynamic_address_assignment() {
     // Check static route was synced from bootstrap
     let route_obj = member_rib.read("/routing/static/2000").await;
     assert!(
-        route_obj.is_some(),
+        route_obj.is_ok(),
         "Static route should be synced from bootstrap"
     );
 
@@ -213,11 +203,11 @@ async fn test_phase3_dynamic_address_assignment() {
     let dynamic_route_name = format!("/routing/dynamic/{}", assigned_addr);
     let route_obj = bootstrap_rib.read(&dynamic_route_name).await;
     assert!(
-        route_obj.is_some(),
+        route_obj.is_ok(),
         "Bootstrap should have created dynamic route for member"
     );
 
-    if let Some(obj) = route_obj {
+    if let Ok(obj) = route_obj {
         assert_eq!(obj.class, "route", "Route should have correct class");
         println!(
             "   ✓ Bootstrap created dynamic route: {}",
@@ -231,11 +221,13 @@ async fn test_phase3_dynamic_address_assignment() {
     let mut member_rmt = Rmt::new(assigned_addr);
 
     // Add forwarding entry using assigned address
-    member_rmt.add_forwarding_entry(ForwardingEntry {
-        dst_addr: bootstrap_addr,
-        next_hop: bootstrap_addr,
-        cost: 1,
-    });
+    member_rmt
+        .add_forwarding_entry(ForwardingEntry {
+            dst_addr: bootstrap_addr,
+            next_hop: bootstrap_addr,
+            cost: 1,
+        })
+        .unwrap();
 
     let next_hop = member_rmt.lookup(bootstrap_addr);
     assert_eq!(
@@ -354,3 +346,532 @@ async fn test_address_pool_exhaustion() {
 
     println!("\n✅ Address pool exhaustion test passed!");
 }
+
+#[tokio::test]
+async fn test_reserved_address_granted_to_named_member() {
+    println!("\n=== Testing Reserved Address Assignment ===\n");
+
+    let bootstrap_addr = 1001;
+    let pool_start = 3100;
+    let pool_end = 3102;
+
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("test-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+
+    let bootstrap_em = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib.clone(),
+        bootstrap_shim.clone(),
+        bootstrap_addr,
+        pool_start,
+        pool_end,
+    ));
+    bootstrap_em
+        .reserve_address("member-reserved", pool_start)
+        .await
+        .unwrap();
+    println!("   ✓ Reserved {} for member-reserved", pool_start);
+
+    let bootstrap_em_clone = bootstrap_em.clone();
+    let bootstrap_shim_clone = bootstrap_shim.clone();
+    let listener = tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_millis(10)).await;
+            if let Ok(Some((pdu, src_addr))) = bootstrap_shim_clone.receive_pdu() {
+                let _ = bootstrap_em_clone.handle_cdap_message(&pdu, src_addr).await;
+            }
+        }
+    });
+
+    let enroll = |name: &'static str| async move {
+        let member_shim = Arc::new(UdpShim::new(0));
+        member_shim.bind("127.0.0.1:0").unwrap();
+        member_shim.register_peer(bootstrap_addr, bootstrap_socket);
+
+        let mut member_em = EnrollmentManager::new(Rib::new(), member_shim, 0);
+        member_em.set_ipcp_name(name.to_string());
+        member_em
+            .enrol_with_bootstrap(bootstrap_addr)
+            .await
+            .unwrap();
+        member_em.local_addr()
+    };
+
+    // Dynamic assignment skips the reserved address
+    let dynamic_addr = enroll("member-dynamic").await;
+    assert_eq!(dynamic_addr, pool_start + 1);
+    println!("   ✓ member-dynamic assigned {}", dynamic_addr);
+
+    // The named member receives its reservation
+    let reserved_addr = enroll("member-reserved").await;
+    assert_eq!(reserved_addr, pool_start);
+    println!("   ✓ member-reserved assigned {}", reserved_addr);
+
+    listener.abort();
+
+    println!("\n✅ Reserved address test passed!");
+}
+
+#[tokio::test]
+async fn test_enrollment_emits_progress_events() {
+    let bootstrap_addr = 1001;
+
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("events-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+
+    let bootstrap_em = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib,
+        bootstrap_shim.clone(),
+        bootstrap_addr,
+        3200,
+        3299,
+    ));
+
+    let listener = tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_millis(10)).await;
+            if let Ok(Some((pdu, src_addr))) = bootstrap_shim.receive_pdu() {
+                let _ = bootstrap_em.handle_cdap_message(&pdu, src_addr).await;
+            }
+        }
+    });
+
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(bootstrap_addr, bootstrap_socket);
+
+    let (events_tx, mut events_rx) = tokio::sync::mpsc::channel(16);
+    let mut member_em = EnrollmentManager::new(Rib::new(), member_shim, 0);
+    member_em.set_ipcp_name("member-events".to_string());
+    member_em.set_event_sender(events_tx);
+
+    member_em
+        .enrol_with_bootstrap(bootstrap_addr)
+        .await
+        .unwrap();
+    listener.abort();
+    drop(member_em);
+
+    let mut events = Vec::new();
+    while let Some(event) = events_rx.recv().await {
+        events.push(event);
+    }
+
+    // The snapshot size depends on the bootstrap's RIB, so only its kind is checked
+    assert!(matches!(events[2], EnrollmentEvent::RibSynced { .. }));
+    events.remove(2);
+    assert_eq!(
+        events,
+        vec![
+            EnrollmentEvent::AttemptStarted {
+                bootstrap_addr,
+                attempt: 1,
+            },
+            EnrollmentEvent::AddressAssigned(3200),
+            EnrollmentEvent::RoutesSynced { routes: 0 },
+            EnrollmentEvent::Enrolled {
+                dif_name: "events-dif".to_string(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_restarted_member_reclaims_persisted_address() {
+    use ari::enrollment::EnrollmentConfig;
+    use std::net::SocketAddr;
+
+    let bootstrap_addr = 1001;
+    let state_path = std::env::temp_dir().join("ari-restart-member-address.toml");
+    let _ = std::fs::remove_file(&state_path);
+
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("test-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+    let bootstrap_em = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib,
+        bootstrap_shim.clone(),
+        bootstrap_addr,
+        3400,
+        3499,
+    ));
+
+    let handler = bootstrap_em.clone();
+    let listener = tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_millis(10)).await;
+            if let Ok(Some((pdu, src_addr))) = bootstrap_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+        }
+    });
+
+    // Each call is a fresh process: new shim, new RIB, no address
+    let enroll = |name: &'static str, state_path: Option<PathBuf>, bind: SocketAddr| async move {
+        let member_shim = Arc::new(UdpShim::new(0));
+        // The previous process's receive thread frees its port within a read timeout
+        for attempt in 0.. {
+            match member_shim.bind(&bind.to_string()) {
+                Err(_) if attempt < 40 => sleep(Duration::from_millis(50)).await,
+                result => {
+                    result.unwrap();
+                    break;
+                }
+            }
+        }
+        member_shim.register_peer(bootstrap_addr, bootstrap_socket);
+        let socket = member_shim.local_addr().unwrap();
+
+        let config = EnrollmentConfig {
+            max_retries: 1,
+            timeout: Duration::from_secs(2),
+            ..EnrollmentConfig::default()
+        };
+        let mut member_em = EnrollmentManager::with_config(Rib::new(), member_shim, 0, config);
+        member_em.set_ipcp_name(name.to_string());
+        if let Some(path) = state_path {
+            member_em.set_address_state_path(path);
+        }
+        let result = member_em.enrol_with_bootstrap(bootstrap_addr).await;
+        result.map(|_| (member_em.local_addr(), socket))
+    };
+    let any_port: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+    let (first_addr, first_socket) =
+        enroll("member-restarting", Some(state_path.clone()), any_port)
+            .await
+            .unwrap();
+    let state = ari::AddressState::load(&state_path).unwrap();
+    assert_eq!(state.ipcp_name, "member-restarting");
+    assert_eq!(state.address, first_addr);
+
+    // Another member takes the next free address meanwhile
+    let (other_addr, other_socket) = enroll("member-other", None, any_port).await.unwrap();
+    assert_ne!(other_addr, first_addr);
+
+    // A fresh grant to the same member hands its previous address back
+    let available = bootstrap_em.address_pool().unwrap().available_count();
+    let (regranted, _) = enroll("member-other", None, other_socket).await.unwrap();
+    assert_ne!(regranted, other_addr);
+    assert_eq!(
+        bootstrap_em.address_pool().unwrap().available_count(),
+        available
+    );
+
+    // Another socket using the name cannot take the live member's address
+    assert!(
+        enroll("member-restarting", Some(state_path.clone()), any_port)
+            .await
+            .is_err()
+    );
+
+    // After a restart on its own socket the same address comes back
+    let (restarted_addr, _) = enroll("member-restarting", Some(state_path.clone()), first_socket)
+        .await
+        .unwrap();
+    assert_eq!(restarted_addr, first_addr);
+
+    listener.abort();
+    let _ = std::fs::remove_file(&state_path);
+}
+
+#[tokio::test]
+async fn test_retried_enrollment_request_is_answered_from_cache() {
+    use ari::cdap::{CdapCapabilities, CdapMessage, CdapOpCode};
+    use ari::enrollment::{EnrollmentRequest, EnrollmentResponse};
+    use ari::pdu::Pdu;
+
+    println!("\n=== Test: Retried Enrollment Request Is Answered From Cache ===\n");
+
+    let bootstrap_addr = 1001;
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("test-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+    let bootstrap_em = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib,
+        bootstrap_shim.clone(),
+        bootstrap_addr,
+        3500,
+        3599,
+    ));
+    let pool_size = bootstrap_em.address_pool().unwrap().available_count();
+
+    let handler = bootstrap_em.clone();
+    let listener = tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_millis(10)).await;
+            if let Ok(Some((pdu, src_addr))) = bootstrap_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+        }
+    });
+
+    let member_shim = UdpShim::new(0);
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(bootstrap_addr, bootstrap_socket);
+
+    // The same request sent twice, as if the first response had been lost
+    let request = EnrollmentRequest {
+        ipcp_name: "member-retrying".to_string(),
+        ipcp_address: 0,
+        dif_name: String::new(),
+        timestamp: 0,
+        request_address: true,
+        preferred_address: None,
+        capabilities: CdapCapabilities::local(),
+        request_id: 42,
+        rebind_secret: None,
+    };
+    let mut assigned = Vec::new();
+    for invoke_id in 1..=2 {
+        let msg = CdapMessage {
+            op_code: CdapOpCode::Create,
+            obj_name: request.ipcp_name.clone(),
+            obj_class: Some("enrollment".to_string()),
+            obj_value: Some(RibValue::Bytes(postcard::to_allocvec(&request).unwrap())),
+            invoke_id,
+            result: ari::CdapResult::Success,
+            result_reason: None,
+            sync_request: None,
+            sync_response: None,
+        };
+        let pdu = Pdu::new_management(0, bootstrap_addr, postcard::to_allocvec(&msg).unwrap());
+        member_shim.send_pdu(&pdu).unwrap();
+
+        let response = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                if let Ok(Some((pdu, _))) = member_shim.receive_pdu()
+                    && let Ok(msg) = postcard::from_bytes::<CdapMessage>(&pdu.payload)
+                    && let Some(RibValue::Bytes(bytes)) = msg.obj_value
+                {
+                    return postcard::from_bytes::<EnrollmentResponse>(&bytes).unwrap();
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("no enrollment response");
+        assert!(response.accepted);
+        assert!(response.rib_snapshot.is_some());
+        assigned.push(response.assigned_address.unwrap());
+    }
+
+    assert_eq!(assigned[0], assigned[1]);
+    assert_eq!(
+        bootstrap_em.address_pool().unwrap().available_count(),
+        pool_size - 1
+    );
+    println!(
+        "✓ Retry got address {} without a second allocation",
+        assigned[0]
+    );
+
+    listener.abort();
+}
+
+#[tokio::test]
+async fn test_claimed_address_cannot_be_rebound_without_secret() {
+    use ari::enrollment::EnrollmentConfig;
+
+    println!("\n=== Test: Claimed Address Cannot Be Rebound Without Secret ===\n");
+
+    let bootstrap_addr = 1001;
+    let member_addr = 5000;
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("test-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+    let mut bootstrap_em = EnrollmentManager::new_bootstrap(
+        bootstrap_rib,
+        bootstrap_shim.clone(),
+        bootstrap_addr,
+        3600,
+        3699,
+    );
+    bootstrap_em.set_rebind_secret(Some("let-me-move".to_string()));
+    let bootstrap_em = Arc::new(bootstrap_em);
+
+    let handler = bootstrap_em.clone();
+    let handler_shim = bootstrap_shim.clone();
+    let listener = tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_millis(10)).await;
+            if let Ok(Some((pdu, src_addr))) = handler_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+        }
+    });
+
+    // A member with a static address, each from its own socket
+    let enroll = |secret: Option<&'static str>| async move {
+        let member_shim = Arc::new(UdpShim::new(member_addr));
+        member_shim.bind("127.0.0.1:0").unwrap();
+        member_shim.register_peer(bootstrap_addr, bootstrap_socket);
+        let socket = member_shim.local_addr().unwrap();
+
+        let config = EnrollmentConfig {
+            max_retries: 1,
+            timeout: Duration::from_secs(2),
+            ..EnrollmentConfig::default()
+        };
+        let mut member_em =
+            EnrollmentManager::with_config(Rib::new(), member_shim, member_addr, config);
+        member_em.set_ipcp_name("member-static".to_string());
+        member_em.set_rebind_secret(secret.map(str::to_string));
+        (member_em.enrol_with_bootstrap(bootstrap_addr).await, socket)
+    };
+
+    let (result, original_socket) = enroll(None).await;
+    result.unwrap();
+    assert_eq!(
+        bootstrap_shim.lookup_peer(member_addr),
+        Some(original_socket)
+    );
+    println!(
+        "✓ Original member bound {} to {}",
+        member_addr, original_socket
+    );
+
+    // An impostor claiming the same address is refused
+    let (result, _) = enroll(None).await;
+    assert!(result.is_err());
+    assert_eq!(
+        bootstrap_shim.lookup_peer(member_addr),
+        Some(original_socket)
+    );
+    println!("✓ Impostor rejected, mapping preserved");
+
+    // With the secret the address may move
+    let (result, new_socket) = enroll(Some("let-me-move")).await;
+    result.unwrap();
+    assert_eq!(bootstrap_shim.lookup_peer(member_addr), Some(new_socket));
+    println!("✓ Member holding the secret rebound to {}", new_socket);
+
+    listener.abort();
+}
+
+#[tokio::test]
+async fn test_member_of_another_dif_fails_enrollment_cleanly() {
+    use ari::EnrollmentError;
+    use ari::enrollment::EnrollmentConfig;
+
+    println!("\n=== Test: Member Of Another DIF Fails Enrollment ===\n");
+
+    let bootstrap_addr = 1001;
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("core.test-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(bootstrap_addr));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+    let bootstrap_em = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib,
+        bootstrap_shim.clone(),
+        bootstrap_addr,
+        3700,
+        3799,
+    ));
+
+    let handler = bootstrap_em.clone();
+    let handler_shim = bootstrap_shim.clone();
+    let listener = tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_millis(10)).await;
+            if let Ok(Some((pdu, src_addr))) = handler_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+        }
+    });
+
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(bootstrap_addr, bootstrap_socket);
+    let config = EnrollmentConfig {
+        max_retries: 3,
+        timeout: Duration::from_secs(2),
+        ..EnrollmentConfig::default()
+    };
+    let member_rib = Rib::new();
+    let mut member_em = EnrollmentManager::with_config(member_rib.clone(), member_shim, 0, config);
+    member_em.set_ipcp_name("member-elsewhere".to_string());
+    member_em.set_dif_name("core.other-dif".to_string());
+
+    let started = std::time::Instant::now();
+    let error = member_em
+        .enrol_with_bootstrap(bootstrap_addr)
+        .await
+        .unwrap_err();
+    listener.abort();
+
+    match &error {
+        EnrollmentError::DifNameMismatch { expected, actual } => {
+            assert_eq!(expected, "core.other-dif");
+            assert_eq!(actual, "core.test-dif");
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+    println!("✓ Enrollment failed: {}", error);
+
+    // No retries, no address handed out and nothing synchronised
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(member_em.local_addr(), 0);
+    assert!(member_rib.read("/dif/name").await.is_err());
+    assert!(bootstrap_shim.lookup_peer(3700).is_none());
+}
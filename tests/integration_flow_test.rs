@@ -7,12 +7,11 @@ use ari::actors::*;
 use ari::efcp::FlowConfig;
 use ari::inter_ipcp_fal::InterIpcpFlowAllocator;
 use ari::rib::{Rib, RibValue};
-use ari::routing::{RouteResolver, RouteResolverConfig};
+use ari::routing::{Route, RouteResolver, RouteResolverConfig};
 use ari::shim::UdpShim;
-use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::{RwLock, mpsc, oneshot};
 
 #[tokio::test]
 async fn test_flow_creation_and_data_transfer() {
@@ -55,6 +54,7 @@ async fn test_flow_creation_and_data_transfer() {
         snapshot_path: PathBuf::from("test-bootstrap-routes.toml"),
         default_ttl_seconds: 3600,
         snapshot_interval_seconds: 0,
+        expiry_sweep_interval_seconds: 0,
     };
     let bootstrap_route_resolver = Arc::new(RouteResolver::new(
         bootstrap_rib_arc.clone(),
@@ -76,11 +76,19 @@ async fn test_flow_creation_and_data_transfer() {
         bootstrap_shim.clone(),
     ));
 
-    let bootstrap_fal_for_rmt = bootstrap_flow_allocator.clone();
+    let (bootstrap_fal_tx, bootstrap_fal_rx) = mpsc::channel(32);
+    let bootstrap_fal_for_actor = bootstrap_flow_allocator.clone();
+    tokio::spawn(async move {
+        InterIpcpFalActor::new(bootstrap_fal_for_actor, bootstrap_fal_rx)
+            .run()
+            .await;
+    });
+
+    let bootstrap_fal_for_rmt = InterIpcpFalHandle::new(bootstrap_fal_tx);
     let bootstrap_resolver_for_rmt = bootstrap_route_resolver.clone();
     tokio::spawn(async move {
         let mut actor = RmtActor::new(bootstrap_addr, bootstrap_rmt_rx);
-        actor.set_flow_allocator(bootstrap_fal_for_rmt);
+        actor.set_fal_handle(bootstrap_fal_for_rmt);
         actor.set_route_resolver(bootstrap_resolver_for_rmt);
         actor.run().await;
     });
@@ -88,18 +96,9 @@ async fn test_flow_creation_and_data_transfer() {
     // Load static route into RouteResolver
     {
         let route_name = "/routing/static/1002".to_string();
-        let route_value = RibValue::Struct({
-            let mut map = HashMap::new();
-            map.insert(
-                "next_hop_address".to_string(),
-                Box::new(RibValue::String("127.0.0.1:9001".to_string())),
-            );
-            map.insert(
-                "next_hop_rina_addr".to_string(),
-                Box::new(RibValue::Integer(1002)),
-            );
-            map
-        });
+        let route_value = RibValue::from(
+            Route::new("127.0.0.1:9001".parse().unwrap()).with_next_hop_rina_addr(1002),
+        );
 
         let rib = bootstrap_rib_arc.read().await;
         rib.create(route_name, "static_route".to_string(), route_value)
@@ -121,7 +120,7 @@ async fn test_flow_creation_and_data_transfer() {
     let _member_rib_handle = RibHandle::new(member_rib_tx);
 
     let (member_efcp_tx, member_efcp_rx) = mpsc::channel(32);
-    let _member_efcp_handle = EfcpHandle::new(member_efcp_tx);
+    let member_efcp_handle = EfcpHandle::new(member_efcp_tx);
 
     let (member_rmt_tx, member_rmt_rx) = mpsc::channel(32);
     let member_rmt_handle = RmtHandle::new(member_rmt_tx);
@@ -147,6 +146,7 @@ async fn test_flow_creation_and_data_transfer() {
         snapshot_path: PathBuf::from("test-member-routes.toml"),
         default_ttl_seconds: 3600,
         snapshot_interval_seconds: 0,
+        expiry_sweep_interval_seconds: 0,
     };
     let member_route_resolver = Arc::new(RouteResolver::new(
         member_rib_arc.clone(),
@@ -168,11 +168,19 @@ async fn test_flow_creation_and_data_transfer() {
         member_shim.clone(),
     ));
 
-    let member_fal_for_rmt = member_flow_allocator.clone();
+    let (member_fal_tx, member_fal_rx) = mpsc::channel(32);
+    let member_fal_for_actor = member_flow_allocator.clone();
+    tokio::spawn(async move {
+        InterIpcpFalActor::new(member_fal_for_actor, member_fal_rx)
+            .run()
+            .await;
+    });
+
+    let member_fal_for_rmt = InterIpcpFalHandle::new(member_fal_tx);
     let member_resolver_for_rmt = member_route_resolver.clone();
     tokio::spawn(async move {
         let mut actor = RmtActor::new(member_addr, member_rmt_rx);
-        actor.set_flow_allocator(member_fal_for_rmt);
+        actor.set_fal_handle(member_fal_for_rmt);
         actor.set_route_resolver(member_resolver_for_rmt);
         actor.run().await;
     });
@@ -180,18 +188,9 @@ async fn test_flow_creation_and_data_transfer() {
     // Load reverse route into RouteResolver
     {
         let route_name = "/routing/static/1001".to_string();
-        let route_value = RibValue::Struct({
-            let mut map = HashMap::new();
-            map.insert(
-                "next_hop_address".to_string(),
-                Box::new(RibValue::String("127.0.0.1:9000".to_string())),
-            );
-            map.insert(
-                "next_hop_rina_addr".to_string(),
-                Box::new(RibValue::Integer(1001)),
-            );
-            map
-        });
+        let route_value = RibValue::from(
+            Route::new("127.0.0.1:9000".parse().unwrap()).with_next_hop_rina_addr(1001),
+        );
 
         let rib = member_rib_arc.read().await;
         rib.create(route_name, "static_route".to_string(), route_value)
@@ -209,23 +208,49 @@ async fn test_flow_creation_and_data_transfer() {
     println!("3. Creating flow and sending data...");
 
     // Allocate flow on bootstrap IPCP
-    let (tx, mut rx) = mpsc::channel(1);
+    let (tx, rx) = oneshot::channel();
     bootstrap_efcp_handle
         .send(EfcpMessage::AllocateFlow {
             local_addr: bootstrap_addr,
             remote_addr: member_addr,
             config: FlowConfig::default(),
+            key: None,
+            remote_cep_id: None,
             response: tx,
         })
         .await
         .unwrap();
 
-    let flow_id = rx.recv().await.unwrap();
+    let flow_id = rx.await.unwrap();
     println!("  ✓ Flow allocated: flow_id={}", flow_id);
 
+    // The member allocates its end against the bootstrap's CEP-ID and
+    // answers with its own, which the bootstrap then binds
+    let member_flow_id = member_efcp_handle
+        .request(|response| EfcpMessage::AllocateFlow {
+            local_addr: member_addr,
+            remote_addr: bootstrap_addr,
+            config: FlowConfig::default(),
+            key: None,
+            remote_cep_id: Some(flow_id),
+            response,
+        })
+        .await
+        .unwrap();
+    bootstrap_efcp_handle
+        .request(|response| EfcpMessage::BindRemoteCep {
+            flow_id,
+            remote_cep_id: member_flow_id,
+            response,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    println!("  ✓ Member flow bound: flow_id={}", member_flow_id);
+
     // Send data on the flow
     let test_data = b"Hello from Bootstrap IPCP!".to_vec();
-    let (tx, mut rx) = mpsc::channel(1);
+    let (tx, rx) = oneshot::channel();
     bootstrap_efcp_handle
         .send(EfcpMessage::SendData {
             flow_id,
@@ -235,7 +260,7 @@ async fn test_flow_creation_and_data_transfer() {
         .await
         .unwrap();
 
-    let send_result = rx.recv().await.unwrap();
+    let send_result = rx.await.unwrap();
     assert!(
         send_result.is_ok(),
         "Failed to send data: {:?}",
@@ -243,12 +268,23 @@ async fn test_flow_creation_and_data_transfer() {
     );
     println!("  ✓ Data sent: {} bytes", test_data.len());
 
-    // Give time for PDU to be received and processed
     println!("\n4. Waiting for data delivery...");
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    let (pdu, _) = member_shim
+        .receive_pdu_within(tokio::time::Duration::from_secs(2))
+        .await
+        .unwrap()
+        .expect("PDU should reach the member");
+    assert_eq!(pdu.dst_cep_id, member_flow_id);
+
+    let delivered = member_efcp_handle
+        .request(|response| EfcpMessage::ReceivePdu { pdu, response })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delivered, Some(test_data));
 
     println!("\n=== Phase 2 Test: PASSED ===");
     println!("✓ Flow creation successful");
     println!("✓ Data transfer successful");
-    println!("✓ PDUs routed through RMT→Shim→Network→Shim→RMT→EFCP");
+    println!("✓ PDUs routed through EFCP→RMT→Shim→Network→Shim→EFCP");
 }
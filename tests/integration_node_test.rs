@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: EUPL-1.2-or-later
+// Copyright © 2026-present ARI Contributors
+
+//! Integration test for the library-level IPCP nodes
+//!
+//! Runs a `MemberNode` against a `BootstrapNode` exactly as the binary
+//! wires them, over loopback UDP.
+
+use ari::cdap::DEFAULT_MAX_FRAGMENT_SIZE;
+use ari::config::{CliArgs, IpcpConfiguration};
+use ari::{
+    BootstrapNode, CdapMessage, CdapOpCode, FlowConfig, FlowError, IpcpState, MemberNode,
+    PduBuilder, PduType, QoSCube, QoSParameters, UdpShim,
+};
+use clap::Parser;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, sleep};
+
+fn configuration(args: &[&str]) -> IpcpConfiguration {
+    let args = CliArgs::parse_from(std::iter::once("ari").chain(args.iter().copied()));
+    let config = IpcpConfiguration::from_cli(args).unwrap();
+    config.validate().unwrap();
+    config
+}
+
+#[tokio::test]
+async fn test_member_node_enrolls_with_bootstrap_node() {
+    let cube = QoSCube::new()
+        .with_class("gold", QoSParameters::default())
+        .with_class("bronze", QoSParameters::default());
+    let mut bootstrap_config = configuration(&[
+        "--mode",
+        "bootstrap",
+        "--name",
+        "node-bootstrap",
+        "--dif-name",
+        "node-dif",
+        "--address",
+        "1001",
+        "--bind",
+        "127.0.0.1:0",
+        "--address-pool-start",
+        "4000",
+        "--address-pool-end",
+        "4099",
+    ]);
+    bootstrap_config.qos_classes = cube.profiles().to_vec();
+    let bootstrap = BootstrapNode::start(bootstrap_config).await.unwrap();
+    assert_eq!(bootstrap.ipcp().state, IpcpState::Operational);
+    let bootstrap_socket = bootstrap.local_socket_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+    let bootstrap_task = tokio::spawn(async move { bootstrap.run(shutdown_rx).await });
+
+    let mut member_config = configuration(&[
+        "--mode",
+        "member",
+        "--name",
+        "node-member",
+        "--dif-name",
+        "node-dif",
+        "--bind",
+        "127.0.0.1:0",
+        "--bootstrap-peers",
+        &bootstrap_socket.to_string(),
+    ]);
+    let state_path = std::env::temp_dir().join("ari-node-member-address.toml");
+    let _ = std::fs::remove_file(&state_path);
+    member_config.address_state_path = Some(state_path.display().to_string());
+    member_config.enrollment_timeout_secs = 2;
+
+    let mut member = MemberNode::start(member_config).await.unwrap();
+    assert_eq!(member.ipcp().state, IpcpState::Enrolling);
+
+    let dif_name = tokio::time::timeout(Duration::from_secs(10), member.enrol())
+        .await
+        .expect("enrollment timed out")
+        .unwrap();
+
+    assert_eq!(dif_name, "node-dif");
+    assert_eq!(member.ipcp().state, IpcpState::Operational);
+    let assigned = member.enrollment_manager().local_addr();
+    assert!((4000..=4099).contains(&assigned));
+    assert_eq!(member.ipcp().address, Some(assigned));
+    assert_eq!(
+        member.enrollment_manager().bootstrap_peer(),
+        Some(bootstrap_socket)
+    );
+    // The DIF's QoS cube is advertised to the member during enrollment
+    assert_eq!(member.enrollment_manager().qos_cube().await, Some(cube));
+    // ... and its flow allocator then refuses flows that name no class
+    let unclassed = member.ipcp().fal.create_request(
+        "app1".to_string(),
+        "app2".to_string(),
+        assigned,
+        1001,
+        FlowConfig::default(),
+    );
+    assert_eq!(unclassed.unwrap_err(), FlowError::QoSClassRequired);
+
+    shutdown_tx.send(()).await.unwrap();
+    let stats = bootstrap_task.await.unwrap();
+    assert!(stats.pdus_handled >= 2, "{:?}", stats);
+    let _ = std::fs::remove_file(&state_path);
+}
+
+#[tokio::test]
+async fn test_bootstrap_loop_counts_burst_and_stops_on_shutdown() {
+    let bootstrap = Arc::new(
+        BootstrapNode::start(configuration(&[
+            "--mode",
+            "bootstrap",
+            "--name",
+            "burst-bootstrap",
+            "--dif-name",
+            "burst-dif",
+            "--address",
+            "1001",
+            "--bind",
+            "127.0.0.1:0",
+        ]))
+        .await
+        .unwrap(),
+    );
+    let bootstrap_socket = bootstrap.local_socket_addr().unwrap().to_string();
+
+    let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+    let runner = tokio::spawn({
+        let bootstrap = bootstrap.clone();
+        async move { bootstrap.run(shutdown_rx).await }
+    });
+
+    let sender = UdpShim::new(2001);
+    sender.bind("127.0.0.1:0").unwrap();
+    let read = CdapMessage::new_request(
+        CdapOpCode::Read,
+        "/dif/name".to_string(),
+        Some("dif_info".to_string()),
+        None,
+        1,
+    );
+    let payload = read
+        .to_payloads(DEFAULT_MAX_FRAGMENT_SIZE)
+        .unwrap()
+        .remove(0);
+    let valid = PduBuilder::new(PduType::Management)
+        .src_addr(2001)
+        .dst_addr(1001)
+        .payload(payload)
+        .build()
+        .unwrap()
+        .serialize()
+        .unwrap();
+    let garbage = PduBuilder::new(PduType::Management)
+        .src_addr(2001)
+        .dst_addr(1001)
+        .payload(vec![0xff; 8])
+        .build()
+        .unwrap()
+        .serialize()
+        .unwrap();
+
+    for _ in 0..20 {
+        sender.send_to(&valid, &bootstrap_socket).unwrap();
+    }
+    sender.send_to(&garbage, &bootstrap_socket).unwrap();
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while bootstrap.stats().pdus_handled + bootstrap.stats().errors < 21 {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "{:?}",
+            bootstrap.stats()
+        );
+        sleep(Duration::from_millis(10)).await;
+    }
+
+    shutdown_tx.send(()).await.unwrap();
+    let stats = tokio::time::timeout(Duration::from_secs(2), runner)
+        .await
+        .expect("loop did not stop on shutdown")
+        .unwrap();
+
+    assert_eq!(stats.pdus_handled, 20);
+    assert_eq!(stats.errors, 1);
+    assert_eq!(stats, bootstrap.stats());
+}
@@ -6,7 +6,7 @@
 //! Tests connection monitoring and automatic re-enrollment when
 //! connection to bootstrap is lost and restored.
 
-use ari::enrollment::{EnrollmentConfig, EnrollmentManager};
+use ari::enrollment::{BackoffJitter, EnrollmentConfig, EnrollmentManager, EnrollmentState};
 use ari::{Rib, UdpShim};
 use std::sync::Arc;
 use std::time::Duration;
@@ -65,8 +65,10 @@ async fn test_connection_monitoring_and_manual_reenrollment() {
         timeout: Duration::from_secs(5),
         max_retries: 3,
         initial_backoff_ms: 500,
+        backoff_jitter: BackoffJitter::None,
         heartbeat_interval_secs: 2, // Check every 2 seconds
         connection_timeout_secs: 4, // Timeout after 4 seconds
+        ..EnrollmentConfig::default()
     };
 
     let mut member_mgr = EnrollmentManager::with_config(
@@ -161,8 +163,10 @@ async fn test_heartbeat_update() {
         timeout: Duration::from_secs(5),
         max_retries: 3,
         initial_backoff_ms: 500,
+        backoff_jitter: BackoffJitter::None,
         heartbeat_interval_secs: 10,
         connection_timeout_secs: 30,
+        ..EnrollmentConfig::default()
     };
 
     let mut member_mgr = EnrollmentManager::with_config(
@@ -209,8 +213,10 @@ async fn test_connection_monitoring_task() {
         timeout: Duration::from_secs(5),
         max_retries: 3,
         initial_backoff_ms: 500,
+        backoff_jitter: BackoffJitter::None,
         heartbeat_interval_secs: 1, // Very short for testing
         connection_timeout_secs: 2,
+        ..EnrollmentConfig::default()
     };
 
     let mut member_mgr = EnrollmentManager::with_config(
@@ -239,3 +245,91 @@ async fn test_connection_monitoring_task() {
 
     println!("✅ Connection monitoring task test passed!");
 }
+
+#[tokio::test]
+async fn test_watchdog_re_enrolls_after_connectivity_gap() {
+    let bootstrap_rib = Rib::new();
+    let _ = bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            ari::RibValue::String("test-dif".to_string()),
+        )
+        .await;
+
+    let bootstrap_shim = Arc::new(UdpShim::new(1001));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+    let bootstrap_mgr = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib,
+        bootstrap_shim.clone(),
+        1001,
+        4000,
+        4999,
+    ));
+
+    // The bootstrap only answers while its handler task is running
+    let serve = || {
+        let shim = bootstrap_shim.clone();
+        let mgr = bootstrap_mgr.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Ok(Some((pdu, src_addr))) = shim.receive_pdu() {
+                    let _ = mgr.handle_cdap_message(&pdu, src_addr).await;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+    };
+    let handler = serve();
+
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(1001, bootstrap_socket);
+
+    let enrollment_config = EnrollmentConfig {
+        timeout: Duration::from_millis(500),
+        max_retries: 1,
+        initial_backoff_ms: 100,
+        backoff_jitter: BackoffJitter::None,
+        heartbeat_interval_secs: 1,
+        connection_timeout_secs: 1,
+        ..EnrollmentConfig::default()
+    };
+    let mut member_mgr =
+        EnrollmentManager::with_config(Rib::new(), member_shim, 0, enrollment_config);
+    member_mgr.set_ipcp_name("watched-member".to_string());
+    member_mgr.enrol_with_bootstrap(1001).await.unwrap();
+    let assigned = member_mgr.local_addr();
+    assert_eq!(assigned, 4000);
+
+    let watchdog = member_mgr.start_connection_monitoring();
+
+    // Connectivity gap: nothing answers and no heartbeat arrives
+    handler.abort();
+    let mut lost = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(50)).await;
+        if member_mgr.state() == EnrollmentState::NotEnrolled {
+            lost = true;
+            break;
+        }
+    }
+    assert!(lost, "watchdog never noticed the lost connection");
+
+    // The bootstrap comes back and the watchdog re-enrolls on its own
+    let handler = serve();
+    let mut recovered = false;
+    for _ in 0..100 {
+        sleep(Duration::from_millis(50)).await;
+        if member_mgr.is_enrolled() && member_mgr.is_connection_healthy().await {
+            recovered = true;
+            break;
+        }
+    }
+    assert!(recovered, "member did not re-enroll after the gap");
+    assert_eq!(member_mgr.local_addr(), assigned);
+
+    watchdog.abort();
+    handler.abort();
+}
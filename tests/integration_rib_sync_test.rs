@@ -1,4 +1,6 @@
-use ari::cdap::CdapMessage;
+use ari::cdap::{CDAP_VERSION, CdapCapabilities, CdapMessage};
+use ari::directory::Directory;
+use ari::enrollment::EnrollmentManager;
 /// Integration test for Phase 6: Incremental RIB Synchronization
 ///
 /// This test verifies:
@@ -6,7 +8,11 @@ use ari::cdap::CdapMessage;
 /// 2. Incremental sync returns only changed objects
 /// 3. Fallback to full sync when version is too old
 /// 4. SyncRequest/SyncResponse messages serialize correctly
+use ari::pdu::Pdu;
 use ari::rib::{Rib, RibChange, RibValue};
+use ari::shim::UdpShim;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_rib_change_log_tracking() {
@@ -402,3 +408,531 @@ async fn test_bandwidth_comparison() {
 
     println!("\n✅ Test passed: Incremental sync is more bandwidth-efficient\n");
 }
+
+#[tokio::test]
+async fn test_member_converges_after_sync_cycle() {
+    println!("\n=== Test: Member Converges After One Sync Cycle ===\n");
+
+    // Bootstrap IPCP
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("sync-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(1001));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+
+    let bootstrap_mgr = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib.clone(),
+        bootstrap_shim.clone(),
+        1001,
+        2000,
+        2999,
+    ));
+
+    let handler = bootstrap_mgr.clone();
+    let handler_shim = bootstrap_shim.clone();
+    let bootstrap_task = tokio::spawn(async move {
+        loop {
+            if let Ok(Some((pdu, src_addr))) = handler_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    // Member IPCP
+    let member_rib = Rib::new();
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(1001, bootstrap_socket);
+
+    let mut member_mgr = EnrollmentManager::new(member_rib.clone(), member_shim.clone(), 0);
+    member_mgr.set_ipcp_name("sync-member".to_string());
+    member_mgr.enrol_with_bootstrap(1001).await.unwrap();
+    println!("✓ Member enrolled (address {})", member_mgr.local_addr());
+
+    let member_mgr = Arc::new(member_mgr);
+    let synced_before = member_mgr.last_synced_version().await;
+
+    // Bootstrap mutates its RIB after enrollment
+    bootstrap_rib
+        .create(
+            "/neighbors/ipcp-x".to_string(),
+            "neighbor".to_string(),
+            RibValue::Integer(3000),
+        )
+        .await
+        .unwrap();
+    bootstrap_rib
+        .update(
+            "/dif/name",
+            RibValue::String("sync-dif-renamed".to_string()),
+        )
+        .await
+        .unwrap();
+    let bootstrap_version = bootstrap_rib.current_version().await;
+    println!("✓ Bootstrap mutated RIB (version {})", bootstrap_version);
+
+    assert!(member_rib.read("/neighbors/ipcp-x").await.is_err());
+
+    // One sync cycle
+    member_mgr.sync_rib().await.unwrap();
+
+    let neighbor = member_rib.read("/neighbors/ipcp-x").await.unwrap();
+    assert_eq!(neighbor.value.as_integer(), Some(3000));
+    let dif_name = member_rib.read("/dif/name").await.unwrap();
+    assert_eq!(dif_name.value.as_string(), Some("sync-dif-renamed"));
+
+    let synced_after = member_mgr.last_synced_version().await;
+    assert!(synced_after > synced_before);
+    assert_eq!(synced_after, bootstrap_version);
+    println!("✓ Member converged (synced version {})", synced_after);
+
+    bootstrap_task.abort();
+
+    println!("\n✅ Test passed: Member converges after one sync cycle\n");
+}
+
+#[tokio::test]
+async fn test_sync_status_reports_bootstrap_version_after_sync() {
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("status-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(1001));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+    let bootstrap_mgr = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib.clone(),
+        bootstrap_shim.clone(),
+        1001,
+        2000,
+        2999,
+    ));
+
+    let handler = bootstrap_mgr.clone();
+    let handler_shim = bootstrap_shim.clone();
+    let bootstrap_task = tokio::spawn(async move {
+        loop {
+            if let Ok(Some((pdu, src_addr))) = handler_shim.receive_pdu_async().await {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+        }
+    });
+
+    let member_rib = Rib::new();
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(1001, bootstrap_socket);
+    let mut member_mgr = EnrollmentManager::new(member_rib.clone(), member_shim, 0);
+    member_mgr.set_ipcp_name("status-member".to_string());
+    member_mgr.enrol_with_bootstrap(1001).await.unwrap();
+    assert_eq!(member_mgr.sync_status().await.last_sync_time, None);
+
+    bootstrap_rib
+        .create(
+            "/neighbors/ipcp-y".to_string(),
+            "neighbor".to_string(),
+            RibValue::Integer(3001),
+        )
+        .await
+        .unwrap();
+    member_mgr.sync_rib().await.unwrap();
+
+    let status = member_mgr.sync_status().await;
+    assert_eq!(
+        status.last_synced_version,
+        bootstrap_rib.current_version().await
+    );
+    assert!(status.last_sync_time.is_some());
+    assert_eq!(status.pending, 0);
+
+    // A member change waits for the next cycle to be pushed
+    member_rib
+        .create(
+            "/directory/pending-app".to_string(),
+            "test".to_string(),
+            RibValue::Integer(1),
+        )
+        .await
+        .unwrap();
+    // Objects in the local namespace are never pushed
+    member_rib
+        .create(
+            "/local/scratch".to_string(),
+            "test".to_string(),
+            RibValue::Integer(2),
+        )
+        .await
+        .unwrap();
+    let status = member_mgr.sync_status().await;
+    assert_eq!(status.pending, 1);
+    assert_eq!(status.local_version, member_rib.current_version().await);
+
+    bootstrap_task.abort();
+}
+
+#[tokio::test]
+async fn test_bootstrap_learns_member_changes() {
+    println!("\n=== Test: Bootstrap Learns Member Changes ===\n");
+
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("mesh-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(1001));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+
+    let bootstrap_mgr = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib.clone(),
+        bootstrap_shim.clone(),
+        1001,
+        2000,
+        2999,
+    ));
+
+    let handler = bootstrap_mgr.clone();
+    let handler_shim = bootstrap_shim.clone();
+    let bootstrap_task = tokio::spawn(async move {
+        loop {
+            if let Ok(Some((pdu, src_addr))) = handler_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    let member_rib = Rib::new();
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(1001, bootstrap_socket);
+
+    let mut member_mgr = EnrollmentManager::new(member_rib.clone(), member_shim.clone(), 0);
+    member_mgr.set_ipcp_name("mesh-member".to_string());
+    member_mgr.enrol_with_bootstrap(1001).await.unwrap();
+    let member_mgr = Arc::new(member_mgr);
+
+    // Local-only enrollment state must not leak to the bootstrap
+    assert!(bootstrap_rib.read("/local/address").await.is_err());
+
+    // Member creates an object locally and syncs
+    member_rib
+        .create(
+            "/neighbors/mesh-member".to_string(),
+            "neighbor".to_string(),
+            RibValue::Integer(2000),
+        )
+        .await
+        .unwrap();
+    member_mgr.sync_rib().await.unwrap();
+
+    let learned = bootstrap_rib.read("/neighbors/mesh-member").await.unwrap();
+    assert_eq!(learned.value.as_integer(), Some(2000));
+    assert!(bootstrap_rib.read("/local/address").await.is_err());
+
+    let pushed = member_rib
+        .read("/neighbors/mesh-member")
+        .await
+        .unwrap()
+        .version;
+
+    assert!(
+        bootstrap_mgr
+            .peer_synced_version("mesh-member")
+            .await
+            .unwrap()
+            >= pushed
+    );
+    println!("✓ Bootstrap learned the member's object");
+
+    bootstrap_task.abort();
+
+    println!("\n✅ Test passed: Bootstrap learns member changes\n");
+}
+
+#[tokio::test]
+async fn test_large_rib_sync_spans_multiple_fragments() {
+    println!("\n=== Test: Large RIB Sync Spans Multiple Fragments ===\n");
+
+    // Small change log so the post-enrollment sync falls back to a full snapshot
+    let bootstrap_rib = Rib::with_change_log_size(100);
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("bulk-dif".to_string()),
+        )
+        .await
+        .unwrap();
+    for i in 0..2000 {
+        bootstrap_rib
+            .create(
+                format!("/directory/app-{:05}", i),
+                "directory_entry".to_string(),
+                RibValue::String(format!("ipcp-{:05}", i)),
+            )
+            .await
+            .unwrap();
+    }
+    let snapshot_len = bootstrap_rib.serialize().await.len();
+    assert!(snapshot_len > 65_507, "snapshot must exceed one datagram");
+    println!("✓ Bootstrap RIB snapshot is {} bytes", snapshot_len);
+
+    let bootstrap_shim = Arc::new(UdpShim::new(1001));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+
+    let bootstrap_mgr = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib.clone(),
+        bootstrap_shim.clone(),
+        1001,
+        2000,
+        2999,
+    ));
+
+    let handler = bootstrap_mgr.clone();
+    let handler_shim = bootstrap_shim.clone();
+    let bootstrap_task = tokio::spawn(async move {
+        loop {
+            if let Ok(Some((pdu, src_addr))) = handler_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    let member_rib = Rib::new();
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(1001, bootstrap_socket);
+
+    let mut member_mgr = EnrollmentManager::new(member_rib.clone(), member_shim.clone(), 0);
+    member_mgr.set_ipcp_name("bulk-member".to_string());
+    member_mgr.enrol_with_bootstrap(1001).await.unwrap();
+    let member_mgr = Arc::new(member_mgr);
+
+    // Enrollment snapshot arrived in fragments and was reassembled
+    assert!(member_rib.count().await >= 2000);
+    let entry = member_rib.read("/directory/app-01999").await.unwrap();
+    assert_eq!(entry.value.as_string(), Some("ipcp-01999"));
+    println!("✓ Member reassembled enrollment snapshot");
+
+    // Overflow the change log so the next sync ships a full snapshot
+    for i in 0..150 {
+        bootstrap_rib
+            .update(
+                &format!("/directory/app-{:05}", i),
+                RibValue::String(format!("ipcp-moved-{:05}", i)),
+            )
+            .await
+            .unwrap();
+    }
+
+    member_mgr.sync_rib().await.unwrap();
+
+    let moved = member_rib.read("/directory/app-00000").await.unwrap();
+    assert_eq!(moved.value.as_string(), Some("ipcp-moved-00000"));
+    let moved = member_rib.read("/directory/app-00149").await.unwrap();
+    assert_eq!(moved.value.as_string(), Some("ipcp-moved-00149"));
+    assert_eq!(
+        member_mgr.last_synced_version().await,
+        bootstrap_rib.current_version().await
+    );
+    println!("✓ Member reassembled full-snapshot sync response");
+
+    bootstrap_task.abort();
+
+    println!("\n✅ Test passed: Large RIB sync spans multiple fragments\n");
+}
+
+#[tokio::test]
+async fn test_directory_registration_syncs_to_member() {
+    println!("\n=== Test: Directory Registration Syncs To Member ===\n");
+
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("directory-dif".to_string()),
+        )
+        .await
+        .unwrap();
+    let bootstrap_shim = Arc::new(UdpShim::new(1001));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+
+    let bootstrap_mgr = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib.clone(),
+        bootstrap_shim.clone(),
+        1001,
+        2000,
+        2999,
+    ));
+
+    let handler = bootstrap_mgr.clone();
+    let handler_shim = bootstrap_shim.clone();
+    let bootstrap_task = tokio::spawn(async move {
+        loop {
+            if let Ok(Some((pdu, src_addr))) = handler_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    let member_rib = Rib::new();
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(1001, bootstrap_socket);
+
+    let mut member_mgr = EnrollmentManager::new(member_rib.clone(), member_shim.clone(), 0);
+    member_mgr.set_ipcp_name("directory-member".to_string());
+    member_mgr.enrol_with_bootstrap(1001).await.unwrap();
+
+    // A name registered on the bootstrap after the member enrolled
+    let bootstrap_directory = Directory::with_rib(bootstrap_rib.clone());
+    bootstrap_directory
+        .register("echo.app".to_string(), 1001)
+        .await
+        .unwrap();
+    println!("✓ Bootstrap registered echo.app");
+
+    let member_directory = Directory::with_rib(member_rib.clone());
+    assert!(member_directory.resolve("echo.app").await.is_none());
+
+    // One sync cycle
+    member_mgr.sync_rib().await.unwrap();
+
+    assert_eq!(member_directory.resolve("echo.app").await, Some(vec![1001]));
+    println!("✓ Member resolves echo.app after sync");
+
+    bootstrap_task.abort();
+
+    println!("\n✅ Test passed: Directory registrations are shared across the DIF\n");
+}
+
+#[tokio::test]
+async fn test_member_without_incremental_sync_gets_full_snapshot() {
+    println!("\n=== Test: Member Without Incremental Sync Gets Full Snapshot ===\n");
+
+    let bootstrap_rib = Rib::new();
+    bootstrap_rib
+        .create(
+            "/dif/name".to_string(),
+            "dif_info".to_string(),
+            RibValue::String("caps-dif".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let bootstrap_shim = Arc::new(UdpShim::new(1001));
+    bootstrap_shim.bind("127.0.0.1:0").unwrap();
+    let bootstrap_socket = bootstrap_shim.local_addr().unwrap();
+
+    let bootstrap_mgr = Arc::new(EnrollmentManager::new_bootstrap(
+        bootstrap_rib.clone(),
+        bootstrap_shim.clone(),
+        1001,
+        2000,
+        2999,
+    ));
+
+    let handler = bootstrap_mgr.clone();
+    let handler_shim = bootstrap_shim.clone();
+    let bootstrap_task = tokio::spawn(async move {
+        loop {
+            if let Ok(Some((pdu, src_addr))) = handler_shim.receive_pdu() {
+                let _ = handler.handle_cdap_message(&pdu, src_addr).await;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    // Member built without incremental sync support
+    let member_rib = Rib::new();
+    let member_shim = Arc::new(UdpShim::new(0));
+    member_shim.bind("127.0.0.1:0").unwrap();
+    member_shim.register_peer(1001, bootstrap_socket);
+
+    let mut member_mgr = EnrollmentManager::new(member_rib.clone(), member_shim.clone(), 0);
+    member_mgr.set_ipcp_name("caps-member".to_string());
+    member_mgr.set_capabilities(CdapCapabilities::local().without_incremental_sync());
+    member_mgr.enrol_with_bootstrap(1001).await.unwrap();
+
+    let negotiated = member_mgr.negotiated_capabilities().await.unwrap();
+    assert_eq!(negotiated.version, CDAP_VERSION);
+    assert!(!negotiated.incremental_sync);
+    assert_eq!(
+        bootstrap_mgr.peer_capabilities("caps-member").await,
+        Some(negotiated)
+    );
+    println!("✓ Both sides agreed on no incremental sync");
+
+    // A change the log could deliver as a delta
+    bootstrap_rib
+        .create(
+            "/neighbors/ipcp-y".to_string(),
+            "neighbor".to_string(),
+            RibValue::Integer(3001),
+        )
+        .await
+        .unwrap();
+
+    let request = CdapMessage::new_sync_request(
+        9,
+        member_mgr.last_synced_version().await,
+        "caps-member".to_string(),
+    );
+    let pdu = Pdu::new_management(
+        member_mgr.local_addr(),
+        1001,
+        postcard::to_allocvec(&request).unwrap(),
+    );
+    member_shim.send_pdu(&pdu).unwrap();
+
+    let sync = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            if let Ok(Some((pdu, _))) = member_shim.receive_pdu()
+                && let Ok(msg) = postcard::from_bytes::<CdapMessage>(&pdu.payload)
+                && let Some(sync) = msg.sync_response
+            {
+                return sync;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("no sync response");
+
+    assert!(sync.changes.is_none());
+    let snapshot = sync.full_snapshot.expect("expected a full snapshot");
+    member_rib.deserialize(&snapshot).await.unwrap();
+    let neighbor = member_rib.read("/neighbors/ipcp-y").await.unwrap();
+    assert_eq!(neighbor.value.as_integer(), Some(3001));
+    println!("✓ Sync response carried a full snapshot instead of deltas");
+
+    bootstrap_task.abort();
+
+    println!("\n✅ Test passed: Capabilities degrade sync to full snapshots\n");
+}